@@ -40,6 +40,7 @@ public enum AppAlertState: Equatable {
     case uninitializedTapSigner(TapSigner)
     case tapSignerWalletFound(WalletId)
     case intializedTapSigner(TapSigner)
+    case sweepPrivateKey(SweepKey, Amount)
 
     func title() -> String {
         switch self {
@@ -88,6 +89,8 @@ public enum AppAlertState: Equatable {
             "Import TAPSIGNER?"
         case .tapSignerNoBackup:
             "No Backup Found"
+        case .sweepPrivateKey:
+            "Sweep Private Key?"
         case let .general(title: title, message: _):
             title
         }
@@ -41,6 +41,9 @@ import SwiftUI
     @ObservationIgnored
     var sendFlowManager: SendFlowManager?
 
+    // BIP129 multisig setup in progress, key records can arrive from anywhere
+    var bsmsCoordinator: BsmsCoordinatorManager? = nil
+
     public var colorScheme: ColorScheme? {
         switch colorSchemeSelection {
         case .light:
@@ -109,6 +109,8 @@ struct CoveApp: App {
                 "Would you like to go to this wallet?"
             case let .tapSignerNoBackup(tapSigner):
                 "Can't change the PIN without taking a backup of the wallet. Would you like to take a backup now?"
+            case let .sweepPrivateKey(_, amount):
+                "Found \(amount.btcStringWithUnit()) on this private key. Would you like to move it into this wallet?"
             case .general(title: _, let message):
                 message
             }
@@ -199,6 +201,12 @@ struct CoveApp: App {
                 // TODO: go to backup screen
             }
             Button("Cancel", role: .cancel) { app.alertState = .none }
+        case let .sweepPrivateKey(key, _):
+            Button("Sweep") {
+                app.alertState = .none
+                sweepPrivateKey(key)
+            }
+            Button("Cancel", role: .cancel) { app.alertState = .none }
         case .invalidWordGroup,
              .errorImportingHotWallet,
              .importedSuccessfully,
@@ -346,6 +354,38 @@ struct CoveApp: App {
         app.pushRoute(route)
     }
 
+    func handlePsbt(_ psbt: Psbt) {
+        do {
+            let matches = try psbtWalletMatches(psbt: psbt)
+            let selectedWallet = Database().globalConfig().selectedWallet()
+
+            // the wallet that saved this transaction, then the selected wallet, then the best match
+            guard
+                let match = matches.first(where: { $0.unsignedTransaction != nil })
+                ?? matches.first(where: { $0.walletId == selectedWallet })
+                ?? matches.first
+            else {
+                Log.error("No wallet matched PSBT \(psbt.txId())")
+                app.alertState = TaggedItem(
+                    .general(
+                        title: "Unable to Open PSBT",
+                        message: "This PSBT doesn't spend from any of your wallets"
+                    ))
+                return
+            }
+
+            if matches.count > 1 {
+                Log.warn("PSBT matches \(matches.count) wallets, using \(match.walletName)")
+            }
+
+            app.pushRoute(RouteFactory().sendSignPsbt(id: match.walletId, psbt: psbt))
+        } catch {
+            Log.error("Unable to find a wallet for PSBT: \(error)")
+            app.alertState = TaggedItem(
+                .general(title: "Unable to Open PSBT", message: error.localizedDescription))
+        }
+    }
+
     func handleFileOpen(_ url: URL) {
         let fileHandler = FileHandler(filePath: url.absoluteString)
 
@@ -360,6 +400,8 @@ struct CoveApp: App {
                 handleAddress(addressWithNetwork)
             case let .transaction(txn):
                 handleTransaction(txn)
+            case let .psbt(psbt):
+                handlePsbt(psbt)
             case let .tapSignerUnused(tapSigner):
                 app.sheetState = .init(.tapSigner(TapSignerRoute.initSelect(tapSigner)))
             case let .tapSignerReady(tapSigner):
@@ -374,6 +416,10 @@ struct CoveApp: App {
                 app.alertState = TaggedItem(
                     .invalidFileFormat(
                         "Currently BIP329 labels must be imported through the wallet actions"))
+            case let .privateKey(key):
+                handlePrivateKey(key)
+            case let .bsmsRecord(record):
+                handleBsmsRecord(record)
             }
         } catch {
             switch error {
@@ -397,6 +443,71 @@ struct CoveApp: App {
         }
     }
 
+    func handlePrivateKey(_ key: SweepKey) {
+        guard let manager = app.walletManager else {
+            app.alertState = TaggedItem(
+                .general(
+                    title: "No Wallet Selected",
+                    message: "Please select a wallet to sweep the private key into"
+                ))
+            return
+        }
+
+        Task {
+            do {
+                let amount = try await manager.rust.sweepKeyBalance(key: key)
+                await MainActor.run { app.alertState = .init(.sweepPrivateKey(key, amount)) }
+            } catch {
+                Log.error("Unable to check private key balance: \(error)")
+                await MainActor.run {
+                    app.alertState = .init(
+                        .general(title: "Unable to Sweep", message: error.localizedDescription))
+                }
+            }
+        }
+    }
+
+    func handleBsmsRecord(_ record: BsmsRecord) {
+        guard let coordinator = app.bsmsCoordinator else {
+            app.alertState = TaggedItem(
+                .general(
+                    title: "No Multisig Setup in Progress",
+                    message: "Start a multisig setup before importing a BSMS key record"
+                ))
+            return
+        }
+
+        coordinator.addKeyRecord(record)
+    }
+
+    /// Encrypted BSMS records are plain hex, only try them while a multisig setup is running
+    func handleEncryptedBsmsRecord(_ string: String) -> Bool {
+        guard let coordinator = app.bsmsCoordinator else { return false }
+        guard let record = try? BsmsRecord(record: string) else { return false }
+
+        coordinator.addKeyRecord(record)
+        return true
+    }
+
+    func sweepPrivateKey(_ key: SweepKey) {
+        guard let manager = app.walletManager else { return }
+
+        Task {
+            do {
+                // goes to the confirm screen, nothing is broadcast until the user swipes to send
+                let fees = try await manager.rust.feeRateOptions()
+                try await manager.rust.sweepKey(
+                    key: key, feeRate: fees.medium(), label: "Swept private key")
+            } catch {
+                Log.error("Unable to sweep private key: \(error)")
+                await MainActor.run {
+                    app.alertState = .init(
+                        .general(title: "Unable to Sweep", message: error.localizedDescription))
+                }
+            }
+        }
+    }
+
     func setInvalidlabels() {
         app.alertState = TaggedItem(
             .invalidFileFormat(
@@ -415,6 +526,8 @@ struct CoveApp: App {
                 handleAddress(addressWithNetwork)
             case let .transaction(transaction):
                 handleTransaction(transaction)
+            case let .psbt(psbt):
+                handlePsbt(psbt)
             case let .tapSignerUnused(tapSigner):
                 app.alertState = .init(.uninitializedTapSigner(tapSigner))
             case let .tapSignerReady(tapSigner):
@@ -435,6 +548,10 @@ struct CoveApp: App {
 
                 // when labels are imported, we need to get the transactions again with the updated labels
                 Task { await manager.rust.getTransactions() }
+            case let .privateKey(key):
+                handlePrivateKey(key)
+            case let .bsmsRecord(record):
+                handleBsmsRecord(record)
             }
         } catch {
             switch error {
@@ -545,6 +662,11 @@ struct CoveApp: App {
         Log.debug("[COVE APP ROOT] onChangeQr")
         guard let scannedCode else { return }
         app.sheetState = .none
+
+        if case let .string(string) = scannedCode.item, handleEncryptedBsmsRecord(string) {
+            return
+        }
+
         do {
             let multiFormat = try scannedCode.item.toMultiFormat()
             handleMultiFormat(multiFormat)
@@ -566,6 +688,8 @@ struct CoveApp: App {
     func onChangeNfc(_: NfcMessage?, _ nfcMessage: NfcMessage?) {
         Log.debug("[COVE APP ROOT] onChangeNfc")
         guard let nfcMessage else { return }
+        if let string = nfcMessage.string(), handleEncryptedBsmsRecord(string) { return }
+
         do {
             let multiFormat = try nfcMessage.tryIntoMultiFormat()
             handleMultiFormat(multiFormat)
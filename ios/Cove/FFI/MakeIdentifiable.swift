@@ -10,3 +10,7 @@ import Foundation
 extension WalletErrorAlert: Identifiable {
     public var id: Self { self }
 }
+
+extension WalletExportFormat: Identifiable {
+    public var id: Self { self }
+}
@@ -27,4 +27,14 @@ public struct FileReader {
         defer { url.stopAccessingSecurityScopedResource() }
         return try String(contentsOf: url, encoding: .utf8)
     }
+
+    func readData() throws -> Data {
+        guard url.startAccessingSecurityScopedResource() else {
+            throw FileReadError(
+                message: "Failed to access the file at \(url.path)")
+        }
+
+        defer { url.stopAccessingSecurityScopedResource() }
+        return try Data(contentsOf: url)
+    }
 }
@@ -0,0 +1,238 @@
+//
+//  BsmsCoordinatorScreen.swift
+//  Cove
+//
+//  BIP129 multisig setup, Cove is the coordinator
+//
+
+import SwiftUI
+
+@Observable final class BsmsCoordinatorManager {
+    let rust: BsmsCoordinator
+
+    var signers: [BsmsSigner] = []
+    var descriptorRecord: BsmsDescriptorRecord? = .none
+    var error: String? = .none
+
+    init(_ rust: BsmsCoordinator) {
+        self.rust = rust
+    }
+
+    /// Key records can come from QR, NFC, file or paste, encrypted ones only while this setup is running
+    func addKeyRecord(_ record: BsmsRecord) {
+        do {
+            _ = try rust.addKeyRecord(record: record)
+            signers = rust.signers()
+
+            if rust.isComplete() {
+                descriptorRecord = try rust.descriptorRecord()
+            }
+        } catch {
+            Log.error("Unable to add BSMS key record: \(error)")
+            self.error = error.localizedDescription
+        }
+    }
+}
+
+struct BsmsCoordinatorScreen: View {
+    @Environment(AppManager.self) private var app
+
+    // setup
+    @State private var threshold = 2
+    @State private var total = 3
+    @State private var encryption: BsmsEncryption = .standard
+    @State private var walletName = ""
+
+    @State private var isPresentingFilePicker = false
+    @State private var showingRecordQr = false
+    @State private var alertMessage: String? = .none
+
+    var coordinator: BsmsCoordinatorManager? {
+        app.bsmsCoordinator
+    }
+
+    var body: some View {
+        Form {
+            if let coordinator {
+                CollectSection(coordinator)
+
+                if let record = coordinator.descriptorRecord {
+                    DescriptorRecordSection(record)
+                }
+            } else {
+                SetupSection
+            }
+        }
+        .navigationTitle("Multisig Setup")
+        .navigationBarTitleDisplayMode(.inline)
+        .fileImporter(
+            isPresented: $isPresentingFilePicker,
+            allowedContentTypes: [.plainText],
+            onCompletion: handleFileImport
+        )
+        .sheet(isPresented: $showingRecordQr) {
+            if let record = coordinator?.descriptorRecord {
+                TabView {
+                    ForEach(record.records, id: \.self) { record in
+                        QrCodeView(text: record)
+                    }
+                }
+                .tabViewStyle(.page)
+                .presentationDetents([.medium, .large])
+            }
+        }
+        .alert(
+            "Multisig Setup",
+            isPresented: Binding(
+                get: { alertMessage != nil || coordinator?.error != nil },
+                set: { if !$0 { alertMessage = nil; coordinator?.error = nil } }
+            ),
+            actions: { Button("OK") {} },
+            message: { Text(alertMessage ?? coordinator?.error ?? "") }
+        )
+        .onDisappear {
+            if coordinator?.descriptorRecord == nil { return }
+            app.bsmsCoordinator = nil
+        }
+    }
+
+    @ViewBuilder
+    var SetupSection: some View {
+        Section(footer: Text("Cove collects each signer's key record and creates the wallet descriptor for the signers to verify")) {
+            Stepper("Signers: \(total)", value: $total, in: 2 ... 15)
+                .onChange(of: total) { _, total in threshold = min(threshold, total) }
+
+            Stepper("Required signatures: \(threshold)", value: $threshold, in: 1 ... total)
+
+            Picker("Encryption", selection: $encryption) {
+                Text("None").tag(BsmsEncryption.noEncryption)
+                Text("Standard").tag(BsmsEncryption.standard)
+                Text("Extended").tag(BsmsEncryption.extended)
+            }
+        }
+
+        Section {
+            Button("Start Setup") {
+                do {
+                    let rust = try BsmsCoordinator(
+                        threshold: UInt32(threshold),
+                        total: UInt32(total),
+                        encryption: encryption
+                    )
+
+                    app.bsmsCoordinator = BsmsCoordinatorManager(rust)
+                } catch {
+                    alertMessage = error.localizedDescription
+                }
+            }
+        }
+    }
+
+    @ViewBuilder
+    func CollectSection(_ coordinator: BsmsCoordinatorManager) -> some View {
+        let tokens = coordinator.rust.tokens()
+
+        Section(header: Text(tokens.count > 1 ? "Signer Tokens" : "Session Token")) {
+            ForEach(Array(tokens.enumerated()), id: \.offset) { index, token in
+                HStack {
+                    if tokens.count > 1 { Text("Signer \(index + 1)") }
+                    Spacer()
+                    Text(token)
+                        .font(.system(.footnote, design: .monospaced))
+                        .textSelection(.enabled)
+                }
+            }
+        }
+
+        Section(
+            header: Text(
+                "Key Records \(coordinator.signers.count) of \(coordinator.rust.total())"
+            )
+        ) {
+            ForEach(Array(coordinator.signers.enumerated()), id: \.offset) { _, signer in
+                HStack {
+                    Text(signer.fingerprint.asUppercase())
+                        .font(.system(.footnote, design: .monospaced))
+                    Spacer()
+                    Text(signer.description)
+                        .font(.footnote)
+                        .foregroundStyle(.secondary)
+                }
+            }
+
+            if coordinator.descriptorRecord == nil {
+                Button("Scan QR", systemImage: "qrcode") { app.scanQr() }
+                Button("NFC", systemImage: "wave.3.right") { app.nfcReader.scan() }
+                Button("File", systemImage: "doc") { isPresentingFilePicker = true }
+                Button("Paste", systemImage: "doc.on.clipboard") { pasteKeyRecord(coordinator) }
+            }
+        }
+    }
+
+    @ViewBuilder
+    func DescriptorRecordSection(_ record: BsmsDescriptorRecord) -> some View {
+        Section(
+            header: Text("Verify First Address"),
+            footer: Text("Make sure every signer shows this same first address before using the wallet")
+        ) {
+            Text(record.firstAddress)
+                .font(.system(.footnote, design: .monospaced))
+                .textSelection(.enabled)
+
+            Button("Show Descriptor Record", systemImage: "qrcode") { showingRecordQr = true }
+
+            ShareLink(item: record.records.joined(separator: "\n\n")) {
+                Label("Share Descriptor Record", systemImage: "square.and.arrow.up")
+            }
+        }
+
+        Section {
+            TextField("Wallet Name", text: $walletName)
+
+            Button("Save Wallet") { saveWallet() }
+        }
+    }
+
+    func pasteKeyRecord(_ coordinator: BsmsCoordinatorManager) {
+        let text = UIPasteboard.general.string ?? ""
+        guard !text.isEmpty else {
+            alertMessage = "No text found on the clipboard."
+            return
+        }
+
+        do {
+            try coordinator.addKeyRecord(BsmsRecord(record: text))
+        } catch {
+            alertMessage = error.localizedDescription
+        }
+    }
+
+    func handleFileImport(result: Result<URL, Error>) {
+        do {
+            let file = try result.get()
+            let contents = try FileReader(for: file).read()
+            try coordinator?.addKeyRecord(BsmsRecord(record: contents))
+        } catch {
+            alertMessage = error.localizedDescription
+        }
+    }
+
+    func saveWallet() {
+        guard let coordinator else { return }
+
+        do {
+            let id = try coordinator.rust.saveWallet(name: walletName.isEmpty ? nil : walletName)
+            app.bsmsCoordinator = nil
+            try app.rust.selectWallet(id: id)
+        } catch {
+            alertMessage = error.localizedDescription
+        }
+    }
+}
+
+#Preview {
+    NavigationStack {
+        BsmsCoordinatorScreen()
+            .environment(AppManager.shared)
+    }
+}
@@ -189,14 +189,27 @@ struct QrCodeImportScreen: View {
             guard let multiQr else { return }
 
             // single QR
-            if !multiQr.isBbqr() {
+            if !multiQr.isBbqr(), !multiQr.isUr() {
                 scanComplete = true
                 scannedCode = TaggedString(stringValue)
                 return
             }
 
-            // BBQr
+            // BBQr or UR
             do {
+                if multiQr.isUr() {
+                    switch try multiQr.handleScanResult(qr: .string(stringValue)) {
+                    case let .completedUr(joined):
+                        scanComplete = true
+                        scannedCode = TaggedString(try joined.finalResult())
+                    case let .inProgressUr(left):
+                        partsLeft = Int(left)
+                    default: break
+                    }
+
+                    return
+                }
+
                 let result = try multiQr.addPart(qr: stringValue)
                 partsLeft = Int(result.partsLeft())
 
@@ -197,6 +197,8 @@ struct HotWalletImportScreen: View {
                 Log.error("Unable to save wallet metadata to database: \(databaseError)")
             case let .BdkError(error):
                 Log.error("Unable to import wallet: \(error)")
+            case let .InvalidAccount(account):
+                Log.error("Invalid account: \(account)")
             }
         } catch {
             Log.error("Unknown error \(error)")
@@ -213,6 +213,10 @@ struct NewWalletSelectScreen: View {
 
             newWalletFromXpub(text)
         }
+
+        NavigationLink(value: routeFactory.bsmsCoordinator()) {
+            Text("Multisig Setup (BSMS)")
+        }
     }
 }
 
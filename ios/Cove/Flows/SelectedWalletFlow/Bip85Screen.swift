@@ -0,0 +1,132 @@
+//
+//  Bip85Screen.swift
+//  Cove
+//
+//  BIP85 child seeds, WIF keys and xprvs derived from this wallet's seed
+//
+
+import SwiftUI
+
+struct Bip85Screen: View {
+    @Environment(AppManager.self) private var app
+    @Environment(AuthManager.self) private var auth
+    @Environment(\.dismiss) private var dismiss
+
+    let id: WalletId
+
+    @State private var isUnlocked = false
+    @State private var application: Bip85Application = .mnemonic(words: 12)
+    @State private var index = 0
+    @State private var child: Bip85Child? = .none
+    @State private var alertMessage: String? = .none
+
+    var body: some View {
+        Group {
+            if isUnlocked || !auth.isAuthEnabled {
+                NavigationStack { Bip85Form }
+            } else {
+                LockView(
+                    lockType: auth.type,
+                    isPinCorrect: auth.checkPin,
+                    bioMetricUnlockMessage: "Derive child keys",
+                    onUnlock: authorize,
+                    backAction: { dismiss() },
+                    content: { EmptyView() }
+                )
+            }
+        }
+        .onDisappear { auth.rust.revokeSensitiveAction() }
+    }
+
+    @ViewBuilder
+    var Bip85Form: some View {
+        Form {
+            Section(footer: Text("Child keys can always be recreated from this wallet's secret words and the index")) {
+                Picker("Type", selection: $application) {
+                    Text("12 Words").tag(Bip85Application.mnemonic(words: 12))
+                    Text("18 Words").tag(Bip85Application.mnemonic(words: 18))
+                    Text("24 Words").tag(Bip85Application.mnemonic(words: 24))
+                    Text("WIF").tag(Bip85Application.wif)
+                    Text("XPRV").tag(Bip85Application.xprv)
+                }
+
+                Stepper("Index: \(index)", value: $index, in: 0 ... 9999)
+
+                Button("Derive") { derive() }
+            }
+            .onChange(of: application) { _, _ in child = .none }
+            .onChange(of: index) { _, _ in child = .none }
+
+            if let child {
+                Section(header: Text(child.path)) {
+                    Text(child.secret)
+                        .font(.system(.footnote, design: .monospaced))
+                        .textSelection(.enabled)
+
+                    if case let .mnemonic(words) = child.application {
+                        Button("Create Wallet") { createWallet(words: words) }
+                    }
+                }
+            }
+        }
+        .navigationTitle("BIP85 Child Keys")
+        .navigationBarTitleDisplayMode(.inline)
+        .toolbar {
+            ToolbarItem(placement: .cancellationAction) {
+                Button("Done") { dismiss() }
+            }
+        }
+        .alert(
+            "BIP85",
+            isPresented: Binding(
+                get: { alertMessage != nil },
+                set: { if !$0 { alertMessage = nil } }
+            ),
+            actions: { Button("OK") {} },
+            message: { Text(alertMessage ?? "") }
+        )
+    }
+
+    /// `LockView` only unlocks with an empty PIN after Face ID succeeded
+    func authorize(_ pin: String) {
+        let proof: SensitiveActionAuth =
+            pin.isEmpty ? .biometric(authenticated: true) : .pin(pin)
+
+        do {
+            try auth.rust.authorizeSensitiveAction(auth: proof)
+            isUnlocked = true
+        } catch {
+            Log.error("Unable to authorize BIP85: \(error)")
+            alertMessage = error.localizedDescription
+            dismiss()
+        }
+    }
+
+    func derive() {
+        do {
+            child = try bip85Derive(id: id, application: application, index: UInt32(index))
+        } catch let error as Bip85Error {
+            alertMessage = describeBip85Error(error: error)
+        } catch {
+            alertMessage = error.localizedDescription
+        }
+    }
+
+    func createWallet(words: UInt8) {
+        do {
+            let metadata = try bip85CreateWallet(id: id, words: words, index: UInt32(index))
+            dismiss()
+            try app.rust.selectWallet(id: metadata.id)
+        } catch let error as Bip85Error {
+            alertMessage = describeBip85Error(error: error)
+        } catch {
+            alertMessage = error.localizedDescription
+        }
+    }
+}
+
+#Preview {
+    Bip85Screen(id: WalletId())
+        .environment(AppManager.shared)
+        .environment(AuthManager.shared)
+}
@@ -14,6 +14,7 @@ struct MoreInfoPopover: View {
     let manager: WalletManager
     @Binding var exporting: SelctedWalletScreenExporterView.Exporting?
     @Binding var isImportingLabels: Bool
+    @Binding var isEnteringSweepKey: Bool
 
     private var hasLabels: Bool {
         labelManager.hasLabels()
@@ -130,6 +131,12 @@ struct MoreInfoPopover: View {
                 DownloadBackupButton(t)
             }
 
+            if metadata.walletType != .watchOnly {
+                Button(action: { isEnteringSweepKey = true }) {
+                    Label("Sweep Private Key", systemImage: "key.horizontal")
+                }
+            }
+
             if manager.hasTransactions {
                 Button(action: {
                     app.pushRoute(.coinControl(.list(metadata.id)))
@@ -152,7 +159,8 @@ struct MoreInfoPopover: View {
         MoreInfoPopover(
             manager: WalletManager(preview: "preview_only"),
             exporting: Binding.constant(nil),
-            isImportingLabels: Binding.constant(false)
+            isImportingLabels: Binding.constant(false),
+            isEnteringSweepKey: Binding.constant(false)
         )
         .environment(AppManager.shared)
     }
@@ -16,6 +16,7 @@ struct SecretWordsScreen: View {
     // private
     @State var words: Mnemonic?
     @State var errorMessage: String?
+    @State private var showingSeedQr = false
 
     var verticalSpacing: CGFloat {
         15
@@ -120,6 +121,16 @@ struct SecretWordsScreen: View {
                     .font(.callout)
                     .fontWeight(.semibold)
             }
+
+            if words != nil {
+                ToolbarItem(placement: .topBarTrailing) {
+                    Button("SeedQR", systemImage: "qrcode") { showingSeedQr = true }
+                        .foregroundStyle(.white)
+                }
+            }
+        }
+        .sheet(isPresented: $showingSeedQr) {
+            if let words { SeedQrBackupView(words: words) }
         }
         .background(
             Image(.newWalletPattern)
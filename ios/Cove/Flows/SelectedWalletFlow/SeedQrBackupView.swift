@@ -0,0 +1,112 @@
+//
+//  SeedQrBackupView.swift
+//  Cove
+//
+//  SeedQR backup of the secret words, as a grid to copy onto a SeedSigner template
+//
+
+import SwiftUI
+
+struct SeedQrBackupView: View {
+    @Environment(\.dismiss) private var dismiss
+
+    let words: Mnemonic
+
+    @State private var isCompact = false
+    @State private var showGridLines = true
+
+    private var grid: Result<SeedQrGrid, Error> {
+        Result {
+            let seedQr = try isCompact
+                ? SeedQr.newCompact(mnemonic: words)
+                : SeedQr.newStandard(mnemonic: words)
+
+            return try seedQr.grid()
+        }
+    }
+
+    var body: some View {
+        NavigationStack {
+            VStack(spacing: 20) {
+                Picker("Format", selection: $isCompact) {
+                    Text("Standard").tag(false)
+                    Text("Compact").tag(true)
+                }
+                .pickerStyle(.segmented)
+
+                switch grid {
+                case let .success(grid):
+                    SeedQrGridView(grid: grid, showGridLines: showGridLines)
+                        .aspectRatio(1, contentMode: .fit)
+
+                    Text("\(grid.size)x\(grid.size), fill in the dark squares on a matching template")
+                        .font(.footnote)
+                        .foregroundStyle(.secondary)
+                        .multilineTextAlignment(.center)
+                case let .failure(error):
+                    Text(error.localizedDescription)
+                        .font(.footnote)
+                        .foregroundStyle(.red)
+                        .multilineTextAlignment(.center)
+                }
+
+                Toggle("Show grid lines", isOn: $showGridLines)
+                    .font(.subheadline)
+
+                Spacer()
+            }
+            .padding()
+            .navigationTitle("SeedQR")
+            .navigationBarTitleDisplayMode(.inline)
+            .toolbar {
+                ToolbarItem(placement: .cancellationAction) {
+                    Button("Done") { dismiss() }
+                }
+            }
+        }
+    }
+}
+
+struct SeedQrGridView: View {
+    let grid: SeedQrGrid
+    let showGridLines: Bool
+
+    var body: some View {
+        Canvas { context, size in
+            let count = CGFloat(grid.size)
+            let module = min(size.width, size.height) / count
+
+            context.fill(Path(CGRect(origin: .zero, size: size)), with: .color(.white))
+
+            for (row, modules) in grid.rows.enumerated() {
+                for (column, isDark) in modules.enumerated() where isDark {
+                    let rect = CGRect(
+                        x: CGFloat(column) * module,
+                        y: CGFloat(row) * module,
+                        width: module,
+                        height: module
+                    )
+
+                    context.fill(Path(rect), with: .color(.black))
+                }
+            }
+
+            guard showGridLines else { return }
+
+            var lines = Path()
+            for index in 0 ... grid.size {
+                let offset = CGFloat(index) * module
+                lines.move(to: CGPoint(x: offset, y: 0))
+                lines.addLine(to: CGPoint(x: offset, y: module * count))
+                lines.move(to: CGPoint(x: 0, y: offset))
+                lines.addLine(to: CGPoint(x: module * count, y: offset))
+            }
+
+            context.stroke(lines, with: .color(.gray.opacity(0.5)), lineWidth: 0.5)
+        }
+    }
+}
+
+#Preview {
+    SeedQrBackupView(words: Mnemonic.preview(numberOfBip39Words: .twelve))
+}
@@ -44,6 +44,10 @@ struct SelectedWalletScreen: View {
     @State private var scannedLabels: TaggedString? = nil
     @State private var isImportingLabels = false
 
+    // sweep a private key typed or pasted in, the only place raw hex keys are accepted
+    @State private var isEnteringSweepKey = false
+    @State private var enteredSweepKey = ""
+
     // private
     @State private var runPostRefresh = false
 
@@ -195,7 +199,8 @@ struct SelectedWalletScreen: View {
                     MoreInfoPopover(
                         manager: manager,
                         exporting: $exporting,
-                        isImportingLabels: $isImportingLabels
+                        isImportingLabels: $isImportingLabels,
+                        isEnteringSweepKey: $isEnteringSweepKey
                     )
                 } label: {
                     Image(systemName: "ellipsis.circle")
@@ -222,6 +227,10 @@ struct SelectedWalletScreen: View {
                 walletId: manager.walletMetadata.id, isVerified: manager.walletMetadata.verified
             )
 
+            if metadata.walletType == .miniscript {
+                SpendingPathsView(manager: manager)
+            }
+
             Transactions
                 .environment(manager)
 
@@ -261,6 +270,31 @@ struct SelectedWalletScreen: View {
             }
         }
         .onChange(of: scannedLabels, initial: false, onChangeOfScannedLabels)
+        .alert("Sweep Private Key", isPresented: $isEnteringSweepKey) {
+            SecureField("WIF or hex private key", text: $enteredSweepKey)
+            Button("Continue", action: checkEnteredSweepKey)
+            Button("Cancel", role: .cancel) { enteredSweepKey = "" }
+        } message: {
+            Text("Move the funds on a private key into this wallet")
+        }
+    }
+
+    func checkEnteredSweepKey() {
+        let entered = enteredSweepKey
+        enteredSweepKey = ""
+
+        Task {
+            do {
+                let key = try SweepKey(key: entered)
+                let amount = try await manager.rust.sweepKeyBalance(key: key)
+                await MainActor.run { app.alertState = .init(.sweepPrivateKey(key, amount)) }
+            } catch {
+                await MainActor.run {
+                    app.alertState = .init(
+                        .general(title: "Unable to Sweep", message: error.localizedDescription))
+                }
+            }
+        }
     }
 
     func onChangeOfScannedLabels(_: TaggedString?, _ labels: TaggedString?) {
@@ -0,0 +1,100 @@
+//
+//  SpendingPathsView.swift
+//  Cove
+//
+//  Spending paths of a miniscript policy wallet, and which ones can be used right now
+//
+
+import SwiftUI
+
+struct SpendingPathsView: View {
+    let manager: WalletManager
+
+    @State private var paths: [SpendingPath] = []
+    @State private var isExpanded = false
+
+    var selected: UInt32? {
+        manager.walletMetadata.spendingPath
+    }
+
+    var available: [SpendingPath] {
+        paths.filter(\.isAvailable)
+    }
+
+    var body: some View {
+        VStack(alignment: .leading, spacing: 8) {
+            Button(action: { withAnimation { isExpanded.toggle() } }) {
+                HStack {
+                    Image(systemName: "signpost.right.and.left")
+                    Text("\(available.count) of \(paths.count) spending paths available")
+                        .font(.caption)
+                        .fontWeight(.semibold)
+
+                    Spacer()
+
+                    Image(systemName: isExpanded ? "chevron.up" : "chevron.down")
+                        .font(.caption)
+                }
+            }
+            .foregroundStyle(.primary)
+
+            if isExpanded {
+                PathRow(label: "Automatic", detail: "First available path", isSelected: selected == nil) {
+                    manager.dispatch(action: .selectSpendingPath(nil))
+                }
+
+                ForEach(paths, id: \.index) { path in
+                    PathRow(
+                        label: path.description,
+                        detail: detail(path),
+                        isSelected: selected == path.index
+                    ) {
+                        manager.dispatch(action: .selectSpendingPath(path.index))
+                    }
+                    .opacity(path.isAvailable ? 1 : 0.6)
+                }
+            }
+        }
+        .padding(.horizontal)
+        .padding(.vertical, 10)
+        .background(Color.coveBg)
+        .task {
+            do {
+                paths = try await manager.rust.spendingPaths()
+            } catch {
+                Log.error("Unable to get spending paths: \(error)")
+            }
+        }
+    }
+
+    func detail(_ path: SpendingPath) -> String {
+        if path.isAvailable { return "\(manager.amountFmt(path.spendable)) spendable now" }
+        return "Available in about \(path.blocksRemaining) blocks"
+    }
+
+    @ViewBuilder
+    func PathRow(label: String, detail: String, isSelected: Bool, action: @escaping () -> Void) -> some View {
+        Button(action: action) {
+            HStack {
+                VStack(alignment: .leading, spacing: 2) {
+                    Text(label)
+                        .font(.footnote)
+                        .multilineTextAlignment(.leading)
+
+                    Text(detail)
+                        .font(.caption2)
+                        .foregroundStyle(.secondary)
+                }
+
+                Spacer()
+
+                if isSelected {
+                    Image(systemName: "checkmark")
+                        .font(.footnote)
+                        .foregroundStyle(.blue)
+                }
+            }
+        }
+        .foregroundStyle(.primary)
+    }
+}
@@ -25,19 +25,40 @@ struct SendFlowConfirmScreen: View {
     @State private var sendState: SendState = .idle
     @State private var isShowingErrorAlert = false
 
+    // independent check of the signed psbt from the hardware wallet
+    @State private var verification: PsbtVerification? = nil
+    @State private var verificationError: String? = nil
+    @State private var isUnsafeConfirmed = false
+
     // popover to change btc and sats
     @State private var showingMenu: Bool = false
 
     // locking task, cancel if its screen is leaving
     @State private var lockingTask: Task<Void, Never>? = nil
 
+    // from the signed transaction once it's been checked, everything that leaves the wallet
+    var sendingAmount: Amount {
+        guard let verification else { return details.sendingAmount() }
+
+        let sats = verification.outputs
+            .filter {
+                switch $0.kind {
+                case .external, .otherWallet: true
+                case .change, .receive: false
+                }
+            }
+            .reduce(UInt64(0)) { $0 + $1.amount.asSats() }
+
+        return Amount.fromSat(sats: sats)
+    }
+
     var fiatAmount: String {
         guard let prices = prices ?? app.prices else {
             app.dispatch(action: .updateFiatPrices)
             return "---"
         }
 
-        let amount = details.sendingAmount()
+        let amount = sendingAmount
         return manager.rust.convertAndDisplayFiat(amount: amount, prices: prices)
     }
 
@@ -45,11 +66,28 @@ struct SendFlowConfirmScreen: View {
         manager.walletMetadata
     }
 
+    // a signed psbt is only safe if the check ran and found nothing, a failed check is not a pass
+    var isSafe: Bool {
+        guard signedPsbt != nil else { return true }
+        return verification?.isSafe == true
+    }
+
+    var canSend: Bool {
+        isSafe || isUnsafeConfirmed
+    }
+
     var body: some View {
         // signed psbt has not been finalized yet
         if let psbt = signedPsbt, signedTransaction == nil {
             FullPageLoadingView()
                 .task {
+                    do {
+                        verification = try await manager.rust.verifySignedPsbt(details: details, psbt: psbt)
+                    } catch {
+                        Log.error("Unable to verify signed PSBT: \(error)")
+                        verificationError = error.localizedDescription
+                    }
+
                     do {
                         signedTransaction = try await manager.rust.finalizePsbt(psbt: psbt)
                     } catch let error as WalletManagerError {
@@ -94,7 +132,7 @@ struct SendFlowConfirmScreen: View {
                             HStack(alignment: .bottom) {
                                 Spacer()
 
-                                Text(manager.amountFmt(details.sendingAmount()))
+                                Text(manager.amountFmt(sendingAmount))
                                     .frame(minWidth: screenWidth / 2)
                                     .font(.system(size: 48, weight: .bold))
                                     .minimumScaleFactor(0.01)
@@ -154,7 +192,27 @@ struct SendFlowConfirmScreen: View {
 
                         Divider()
 
-                        SendFlowDetailsView(manager: manager, details: details, prices: prices)
+                        // show what the hardware wallet actually signed, not what we exported
+                        if let verification {
+                            SendFlowSignedOutputsView(manager: manager, verification: verification)
+                        } else {
+                            SendFlowDetailsView(manager: manager, details: details, prices: prices)
+                        }
+
+                        if !isSafe {
+                            SendFlowPsbtWarningsView(
+                                manager: manager,
+                                warnings: verification?.warnings ?? [],
+                                verificationError: verificationError
+                            )
+
+                            Toggle(isOn: $isUnsafeConfirmed) {
+                                Text("I've reviewed the warnings and want to send this transaction anyway")
+                                    .font(.footnote)
+                                    .fontWeight(.medium)
+                            }
+                            .tint(.red)
+                        }
                     }
                 }
                 .scrollIndicators(.hidden)
@@ -163,6 +221,12 @@ struct SendFlowConfirmScreen: View {
                 .background(Color.coveBg)
 
                 SwipeToSendView(sendState: $sendState) {
+                    guard canSend else {
+                        sendState = .error("Review the warnings before sending this transaction")
+                        isShowingErrorAlert = true
+                        return
+                    }
+
                     sendState = .sending
                     Task {
                         do {
@@ -186,6 +250,8 @@ struct SendFlowConfirmScreen: View {
                     }
                 }
                 .frame(maxWidth: .infinity)
+                .disabled(!canSend)
+                .opacity(canSend ? 1 : 0.5)
                 .padding(.horizontal)
                 .padding(.bottom, 6)
                 .padding(.top, 20)
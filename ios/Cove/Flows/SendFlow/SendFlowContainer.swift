@@ -70,6 +70,8 @@ public struct SendFlowContainer: View {
             )
         case let .hardwareExport(id: id, details: details):
             SendFlowHardwareScreen(id: id, manager: manager, details: details)
+        case let .signPsbt(id: id, psbt: psbt):
+            SendFlowSignPsbtScreen(id: id, manager: manager, psbt: psbt)
         }
     }
 
@@ -48,10 +48,17 @@ struct SendFlowHardwareScreen: View {
     // file import
     @State private var isPresentingFilePicker = false
 
+    // signatures collected so far, only for multisig wallets
+    @State private var multisigStatus: MultisigSignatureStatus? = .none
+
     var metadata: WalletMetadata {
         manager.walletMetadata
     }
 
+    var isMultisig: Bool {
+        metadata.walletType == .multisig
+    }
+
     var fiatAmount: String {
         guard let prices = prices ?? app.prices else {
             app.dispatch(action: .updateFiatPrices)
@@ -226,6 +233,10 @@ struct SendFlowHardwareScreen: View {
                 onCompletion: handleFileImport
             )
             .onAppear {
+                if isMultisig {
+                    multisigStatus = try? manager.rust.multisigSignatureStatus(txId: details.id())
+                }
+
                 let total = details.outputs().count + details.inputs().count
                 if total == 3 { inputOutputDetailsPresentationSize = .height(300) }
                 if total > 3 { inputOutputDetailsPresentationSize = .height(400) }
@@ -257,6 +268,12 @@ struct SendFlowHardwareScreen: View {
     func handleFileImport(result: Result<URL, Error>) {
         do {
             let file = try result.get()
+
+            if isMultisig {
+                let data = try FileReader(for: file).readData()
+                return try importMultisigSignatures(data)
+            }
+
             let fileContents = try FileReader(for: file).read()
 
             let (txnRecord, signedTransaction) = try txnRecordAndSignedTxn(fileContents)
@@ -297,6 +314,29 @@ struct SendFlowHardwareScreen: View {
         }
     }
 
+    /// Add a cosigner's signed PSBT, once enough signatures are collected go to the confirm screen
+    func importMultisigSignatures(_ data: Data) throws {
+        let psbt = try signedPsbt(from: data)
+        let status = try manager.rust.addMultisigSignatures(psbt: psbt)
+        multisigStatus = status
+
+        guard status.isComplete else { return }
+
+        let signedPsbt = try manager.rust.multisigSignedPsbt(txId: details.id())
+        let route = RouteFactory().sendConfirm(id: id, details: details, signedPsbt: signedPsbt)
+        app.pushRoute(route)
+    }
+
+    /// Binary PSBT or base64 text
+    func signedPsbt(from data: Data) throws -> Psbt {
+        if let psbt = try? Psbt(data: data) { return psbt }
+
+        let string = String(decoding: data, as: UTF8.self)
+            .trimmingCharacters(in: .whitespacesAndNewlines)
+
+        return try Psbt(data: Data(base64Encoded: string) ?? data)
+    }
+
     func txnRecordAndSignedTxn(_ hex: String) throws -> (
         UnsignedTransactionRecord, BitcoinTransaction
     ) {
@@ -340,6 +380,13 @@ struct SendFlowHardwareScreen: View {
                     .foregroundColor(.secondary)
 
                 Spacer()
+
+                if let multisigStatus {
+                    Text("\(multisigStatus.collected) of \(multisigStatus.required) signatures")
+                        .font(.footnote)
+                        .fontWeight(.semibold)
+                        .foregroundColor(multisigStatus.isComplete ? .green : .secondary)
+                }
             }
 
             HStack {
@@ -443,6 +490,13 @@ struct SendFlowHardwareScreen: View {
             }
         }
 
+        Button("UR QR Code") {
+            let qrs = confirmDetailsPsbtToUr(
+                details: details, urType: .cryptoPsbt, maxFragmentLen: 200
+            )
+            sheetState = .init(.exportQr(qrs))
+        }
+
         Button("NFC") {
             app.nfcWriter.writeToTag(data: details.psbtBytes())
         }
@@ -476,6 +530,10 @@ struct SendFlowHardwareScreen: View {
             }
 
             do {
+                if isMultisig {
+                    return try importMultisigSignatures(Data(code.utf8))
+                }
+
                 let (txnRecord, signedTransaction) = try txnRecordAndSignedTxn(code)
                 let route = RouteFactory()
                     .sendConfirm(
@@ -33,6 +33,7 @@ extension WeakReconciler: SendFlowManagerReconciler where Reconciler == SendFlow
     var selectedFeeRate: FeeRateOptionWithTotalFee? = nil
     var feeRateOptions: FeeRateOptionsWithTotalFee? = nil
     var maxSelected: Amount? = nil
+    var recipients: [Recipient] = []
 
     // presenting
     var sendAmountFiat: String = ""
@@ -147,6 +148,10 @@ extension WeakReconciler: SendFlowManagerReconciler where Reconciler == SendFlow
         case let .updateFocusField(field):
             self.presenter.focusField = field
 
+        case let .updateRecipients(recipients):
+            self.refreshPresenters()
+            self.recipients = recipients
+
         case let .setAlert(alertState):
             Log.warn("setAlert: \(alertState)")
             self.presenter.alertState = .init(alertState)
@@ -0,0 +1,57 @@
+//
+//  SendFlowPsbtWarningsView.swift
+//  Cove
+//
+//  Problems found checking a signed PSBT against what we exported to the hardware wallet
+//
+
+import SwiftUI
+
+struct SendFlowPsbtWarningsView: View {
+    let manager: WalletManager
+    let warnings: [PsbtWarning]
+    var verificationError: String? = nil
+
+    var body: some View {
+        VStack(alignment: .leading, spacing: 10) {
+            Label("Review before sending", systemImage: "exclamationmark.triangle.fill")
+                .font(.headline)
+                .foregroundStyle(.red)
+
+            if let verificationError {
+                Text("Unable to check the signed transaction: \(verificationError)")
+                    .font(.footnote)
+                    .fixedSize(horizontal: false, vertical: true)
+            }
+
+            ForEach(Array(warnings.enumerated()), id: \.offset) { _, warning in
+                Text(description(warning))
+                    .font(.footnote)
+                    .fixedSize(horizontal: false, vertical: true)
+            }
+        }
+        .padding()
+        .frame(maxWidth: .infinity, alignment: .leading)
+        .background(Color.red.opacity(0.1))
+        .clipShape(RoundedRectangle(cornerRadius: 10))
+    }
+
+    func description(_ warning: PsbtWarning) -> String {
+        switch warning {
+        case let .changePathNotInWallet(index: index, path: path):
+            "Output \(index + 1) claims to be change at \(path), but that path isn't in this wallet"
+        case let .changeIndexTooHigh(index: index, derivationIndex: derivationIndex):
+            "Output \(index + 1) is change at index \(derivationIndex), too far ahead for the wallet to find"
+        case let .feeAboveConfirmed(confirmed: confirmed, actual: actual):
+            "The fee is \(manager.amountFmt(actual)), you confirmed \(manager.amountFmt(confirmed))"
+        case .unknownFee:
+            "Unable to work out the fee, the signed PSBT is missing input details"
+        case let .outputChanged(index: index):
+            "Output \(index + 1) changed after the transaction was exported"
+        case .inputsChanged:
+            "The inputs changed after the transaction was exported"
+        case let .nonDefaultSighash(index: index, sighash: sighash):
+            "Input \(index + 1) is signed with \(sighash) instead of ALL"
+        }
+    }
+}
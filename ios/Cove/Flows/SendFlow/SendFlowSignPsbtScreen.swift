@@ -0,0 +1,58 @@
+//
+//  SendFlowSignPsbtScreen.swift
+//  Cove
+//
+//  Review a PSBT from a coordinator or another device, hot wallets sign it here, cold wallets
+//  get the usual hardware export options
+//
+
+import SwiftUI
+
+struct SendFlowSignPsbtScreen: View {
+    let id: WalletId
+    let manager: WalletManager
+    let psbt: Psbt
+
+    @State private var details: ConfirmDetails? = .none
+    @State private var errorMessage: String? = .none
+
+    var body: some View {
+        Group {
+            if let details {
+                if manager.walletMetadata.walletType == .hot {
+                    SendFlowConfirmScreen(
+                        id: id, manager: manager, details: details,
+                        signedTransaction: nil, signedPsbt: nil
+                    )
+                } else {
+                    SendFlowHardwareScreen(id: id, manager: manager, details: details)
+                }
+            } else if let errorMessage {
+                VStack(spacing: 12) {
+                    Text("Unable to open PSBT")
+                        .font(.headline)
+
+                    Text(errorMessage)
+                        .font(.footnote)
+                        .foregroundStyle(.secondary)
+                        .multilineTextAlignment(.center)
+                }
+                .padding()
+            } else {
+                ProgressView()
+            }
+        }
+        .task { await loadDetails() }
+    }
+
+    func loadDetails() async {
+        guard details == nil else { return }
+
+        do {
+            details = try await manager.rust.confirmDetailsForPsbt(psbt: psbt)
+        } catch {
+            Log.error("Unable to get confirm details for PSBT: \(error)")
+            errorMessage = error.localizedDescription
+        }
+    }
+}
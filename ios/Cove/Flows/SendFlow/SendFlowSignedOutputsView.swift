@@ -0,0 +1,71 @@
+//
+//  SendFlowSignedOutputsView.swift
+//  Cove
+//
+//  The outputs of the signed transaction from the hardware wallet, what actually gets broadcast
+//
+
+import SwiftUI
+
+struct SendFlowSignedOutputsView: View {
+    let manager: WalletManager
+    let verification: PsbtVerification
+
+    var body: some View {
+        VStack(alignment: .leading, spacing: 12) {
+            Text("Signed transaction")
+                .font(.headline)
+                .fontWeight(.bold)
+
+            ForEach(verification.outputs, id: \.index) { output in
+                HStack(alignment: .top) {
+                    VStack(alignment: .leading, spacing: 4) {
+                        Text(kind(output.kind))
+                            .font(.footnote)
+                            .fontWeight(.medium)
+                            .foregroundStyle(.secondary)
+
+                        Text(output.address?.spacedOut() ?? "Unknown script")
+                            .font(.system(.footnote, design: .none))
+                            .fontWeight(.semibold)
+                            .lineLimit(3)
+                    }
+
+                    Spacer()
+
+                    Text(manager.amountFmt(output.amount))
+                        .font(.footnote)
+                        .fontWeight(.semibold)
+                }
+            }
+
+            HStack {
+                Text("Network Fee")
+                    .font(.footnote)
+                    .fontWeight(.medium)
+                    .foregroundStyle(.secondary)
+
+                Spacer()
+
+                Text(verification.fee.map { manager.amountFmt($0) } ?? "Unknown")
+                    .font(.footnote)
+                    .fontWeight(.medium)
+                    .foregroundStyle(.secondary)
+            }
+        }
+        .frame(maxWidth: .infinity, alignment: .leading)
+    }
+
+    func kind(_ kind: OutputKind) -> String {
+        switch kind {
+        case .external:
+            "Sending to"
+        case .change:
+            "Change"
+        case .receive:
+            "Your receive address"
+        case let .otherWallet(id: _, name: name):
+            "To your wallet \(name)"
+        }
+    }
+}
@@ -0,0 +1,210 @@
+//
+//  SignMessageScreen.swift
+//  Cove
+//
+//  Prove ownership of one of the wallet's addresses, hot wallets sign here, cold wallets sign the
+//  BIP322 PSBT on the hardware wallet
+//
+
+import SwiftUI
+
+struct SignMessageScreen: View {
+    @Environment(\.dismiss) private var dismiss
+
+    let manager: WalletManager
+
+    @State private var addresses: [AddressInfoWithDerivation] = []
+    @State private var selectedIndex: Int? = .none
+    @State private var isLoadingAddresses = true
+    @State private var message = ""
+    @State private var format: MessageSignatureFormat? = .none
+    @State private var signature: String? = .none
+    @State private var qrs: [QrCodeView] = []
+    @State private var errorMessage: String? = .none
+
+    // animated ur
+    private let startedAt: Date = .now
+    private let every: TimeInterval = 0.250
+
+    var isHot: Bool {
+        manager.walletMetadata.walletType == .hot
+    }
+
+    var address: AddressInfoWithDerivation? {
+        guard let selectedIndex, addresses.indices.contains(selectedIndex) else { return .none }
+        return addresses[selectedIndex]
+    }
+
+    var body: some View {
+        NavigationStack {
+            Form {
+                Section(header: Text("Address")) {
+                    if isLoadingAddresses {
+                        ProgressView()
+                    } else if addresses.isEmpty {
+                        Text("None of this wallet's addresses have received bitcoin yet")
+                            .font(.footnote)
+                            .foregroundStyle(.secondary)
+
+                        Button("Use Next Receive Address") { Task { await loadNextAddress() } }
+                    } else {
+                        Picker("Address", selection: $selectedIndex) {
+                            ForEach(Array(addresses.enumerated()), id: \.offset) { index, address in
+                                Text(address.addressSpacedOut())
+                                    .font(.system(.footnote, design: .monospaced))
+                                    .tag(Int?.some(index))
+                            }
+                        }
+                        .pickerStyle(.navigationLink)
+                    }
+
+                    if let address {
+                        Text(address.addressSpacedOut())
+                            .font(.system(.footnote, design: .monospaced))
+                            .textSelection(.enabled)
+
+                        if let derivationPath = address.derivationPath() {
+                            Text(derivationPath)
+                                .font(.caption)
+                                .foregroundStyle(.secondary)
+                        }
+                    }
+                }
+
+                Section(header: Text("Message")) {
+                    TextField("Message to sign", text: $message, axis: .vertical)
+                        .lineLimit(3 ... 8)
+
+                    Picker("Format", selection: $format) {
+                        Text("Default").tag(MessageSignatureFormat?.none)
+                        if isHot {
+                            Text("Legacy").tag(MessageSignatureFormat?.some(.legacy))
+                        }
+                        Text("BIP322 Simple").tag(MessageSignatureFormat?.some(.bip322Simple))
+                        Text("BIP322 Full").tag(MessageSignatureFormat?.some(.bip322Full))
+                    }
+                }
+
+                Section {
+                    if isHot {
+                        Button("Sign Message") { signMessage() }
+                            .disabled(message.isEmpty || address == nil)
+                    } else {
+                        Button("Create PSBT for Hardware Wallet") { Task { await createPsbt() } }
+                            .disabled(message.isEmpty || address == nil)
+
+                        if !qrs.isEmpty {
+                            TimelineView(.periodic(from: startedAt, by: every)) { context in
+                                let index = abs(
+                                    Int(context.date.distance(to: startedAt) / every) % qrs.count)
+                                qrs[index]
+                            }
+
+                            Button("Paste Signed PSBT") { pasteSignedPsbt() }
+                        }
+                    }
+                }
+
+                if let signature {
+                    Section(header: Text("Signature")) {
+                        Text(signature)
+                            .font(.system(.footnote, design: .monospaced))
+                            .textSelection(.enabled)
+
+                        Button("Copy", systemImage: "doc.on.doc") {
+                            UIPasteboard.general.string = signature
+                        }
+                    }
+                }
+
+                if let errorMessage {
+                    Text(errorMessage)
+                        .font(.footnote)
+                        .foregroundStyle(.red)
+                }
+            }
+            .navigationTitle("Sign Message")
+            .navigationBarTitleDisplayMode(.inline)
+            .toolbar {
+                ToolbarItem(placement: .cancellationAction) {
+                    Button("Done") { dismiss() }
+                }
+            }
+        }
+        .task { await loadAddresses() }
+        .onChange(of: selectedIndex) { _, _ in
+            signature = .none
+            qrs = []
+        }
+    }
+
+    // ownership is proven for addresses that have been paid, so those are the ones to pick from
+    func loadAddresses() async {
+        defer { isLoadingAddresses = false }
+
+        do {
+            addresses = try await manager.rust.usedAddresses()
+            selectedIndex = addresses.isEmpty ? .none : 0
+        } catch {
+            Log.error("Unable to get addresses to sign with: \(error)")
+            errorMessage = error.localizedDescription
+        }
+    }
+
+    func loadNextAddress() async {
+        do {
+            let next = try await manager.rust.nextAddress()
+            addresses.append(next)
+            selectedIndex = addresses.count - 1
+        } catch {
+            Log.error("Unable to get address to sign with: \(error)")
+            errorMessage = error.localizedDescription
+        }
+    }
+
+    func signMessage() {
+        guard let address else { return }
+        errorMessage = .none
+
+        do {
+            signature = try manager.rust.signMessage(
+                address: address, message: message, format: format)
+        } catch {
+            Log.error("Unable to sign message: \(error)")
+            errorMessage = error.localizedDescription
+        }
+    }
+
+    func createPsbt() async {
+        guard let address else { return }
+        errorMessage = .none
+
+        do {
+            let psbt = try await manager.rust.messageSigningPsbt(address: address, message: message)
+            qrs = psbtUrParts(psbt: psbt, urType: .cryptoPsbt, maxFragmentLen: 200)
+                .map { QrCodeView(text: $0) }
+        } catch {
+            Log.error("Unable to create message signing PSBT: \(error)")
+            errorMessage = error.localizedDescription
+        }
+    }
+
+    func pasteSignedPsbt() {
+        errorMessage = .none
+
+        // hardware wallets can't do legacy signatures, simple needs native segwit or taproot
+        let addressType = manager.walletMetadata.addressType
+        let defaultFormat: MessageSignatureFormat =
+            addressType == .nativeSegwit || addressType == .taproot ? .bip322Simple : .bip322Full
+
+        do {
+            let string = UIPasteboard.general.string ?? ""
+            let data = Data(base64Encoded: string.trimmingCharacters(in: .whitespacesAndNewlines))
+            let psbt = try Psbt(data: data ?? Data(string.utf8))
+            signature = try messageSignatureFromPsbt(psbt: psbt, format: format ?? defaultFormat)
+        } catch {
+            Log.error("Unable to get signature from PSBT: \(error)")
+            errorMessage = error.localizedDescription
+        }
+    }
+}
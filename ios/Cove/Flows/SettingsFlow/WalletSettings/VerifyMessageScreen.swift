@@ -0,0 +1,81 @@
+//
+//  VerifyMessageScreen.swift
+//  Cove
+//
+//  Verify a legacy or BIP322 signed message for any address
+//
+
+import SwiftUI
+
+struct VerifyMessageScreen: View {
+    @Environment(\.dismiss) private var dismiss
+
+    let network: Network
+
+    @State private var address = ""
+    @State private var message = ""
+    @State private var signature = ""
+    @State private var isValid: Bool? = .none
+    @State private var errorMessage: String? = .none
+
+    var body: some View {
+        NavigationStack {
+            Form {
+                Section(header: Text("Address")) {
+                    TextField("Address", text: $address)
+                        .autocorrectionDisabled()
+                        .textInputAutocapitalization(.never)
+                }
+
+                Section(header: Text("Message")) {
+                    TextField("Signed message", text: $message, axis: .vertical)
+                        .lineLimit(3 ... 8)
+                }
+
+                Section(header: Text("Signature")) {
+                    TextField("Base64 signature", text: $signature, axis: .vertical)
+                        .autocorrectionDisabled()
+                        .textInputAutocapitalization(.never)
+                }
+
+                Section {
+                    Button("Verify") { verify() }
+                        .disabled(address.isEmpty || signature.isEmpty)
+
+                    if let isValid {
+                        Label(
+                            isValid ? "Valid signature" : "Signature does not match",
+                            systemImage: isValid ? "checkmark.seal.fill" : "xmark.seal.fill"
+                        )
+                        .foregroundStyle(isValid ? .green : .red)
+                    }
+
+                    if let errorMessage {
+                        Text(errorMessage)
+                            .font(.footnote)
+                            .foregroundStyle(.red)
+                    }
+                }
+            }
+            .navigationTitle("Verify Message")
+            .navigationBarTitleDisplayMode(.inline)
+            .toolbar {
+                ToolbarItem(placement: .cancellationAction) {
+                    Button("Done") { dismiss() }
+                }
+            }
+        }
+    }
+
+    func verify() {
+        isValid = .none
+        errorMessage = .none
+
+        do {
+            let address = try Address.fromString(address: address, network: network)
+            isValid = try verifyMessage(address: address, message: message, signature: signature)
+        } catch {
+            errorMessage = error.localizedDescription
+        }
+    }
+}
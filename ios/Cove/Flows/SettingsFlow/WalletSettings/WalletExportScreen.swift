@@ -0,0 +1,102 @@
+//
+//  WalletExportScreen.swift
+//  Cove
+//
+//  Export the wallet's public descriptors to Sparrow, Bitcoin Core, Specter and others
+//
+
+import SwiftUI
+
+struct WalletExportScreen: View {
+    @Environment(\.dismiss) private var dismiss
+
+    let manager: WalletManager
+    let format: WalletExportFormat
+
+    @State private var export: WalletExport? = .none
+    @State private var qrs: [QrCodeView] = []
+    @State private var errorMessage: String? = .none
+
+    // animated bbqr or ur
+    private let startedAt: Date = .now
+    private let every: TimeInterval = 0.250
+
+    var body: some View {
+        NavigationStack {
+            ScrollView {
+                VStack(spacing: 20) {
+                    if let export {
+                        ExportQr
+
+                        Text(export.fileName())
+                            .font(.footnote)
+                            .foregroundStyle(.secondary)
+
+                        HStack(spacing: 20) {
+                            Button("Copy", systemImage: "doc.on.doc") {
+                                UIPasteboard.general.string = export.content()
+                            }
+
+                            ShareLink(item: export.content()) {
+                                Label("Share", systemImage: "square.and.arrow.up")
+                            }
+                        }
+                    } else if let errorMessage {
+                        Text(errorMessage)
+                            .font(.footnote)
+                            .foregroundStyle(.red)
+                            .multilineTextAlignment(.center)
+                    } else {
+                        ProgressView()
+                    }
+                }
+                .padding()
+            }
+            .navigationTitle(walletExportFormatToString(format: format))
+            .navigationBarTitleDisplayMode(.inline)
+            .toolbar {
+                ToolbarItem(placement: .cancellationAction) {
+                    Button("Done") { dismiss() }
+                }
+            }
+        }
+        .task { await loadExport() }
+    }
+
+    @ViewBuilder
+    var ExportQr: some View {
+        if !qrs.isEmpty {
+            TimelineView(.periodic(from: startedAt, by: every)) { context in
+                let index = abs(Int(context.date.distance(to: startedAt) / every) % qrs.count)
+                qrs[index]
+            }
+
+            if qrs.count > 1 {
+                Text("Scan all the parts of this animated QR")
+                    .font(.footnote)
+                    .foregroundStyle(.secondary)
+            }
+        }
+    }
+
+    func loadExport() async {
+        do {
+            let export = try await manager.rust.exportWallet(format: format)
+            let parts = try export.isUr() ? export.toUr(maxFragmentLen: 200) : export.toBbqr()
+            qrs = parts.map { QrCodeView(text: $0) }
+            self.export = export
+        } catch {
+            Log.error("Unable to export wallet: \(error)")
+            errorMessage = error.localizedDescription
+        }
+    }
+}
+
+#Preview {
+    AsyncPreview {
+        WalletExportScreen(
+            manager: WalletManager(preview: "preview_only"),
+            format: .descriptor
+        )
+    }
+}
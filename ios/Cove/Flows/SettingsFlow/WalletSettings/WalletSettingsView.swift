@@ -9,6 +9,16 @@ struct WalletSettingsView: View {
 
     @State private var showingDeleteConfirmation = false
     @State private var showingSecretWordsConfirmation = false
+    @State private var showingBip85 = false
+    @State private var exportFormat: WalletExportFormat? = .none
+    @State private var showingSignMessage = false
+    @State private var showingVerifyMessage = false
+
+    // accounts
+    @State private var isDiscoveringAccounts = false
+    @State private var discoveredAccounts: [UInt32]? = .none
+    @State private var newAccount = 1
+    @State private var alertMessage: String? = .none
 
     init(manager: WalletManager) {
         self.manager = manager
@@ -59,6 +69,33 @@ struct WalletSettingsView: View {
                         .foregroundColor(.secondary)
                 }
                 .font(.subheadline)
+
+                if metadata.account > 0 {
+                    HStack {
+                        Text("Account")
+                        Spacer()
+                        Text(String(metadata.account))
+                            .foregroundColor(.secondary)
+                    }
+                    .font(.subheadline)
+                }
+            }
+
+            if metadata.walletType == .hot {
+                AccountsSection
+            }
+
+            ExportSection
+
+            Section(
+                header: Text("Messages"),
+                footer: Text("Prove you own an address to an exchange or auditor")
+            ) {
+                Button("Sign Message") { showingSignMessage = true }
+                    .font(.subheadline)
+
+                Button("Verify Message") { showingVerifyMessage = true }
+                    .font(.subheadline)
             }
 
             Section(header: Text("Settings")) {
@@ -137,6 +174,13 @@ struct WalletSettingsView: View {
                         Text("View Secret Words")
                             .font(.subheadline)
                     }
+
+                    Button {
+                        showingBip85 = true
+                    } label: {
+                        Text("Derive Child Keys (BIP85)")
+                            .font(.subheadline)
+                    }
                 }
 
                 Button {
@@ -173,10 +217,114 @@ struct WalletSettingsView: View {
                 )
             }
         }
+        .alert(
+            "Accounts",
+            isPresented: Binding(
+                get: { alertMessage != nil },
+                set: { if !$0 { alertMessage = nil } }
+            ),
+            actions: { Button("OK") {} },
+            message: { Text(alertMessage ?? "") }
+        )
+        .sheet(isPresented: $showingBip85) {
+            Bip85Screen(id: manager.walletMetadata.id)
+        }
+        .sheet(item: $exportFormat) { format in
+            WalletExportScreen(manager: manager, format: format)
+        }
+        .sheet(isPresented: $showingSignMessage) {
+            SignMessageScreen(manager: manager)
+        }
+        .sheet(isPresented: $showingVerifyMessage) {
+            VerifyMessageScreen(network: metadata.network)
+        }
         .onDisappear { manager.validateMetadata() }
         .onAppear { manager.validateMetadata() }
         .scrollContentBackground(.hidden)
     }
+
+    @ViewBuilder
+    var ExportSection: some View {
+        Section(
+            header: Text("Export"),
+            footer: Text("Public keys only, to watch or co-sign this wallet in another wallet")
+        ) {
+            ForEach(
+                [
+                    WalletExportFormat.descriptor, .bitcoinCore, .specter, .coldcard,
+                    .cryptoOutput, .cryptoAccount,
+                ],
+                id: \.self
+            ) { format in
+                Button(walletExportFormatToString(format: format)) { exportFormat = format }
+                    .font(.subheadline)
+            }
+        }
+    }
+
+    @ViewBuilder
+    var AccountsSection: some View {
+        Section(
+            header: Text("Accounts"),
+            footer: Text("Each account of these secret words is added as its own wallet")
+        ) {
+            if let discoveredAccounts {
+                if discoveredAccounts.isEmpty {
+                    Text("No other used accounts found")
+                        .font(.subheadline)
+                        .foregroundColor(.secondary)
+                }
+
+                ForEach(discoveredAccounts, id: \.self) { account in
+                    Button("Add Account \(account)") { addAccount(account) }
+                        .font(.subheadline)
+                }
+            }
+
+            Button {
+                discoverAccounts()
+            } label: {
+                HStack {
+                    Text("Find Used Accounts")
+                    Spacer()
+                    if isDiscoveringAccounts { ProgressView() }
+                }
+                .font(.subheadline)
+            }
+            .disabled(isDiscoveringAccounts)
+
+            Stepper("Account \(newAccount)", value: $newAccount, in: 1 ... 100)
+                .font(.subheadline)
+
+            Button("Add Account \(newAccount)") { addAccount(UInt32(newAccount)) }
+                .font(.subheadline)
+        }
+    }
+
+    func discoverAccounts() {
+        isDiscoveringAccounts = true
+
+        Task {
+            do {
+                discoveredAccounts = try await manager.rust.discoverAccounts()
+            } catch {
+                Log.error("Unable to discover accounts: \(error)")
+                alertMessage = error.localizedDescription
+            }
+
+            isDiscoveringAccounts = false
+        }
+    }
+
+    func addAccount(_ account: UInt32) {
+        do {
+            let metadata = try manager.rust.addAccount(account: account)
+            app.resetRoute(to: .selectedWallet(metadata.id))
+        } catch {
+            Log.error("Unable to add account: \(error)")
+            alertMessage = error.localizedDescription
+        }
+    }
 }
 
 #Preview {
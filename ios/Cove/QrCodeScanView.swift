@@ -107,6 +107,21 @@ struct QrCodeScanView: View {
                         return newMultiQr
                     }()
 
+            // animated UR, fountain coded so parts can come in any order
+            if multiQr.isUr() {
+                switch try multiQr.handleScanResult(qr: qr) {
+                case let .completedUr(joined):
+                    scanComplete = true
+                    scannedCode = TaggedItem(StringOrData.string(try joined.finalResult()))
+                    dismiss()
+                case let .inProgressUr(left):
+                    partsLeft = Int(left)
+                default: break
+                }
+
+                return
+            }
+
             // single QR
             if !multiQr.isBbqr() {
                 scanComplete = true
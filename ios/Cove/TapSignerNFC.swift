@@ -32,8 +32,8 @@ class TapSignerNFC {
         }
     }
 
-    public func derive(pin: String) async -> Result<DeriveInfo, TapSignerReaderError> {
-        await performTapSignerCmd(cmd: .derive(pin: pin)) { $0?.deriveResponse }
+    public func derive(pin: String, account: UInt32 = 0) async -> Result<DeriveInfo, TapSignerReaderError> {
+        await performTapSignerCmd(cmd: .derive(pin: pin, account: account)) { $0?.deriveResponse }
     }
 
     public func changePin(currentPin: String, newPin: String) async -> Result<
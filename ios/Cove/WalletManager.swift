@@ -208,6 +208,11 @@ extension WeakReconciler: WalletManagerReconciler where Reconciler == WalletMana
 
         case let .sendFlowError(error):
             self.sendFlowErrorAlert = TaggedItem(error)
+
+        case let .replacementConfirmed(confirmed, replaced):
+            let confirmed = confirmed.asHashString()
+            let replaced = replaced.asHashString()
+            self.logger.debug("replacement confirmed: \(confirmed), replaced: \(replaced)")
         }
     }
 
@@ -46,6 +46,7 @@ public extension SendRoute {
         case let .coinControlSetAmount(id: id, utxos: _): id
         case let .confirm(args): args.id
         case let .hardwareExport(id: id, details: _): id
+        case let .signPsbt(id: id, psbt: _): id
         }
     }
 }
@@ -185,6 +186,12 @@ extension TapSigner: @retroactive Equatable {
     }
 }
 
+extension SweepKey: @retroactive Equatable {
+    public static func == (lhs: SweepKey, rhs: SweepKey) -> Bool {
+        lhs.isEqual(rhs: rhs)
+    }
+}
+
 public extension WalletMetadata {
     func isTapSigner() -> Bool {
         hardwareMetadata?.isTapSigner() ?? false
@@ -1,5 +1,26 @@
+mod avoid_change;
 mod cove_default;
 mod deterministic_random_draw;
+mod largest_first;
+mod oldest_first;
+mod privacy;
 
+#[cfg(test)]
+mod test_util;
+
+pub use avoid_change::AvoidChangeCoinSelection;
 pub use cove_default::CoveDefaultCoinSelection;
 pub use deterministic_random_draw::DeterministicRandomDraw;
+pub use largest_first::LargestFirstCoinSelection;
+pub use oldest_first::OldestFirstCoinSelection;
+pub use privacy::PrivacyCoinSelection;
+
+/// From default set in BDK
+const DEFAULT_SIZE_OF_CHANGE: u64 = 8 + 1 + 22;
+
+/// Size of a change output paying to the script, amount, script length and the script itself
+///
+/// The default assumes a P2WPKH change output, taproot change outputs are 12 bytes bigger
+pub fn size_of_change(change_script: &bdk_wallet::bitcoin::Script) -> u64 {
+    8 + 1 + change_script.len() as u64
+}
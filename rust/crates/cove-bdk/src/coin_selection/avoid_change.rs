@@ -0,0 +1,134 @@
+use bdk_wallet::{
+    WeightedUtxo,
+    bitcoin::{Amount, FeeRate, Script, key::rand::RngCore},
+    coin_selection::{
+        BranchAndBoundCoinSelection, CoinSelectionAlgorithm, CoinSelectionResult, Excess,
+        InsufficientFunds,
+    },
+};
+
+use super::{DEFAULT_SIZE_OF_CHANGE, LargestFirstCoinSelection};
+use crate::util::select_sorted_utxos;
+
+/// Try to build a transaction without a change output
+///
+/// First searches for a combination of UTXOs that matches the target (branch and bound), then
+/// for a single UTXO that covers the target with only a dust amount left over, which goes to
+/// the fee. If neither is possible it falls back to largest first, which creates change.
+#[derive(Debug, Clone)]
+pub struct AvoidChangeCoinSelection(BranchAndBoundCoinSelection<LargestFirstCoinSelection>);
+
+impl AvoidChangeCoinSelection {
+    pub fn new(size_of_change: u64) -> Self {
+        Self(BranchAndBoundCoinSelection::new(size_of_change, LargestFirstCoinSelection))
+    }
+}
+
+impl Default for AvoidChangeCoinSelection {
+    fn default() -> Self {
+        Self::new(DEFAULT_SIZE_OF_CHANGE)
+    }
+}
+
+impl CoinSelectionAlgorithm for AvoidChangeCoinSelection {
+    fn coin_select<R: RngCore>(
+        &self,
+        required_utxos: Vec<WeightedUtxo>,
+        mut optional_utxos: Vec<WeightedUtxo>,
+        fee_rate: FeeRate,
+        target_amount: Amount,
+        drain_script: &Script,
+        rand: &mut R,
+    ) -> Result<CoinSelectionResult, InsufficientFunds> {
+        let result = self.0.coin_select(
+            required_utxos.clone(),
+            optional_utxos.clone(),
+            fee_rate,
+            target_amount,
+            drain_script,
+            rand,
+        )?;
+
+        if is_changeless(&result) {
+            return Ok(result);
+        }
+
+        // smallest UTXO first so the least amount possible is lost to fees
+        optional_utxos.sort_by_key(|utxo| utxo.utxo.txout().value);
+
+        for optional_utxo in optional_utxos {
+            let utxos = required_utxos
+                .iter()
+                .cloned()
+                .chain(std::iter::once(optional_utxo))
+                .map(|utxo| (true, utxo));
+
+            match select_sorted_utxos(utxos, fee_rate, target_amount, drain_script) {
+                Ok(single) if is_changeless(&single) => return Ok(single),
+                _ => continue,
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+fn is_changeless(result: &CoinSelectionResult) -> bool {
+    matches!(result.excess, Excess::NoChange { .. })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coin_selection::test_util::*;
+
+    #[test]
+    fn test_exact_match_has_no_change() {
+        let utxos = vec![utxo(0, 75_000), utxo(1, 30_000), utxo(2, 20_000), utxo(3, 110_000)];
+
+        let result = AvoidChangeCoinSelection::default()
+            .coin_select(
+                vec![],
+                utxos,
+                FeeRate::ZERO,
+                Amount::from_sat(50_000),
+                &drain(),
+                &mut rng(),
+            )
+            .unwrap();
+
+        assert!(is_changeless(&result));
+        assert_eq!(selected_values(&result).iter().sum::<u64>(), 50_000);
+    }
+
+    #[test]
+    fn test_single_utxo_with_dust_left_over() {
+        let utxos = vec![utxo(0, 100_000), utxo(1, 50_200)];
+
+        let result = AvoidChangeCoinSelection::default()
+            .coin_select(
+                vec![],
+                utxos,
+                FeeRate::ZERO,
+                Amount::from_sat(50_000),
+                &drain(),
+                &mut rng(),
+            )
+            .unwrap();
+
+        assert!(is_changeless(&result));
+        assert_eq!(selected_indexes(&result), vec![1]);
+    }
+
+    #[test]
+    fn test_falls_back_to_change() {
+        let utxos = vec![utxo(0, 100_000), utxo(1, 90_000)];
+
+        let result = AvoidChangeCoinSelection::default()
+            .coin_select(vec![], utxos, fee_rate(), Amount::from_sat(50_000), &drain(), &mut rng())
+            .unwrap();
+
+        assert!(!is_changeless(&result));
+        assert_eq!(selected_indexes(&result), vec![0]);
+    }
+}
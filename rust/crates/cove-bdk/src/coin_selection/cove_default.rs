@@ -1,24 +1,37 @@
-use super::DeterministicRandomDraw;
+use std::collections::HashSet;
+
+use super::{DEFAULT_SIZE_OF_CHANGE, DeterministicRandomDraw};
 use bdk_wallet::{
     WeightedUtxo,
-    bitcoin::{Amount, FeeRate, Script, key::rand::RngCore},
+    bitcoin::{Amount, FeeRate, OutPoint, Script, key::rand::RngCore},
     coin_selection::{
         BranchAndBoundCoinSelection, CoinSelectionAlgorithm, CoinSelectionResult, InsufficientFunds,
     },
 };
 
 #[derive(Debug, Clone, Default)]
-pub struct CoveDefaultCoinSelection(BranchAndBoundCoinSelection<DeterministicRandomDraw>);
+pub struct CoveDefaultCoinSelection {
+    inner: BranchAndBoundCoinSelection<DeterministicRandomDraw>,
 
-/// From default set in BDK
-const DEFAULT_SIZE_OF_CHANGE: u64 = 8 + 1 + 22;
+    /// utxos the user froze, never selected even if they are available
+    frozen: HashSet<OutPoint>,
+}
 
 impl CoveDefaultCoinSelection {
     pub fn new(seed: u64) -> Self {
-        Self(BranchAndBoundCoinSelection::new(
-            DEFAULT_SIZE_OF_CHANGE,
-            DeterministicRandomDraw::new(seed),
-        ))
+        Self::new_with_size_of_change(seed, DEFAULT_SIZE_OF_CHANGE)
+    }
+
+    pub fn new_with_size_of_change(seed: u64, size_of_change: u64) -> Self {
+        let inner =
+            BranchAndBoundCoinSelection::new(size_of_change, DeterministicRandomDraw::new(seed));
+
+        Self { inner, frozen: HashSet::new() }
+    }
+
+    pub fn with_frozen(mut self, frozen: impl IntoIterator<Item = OutPoint>) -> Self {
+        self.frozen = frozen.into_iter().collect();
+        self
     }
 }
 
@@ -26,13 +39,17 @@ impl CoinSelectionAlgorithm for CoveDefaultCoinSelection {
     fn coin_select<R: RngCore>(
         &self,
         required_utxos: Vec<WeightedUtxo>,
-        optional_utxos: Vec<WeightedUtxo>,
+        mut optional_utxos: Vec<WeightedUtxo>,
         fee_rate: FeeRate,
         target_amount: Amount,
         drain_script: &Script,
         rand: &mut R,
     ) -> Result<CoinSelectionResult, InsufficientFunds> {
-        self.0.coin_select(
+        if !self.frozen.is_empty() {
+            optional_utxos.retain(|utxo| !self.frozen.contains(&utxo.utxo.outpoint()));
+        }
+
+        self.inner.coin_select(
             required_utxos,
             optional_utxos,
             fee_rate,
@@ -0,0 +1,84 @@
+use bdk_wallet::{
+    WeightedUtxo,
+    bitcoin::{Amount, FeeRate, Script, key::rand::RngCore},
+    coin_selection::{CoinSelectionAlgorithm, CoinSelectionResult, InsufficientFunds},
+};
+
+use crate::util::select_sorted_utxos;
+
+/// Spend the largest UTXOs first, uses the fewest inputs possible
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LargestFirstCoinSelection;
+
+impl CoinSelectionAlgorithm for LargestFirstCoinSelection {
+    fn coin_select<R: RngCore>(
+        &self,
+        required_utxos: Vec<WeightedUtxo>,
+        mut optional_utxos: Vec<WeightedUtxo>,
+        fee_rate: FeeRate,
+        target_amount: Amount,
+        drain_script: &Script,
+        _rand: &mut R,
+    ) -> Result<CoinSelectionResult, InsufficientFunds> {
+        optional_utxos.sort_by_key(|utxo| std::cmp::Reverse(utxo.utxo.txout().value));
+
+        let utxos = required_utxos
+            .into_iter()
+            .map(|utxo| (true, utxo))
+            .chain(optional_utxos.into_iter().map(|utxo| (false, utxo)));
+
+        select_sorted_utxos(utxos, fee_rate, target_amount, drain_script)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coin_selection::test_util::*;
+
+    #[test]
+    fn test_selects_largest_utxo() {
+        let utxos = vec![utxo(0, 20_000), utxo(1, 90_000), utxo(2, 50_000)];
+
+        let result = LargestFirstCoinSelection
+            .coin_select(vec![], utxos, fee_rate(), Amount::from_sat(40_000), &drain(), &mut rng())
+            .unwrap();
+
+        assert_eq!(selected_values(&result), vec![90_000]);
+    }
+
+    #[test]
+    fn test_required_utxos_are_always_used() {
+        let required = vec![utxo(0, 10_000)];
+        let optional = vec![utxo(1, 90_000), utxo(2, 50_000)];
+
+        let result = LargestFirstCoinSelection
+            .coin_select(
+                required,
+                optional,
+                fee_rate(),
+                Amount::from_sat(40_000),
+                &drain(),
+                &mut rng(),
+            )
+            .unwrap();
+
+        assert_eq!(selected_values(&result), vec![10_000, 90_000]);
+    }
+
+    #[test]
+    fn test_insufficient_funds() {
+        let utxos = vec![utxo(0, 20_000), utxo(1, 10_000)];
+
+        let result = LargestFirstCoinSelection.coin_select(
+            vec![],
+            utxos,
+            fee_rate(),
+            Amount::from_sat(40_000),
+            &drain(),
+            &mut rng(),
+        );
+
+        assert!(result.is_err());
+    }
+}
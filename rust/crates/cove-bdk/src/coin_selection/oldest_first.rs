@@ -0,0 +1,64 @@
+use bdk_wallet::{
+    WeightedUtxo,
+    bitcoin::{Amount, FeeRate, Script, key::rand::RngCore},
+    coin_selection::{CoinSelectionAlgorithm, CoinSelectionResult, InsufficientFunds},
+};
+
+use crate::util::{confirmation_height, select_sorted_utxos};
+
+/// Spend the oldest UTXOs first, unconfirmed UTXOs are only used as a last resort
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OldestFirstCoinSelection;
+
+impl CoinSelectionAlgorithm for OldestFirstCoinSelection {
+    fn coin_select<R: RngCore>(
+        &self,
+        required_utxos: Vec<WeightedUtxo>,
+        mut optional_utxos: Vec<WeightedUtxo>,
+        fee_rate: FeeRate,
+        target_amount: Amount,
+        drain_script: &Script,
+        _rand: &mut R,
+    ) -> Result<CoinSelectionResult, InsufficientFunds> {
+        optional_utxos.sort_by_key(|utxo| confirmation_height(utxo).unwrap_or(u32::MAX));
+
+        let utxos = required_utxos
+            .into_iter()
+            .map(|utxo| (true, utxo))
+            .chain(optional_utxos.into_iter().map(|utxo| (false, utxo)));
+
+        select_sorted_utxos(utxos, fee_rate, target_amount, drain_script)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coin_selection::test_util::*;
+
+    #[test]
+    fn test_selects_oldest_utxos() {
+        let utxos = vec![
+            confirmed_utxo(0, 50_000, 300),
+            confirmed_utxo(1, 50_000, 100),
+            confirmed_utxo(2, 50_000, 200),
+        ];
+
+        let result = OldestFirstCoinSelection
+            .coin_select(vec![], utxos, fee_rate(), Amount::from_sat(60_000), &drain(), &mut rng())
+            .unwrap();
+
+        assert_eq!(selected_indexes(&result), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_unconfirmed_utxos_are_used_last() {
+        let utxos = vec![utxo(0, 100_000), confirmed_utxo(1, 50_000, 800_000)];
+
+        let result = OldestFirstCoinSelection
+            .coin_select(vec![], utxos, fee_rate(), Amount::from_sat(40_000), &drain(), &mut rng())
+            .unwrap();
+
+        assert_eq!(selected_indexes(&result), vec![1]);
+    }
+}
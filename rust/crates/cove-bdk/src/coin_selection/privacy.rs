@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+
+use bdk_wallet::{
+    WeightedUtxo,
+    bitcoin::{Amount, FeeRate, OutPoint, Script, ScriptBuf, key::rand::RngCore},
+    coin_selection::{CoinSelectionAlgorithm, CoinSelectionResult, InsufficientFunds},
+};
+
+use crate::util::select_sorted_utxos;
+
+/// Avoid linking UTXOs from different sources in the same transaction
+///
+/// UTXOs are grouped into clusters by their label, unlabelled UTXOs are grouped by address.
+/// The smallest cluster that can pay for the transaction on its own is used. If no single
+/// cluster has enough, the largest clusters are combined so as few as possible are mixed.
+#[derive(Debug, Clone, Default)]
+pub struct PrivacyCoinSelection {
+    labels: HashMap<OutPoint, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Cluster {
+    Label(String),
+    Address(ScriptBuf),
+}
+
+impl PrivacyCoinSelection {
+    /// Labels for the wallets UTXOs, usually the BIP329 label of the output or its transaction
+    pub fn new(labels: impl IntoIterator<Item = (OutPoint, String)>) -> Self {
+        let labels = labels.into_iter().filter(|(_, label)| !label.trim().is_empty()).collect();
+        Self { labels }
+    }
+
+    fn cluster(&self, utxo: &WeightedUtxo) -> Cluster {
+        match self.labels.get(&utxo.utxo.outpoint()) {
+            Some(label) => Cluster::Label(label.clone()),
+            None => Cluster::Address(utxo.utxo.txout().script_pubkey.clone()),
+        }
+    }
+
+    /// Group the UTXOs by cluster, UTXOs in each group are sorted largest first
+    fn clusters(&self, utxos: Vec<WeightedUtxo>) -> Vec<(Amount, Vec<WeightedUtxo>)> {
+        let mut clusters: HashMap<Cluster, Vec<WeightedUtxo>> = HashMap::new();
+        for utxo in utxos {
+            clusters.entry(self.cluster(&utxo)).or_default().push(utxo);
+        }
+
+        clusters
+            .into_values()
+            .map(|mut utxos| {
+                utxos.sort_by_key(|utxo| std::cmp::Reverse(utxo.utxo.txout().value));
+                let total = utxos.iter().map(|utxo| utxo.utxo.txout().value).sum();
+                (total, utxos)
+            })
+            .collect()
+    }
+}
+
+impl CoinSelectionAlgorithm for PrivacyCoinSelection {
+    fn coin_select<R: RngCore>(
+        &self,
+        required_utxos: Vec<WeightedUtxo>,
+        optional_utxos: Vec<WeightedUtxo>,
+        fee_rate: FeeRate,
+        target_amount: Amount,
+        drain_script: &Script,
+        _rand: &mut R,
+    ) -> Result<CoinSelectionResult, InsufficientFunds> {
+        let mut clusters = self.clusters(optional_utxos);
+
+        // sort by total first, then by the first outpoint so the result is deterministic
+        clusters.sort_by_key(|(total, utxos)| (*total, utxos[0].utxo.outpoint()));
+
+        // try the smallest cluster that covers the whole amount by itself
+        for (_, cluster) in &clusters {
+            let utxos = required_utxos
+                .iter()
+                .cloned()
+                .map(|utxo| (true, utxo))
+                .chain(cluster.iter().cloned().map(|utxo| (false, utxo)));
+
+            if let Ok(result) = select_sorted_utxos(utxos, fee_rate, target_amount, drain_script) {
+                return Ok(result);
+            }
+        }
+
+        // no single cluster is enough, use the largest clusters first to mix as few as possible
+        let utxos = required_utxos.into_iter().map(|utxo| (true, utxo)).chain(
+            clusters.into_iter().rev().flat_map(|(_, utxos)| utxos).map(|utxo| (false, utxo)),
+        );
+
+        select_sorted_utxos(utxos, fee_rate, target_amount, drain_script)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coin_selection::test_util::*;
+
+    fn coin_selection() -> PrivacyCoinSelection {
+        PrivacyCoinSelection::new([
+            (outpoint(0), "exchange".to_string()),
+            (outpoint(1), "exchange".to_string()),
+            (outpoint(2), "salary".to_string()),
+        ])
+    }
+
+    fn utxos() -> Vec<WeightedUtxo> {
+        vec![
+            utxo(0, 60_000),
+            utxo(1, 40_000),
+            utxo(2, 30_000),
+            utxo_to_address(3, 45_000, 10),
+            utxo_to_address(4, 35_000, 10),
+            utxo(5, 10_000),
+        ]
+    }
+
+    #[test]
+    fn test_uses_smallest_cluster_that_covers_amount() {
+        let result = coin_selection()
+            .coin_select(
+                vec![],
+                utxos(),
+                fee_rate(),
+                Amount::from_sat(25_000),
+                &drain(),
+                &mut rng(),
+            )
+            .unwrap();
+
+        assert_eq!(selected_indexes(&result), vec![2]);
+    }
+
+    #[test]
+    fn test_same_address_is_one_cluster() {
+        let result = coin_selection()
+            .coin_select(
+                vec![],
+                utxos(),
+                fee_rate(),
+                Amount::from_sat(70_000),
+                &drain(),
+                &mut rng(),
+            )
+            .unwrap();
+
+        assert_eq!(selected_indexes(&result), vec![3, 4]);
+    }
+
+    #[test]
+    fn test_labelled_cluster() {
+        let result = coin_selection()
+            .coin_select(
+                vec![],
+                utxos(),
+                fee_rate(),
+                Amount::from_sat(90_000),
+                &drain(),
+                &mut rng(),
+            )
+            .unwrap();
+
+        assert_eq!(selected_indexes(&result), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_combines_largest_clusters_when_needed() {
+        let result = coin_selection()
+            .coin_select(
+                vec![],
+                utxos(),
+                fee_rate(),
+                Amount::from_sat(150_000),
+                &drain(),
+                &mut rng(),
+            )
+            .unwrap();
+
+        assert_eq!(selected_indexes(&result), vec![0, 1, 3, 4]);
+    }
+}
@@ -0,0 +1,86 @@
+use std::str::FromStr as _;
+
+use bdk_wallet::{
+    KeychainKind, LocalOutput, Utxo, WeightedUtxo,
+    bitcoin::{
+        Amount, BlockHash, FeeRate, OutPoint, ScriptBuf, TxOut, Txid, Weight,
+        key::rand::rngs::mock::StepRng,
+    },
+    chain::{BlockId, ChainPosition, ConfirmationBlockTime},
+    coin_selection::CoinSelectionResult,
+};
+
+/// Satisfaction weight of a P2WPKH input
+const P2WPKH_SATISFACTION_WEIGHT: u64 = 108;
+
+const TXID: &str = "d97bf8892657980426c879e4ab2001f09342f1ab61cfa602741a7715a3d60290";
+
+/// An unconfirmed UTXO, the `index` is used as the vout and to derive a unique address
+pub fn utxo(index: u32, sats: u64) -> WeightedUtxo {
+    new_utxo(index, sats, index, None)
+}
+
+pub fn confirmed_utxo(index: u32, sats: u64, height: u32) -> WeightedUtxo {
+    new_utxo(index, sats, index, Some(height))
+}
+
+/// An unconfirmed UTXO paid to the address for `address_index`
+pub fn utxo_to_address(index: u32, sats: u64, address_index: u32) -> WeightedUtxo {
+    new_utxo(index, sats, address_index, None)
+}
+
+pub fn outpoint(index: u32) -> OutPoint {
+    OutPoint { txid: Txid::from_str(TXID).unwrap(), vout: index }
+}
+
+pub fn fee_rate() -> FeeRate {
+    FeeRate::from_sat_per_vb_unchecked(1)
+}
+
+pub fn drain() -> ScriptBuf {
+    script(u32::MAX)
+}
+
+pub fn rng() -> StepRng {
+    StepRng::new(0, 1)
+}
+
+pub fn selected_values(result: &CoinSelectionResult) -> Vec<u64> {
+    result.selected.iter().map(|utxo| utxo.txout().value.to_sat()).collect()
+}
+
+pub fn selected_indexes(result: &CoinSelectionResult) -> Vec<u32> {
+    result.selected.iter().map(|utxo| utxo.outpoint().vout).collect()
+}
+
+fn new_utxo(index: u32, sats: u64, address_index: u32, height: Option<u32>) -> WeightedUtxo {
+    let chain_position = match height {
+        Some(height) => ChainPosition::Confirmed {
+            anchor: ConfirmationBlockTime {
+                block_id: BlockId { height, hash: BlockHash::from_str(&"00".repeat(32)).unwrap() },
+                confirmation_time: 1_700_000_000,
+            },
+            transitively: None,
+        },
+        None => ChainPosition::Unconfirmed { last_seen: Some(1_700_000_000) },
+    };
+
+    let local = LocalOutput {
+        outpoint: outpoint(index),
+        txout: TxOut { value: Amount::from_sat(sats), script_pubkey: script(address_index) },
+        keychain: KeychainKind::External,
+        is_spent: false,
+        derivation_index: index,
+        chain_position,
+    };
+
+    WeightedUtxo {
+        satisfaction_weight: Weight::from_wu(P2WPKH_SATISFACTION_WEIGHT),
+        utxo: Utxo::Local(local),
+    }
+}
+
+/// P2WPKH script with the index as the pubkey hash
+fn script(index: u32) -> ScriptBuf {
+    ScriptBuf::from_hex(&format!("0014{index:040x}")).unwrap()
+}
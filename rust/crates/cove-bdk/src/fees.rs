@@ -0,0 +1,75 @@
+use bdk_wallet::bitcoin::{Amount, FeeRate};
+
+/// Default incremental relay fee in Bitcoin Core, 1 sat/vB
+pub const INCREMENTAL_RELAY_FEE: FeeRate = FeeRate::from_sat_per_kwu(250);
+
+/// The lowest fee rate a replacement can pay and still be relayed (BIP125)
+///
+/// The replacement has to pay a higher fee rate than the original, and its absolute fee has to
+/// cover the original fee plus the incremental relay fee for the replacement's own size. A
+/// smaller replacement (a cancel) needs a higher fee rate to meet the absolute fee
+pub fn min_replacement_fee_rate(
+    original_fee: Amount,
+    original_vsize: u64,
+    replacement_vsize: u64,
+) -> FeeRate {
+    let original_vsize = original_vsize.max(1);
+    let replacement_vsize = replacement_vsize.max(1);
+
+    let original_rate = sat_per_kwu_ceil(original_fee.to_sat(), original_vsize);
+    let above_original = original_rate + INCREMENTAL_RELAY_FEE.to_sat_per_kwu();
+
+    let incremental_fee = INCREMENTAL_RELAY_FEE.to_sat_per_kwu() * replacement_vsize / 250;
+    let min_fee = original_fee.to_sat() + incremental_fee;
+    let covers_original_fee = sat_per_kwu_ceil(min_fee, replacement_vsize);
+
+    FeeRate::from_sat_per_kwu(above_original.max(covers_original_fee))
+}
+
+/// 1 sat/vB = 250 sat/kwu, rounded up so the fee at this rate is never short
+fn sat_per_kwu_ceil(fee: u64, vsize: u64) -> u64 {
+    (fee * 250).div_ceil(vsize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fee_at(rate: FeeRate, vsize: u64) -> Amount {
+        rate.fee_vb(vsize).unwrap()
+    }
+
+    #[test]
+    fn test_same_size_replacement_adds_the_increment() {
+        // 2 sat/vB original
+        let rate = min_replacement_fee_rate(Amount::from_sat(282), 141, 141);
+        assert_eq!(rate, FeeRate::from_sat_per_vb_unchecked(3));
+        assert_eq!(fee_at(rate, 141), Amount::from_sat(282 + 141));
+    }
+
+    #[test]
+    fn test_fractional_original_rate_rounds_up() {
+        // 1.5 sat/vB original, the replacement must be above 2.5 sat/vB
+        let rate = min_replacement_fee_rate(Amount::from_sat(300), 200, 200);
+        assert_eq!(rate, FeeRate::from_sat_per_kwu(625));
+        assert!(fee_at(rate, 200) >= Amount::from_sat(300 + 200));
+    }
+
+    #[test]
+    fn test_smaller_replacement_pays_the_original_fee_plus_increment() {
+        // cancelling a 10 sat/vB, 250 vB transaction with a 110 vB replacement, 11 sat/vB is not
+        // enough to pay the original 2500 sats plus 110 sats for the replacement
+        let rate = min_replacement_fee_rate(Amount::from_sat(2500), 250, 110);
+        assert!(rate > FeeRate::from_sat_per_vb_unchecked(11));
+        assert!(fee_at(rate, 110) >= Amount::from_sat(2500 + 110));
+        assert_eq!(rate, FeeRate::from_sat_per_kwu(5932));
+    }
+
+    #[test]
+    fn test_larger_replacement_keeps_the_fee_rate_floor() {
+        // adding an input makes the replacement bigger, the absolute fee is covered by the rate
+        let rate = min_replacement_fee_rate(Amount::from_sat(1000), 200, 270);
+        assert_eq!(rate, FeeRate::from_sat_per_vb_unchecked(6));
+        assert!(fee_at(rate, 270) >= Amount::from_sat(1000 + 270));
+    }
+}
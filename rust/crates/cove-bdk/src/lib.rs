@@ -1,3 +1,4 @@
 pub mod coin_selection;
 pub mod descriptor_ext;
+pub mod fees;
 pub mod util;
@@ -1,6 +1,7 @@
 use bdk_wallet::{
-    IsDust as _, WeightedUtxo,
+    IsDust as _, Utxo, WeightedUtxo,
     bitcoin::{Amount, FeeRate, Script, TxIn, Weight, consensus::serialize},
+    chain::ChainPosition,
     coin_selection::{CoinSelectionResult, Excess, InsufficientFunds},
 };
 
@@ -66,3 +67,13 @@ pub fn decide_change(remaining_amount: Amount, fee_rate: FeeRate, drain_script:
         Excess::Change { amount: drain_val, fee: change_fee }
     }
 }
+
+/// Height the UTXO was confirmed at, `None` if it's unconfirmed or not from our wallet
+pub fn confirmation_height(utxo: &WeightedUtxo) -> Option<u32> {
+    let Utxo::Local(local) = &utxo.utxo else { return None };
+
+    match &local.chain_position {
+        ChainPosition::Confirmed { anchor, .. } => Some(anchor.block_id.height),
+        ChainPosition::Unconfirmed { .. } => None,
+    }
+}
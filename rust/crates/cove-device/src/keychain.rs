@@ -2,7 +2,9 @@
 
 use std::{str::FromStr as _, sync::Arc};
 
-use bdk_wallet::bitcoin::bip32::Xpub;
+use bdk_wallet::bitcoin::bip32::{ChildNumber, Xpriv, Xpub};
+use bdk_wallet::bitcoin::hashes::{Hash as _, HashEngine as _, Hmac, HmacEngine, sha512};
+use bdk_wallet::bitcoin::{NetworkKind, secp256k1::Secp256k1};
 use bdk_wallet::descriptor::ExtendedDescriptor;
 use bip39::Mnemonic;
 use once_cell::sync::OnceCell;
@@ -27,6 +29,9 @@ pub enum KeychainError {
 
     #[error("unable to decrypt: {0}")]
     Decrypt(String),
+
+    #[error("unable to derive key: {0}")]
+    Derive(String),
 }
 
 #[uniffi::export(callback_interface)]
@@ -38,6 +43,9 @@ pub trait KeychainAccess: Send + Sync + std::fmt::Debug + 'static {
 
 static REF: OnceCell<Keychain> = OnceCell::new();
 
+/// `m/83696968'`, the BIP85 purpose
+const BIP85_PURPOSE: u32 = 83_696_968;
+
 #[derive(Debug, Clone, uniffi::Object)]
 pub struct Keychain(Arc<Box<dyn KeychainAccess>>);
 
@@ -108,6 +116,26 @@ impl Keychain {
         Ok(Some(mnemonic))
     }
 
+    /// BIP85 entropy derived from the wallet's seed, `None` if the wallet has no seed
+    ///
+    /// `path` is the application part after `m/83696968'`, all hardened,
+    /// ex. `[39, 0, 12, 0]` for the first 12 word child seed
+    pub fn derive_bip85_entropy(
+        &self,
+        id: &WalletId,
+        path: &[u32],
+    ) -> Result<Option<[u8; 64]>, KeychainError> {
+        let Some(mnemonic) = self.get_wallet_key(id)? else {
+            return Ok(None);
+        };
+
+        let seed = mnemonic.to_seed("");
+        let master = Xpriv::new_master(NetworkKind::Main, &seed)
+            .map_err(|error| KeychainError::Derive(error.to_string()))?;
+
+        bip85_entropy(&master, path).map(Some)
+    }
+
     fn delete_wallet_key(&self, id: &WalletId) -> bool {
         let encryption_key_key = wallet_mnemonic_encryption_and_nonce_key_name(id);
         let key = wallet_mnemonic_key_name(id);
@@ -249,3 +277,22 @@ fn wallet_tap_signer_encryption_key_and_nonce_key_name(id: &WalletId) -> String
 fn wallet_tap_signer_backup_key_name(id: &WalletId) -> String {
     format!("{id}::tap_signer_backup")
 }
+
+/// BIP85 entropy for `m/83696968'/{path}'` under `master`,
+/// HMAC-SHA512 of the derived private key with the key `bip-entropy-from-k`
+pub fn bip85_entropy(master: &Xpriv, path: &[u32]) -> Result<[u8; 64], KeychainError> {
+    let path = std::iter::once(BIP85_PURPOSE)
+        .chain(path.iter().copied())
+        .map(ChildNumber::from_hardened_idx)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| KeychainError::Derive(error.to_string()))?;
+
+    let child = master
+        .derive_priv(&Secp256k1::new(), &path)
+        .map_err(|error| KeychainError::Derive(error.to_string()))?;
+
+    let mut engine = HmacEngine::<sha512::Hash>::new(b"bip-entropy-from-k");
+    engine.input(&child.private_key.secret_bytes());
+
+    Ok(Hmac::<sha512::Hash>::from_engine(engine).to_byte_array())
+}
@@ -1,3 +1,5 @@
+pub mod bip21;
+
 use std::hash::Hash;
 use std::str::FromStr as _;
 use std::{hash::Hasher, sync::Arc};
@@ -17,6 +19,7 @@ use serde::Deserialize;
 use strum::IntoEnumIterator as _;
 
 use crate::{Network, amount::Amount, transaction::TransactionDirection};
+use bip21::{Bip21, Bip21Error};
 
 #[derive(
     Debug,
@@ -57,6 +60,8 @@ pub struct AddressWithNetwork {
     pub address: Address,
     pub network: Network,
     pub amount: Option<Amount>,
+    pub label: Option<String>,
+    pub message: Option<String>,
 }
 
 type Error = AddressError;
@@ -81,6 +86,9 @@ pub enum AddressError {
 
     #[error("empty address")]
     EmptyAddress,
+
+    #[error("invalid payment uri: {0}")]
+    InvalidBip21(String),
 }
 
 impl Clone for AddressInfo {
@@ -142,61 +150,39 @@ impl Address {
 
 impl AddressWithNetwork {
     pub fn try_new(str: &str) -> Result<Self, Error> {
-        let str = str.trim();
-        let str = str.trim_start_matches("bitcoin:");
-
-        let (address_str, amount) = extract_amount(str);
+        let bip21 = Bip21::parse(str).map_err(|error| match error {
+            Bip21Error::MissingAddress | Bip21Error::InvalidAddress(_) => Error::InvalidAddress,
+            error => Error::InvalidBip21(error.to_string()),
+        })?;
 
-        let address: BdkAddress<NetworkUnchecked> =
-            address_str.parse().map_err(|_| Error::InvalidAddress)?;
+        let Bip21 { address, amount, label, message, .. } = bip21;
 
-        let network = Network::Bitcoin;
-        if let Ok(address) = address.clone().require_network(network.into()) {
-            return Ok(Self { address: address.into(), network, amount });
-        }
-
-        let network = Network::Testnet;
-        if let Ok(address) = address.clone().require_network(network.into()) {
-            return Ok(Self { address: address.into(), network, amount });
-        }
+        let network = [Network::Bitcoin, Network::Testnet, Network::Signet, Network::Regtest]
+            .into_iter()
+            .find(|network| address.is_valid_for_network((*network).into()))
+            .ok_or(Error::UnsupportedNetwork)?;
 
-        let network = Network::Signet;
-        if let Ok(address) = address.require_network(network.into()) {
-            return Ok(Self { address: address.into(), network, amount });
-        }
+        let address = address.require_network(network.into()).expect("just checked").into();
+        Ok(Self { address, network, amount, label, message })
+    }
 
-        Err(Error::UnsupportedNetwork)
+    /// The label to use for a transaction paying this address, from the BIP21 label or message
+    pub fn transaction_label(&self) -> Option<String> {
+        self.label.clone().or_else(|| self.message.clone()).filter(|label| !label.is_empty())
     }
 
     pub fn is_valid_for_network(&self, network: Network) -> bool {
+        // regtest segwit addresses use their own `bcrt` prefix, so checking the kind isn't enough
+        if self.network == Network::Regtest || network == Network::Regtest {
+            return self.address.clone().into_unchecked().is_valid_for_network(network.into());
+        }
+
         let current_network_type = NetworkKind::from(self.network);
         let network_type = NetworkKind::from(network);
         current_network_type == network_type
     }
 }
 
-fn extract_amount(full_qr: &str) -> (&str, Option<Amount>) {
-    let Some(pos) = full_qr.find("?amount=") else { return (full_qr, None) };
-
-    let address = &full_qr[..pos];
-
-    let number_start = pos + 8;
-    let mut number_end = number_start;
-    for char in full_qr[number_start..].chars() {
-        if char.is_ascii_digit() || char == '.' {
-            number_end += 1;
-        } else {
-            break;
-        }
-    }
-
-    let number = &full_qr[number_start..number_end];
-    let Ok(amount_float) = number.parse::<f64>() else { return (address, None) };
-    let Ok(amount) = Amount::from_btc(amount_float) else { return (address, None) };
-
-    (address, Some(amount))
-}
-
 #[uniffi::export]
 fn address_is_equal(lhs: Arc<Address>, rhs: Arc<Address>) -> bool {
     lhs == rhs
@@ -221,6 +207,19 @@ impl AddressWithNetwork {
         self.amount.map(Arc::new)
     }
 
+    fn label(&self) -> Option<String> {
+        self.label.clone()
+    }
+
+    fn message(&self) -> Option<String> {
+        self.message.clone()
+    }
+
+    #[uniffi::method(name = "transactionLabel")]
+    fn ffi_transaction_label(&self) -> Option<String> {
+        self.transaction_label()
+    }
+
     #[uniffi::method(name = "isValidForNetwork")]
     pub fn ffi_is_valid_for_network(&self, network: Network) -> bool {
         self.is_valid_for_network(network)
@@ -408,38 +407,6 @@ pub mod ffi {
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_extract_amount_no_amount() {
-        let a = "bc1q0g0vn4yqyk0zjwxw0zv5pltyy9jm89vclxgsv3f";
-        let (a, amount) = extract_amount(a);
-        assert_eq!(a, "bc1q0g0vn4yqyk0zjwxw0zv5pltyy9jm89vclxgsv3f");
-        assert_eq!(amount, None);
-    }
-
-    #[test]
-    fn test_extract_amount_with_amount() {
-        let a = "bc1q0g0vn4yqyk0zjwxw0zv5pltyy9jm89vclxgsv3f?amount=0.001";
-        let (a, amount) = extract_amount(a);
-        assert_eq!(a, "bc1q0g0vn4yqyk0zjwxw0zv5pltyy9jm89vclxgsv3f");
-        assert_eq!(amount, Some(Amount::from_btc(0.001).unwrap()));
-    }
-
-    #[test]
-    fn test_extract_amount_with_amount_and_spaces() {
-        let a = "bc1q0g0vn4yqyk0zjwxw0zv5pltyy9jm89vclxgsv3f?amount=0.001  ";
-        let (a, amount) = extract_amount(a);
-        assert_eq!(a, "bc1q0g0vn4yqyk0zjwxw0zv5pltyy9jm89vclxgsv3f");
-        assert_eq!(amount, Some(Amount::from_btc(0.001).unwrap()));
-    }
-
-    #[test]
-    fn test_extract_amount_with_amount_and_other_query_params() {
-        let a = "bc1q0g0vn4yqyk0zjwxw0zv5pltyy9jm89vclxgsv3f?amount=0.002&foo=bar";
-        let (a, amount) = extract_amount(a);
-        assert_eq!(a, "bc1q0g0vn4yqyk0zjwxw0zv5pltyy9jm89vclxgsv3f");
-        assert_eq!(amount, Some(Amount::from_btc(0.002).unwrap()));
-    }
-
     #[test]
     fn test_address_string_spaced_out() {
         let address = "bc1pkdj04w4lxsv570j5nsd249lqe4w4j608r2nq9997ruh0wv96cnksy5jeny";
@@ -483,5 +450,36 @@ mod tests {
 
         assert!(address_with_network.is_ok());
         assert(address_with_network.unwrap(), Some(Amount::from_btc(0.002).unwrap()));
+
+        let address_with_network = AddressWithNetwork::try_new(
+            "bitcoin:bc1q00000002ltfnxz6lt9g655akfz0lm6k9wva2rm?amount=0.001&message=Coffee%20beans",
+        )
+        .unwrap();
+
+        assert_eq!(address_with_network.label, None);
+        assert_eq!(address_with_network.transaction_label().as_deref(), Some("Coffee beans"));
+
+        let address_with_network = AddressWithNetwork::try_new(
+            "bitcoin:bc1q00000002ltfnxz6lt9g655akfz0lm6k9wva2rm?req-pop=callback",
+        );
+
+        assert!(matches!(address_with_network, Err(AddressError::InvalidBip21(_))));
+    }
+
+    #[test]
+    fn test_regtest_address_with_network() {
+        let address = "bcrt1qqqqsyqcyq5rqwzqfpg9scrgwpugpzysnard0ew";
+        let address_with_network = AddressWithNetwork::try_new(address).unwrap();
+
+        assert_eq!(address_with_network.network, Network::Regtest);
+        assert!(address_with_network.is_valid_for_network(Network::Regtest));
+        assert!(!address_with_network.is_valid_for_network(Network::Testnet));
+        assert!(!address_with_network.is_valid_for_network(Network::Bitcoin));
+
+        assert!(Address::from_string(address, Network::Regtest).is_ok());
+        assert!(matches!(
+            Address::from_string(address, Network::Signet),
+            Err(AddressError::WrongNetwork { valid_for: Network::Regtest, .. })
+        ));
     }
 }
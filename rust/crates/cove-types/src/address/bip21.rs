@@ -0,0 +1,422 @@
+//! BIP21 `bitcoin:` payment URIs
+//!
+//! https://github.com/bitcoin/bips/blob/master/bip-0021.mediawiki
+
+use std::fmt::{self, Display, Write as _};
+
+use bitcoin::{Address as BdkAddress, Denomination, address::NetworkUnchecked};
+
+use crate::{address::Address, amount::Amount};
+
+type Error = Bip21Error;
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// A parsed BIP21 URI, also accepts a bare address with an optional query string
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Object)]
+pub struct Bip21 {
+    pub address: BdkAddress<NetworkUnchecked>,
+    pub amount: Option<Amount>,
+    pub label: Option<String>,
+    pub message: Option<String>,
+    /// BOLT11 invoice to use instead of the on-chain address
+    pub lightning: Option<String>,
+    /// BIP78 payjoin endpoint
+    pub payjoin: Option<String>,
+    /// Any other parameters we don't know about, that are safe to ignore
+    pub other_params: Vec<Bip21Param>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, uniffi::Record)]
+pub struct Bip21Param {
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error, uniffi::Error)]
+pub enum Bip21Error {
+    #[error("missing address")]
+    MissingAddress,
+
+    #[error("invalid address: {0}")]
+    InvalidAddress(String),
+
+    #[error("invalid amount: {0}")]
+    InvalidAmount(String),
+
+    #[error("invalid encoding for parameter: {0}")]
+    InvalidEncoding(String),
+
+    #[error("parameter is set more than once: {0}")]
+    DuplicateParam(String),
+
+    #[error("unsupported required parameter: {0}")]
+    UnsupportedRequiredParam(String),
+}
+
+impl Bip21 {
+    /// A payment request for one of our addresses, empty label and message are left out
+    pub fn new_request(
+        address: Address,
+        amount: Option<Amount>,
+        label: Option<String>,
+        message: Option<String>,
+    ) -> Self {
+        let non_empty = |string: Option<String>| {
+            string.map(|string| string.trim().to_string()).filter(|string| !string.is_empty())
+        };
+
+        Self {
+            address: address.into_unchecked(),
+            amount: amount.filter(|amount| *amount != Amount::ZERO),
+            label: non_empty(label),
+            message: non_empty(message),
+            lightning: None,
+            payjoin: None,
+            other_params: Vec::new(),
+        }
+    }
+
+    pub fn parse(uri: &str) -> Result<Self> {
+        let uri = uri.trim();
+        let uri = strip_scheme(uri);
+
+        let (address, query) = match uri.split_once('?') {
+            Some((address, query)) => (address, Some(query)),
+            None => (uri, None),
+        };
+
+        if address.is_empty() {
+            return Err(Error::MissingAddress);
+        }
+
+        let address: BdkAddress<NetworkUnchecked> =
+            address.parse().map_err(|error| Error::InvalidAddress(error.to_string()))?;
+
+        let mut bip21 = Self {
+            address,
+            amount: None,
+            label: None,
+            message: None,
+            lightning: None,
+            payjoin: None,
+            other_params: Vec::new(),
+        };
+
+        let Some(query) = query else { return Ok(bip21) };
+
+        for param in query.split('&').filter(|param| !param.is_empty()) {
+            let (key, value) = param.split_once('=').unwrap_or((param, ""));
+            let value =
+                percent_decode(value).ok_or_else(|| Error::InvalidEncoding(key.to_string()))?;
+
+            // uppercase URIs are common in QR codes, they fit in the alphanumeric mode
+            match key.to_ascii_lowercase().as_str() {
+                "amount" => set_once(&mut bip21.amount, parse_amount(&value)?, key)?,
+                "label" => set_once(&mut bip21.label, value, key)?,
+                "message" => set_once(&mut bip21.message, value, key)?,
+                "lightning" => set_once(&mut bip21.lightning, value, key)?,
+                "pj" => set_once(&mut bip21.payjoin, value, key)?,
+                lowercase if lowercase.starts_with("req-") => {
+                    return Err(Error::UnsupportedRequiredParam(key.to_string()));
+                }
+                _ => bip21.other_params.push(Bip21Param { key: key.to_string(), value }),
+            }
+        }
+
+        Ok(bip21)
+    }
+
+    /// The label to use for a transaction paying this URI, the label if there is one otherwise
+    /// the message
+    pub fn transaction_label(&self) -> Option<String> {
+        self.label.clone().or_else(|| self.message.clone()).filter(|label| !label.is_empty())
+    }
+}
+
+impl Display for Bip21 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "bitcoin:{}", self.address.assume_checked_ref())?;
+
+        let amount = self.amount.map(|amount| format_btc(amount.as_sats()));
+        let params = [
+            ("amount", amount.as_ref()),
+            ("label", self.label.as_ref()),
+            ("message", self.message.as_ref()),
+            ("lightning", self.lightning.as_ref()),
+            ("pj", self.payjoin.as_ref()),
+        ];
+
+        let params =
+            params.into_iter().filter_map(|(key, value)| Some((key, value?.as_str()))).chain(
+                self.other_params.iter().map(|param| (param.key.as_str(), param.value.as_str())),
+            );
+
+        for (index, (key, value)) in params.enumerate() {
+            let separator = if index == 0 { '?' } else { '&' };
+            write!(f, "{separator}{key}={}", percent_encode(value))?;
+        }
+
+        Ok(())
+    }
+}
+
+fn strip_scheme(uri: &str) -> &str {
+    const SCHEME: &str = "bitcoin:";
+
+    match uri.get(..SCHEME.len()) {
+        Some(scheme) if scheme.eq_ignore_ascii_case(SCHEME) => &uri[SCHEME.len()..],
+        _ => uri,
+    }
+}
+
+fn set_once<T>(field: &mut Option<T>, value: T, key: &str) -> Result<()> {
+    if field.is_some() {
+        return Err(Error::DuplicateParam(key.to_string()));
+    }
+
+    *field = Some(value);
+    Ok(())
+}
+
+fn parse_amount(amount: &str) -> Result<Amount> {
+    let amount = bitcoin::Amount::from_str_in(amount, Denomination::Bitcoin)
+        .map_err(|error| Error::InvalidAmount(format!("{amount}: {error}")))?;
+
+    Ok(amount.into())
+}
+
+/// Decimal BTC without trailing zeros, BIP21 amounts are always in BTC
+fn format_btc(sats: u64) -> String {
+    let whole = sats / 100_000_000;
+    let fraction = sats % 100_000_000;
+
+    if fraction == 0 {
+        return whole.to_string();
+    }
+
+    let fraction = format!("{fraction:08}");
+    format!("{whole}.{}", fraction.trim_end_matches('0'))
+}
+
+/// Percent encode everything except the RFC 3986 unreserved characters
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            byte => {
+                let _ = write!(encoded, "%{byte:02X}");
+            }
+        }
+    }
+
+    encoded
+}
+
+fn percent_decode(value: &str) -> Option<String> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'%' => {
+                let hex = bytes.get(index + 1..index + 3)?;
+                if !hex.iter().all(u8::is_ascii_hexdigit) {
+                    return None;
+                }
+
+                let hex = std::str::from_utf8(hex).ok()?;
+                decoded.push(u8::from_str_radix(hex, 16).ok()?);
+                index += 3;
+            }
+            byte => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+
+    String::from_utf8(decoded).ok()
+}
+
+mod ffi {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[uniffi::export]
+    impl Bip21 {
+        #[uniffi::constructor(name = "new")]
+        pub fn new(uri: String) -> Result<Self> {
+            Self::parse(&uri)
+        }
+
+        fn address_string(&self) -> String {
+            self.address.clone().assume_checked().to_string()
+        }
+
+        fn amount(&self) -> Option<Arc<Amount>> {
+            self.amount.map(Arc::new)
+        }
+
+        fn label(&self) -> Option<String> {
+            self.label.clone()
+        }
+
+        fn message(&self) -> Option<String> {
+            self.message.clone()
+        }
+
+        fn lightning(&self) -> Option<String> {
+            self.lightning.clone()
+        }
+
+        fn payjoin(&self) -> Option<String> {
+            self.payjoin.clone()
+        }
+
+        fn other_params(&self) -> Vec<Bip21Param> {
+            self.other_params.clone()
+        }
+
+        #[uniffi::method(name = "transactionLabel")]
+        fn ffi_transaction_label(&self) -> Option<String> {
+            self.transaction_label()
+        }
+
+        fn uri(&self) -> String {
+            self.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "bc1q00000002ltfnxz6lt9g655akfz0lm6k9wva2rm";
+
+    #[test]
+    fn test_bare_address() {
+        let bip21 = Bip21::parse(ADDRESS).unwrap();
+        assert_eq!(bip21.address.assume_checked_ref().to_string(), ADDRESS);
+        assert_eq!(bip21.amount, None);
+        assert_eq!(bip21.label, None);
+    }
+
+    #[test]
+    fn test_amount_without_scheme() {
+        let bip21 = Bip21::parse(&format!("{ADDRESS}?amount=0.001  ")).unwrap();
+        assert_eq!(bip21.amount, Some(Amount::from_sat(100_000)));
+    }
+
+    #[test]
+    fn test_all_known_params() {
+        let uri = format!(
+            "bitcoin:{ADDRESS}?amount=20.3&label=Luke-Jr&message=Donation%20for%20project%20xyz&lightning=lnbc1pvjluez&pj=https://example.com/pj"
+        );
+
+        let bip21 = Bip21::parse(&uri).unwrap();
+        assert_eq!(bip21.amount, Some(Amount::from_sat(2_030_000_000)));
+        assert_eq!(bip21.label.as_deref(), Some("Luke-Jr"));
+        assert_eq!(bip21.message.as_deref(), Some("Donation for project xyz"));
+        assert_eq!(bip21.lightning.as_deref(), Some("lnbc1pvjluez"));
+        assert_eq!(bip21.payjoin.as_deref(), Some("https://example.com/pj"));
+        assert!(bip21.other_params.is_empty());
+        assert_eq!(bip21.transaction_label().as_deref(), Some("Luke-Jr"));
+    }
+
+    #[test]
+    fn test_uppercase_uri() {
+        let uri = format!("BITCOIN:{}?AMOUNT=0.5&LABEL=COFFEE", ADDRESS.to_uppercase());
+        let bip21 = Bip21::parse(&uri).unwrap();
+
+        assert_eq!(bip21.amount, Some(Amount::from_sat(50_000_000)));
+        assert_eq!(bip21.label.as_deref(), Some("COFFEE"));
+    }
+
+    #[test]
+    fn test_message_used_as_label_fallback() {
+        let bip21 = Bip21::parse(&format!("bitcoin:{ADDRESS}?message=rent%20%E2%82%BF")).unwrap();
+        assert_eq!(bip21.transaction_label().as_deref(), Some("rent ₿"));
+    }
+
+    #[test]
+    fn test_unknown_params_are_kept() {
+        let bip21 = Bip21::parse(&format!("bitcoin:{ADDRESS}?amount=0.002&foo=bar")).unwrap();
+        assert_eq!(bip21.amount, Some(Amount::from_sat(200_000)));
+        assert_eq!(
+            bip21.other_params,
+            vec![Bip21Param { key: "foo".to_string(), value: "bar".to_string() }]
+        );
+    }
+
+    #[test]
+    fn test_unknown_required_param_is_rejected() {
+        let error = Bip21::parse(&format!("bitcoin:{ADDRESS}?req-somethingyoudontunderstand=50"));
+        assert_eq!(
+            error,
+            Err(Bip21Error::UnsupportedRequiredParam("req-somethingyoudontunderstand".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_invalid_amount() {
+        let error = Bip21::parse(&format!("bitcoin:{ADDRESS}?amount=1,000"));
+        assert!(matches!(error, Err(Bip21Error::InvalidAmount(_))));
+
+        let error = Bip21::parse(&format!("bitcoin:{ADDRESS}?amount=0.000000001"));
+        assert!(matches!(error, Err(Bip21Error::InvalidAmount(_))));
+    }
+
+    #[test]
+    fn test_duplicate_param() {
+        let error = Bip21::parse(&format!("bitcoin:{ADDRESS}?amount=1&amount=2"));
+        assert_eq!(error, Err(Bip21Error::DuplicateParam("amount".to_string())));
+    }
+
+    #[test]
+    fn test_invalid_encoding() {
+        let error = Bip21::parse(&format!("bitcoin:{ADDRESS}?label=bad%zz"));
+        assert_eq!(error, Err(Bip21Error::InvalidEncoding("label".to_string())));
+    }
+
+    #[test]
+    fn test_request_uri() {
+        let address = Address::from_string(ADDRESS, crate::Network::Bitcoin).unwrap();
+        let request = Bip21::new_request(
+            address,
+            Some(Amount::from_sat(150_000)),
+            Some("Invoice #12".to_string()),
+            Some(" ".to_string()),
+        );
+
+        assert_eq!(
+            request.to_string(),
+            format!("bitcoin:{ADDRESS}?amount=0.0015&label=Invoice%20%2312")
+        );
+
+        let parsed = Bip21::parse(&request.to_string()).unwrap();
+        assert_eq!(parsed, request);
+    }
+
+    #[test]
+    fn test_request_uri_whole_btc_and_no_params() {
+        let address = Address::from_string(ADDRESS, crate::Network::Bitcoin).unwrap();
+
+        let request = Bip21::new_request(address.clone(), Some(Amount::ONE_BTC), None, None);
+        assert_eq!(request.to_string(), format!("bitcoin:{ADDRESS}?amount=1"));
+
+        let request = Bip21::new_request(address, None, None, None);
+        assert_eq!(request.to_string(), format!("bitcoin:{ADDRESS}"));
+    }
+
+    #[test]
+    fn test_missing_address() {
+        assert_eq!(Bip21::parse("bitcoin:?amount=1"), Err(Bip21Error::MissingAddress));
+    }
+}
@@ -8,7 +8,7 @@ use crate::{
     BdkTxId, Network, TxId, address::Address, amount::Amount, fees::FeeRate, psbt::Psbt,
     utxo::UtxoType,
 };
-use bitcoin::{FeeRate as BdkFeeRate, TxOut};
+use bitcoin::{Amount as BdkAmount, FeeRate as BdkFeeRate, TxOut};
 
 use ahash::AHashMap as HashMap;
 
@@ -25,10 +25,28 @@ pub struct ConfirmDetails {
     pub fee_rate: FeeRate,
     pub fee_percentage: u64,
     pub sending_to: Address,
+    /// Every external output, more than one for a batch send
+    #[serde(default)]
+    pub recipients: Vec<AddressAndAmount>,
     pub psbt: Psbt,
     pub more_details: InputOutputDetails,
 }
 
+/// Confirm details for a child-pays-for-parent transaction, the child spends our unconfirmed
+/// output(s) of the parent so the package confirms at the effective package fee rate
+#[derive(Debug, Clone, Hash, Eq, PartialEq, uniffi::Object)]
+pub struct CpfpConfirmDetails {
+    pub parent_tx_id: TxId,
+    pub parent_vsize: u64,
+    pub parent_fee: Amount,
+    /// Unconfirmed ancestors of the parent, they confirm with it so the child pays for them too
+    pub ancestors: CpfpPackage,
+    pub child_vsize: u64,
+    pub child_fee: Amount,
+    pub effective_fee_rate: FeeRate,
+    pub details: ConfirmDetails,
+}
+
 #[derive(Debug, Clone, Hash, Eq, PartialEq, uniffi::Record, Serialize, Deserialize)]
 pub struct AddressAndAmount {
     #[serde(default)]
@@ -102,6 +120,25 @@ impl ConfirmDetails {
         self.sending_to.clone()
     }
 
+    /// All the external outputs, falls back to the single `sending_to` output for details saved
+    /// before batch sends were supported
+    pub fn recipients(&self) -> Vec<AddressAndAmount> {
+        if !self.recipients.is_empty() {
+            return self.recipients.clone();
+        }
+
+        vec![AddressAndAmount {
+            label: None,
+            utxo_type: None,
+            address: Arc::new(self.sending_to.clone()),
+            amount: Arc::new(self.sending_amount),
+        }]
+    }
+
+    pub fn is_batch(&self) -> bool {
+        self.recipients.len() > 1
+    }
+
     pub fn inputs(&self) -> Vec<AddressAndAmount> {
         self.more_details.inputs.clone()
     }
@@ -123,29 +160,124 @@ impl ConfirmDetails {
     }
 
     pub fn psbt_to_bbqr(&self) -> Result<Vec<String>> {
-        use bbqr::{
-            encode::Encoding,
-            file_type::FileType,
-            qr::Version,
-            split::{Split, SplitOptions},
-        };
-
         let data = self.psbt.0.serialize();
 
-        let split = Split::try_from_data(
-            data.as_slice(),
-            FileType::Psbt,
-            SplitOptions {
-                encoding: Encoding::Zlib,
-                min_split_number: 1,
-                max_split_number: 100,
-                min_version: Version::V01,
-                max_version: Version::V15,
-            },
-        )
-        .map_err(|e| ConfirmDetailsError::QrCodeCreation(e.to_string()))?;
-
-        Ok(split.parts)
+        crate::qr::bbqr_split(&data, bbqr::file_type::FileType::Psbt)
+            .map_err(|e| ConfirmDetailsError::QrCodeCreation(e.to_string()))
+    }
+}
+
+/// Total size and fee of unconfirmed transactions that confirm together
+#[derive(Debug, Clone, Copy, Default, Hash, Eq, PartialEq)]
+pub struct CpfpPackage {
+    pub vsize: u64,
+    pub fee: BdkAmount,
+}
+
+impl CpfpPackage {
+    pub fn new(vsize: u64, fee: BdkAmount) -> Self {
+        Self { vsize, fee }
+    }
+
+    pub fn with(self, vsize: u64, fee: BdkAmount) -> Self {
+        Self { vsize: self.vsize + vsize, fee: self.fee + fee }
+    }
+
+    /// Fee a child of `child_vsize` has to pay so the package and the child together hit the
+    /// target fee rate, `None` if the package already pays at least the target
+    pub fn child_fee(&self, child_vsize: u64, target_fee_rate: BdkFeeRate) -> Option<BdkAmount> {
+        let package_target = target_fee_rate.fee_vb(self.vsize)?;
+        if self.fee >= package_target {
+            return None;
+        }
+
+        target_fee_rate.fee_vb(self.vsize + child_vsize)?.checked_sub(self.fee)
+    }
+
+    pub fn fee_rate(&self) -> BdkFeeRate {
+        // 1 sat/vB = 250 sat/kwu
+        BdkFeeRate::from_sat_per_kwu(self.fee.to_sat() * 250 / self.vsize.max(1))
+    }
+}
+
+impl CpfpConfirmDetails {
+    pub fn new(
+        parent_tx_id: TxId,
+        parent_vsize: u64,
+        parent_fee: Amount,
+        ancestors: CpfpPackage,
+        child_vsize: u64,
+        child_fee: Amount,
+        details: ConfirmDetails,
+    ) -> Self {
+        let package =
+            ancestors.with(parent_vsize, parent_fee.into()).with(child_vsize, child_fee.into());
+
+        Self {
+            parent_tx_id,
+            parent_vsize,
+            parent_fee,
+            ancestors,
+            child_vsize,
+            child_fee,
+            effective_fee_rate: package.fee_rate().into(),
+            details,
+        }
+    }
+
+    fn package(&self) -> CpfpPackage {
+        self.ancestors
+            .with(self.parent_vsize, self.parent_fee.into())
+            .with(self.child_vsize, self.child_fee.into())
+    }
+}
+
+#[uniffi::export]
+impl CpfpConfirmDetails {
+    pub fn parent_tx_id(&self) -> TxId {
+        self.parent_tx_id
+    }
+
+    pub fn parent_vsize(&self) -> u64 {
+        self.parent_vsize
+    }
+
+    pub fn parent_fee(&self) -> Amount {
+        self.parent_fee
+    }
+
+    pub fn child_vsize(&self) -> u64 {
+        self.child_vsize
+    }
+
+    pub fn child_fee(&self) -> Amount {
+        self.child_fee
+    }
+
+    pub fn ancestors_vsize(&self) -> u64 {
+        self.ancestors.vsize
+    }
+
+    pub fn ancestors_fee(&self) -> Amount {
+        self.ancestors.fee.into()
+    }
+
+    /// Parent, its unconfirmed ancestors and the child
+    pub fn package_vsize(&self) -> u64 {
+        self.package().vsize
+    }
+
+    pub fn package_fee(&self) -> Amount {
+        self.package().fee.into()
+    }
+
+    pub fn effective_fee_rate(&self) -> FeeRate {
+        self.effective_fee_rate
+    }
+
+    /// Details of the child transaction, used for signing and the hardware export
+    pub fn details(&self) -> ConfirmDetails {
+        self.details.clone()
     }
 }
 
@@ -258,12 +390,31 @@ mod ffi_preview {
                 fee_rate: BdkFeeRate::from_sat_per_vb_unchecked(3).into(),
                 fee_percentage: 3,
                 sending_to: Address::preview_new(),
+                recipients: vec![],
                 psbt,
                 more_details,
             }
         }
     }
 
+    #[uniffi::export]
+    impl CpfpConfirmDetails {
+        #[uniffi::constructor]
+        pub fn preview_new() -> Self {
+            let details = ConfirmDetails::preview_new(20448);
+            let parent_tx_id = details.id();
+
+            Self::new(
+                parent_tx_id,
+                141,
+                Amount::from_sat(141),
+                110,
+                Amount::from_sat(2369),
+                details,
+            )
+        }
+    }
+
     fn psbt_preview_new() -> Psbt {
         let psbt_hex = "70736274ff01009a020000000258e87a21b56daf0c23be8e7070456c336f7cbaa5c8757924f545887bb2abdd750000000000ffffffff838d0427d0ec650a68aa46bb0b098aea4422c071b2ca78352a077959d07cea1d0100000000ffffffff0270aaf00800000000160014d85c2b71d0060b09c9886aeb815e50991dda124d00e1f5050000000016001400aea9a2e5f0f876a588df5546e8742d1d87008f000000000000000000";
         let psbt_bytes = hex::decode(psbt_hex).expect("unable to decode psbt hex");
@@ -271,3 +422,48 @@ mod ffi_preview {
         BdkPsbt::deserialize(&psbt_bytes).expect("unable to deserialize psbt").into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sats(sats: u64) -> BdkAmount {
+        BdkAmount::from_sat(sats)
+    }
+
+    fn sat_per_vb(rate: u64) -> BdkFeeRate {
+        BdkFeeRate::from_sat_per_vb_unchecked(rate)
+    }
+
+    #[test]
+    fn test_child_pays_for_parent() {
+        // 200 vB parent paying 1 sat/vB, a 110 vB child to bring both to 10 sat/vB
+        let parent = CpfpPackage::new(200, sats(200));
+        let child_fee = parent.child_fee(110, sat_per_vb(10)).unwrap();
+        assert_eq!(child_fee, sats(3100 - 200));
+
+        let package = parent.with(110, child_fee);
+        assert_eq!(package.fee_rate(), sat_per_vb(10));
+    }
+
+    #[test]
+    fn test_child_pays_for_unconfirmed_ancestors() {
+        // the parent spends an unconfirmed 150 vB grandparent paying 2 sat/vB
+        let ancestors = CpfpPackage::new(150, sats(300));
+        let package = ancestors.with(200, sats(200));
+
+        let child_fee = package.child_fee(110, sat_per_vb(10)).unwrap();
+        assert_eq!(child_fee, sats(4600 - 500));
+
+        // ignoring the grandparent would leave the package short of the target
+        let parent_only = CpfpPackage::new(200, sats(200)).child_fee(110, sat_per_vb(10)).unwrap();
+        assert!(package.with(110, parent_only).fee_rate() < sat_per_vb(10));
+        assert_eq!(package.with(110, child_fee).fee_rate(), sat_per_vb(10));
+    }
+
+    #[test]
+    fn test_package_already_at_target() {
+        let package = CpfpPackage::new(150, sats(3000)).with(200, sats(500));
+        assert_eq!(package.child_fee(110, sat_per_vb(10)), None);
+    }
+}
@@ -0,0 +1,173 @@
+use std::sync::Arc;
+
+use bitcoin::Weight;
+
+use crate::{
+    OutPoint,
+    amount::Amount,
+    fees::{BdkFeeRate, FeeRate},
+};
+
+/// A proposal to merge many small UTXOs into a single internal UTXO while fees are low
+#[derive(Debug, Clone, Hash, Eq, PartialEq, uniffi::Object)]
+pub struct ConsolidationPlan {
+    pub utxos: Vec<Arc<OutPoint>>,
+    pub total_amount: Amount,
+    pub fee_rate: FeeRate,
+    pub future_fee_rate: FeeRate,
+
+    /// fee paid by the consolidation transaction at `fee_rate`
+    pub fee: Amount,
+
+    /// fee to spend every UTXO as an input later at `future_fee_rate`
+    pub future_fee_without_consolidation: Amount,
+
+    /// fee to spend the one consolidated UTXO later at `future_fee_rate`
+    pub future_fee_with_consolidation: Amount,
+}
+
+impl ConsolidationPlan {
+    /// - `input_vsizes`: the virtual size of each UTXO when spent as an input
+    /// - `consolidated_input_vsize`: the virtual size of spending the consolidated UTXO
+    pub fn new(
+        utxos: Vec<(bitcoin::OutPoint, Amount)>,
+        input_vsizes: &[u64],
+        consolidated_input_vsize: u64,
+        fee_rate: BdkFeeRate,
+        future_fee_rate: BdkFeeRate,
+        fee: Amount,
+    ) -> Self {
+        let total_amount = utxos.iter().map(|(_, amount)| amount.as_sats()).sum();
+        let utxos = utxos.into_iter().map(|(outpoint, _)| Arc::new(outpoint.into())).collect();
+
+        let all_inputs_vsize = input_vsizes.iter().sum();
+        let future_fee_without_consolidation = fee_for_vsize(future_fee_rate, all_inputs_vsize);
+        let future_fee_with_consolidation =
+            fee_for_vsize(future_fee_rate, consolidated_input_vsize);
+
+        Self {
+            utxos,
+            total_amount: Amount::from_sat(total_amount),
+            fee_rate: fee_rate.into(),
+            future_fee_rate: future_fee_rate.into(),
+            fee,
+            future_fee_without_consolidation,
+            future_fee_with_consolidation,
+        }
+    }
+
+    /// Sats saved by consolidating now instead of spending the UTXOs at the future fee rate,
+    /// negative if consolidating costs more than it saves
+    pub fn savings(&self) -> i64 {
+        let without = self.future_fee_without_consolidation.as_sats() as i64;
+        let with = self.fee.as_sats() as i64 + self.future_fee_with_consolidation.as_sats() as i64;
+
+        without - with
+    }
+
+    pub fn outpoints(&self) -> Vec<bitcoin::OutPoint> {
+        self.utxos.iter().map(|outpoint| outpoint.as_ref().into()).collect()
+    }
+}
+
+fn fee_for_vsize(fee_rate: BdkFeeRate, vsize: u64) -> Amount {
+    (fee_rate * Weight::from_vb_unchecked(vsize)).into()
+}
+
+mod ffi {
+    use super::*;
+
+    #[uniffi::export]
+    impl ConsolidationPlan {
+        pub fn utxos(&self) -> Vec<Arc<OutPoint>> {
+            self.utxos.clone()
+        }
+
+        pub fn utxo_count(&self) -> u32 {
+            self.utxos.len() as u32
+        }
+
+        pub fn total_amount(&self) -> Amount {
+            self.total_amount
+        }
+
+        pub fn fee_rate(&self) -> FeeRate {
+            self.fee_rate
+        }
+
+        pub fn future_fee_rate(&self) -> FeeRate {
+            self.future_fee_rate
+        }
+
+        pub fn fee(&self) -> Amount {
+            self.fee
+        }
+
+        pub fn future_fee_without_consolidation(&self) -> Amount {
+            self.future_fee_without_consolidation
+        }
+
+        pub fn future_fee_with_consolidation(&self) -> Amount {
+            self.future_fee_with_consolidation
+        }
+
+        #[uniffi::method(name = "savings")]
+        pub fn _savings(&self) -> i64 {
+            self.savings()
+        }
+
+        pub fn is_worth_it(&self) -> bool {
+            self.savings() > 0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr as _;
+
+    fn utxos(count: u32) -> Vec<(bitcoin::OutPoint, Amount)> {
+        let txid = bitcoin::Txid::from_str(
+            "d97bf8892657980426c879e4ab2001f09342f1ab61cfa602741a7715a3d60290",
+        )
+        .unwrap();
+
+        (0..count)
+            .map(|vout| (bitcoin::OutPoint { txid, vout }, Amount::from_sat(10_000)))
+            .collect()
+    }
+
+    #[test]
+    fn test_consolidating_at_low_fee_rate_saves() {
+        // 10 p2wpkh inputs (68 vB each), consolidation tx at 2 sat/vB is ~ 11 + 680 + 31 vB
+        let plan = ConsolidationPlan::new(
+            utxos(10),
+            &[68; 10],
+            68,
+            BdkFeeRate::from_sat_per_vb_unchecked(2),
+            BdkFeeRate::from_sat_per_vb_unchecked(50),
+            Amount::from_sat(1_444),
+        );
+
+        assert_eq!(plan.total_amount.as_sats(), 100_000);
+        assert_eq!(plan.future_fee_without_consolidation.as_sats(), 34_000);
+        assert_eq!(plan.future_fee_with_consolidation.as_sats(), 3_400);
+        assert_eq!(plan.savings(), 34_000 - 1_444 - 3_400);
+        assert_eq!(plan.outpoints().len(), 10);
+    }
+
+    #[test]
+    fn test_consolidating_at_same_fee_rate_costs_more() {
+        let plan = ConsolidationPlan::new(
+            utxos(10),
+            &[68; 10],
+            68,
+            BdkFeeRate::from_sat_per_vb_unchecked(10),
+            BdkFeeRate::from_sat_per_vb_unchecked(10),
+            Amount::from_sat(7_220),
+        );
+
+        assert!(plan.savings() < 0);
+    }
+}
@@ -10,9 +10,11 @@ pub mod amount;
 pub mod color;
 pub mod color_scheme;
 pub mod confirm;
+pub mod consolidation;
 pub mod fees;
 pub mod network;
 pub mod psbt;
+pub mod qr;
 pub mod redb;
 pub mod transaction;
 pub mod unit;
@@ -21,7 +23,10 @@ pub mod utxo;
 // export the types
 pub use address_index::AddressIndex;
 pub use block_size::BlockSizeLast;
-pub use confirm::{ConfirmDetails, ConfirmDetailsError, InputOutputDetails, SplitOutput};
+pub use confirm::{
+    ConfirmDetails, ConfirmDetailsError, CpfpConfirmDetails, CpfpPackage, InputOutputDetails,
+    SplitOutput,
+};
 
 pub use chain_position::ChainPosition;
 pub use network::Network;
@@ -23,6 +23,7 @@ pub enum Network {
     Testnet,
     Testnet4,
     Signet,
+    Regtest,
 }
 
 use strum::IntoEnumIterator;
@@ -44,6 +45,7 @@ impl From<Network> for u8 {
             Network::Testnet => 1,
             Network::Testnet4 => 4,
             Network::Signet => 2,
+            Network::Regtest => 3,
         }
     }
 }
@@ -57,6 +59,7 @@ impl TryFrom<u8> for Network {
             1 => Ok(Network::Testnet),
             4 => Ok(Network::Testnet4),
             2 => Ok(Network::Signet),
+            3 => Ok(Network::Regtest),
             _ => Err(format!("Unknown network: {}", value)),
         }
     }
@@ -72,6 +75,7 @@ impl TryFrom<&str> for Network {
             "testnet4" | "Testnet4" => Ok(Network::Testnet4),
             "signet" | "Signet" => Ok(Network::Signet),
             "mutinynet" | "Mutinynet" => Ok(Network::Signet),
+            "regtest" | "Regtest" => Ok(Network::Regtest),
             _ => Err(format!("Unknown network: {}", value)),
         }
     }
@@ -84,6 +88,7 @@ impl From<Network> for bitcoin::Network {
             Network::Testnet => bitcoin::Network::Testnet,
             Network::Testnet4 => bitcoin::Network::Testnet4,
             Network::Signet => bitcoin::Network::Signet,
+            Network::Regtest => bitcoin::Network::Regtest,
         }
     }
 }
@@ -95,6 +100,7 @@ impl From<bitcoin::Network> for Network {
             bitcoin::Network::Testnet => Network::Testnet,
             bitcoin::Network::Testnet4 => Network::Testnet4,
             bitcoin::Network::Signet => Network::Signet,
+            bitcoin::Network::Regtest => Network::Regtest,
             network => panic!("unsupported network: {network:?}"),
         }
     }
@@ -107,6 +113,7 @@ impl From<Network> for Params {
             Network::Testnet => Params::TESTNET3,
             Network::Testnet4 => Params::TESTNET4,
             Network::Signet => Params::SIGNET,
+            Network::Regtest => Params::REGTEST,
         }
     }
 }
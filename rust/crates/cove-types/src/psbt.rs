@@ -1,7 +1,7 @@
 use bdk_wallet::psbt::PsbtUtils as _;
 use bitcoin::{Amount as BdkAmount, TxIn, TxOut};
 use derive_more::{AsRef, Deref, From, Into};
-use std::fmt::Debug;
+use std::{fmt::Debug, str::FromStr as _};
 
 use crate::{TxId, amount::Amount};
 pub type BdkPsbt = bdk_wallet::bitcoin::Psbt;
@@ -80,6 +80,27 @@ impl Psbt {
 }
 
 impl Psbt {
+    /// Binary PSBT, starts with the `psbt\xff` magic
+    pub fn try_from_data(data: &[u8]) -> Result<Self> {
+        let psbt = BdkPsbt::deserialize(data).map_err(|e| PsbtError::Other(e.to_string()))?;
+        Ok(psbt.into())
+    }
+
+    /// Base64 or hex encoded PSBT
+    pub fn try_from_str(string: &str) -> Result<Self> {
+        let string = string.trim();
+
+        // hex of the `psbt\xff` magic
+        let is_hex = string.get(..10).is_some_and(|magic| magic.eq_ignore_ascii_case("70736274ff"));
+        if is_hex {
+            let data = hex::decode(string).map_err(|e| PsbtError::Other(e.to_string()))?;
+            return Self::try_from_data(&data);
+        }
+
+        let psbt = BdkPsbt::from_str(string).map_err(|e| PsbtError::Other(e.to_string()))?;
+        Ok(psbt.into())
+    }
+
     /// Get all UTXOs
     pub fn utxos(&self) -> Vec<(TxIn, TxOut)> {
         self.utxos_iter().map(|(tx_in, tx_out)| (tx_in.clone(), tx_out)).collect()
@@ -104,3 +125,24 @@ impl Debug for Psbt {
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // unsigned psbt from BIP174
+    const PSBT: &str = "cHNidP8BAHUCAAAAASaBcTce3/KF6Tet7qSze3gADAVmy7OtZGQXE8pCFxv2AAAAAAD+////AtPf9QUAAAAAGXapFNDFmQPFusKGh2DpD9UhpGZap2UgiKwA4fUFAAAAABepFDVF5uM7gyxHBQ8k0+65PJwDlIvHh7MuEwAAAQD9pQEBAAAAAAECiaPHHqtNIOA3G7ukzGmPopXJRjr6Ljl/hTPMti+VZ+UBAAAAFxYAFL4Y0VKpsBIDna89p95PUzSe7LmF/////4b4qkOnHf8USIk6UwpyN+9rRgi7st0tAXHmOuxqSJC0AQAAABcWABT+Pp7xp0XpdNkCxDVZQ6vLNL1TU/////8CAMLrCwAAAAAZdqkUhc/xCX/Z4Ai7NK9wnGIZeziXikiIrHL++E4sAAAAF6kUM5cluiHv1irHU6m80GfWx6ajnQWHAkcwRAIgJxK+IuAnDzlPVoMR3HyppolwuAJf3TskAinwf4pfOiQCIAGLONfc0xTnNMkna9b7QPZzMlvEuqFEyADS8vAtsnZcASED0uFWdJQbrUqZY3LLh+GFbTZSYG2YVi/jnF6efkE/IQUCSDBFAiEA0SuFLYXc2WHS9fSrZgZU327tzHlMDDPOXMMJ/7X85Y0CIGczio4OFyXBl/saiK9Z9R5E5CVbIBZ8hoQDHAXR8lkqASECI7cr7vCWXRC+B3jv7NYfysb3mk6haTkzgHNEZPhPKrMAAAAAAAAA";
+
+    #[test]
+    fn test_parse_psbt_encodings() {
+        let psbt = Psbt::try_from_str(PSBT).unwrap();
+        let data = psbt.0.serialize();
+
+        assert_eq!(Psbt::try_from_data(&data).unwrap(), psbt);
+        assert_eq!(Psbt::try_from_str(&hex::encode(&data)).unwrap(), psbt);
+        assert_eq!(Psbt::try_from_str(&hex::encode_upper(&data)).unwrap(), psbt);
+
+        assert!(Psbt::try_from_str("not a psbt").is_err());
+        assert!(Psbt::try_from_data(&data[..20]).is_err());
+    }
+}
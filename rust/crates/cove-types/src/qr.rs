@@ -0,0 +1,21 @@
+use bbqr::{
+    encode::Encoding,
+    file_type::FileType,
+    qr::Version,
+    split::{Split, SplitError, SplitOptions},
+};
+
+/// Split data into animated BBQr parts, zlib compressed, using as few and as small QR codes
+/// as will fit
+pub fn bbqr_split(data: &[u8], file_type: FileType) -> Result<Vec<String>, SplitError> {
+    let options = SplitOptions {
+        encoding: Encoding::Zlib,
+        min_split_number: 1,
+        max_split_number: 100,
+        min_version: Version::V01,
+        max_version: Version::V15,
+    };
+
+    let split = Split::try_from_data(data, file_type, options)?;
+    Ok(split.parts)
+}
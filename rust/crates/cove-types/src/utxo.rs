@@ -39,6 +39,8 @@ pub struct Utxo {
     pub derivation_index: u32,
     pub block_height: u32,
     pub type_: UtxoType,
+    /// frozen utxos are never spent, the user has to unfreeze them first
+    pub frozen: bool,
 }
 
 #[derive(Debug, Clone, Hash, Eq, PartialEq, uniffi::Object)]
@@ -98,6 +100,7 @@ impl Utxo {
             derivation_index,
             block_height,
             type_,
+            frozen: false,
         };
 
         Ok(utxo)
@@ -223,6 +226,7 @@ pub mod ffi_preview {
                 derivation_index: 0,
                 block_height,
                 type_,
+                frozen: false,
             }
         }
     }
@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 
 use bdk_file_store::Store as FileStore;
-use bdk_wallet::{KeychainKind, Wallet};
+use bdk_wallet::{KeychainKind, Wallet, descriptor::ExtendedDescriptor};
 use bitcoin::Network;
 use eyre::{Context as _, ContextCompat as _, Result};
 use tracing::{info, warn};
@@ -36,6 +36,20 @@ impl BdkStore {
         Ok(me)
     }
 
+    /// The public descriptors BDK saved for this wallet, external then internal
+    pub fn public_descriptors(&mut self) -> Result<[ExtendedDescriptor; 2]> {
+        let wallet = Wallet::load()
+            .check_network(self.network)
+            .load_wallet(&mut self.conn)
+            .context("failed to load wallet")?
+            .context("no wallet found")?;
+
+        let external = wallet.public_descriptor(KeychainKind::External).clone();
+        let internal = wallet.public_descriptor(KeychainKind::Internal).clone();
+
+        Ok([external, internal])
+    }
+
     // check if we have a file store
     // if we do, migrate to the new SQLite store
     fn check_and_migrate_from_file_store(&mut self) -> Result<bool> {
@@ -0,0 +1,294 @@
+//! BIP85 deterministic child seeds, WIF keys and xprvs derived from a hot wallet's seed
+//!
+//! Everything here hands out private keys, so the user has to authenticate through the auth
+//! manager first
+
+use bdk_wallet::bitcoin::{
+    NetworkKind, PrivateKey,
+    bip32::{ChainCode, ChildNumber, Fingerprint as BdkFingerprint, Xpriv},
+    secp256k1::SecretKey,
+};
+use bip39::{Language, Mnemonic as Bip39Mnemonic};
+use cove_types::Network;
+
+use crate::{
+    database::Database,
+    keychain::{Keychain, KeychainError},
+    manager::auth_manager::{AUTH_MANAGER, AuthManagerError},
+    mnemonic::MnemonicExt as _,
+    wallet::{
+        Wallet,
+        fingerprint::Fingerprint,
+        metadata::{WalletId, WalletMetadata},
+    },
+};
+
+/// BIP85 application numbers
+const BIP39_APPLICATION: u32 = 39;
+const WIF_APPLICATION: u32 = 2;
+const XPRV_APPLICATION: u32 = 32;
+
+/// BIP39 language code for english, the only wordlist we support
+const ENGLISH: u32 = 0;
+
+/// Largest index allowed, BIP85 indexes are hardened
+const MAX_INDEX: u32 = (1 << 31) - 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Error, thiserror::Error)]
+pub enum Bip85Error {
+    #[error(transparent)]
+    Auth(#[from] AuthManagerError),
+
+    #[error("this wallet has no seed to derive from")]
+    NoSeed,
+
+    #[error("{0} words is not supported, use 12, 18 or 24")]
+    InvalidWordCount(u8),
+
+    #[error("index must be less than 2^31")]
+    InvalidIndex,
+
+    #[error("unable to derive: {0}")]
+    Derive(String),
+
+    #[error("wallet already exists")]
+    WalletAlreadyExists(WalletId),
+
+    #[error("unable to create wallet: {0}")]
+    CreateWallet(String),
+}
+
+type Error = Bip85Error;
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, uniffi::Enum)]
+pub enum Bip85Application {
+    /// English BIP39 mnemonic, 12, 18 or 24 words
+    Mnemonic { words: u8 },
+    /// Compressed WIF private key
+    Wif,
+    /// Master xprv for another wallet
+    Xprv,
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, uniffi::Record)]
+pub struct Bip85Child {
+    pub application: Bip85Application,
+    pub index: u32,
+    /// ex. "m/83696968'/39'/0'/12'/0'"
+    pub path: String,
+    /// The words, WIF or xprv, this is secret
+    pub secret: String,
+}
+
+impl Bip85Application {
+    /// The application part of the path, after `m/83696968'`
+    fn path(&self, index: u32) -> Result<Vec<u32>> {
+        if index > MAX_INDEX {
+            return Err(Error::InvalidIndex);
+        }
+
+        let path = match self {
+            Self::Mnemonic { words } => {
+                entropy_length(*words)?;
+                vec![BIP39_APPLICATION, ENGLISH, u32::from(*words), index]
+            }
+            Self::Wif => vec![WIF_APPLICATION, index],
+            Self::Xprv => vec![XPRV_APPLICATION, index],
+        };
+
+        Ok(path)
+    }
+
+    /// Turn the 64 bytes of BIP85 entropy into the application's secret
+    fn secret(&self, entropy: &[u8; 64], network: NetworkKind) -> Result<String> {
+        let secret = match self {
+            Self::Mnemonic { words } => {
+                let length = entropy_length(*words)?;
+                Bip39Mnemonic::from_entropy_in(Language::English, &entropy[..length])
+                    .map_err(|error| Error::Derive(error.to_string()))?
+                    .to_string()
+            }
+
+            Self::Wif => {
+                let key = SecretKey::from_slice(&entropy[..32])
+                    .map_err(|error| Error::Derive(error.to_string()))?;
+
+                PrivateKey::new(key, network).to_wif()
+            }
+
+            // chain code first, then the private key
+            Self::Xprv => {
+                let chain_code: [u8; 32] = entropy[..32].try_into().expect("32 bytes");
+                let private_key = SecretKey::from_slice(&entropy[32..])
+                    .map_err(|error| Error::Derive(error.to_string()))?;
+
+                Xpriv {
+                    network,
+                    depth: 0,
+                    parent_fingerprint: BdkFingerprint::default(),
+                    child_number: ChildNumber::from_normal_idx(0).expect("valid index"),
+                    private_key,
+                    chain_code: ChainCode::from(chain_code),
+                }
+                .to_string()
+            }
+        };
+
+        Ok(secret)
+    }
+}
+
+fn entropy_length(words: u8) -> Result<usize> {
+    match words {
+        12 => Ok(16),
+        18 => Ok(24),
+        24 => Ok(32),
+        words => Err(Error::InvalidWordCount(words)),
+    }
+}
+
+fn path_string(path: &[u32]) -> String {
+    let path = path.iter().map(|index| format!("/{index}'")).collect::<String>();
+    format!("m/83696968'{path}")
+}
+
+/// Derive a child secret from the wallet's seed, needs a recent auth
+pub fn derive(id: &WalletId, application: Bip85Application, index: u32) -> Result<Bip85Child> {
+    AUTH_MANAGER.check_sensitive_action_authorized()?;
+
+    let path = application.path(index)?;
+    let entropy = Keychain::global().derive_bip85_entropy(id, &path)?.ok_or(Error::NoSeed)?;
+
+    let network = Database::global().global_config.selected_network();
+    let secret = application.secret(&entropy, NetworkKind::from(network))?;
+
+    Ok(Bip85Child { application, index, path: path_string(&path), secret })
+}
+
+/// Create and select a new hot wallet from the wallet's child seed at `index`
+pub fn create_wallet(id: &WalletId, words: u8, index: u32) -> Result<WalletMetadata> {
+    let child = derive(id, Bip85Application::Mnemonic { words }, index)?;
+    let mnemonic = Bip39Mnemonic::parse_in_normalized(Language::English, &child.secret)
+        .map_err(|error| Error::Derive(error.to_string()))?;
+
+    let database = Database::global();
+    let network: Network = database.global_config.selected_network();
+    let mode = database.global_config.wallet_mode();
+
+    // make sure its not already imported
+    let fingerprint: Fingerprint = mnemonic.xpub(network.into()).fingerprint().into();
+    let existing = database.wallets.get_all(network, mode).unwrap_or_default();
+    if let Some(wallet) = existing
+        .into_iter()
+        .filter(|wallet| wallet.account == 0)
+        .find(|wallet| wallet.master_fingerprint.as_deref() == Some(&fingerprint))
+    {
+        return Err(Error::WalletAlreadyExists(wallet.id));
+    }
+
+    let parent_name = database
+        .wallets
+        .get(id, network, mode)
+        .ok()
+        .flatten()
+        .map(|metadata| metadata.name)
+        .unwrap_or_else(|| "Wallet".to_string());
+
+    // the child seed is backed up by the parent seed and its index
+    let mut metadata = WalletMetadata::new(format!("{parent_name} #{index}"), Some(fingerprint));
+    metadata.verified = true;
+
+    let wallet = Wallet::try_new_persisted_and_selected(metadata, mnemonic, None)
+        .map_err(|error| Error::CreateWallet(error.to_string()))?;
+
+    Ok(wallet.metadata)
+}
+
+impl From<KeychainError> for Bip85Error {
+    fn from(error: KeychainError) -> Self {
+        Self::Derive(error.to_string())
+    }
+}
+
+// MARK: ffi
+
+#[uniffi::export]
+fn bip85_derive(id: WalletId, application: Bip85Application, index: u32) -> Result<Bip85Child> {
+    derive(&id, application, index)
+}
+
+#[uniffi::export]
+fn bip85_create_wallet(id: WalletId, words: u8, index: u32) -> Result<WalletMetadata> {
+    create_wallet(&id, words, index)
+}
+
+#[uniffi::export]
+fn describe_bip85_error(error: Bip85Error) -> String {
+    error.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr as _;
+
+    use super::*;
+    use cove_device::keychain::bip85_entropy;
+
+    // test vectors from BIP85
+    const MASTER: &str = "xprv9s21ZrQH143K2LBWUUQRFXhucrQqBpKdRRxNVq2zBqsx8HVqFk2uYo8kmbaLLHRdqtQpUm98uKfu3vca1LqdGhUtyoFnCNkfmXRyPXLjbKb";
+
+    fn child(application: Bip85Application, index: u32) -> String {
+        let master = Xpriv::from_str(MASTER).unwrap();
+        let path = application.path(index).unwrap();
+        let entropy = bip85_entropy(&master, &path).unwrap();
+
+        application.secret(&entropy, NetworkKind::Main).unwrap()
+    }
+
+    #[test]
+    fn test_entropy_from_k() {
+        let master = Xpriv::from_str(MASTER).unwrap();
+        let entropy = bip85_entropy(&master, &[0, 0]).unwrap();
+
+        assert_eq!(
+            hex::encode(entropy),
+            "efecfbccffea313214232d29e71563d941229afb4338c21f9517c41aaa0d16f00b83d2a09ef747e7a64e8e2bd5a14869e693da66ce94ac2da570ab7ee48618f7"
+        );
+    }
+
+    #[test]
+    fn test_bip39_child() {
+        let words = child(Bip85Application::Mnemonic { words: 12 }, 0);
+        assert_eq!(
+            words,
+            "girl mad pet galaxy egg matter matrix prison refuse sense ordinary nose"
+        );
+
+        let words = child(Bip85Application::Mnemonic { words: 24 }, 0);
+        assert_eq!(words.split_whitespace().count(), 24);
+
+        let path = Bip85Application::Mnemonic { words: 18 }.path(0).unwrap();
+        assert_eq!(path_string(&path), "m/83696968'/39'/0'/18'/0'");
+
+        assert_eq!(
+            Bip85Application::Mnemonic { words: 15 }.path(0),
+            Err(Error::InvalidWordCount(15))
+        );
+    }
+
+    #[test]
+    fn test_wif_and_xprv_children() {
+        assert_eq!(
+            child(Bip85Application::Wif, 0),
+            "Kzyv4uF39d4Jrw2W7UryTHwZr1zQVNk4dAFyqE6BuMrMh1Za7uhp"
+        );
+
+        assert_eq!(
+            child(Bip85Application::Xprv, 0),
+            "xprv9s21ZrQH143K2srSbCSg4m4kLvPMzcWydgmKEnMmoZUurYuBuYG46c6P71UGXMzmriLzCCBvKQWBUv3vPB3m1SATMhp3uEjXHJ42jFg7myX"
+        );
+
+        assert_eq!(Bip85Application::Wif.path(1 << 31), Err(Error::InvalidIndex));
+    }
+}
@@ -0,0 +1,685 @@
+//! BIP129 Bitcoin Secure Multisig Setup (BSMS), Cove acts as the coordinator
+//!
+//! The coordinator hands out a session token, collects each signer's signed `BSMS 1.0` key
+//! record, and outputs the descriptor record and first address for the signers to verify
+
+use std::{str::FromStr as _, sync::Arc};
+
+use aes::cipher::{KeyIvInit as _, StreamCipher as _, generic_array::GenericArray};
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use bdk_wallet::{
+    bitcoin::{
+        hashes::{Hash as _, HashEngine as _, Hmac, HmacEngine, sha256, sha512},
+        secp256k1::{Secp256k1, VerifyOnly},
+        sign_message::{MessageSignature, signed_msg_hash},
+    },
+    keys::DescriptorPublicKey,
+};
+use parking_lot::Mutex;
+use rand::RngCore as _;
+use tracing::debug;
+
+use crate::{
+    database::Database,
+    multisig::MultisigExport,
+    network::Network,
+    wallet::{Wallet, WalletError, fingerprint::Fingerprint, metadata::WalletId},
+};
+
+const BSMS_VERSION: &str = "BSMS 1.0";
+
+/// Token used when the session is not encrypted
+const NO_ENCRYPTION_TOKEN: &str = "00";
+
+/// Path restrictions for the descriptor record, receive and change
+const PATH_RESTRICTIONS: &str = "/0/*,/1/*";
+
+const PBKDF2_SALT: &[u8] = b"No SPOF";
+const PBKDF2_ITERATIONS: u32 = 2048;
+
+const MAC_LEN: usize = 32;
+const MAX_DESCRIPTION_LEN: usize = 80;
+
+type Aes256Ctr = ctr::Ctr128BE<aes::Aes256>;
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Default, uniffi::Enum)]
+pub enum BsmsEncryption {
+    NoEncryption,
+    /// One 64 bit token shared by every signer
+    #[default]
+    Standard,
+    /// A separate 128 bit token for each signer
+    Extended,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Error, thiserror::Error)]
+pub enum BsmsError {
+    #[error("unsupported BSMS version, only BSMS 1.0 is supported")]
+    UnsupportedVersion,
+
+    #[error("invalid key record: {0}")]
+    InvalidKeyRecord(String),
+
+    #[error("unable to decrypt record, it was not encrypted for this session")]
+    DecryptionFailed,
+
+    #[error("key record is for a different session")]
+    TokenMismatch,
+
+    #[error("key record must be encrypted for this session")]
+    NotEncrypted,
+
+    #[error("key record signature is invalid")]
+    InvalidSignature,
+
+    #[error("key record for {0} was already added")]
+    DuplicateSigner(String),
+
+    #[error("already have all {0} key records")]
+    TooManySigners(u32),
+
+    #[error("still waiting for {0} key records")]
+    MissingSigners(u32),
+
+    #[error("invalid multisig setup: {0}")]
+    InvalidSetup(String),
+
+    #[error("unable to save wallet: {0}")]
+    SaveWallet(String),
+}
+
+type Error = BsmsError;
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// A scanned or imported BSMS record, encrypted or not, decrypted by the coordinator
+///
+/// Encrypted records are just hex, so they are only accepted from the multisig setup screen,
+/// `MultiFormat` only recognizes plaintext records
+#[derive(Debug, Clone, Hash, Eq, PartialEq, uniffi::Object)]
+pub struct BsmsRecord(String);
+
+/// A signer's key record, after decrypting and verifying it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BsmsKeyRecord {
+    pub token: String,
+    pub key: DescriptorPublicKey,
+    /// The key exactly as written by the signer, it's part of the signed message
+    key_line: String,
+    pub description: String,
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, uniffi::Record)]
+pub struct BsmsSigner {
+    pub fingerprint: Arc<Fingerprint>,
+    pub description: String,
+}
+
+/// The final round, send `records` back to each signer to verify and import
+#[derive(Debug, Clone, Hash, Eq, PartialEq, uniffi::Record)]
+pub struct BsmsDescriptorRecord {
+    pub descriptor: String,
+    pub first_address: String,
+    /// One per signer when using extended encryption, otherwise a single record for all signers
+    pub records: Vec<String>,
+}
+
+#[derive(Debug, uniffi::Object)]
+pub struct BsmsCoordinator {
+    threshold: u32,
+    total: u32,
+    encryption: BsmsEncryption,
+    tokens: Vec<String>,
+    key_records: Mutex<Vec<BsmsKeyRecord>>,
+}
+
+// MARK: records
+
+impl BsmsRecord {
+    /// Plaintext or encrypted records, only use this during a BSMS session
+    pub fn try_from_str(string: &str) -> Result<Self> {
+        let string = string.trim();
+
+        if let Ok(record) = Self::try_from_plaintext(string) {
+            return Ok(record);
+        }
+
+        // encrypted records are hex: MAC || CIPHERTEXT
+        let min_len = (MAC_LEN + BSMS_VERSION.len()) * 2;
+        if string.len() > min_len && string.len() % 2 == 0 && hex::decode(string).is_ok() {
+            return Ok(Self(string.to_ascii_lowercase()));
+        }
+
+        Err(Error::UnsupportedVersion)
+    }
+
+    /// Only unencrypted records, these start with the `BSMS 1.0` version line
+    pub fn try_from_plaintext(string: &str) -> Result<Self> {
+        let string = string.trim();
+        if !string.starts_with(BSMS_VERSION) {
+            return Err(Error::UnsupportedVersion);
+        }
+
+        Ok(Self(string.to_string()))
+    }
+
+    fn is_encrypted(&self) -> bool {
+        !self.0.starts_with(BSMS_VERSION)
+    }
+}
+
+impl BsmsKeyRecord {
+    /// Parse a plaintext key record
+    ///
+    /// ```text
+    /// BSMS 1.0
+    /// TOKEN
+    /// [ORIGIN]XPUB
+    /// DESCRIPTION
+    /// SIG
+    /// ```
+    pub fn try_from_str(record: &str) -> Result<Self> {
+        let lines = record.trim().lines().map(str::trim).collect::<Vec<_>>();
+        let [version, token, key, description, signature] = lines.as_slice() else {
+            return Err(Error::InvalidKeyRecord(format!("expected 5 lines, got {}", lines.len())));
+        };
+
+        if *version != BSMS_VERSION {
+            return Err(Error::UnsupportedVersion);
+        }
+
+        if description.len() > MAX_DESCRIPTION_LEN {
+            return Err(Error::InvalidKeyRecord("description is too long".into()));
+        }
+
+        let key_line = *key;
+        let key = DescriptorPublicKey::from_str(key_line)
+            .map_err(|error| Error::InvalidKeyRecord(error.to_string()))?;
+
+        let DescriptorPublicKey::XPub(xpub) = &key else {
+            return Err(Error::InvalidKeyRecord("key must be an xpub".into()));
+        };
+
+        if xpub.origin.is_none() {
+            return Err(Error::InvalidKeyRecord("key is missing its origin".into()));
+        }
+
+        Ok(Self {
+            token: token.to_string(),
+            key,
+            key_line: key_line.to_string(),
+            description: description.to_string(),
+            signature: signature.to_string(),
+        })
+    }
+
+    /// The first four lines, signed by the signer's key
+    fn message(&self) -> String {
+        [BSMS_VERSION, &self.token, &self.key_line, &self.description].join("\n")
+    }
+
+    /// Check the signature was made with the private key of the xpub in the record
+    pub fn verify(&self, secp: &Secp256k1<VerifyOnly>) -> Result<()> {
+        let DescriptorPublicKey::XPub(xpub) = &self.key else {
+            return Err(Error::InvalidSignature);
+        };
+
+        let signature = BASE64.decode(&self.signature).map_err(|_| Error::InvalidSignature)?;
+        let signature =
+            MessageSignature::from_slice(&signature).map_err(|_| Error::InvalidSignature)?;
+
+        let message_hash = signed_msg_hash(&self.message());
+        let public_key =
+            signature.recover_pubkey(secp, message_hash).map_err(|_| Error::InvalidSignature)?;
+
+        if public_key.inner != xpub.xkey.public_key {
+            return Err(Error::InvalidSignature);
+        }
+
+        Ok(())
+    }
+
+    pub fn fingerprint(&self) -> Fingerprint {
+        self.key.master_fingerprint().into()
+    }
+}
+
+// MARK: encryption
+
+fn encryption_key(token: &[u8]) -> [u8; 32] {
+    // PBKDF2-HMAC-SHA512, only the first block is needed for a 32 byte key
+    let mut engine = HmacEngine::<sha512::Hash>::new(token);
+    engine.input(PBKDF2_SALT);
+    engine.input(&1u32.to_be_bytes());
+
+    let mut block = Hmac::<sha512::Hash>::from_engine(engine).to_byte_array();
+    let mut key = block;
+
+    for _ in 1..PBKDF2_ITERATIONS {
+        let mut engine = HmacEngine::<sha512::Hash>::new(token);
+        engine.input(&block);
+        block = Hmac::<sha512::Hash>::from_engine(engine).to_byte_array();
+
+        key.iter_mut().zip(block.iter()).for_each(|(key, block)| *key ^= block);
+    }
+
+    key[..32].try_into().expect("64 byte hash")
+}
+
+fn mac(key: &[u8; 32], token: &str, data: &[u8]) -> [u8; MAC_LEN] {
+    let hmac_key = sha256::Hash::hash(key);
+
+    let mut engine = HmacEngine::<sha256::Hash>::new(hmac_key.as_byte_array());
+    engine.input(token.as_bytes());
+    engine.input(data);
+
+    Hmac::<sha256::Hash>::from_engine(engine).to_byte_array()
+}
+
+/// Encrypt a record, returns hex encoded `MAC || CIPHERTEXT`
+pub fn encrypt(token: &str, data: &str) -> Result<String> {
+    let token_bytes = hex::decode(token).map_err(|_| Error::TokenMismatch)?;
+    let key = encryption_key(&token_bytes);
+    let mac = mac(&key, token, data.as_bytes());
+
+    let mut ciphertext = data.as_bytes().to_vec();
+    let mut cipher =
+        Aes256Ctr::new(GenericArray::from_slice(&key), GenericArray::from_slice(&mac[..16]));
+    cipher.apply_keystream(&mut ciphertext);
+
+    Ok(hex::encode([mac.as_slice(), &ciphertext].concat()))
+}
+
+/// Decrypt a hex encoded record, fails if the MAC doesn't match
+pub fn decrypt(token: &str, encrypted: &str) -> Result<String> {
+    let token_bytes = hex::decode(token).map_err(|_| Error::TokenMismatch)?;
+    let encrypted = hex::decode(encrypted).map_err(|_| Error::DecryptionFailed)?;
+
+    if encrypted.len() <= MAC_LEN {
+        return Err(Error::DecryptionFailed);
+    }
+
+    let (expected_mac, ciphertext) = encrypted.split_at(MAC_LEN);
+    let key = encryption_key(&token_bytes);
+
+    let mut plaintext = ciphertext.to_vec();
+    let iv = GenericArray::from_slice(&expected_mac[..16]);
+    let mut cipher = Aes256Ctr::new(GenericArray::from_slice(&key), iv);
+    cipher.apply_keystream(&mut plaintext);
+
+    if mac(&key, token, &plaintext) != expected_mac {
+        return Err(Error::DecryptionFailed);
+    }
+
+    String::from_utf8(plaintext).map_err(|_| Error::DecryptionFailed)
+}
+
+// MARK: coordinator
+
+impl BsmsCoordinator {
+    pub fn try_new(threshold: u32, total: u32, encryption: BsmsEncryption) -> Result<Self> {
+        if total < 2 || threshold == 0 || threshold > total {
+            return Err(Error::InvalidSetup(format!("{threshold} of {total}")));
+        }
+
+        let tokens = match encryption {
+            BsmsEncryption::NoEncryption => vec![NO_ENCRYPTION_TOKEN.to_string()],
+            BsmsEncryption::Standard => vec![random_token(8)],
+            BsmsEncryption::Extended => (0..total).map(|_| random_token(16)).collect(),
+        };
+
+        Ok(Self { threshold, total, encryption, tokens, key_records: Mutex::new(vec![]) })
+    }
+
+    /// Decrypt and verify a signer's key record, returns the number of records collected
+    pub fn add_key_record(&self, record: &BsmsRecord) -> Result<u32> {
+        let plaintext = match record.is_encrypted() {
+            false if self.encryption != BsmsEncryption::NoEncryption => {
+                return Err(Error::NotEncrypted);
+            }
+            false => record.0.clone(),
+            true => self
+                .tokens
+                .iter()
+                .find_map(|token| decrypt(token, &record.0).ok())
+                .ok_or(Error::DecryptionFailed)?,
+        };
+
+        let key_record = BsmsKeyRecord::try_from_str(&plaintext)?;
+        if !self.tokens.contains(&key_record.token.to_ascii_lowercase()) {
+            return Err(Error::TokenMismatch);
+        }
+
+        key_record.verify(&Secp256k1::verification_only())?;
+
+        let mut key_records = self.key_records.lock();
+        if key_records.len() as u32 >= self.total {
+            return Err(Error::TooManySigners(self.total));
+        }
+
+        let fingerprint = key_record.fingerprint();
+        if key_records.iter().any(|r| r.key == key_record.key || r.fingerprint() == fingerprint) {
+            return Err(Error::DuplicateSigner(fingerprint.as_uppercase()));
+        }
+
+        debug!("added BSMS key record for {}", fingerprint.as_uppercase());
+        key_records.push(key_record);
+
+        Ok(key_records.len() as u32)
+    }
+
+    /// The descriptor template with the `/**` receive and change shorthand
+    fn descriptor_template(&self) -> Result<String> {
+        let key_records = self.key_records.lock();
+        let missing = self.total - key_records.len() as u32;
+        if missing > 0 {
+            return Err(Error::MissingSigners(missing));
+        }
+
+        let keys =
+            key_records.iter().map(|record| format!("{}/**", record.key)).collect::<Vec<_>>();
+        Ok(format!("wsh(sortedmulti({},{}))", self.threshold, keys.join(",")))
+    }
+
+    pub fn multisig_export(&self) -> Result<MultisigExport> {
+        let template = self.descriptor_template()?;
+        MultisigExport::try_from_str(&template.replace("/**", "/<0;1>/*"))
+            .map_err(|error| Error::InvalidSetup(error.to_string()))
+    }
+
+    pub fn descriptor_record_for_network(&self, network: Network) -> Result<BsmsDescriptorRecord> {
+        let descriptor = self.descriptor_template()?;
+        let export = self.multisig_export()?;
+
+        let first_address = export
+            .external
+            .at_derivation_index(0)
+            .map_err(|error| Error::InvalidSetup(error.to_string()))?
+            .address(network.into())
+            .map_err(|error| Error::InvalidSetup(error.to_string()))?
+            .to_string();
+
+        let record = [BSMS_VERSION, &descriptor, PATH_RESTRICTIONS, &first_address].join("\n");
+        let records = match self.encryption {
+            BsmsEncryption::NoEncryption => vec![record],
+            _ => self.tokens.iter().map(|token| encrypt(token, &record)).collect::<Result<_>>()?,
+        };
+
+        Ok(BsmsDescriptorRecord { descriptor, first_address, records })
+    }
+}
+
+fn random_token(len: usize) -> String {
+    let mut token = vec![0u8; len];
+    rand::rng().fill_bytes(&mut token);
+    hex::encode(token)
+}
+
+// MARK: ffi
+
+#[uniffi::export]
+impl BsmsRecord {
+    /// For the multisig setup screen, accepts encrypted records as well as plaintext
+    #[uniffi::constructor(name = "new")]
+    pub fn new(record: String) -> Result<Self> {
+        Self::try_from_str(&record)
+    }
+}
+
+#[uniffi::export]
+impl BsmsCoordinator {
+    #[uniffi::constructor(name = "new")]
+    pub fn new(threshold: u32, total: u32, encryption: BsmsEncryption) -> Result<Self> {
+        Self::try_new(threshold, total, encryption)
+    }
+
+    /// Tokens to give to the signers, one for every signer unless using extended encryption
+    pub fn tokens(&self) -> Vec<String> {
+        self.tokens.clone()
+    }
+
+    pub fn threshold(&self) -> u32 {
+        self.threshold
+    }
+
+    pub fn total(&self) -> u32 {
+        self.total
+    }
+
+    #[uniffi::method(name = "addKeyRecord")]
+    pub fn ffi_add_key_record(&self, record: Arc<BsmsRecord>) -> Result<u32> {
+        self.add_key_record(&record)
+    }
+
+    pub fn signers(&self) -> Vec<BsmsSigner> {
+        self.key_records
+            .lock()
+            .iter()
+            .map(|record| BsmsSigner {
+                fingerprint: Arc::new(record.fingerprint()),
+                description: record.description.clone(),
+            })
+            .collect()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.key_records.lock().len() as u32 == self.total
+    }
+
+    pub fn descriptor_record(&self) -> Result<BsmsDescriptorRecord> {
+        let network = Database::global().global_config.selected_network();
+        self.descriptor_record_for_network(network)
+    }
+
+    /// Save the multisig wallet, stored like any other imported multisig wallet
+    pub fn save_wallet(&self, name: Option<String>) -> Result<WalletId> {
+        let mut export = self.multisig_export()?;
+        export.name = name.filter(|name| !name.trim().is_empty());
+
+        let wallet = Wallet::try_new_persisted_from_multisig(export)
+            .map_err(|error: WalletError| Error::SaveWallet(error.to_string()))?;
+
+        Ok(wallet.id)
+    }
+}
+
+#[uniffi::export]
+fn describe_bsms_error(error: BsmsError) -> String {
+    error.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bdk_wallet::bitcoin::{
+        NetworkKind,
+        bip32::{DerivationPath, Xpriv, Xpub},
+        secp256k1::{All, Message},
+    };
+
+    fn signer(seed: u8, token: &str, description: &str) -> String {
+        let secp = Secp256k1::new();
+        let master = Xpriv::new_master(NetworkKind::Test, &[seed; 32]).unwrap();
+        let path = DerivationPath::from_str("m/48'/1'/0'/2'").unwrap();
+        let xpriv = master.derive_priv(&secp, &path).unwrap();
+        let xpub = Xpub::from_priv(&secp, &xpriv);
+
+        let key = format!("[{}/48'/1'/0'/2']{xpub}", master.fingerprint(&secp));
+        let message = [BSMS_VERSION, token, &key, description].join("\n");
+
+        let signature = sign(&secp, &xpriv, &message);
+        [message, signature].join("\n")
+    }
+
+    fn sign(secp: &Secp256k1<All>, xpriv: &Xpriv, message: &str) -> String {
+        let hash = signed_msg_hash(message);
+        let message = Message::from_digest(hash.to_byte_array());
+        let signature = secp.sign_ecdsa_recoverable(&message, &xpriv.private_key);
+
+        BASE64.encode(MessageSignature::new(signature, true).serialize())
+    }
+
+    #[test]
+    fn test_encryption_round_trip() {
+        let token = random_token(8);
+        let encrypted = encrypt(&token, "BSMS 1.0\nhello").unwrap();
+
+        assert_eq!(decrypt(&token, &encrypted).unwrap(), "BSMS 1.0\nhello");
+        assert_eq!(decrypt(&random_token(8), &encrypted), Err(BsmsError::DecryptionFailed));
+
+        // tampering with the ciphertext fails the MAC check
+        let mut tampered = encrypted.clone();
+        let last_byte = if tampered.ends_with("00") { "ff" } else { "00" };
+        tampered.replace_range(tampered.len() - 2.., last_byte);
+        assert_eq!(decrypt(&token, &tampered), Err(BsmsError::DecryptionFailed));
+    }
+
+    // BIP129 encryption test values for a 64 bit and a 128 bit token, computed independently
+    // with Python's `hashlib`, `hmac` and `cryptography` packages
+    const VECTOR_TOKEN: &str = "a54044308ceac9b7";
+    const VECTOR_ENCRYPTION_KEY: &str =
+        "847fba0c67fac00c7db56b106ac5040de236765fbb7e3bf7dedcbb6b3baeaa61";
+    const VECTOR_MAC: &str = "d77b438c716f50c9227f3ef9208658421568558c1c5bfc0492bea4c74c6c3174";
+
+    const VECTOR_PLAINTEXT: &str = "BSMS 1.0
+a54044308ceac9b7
+[5a0804e3/48'/1'/0'/2']tpubDFH9dgzveyD8zTbPUFuLrGmCydNvxehyNdUXKJAQN8x4aZ4j6UZqGfnqFrD4NqyaTVGKbvEW54tsvPTK2UoSbCC1PJY8iCNiwTL3RWZEheQ
+Signer 1 key
+H/IHW5dMGYsrRdYEz3ux+kKnkWBtxHzfYkREpnYbco38VnMdIThZdaIAoa+hXMlpWYdrX7nSM3IOD/SDCXaCvS0=";
+
+    const VECTOR_CIPHERTEXT: &str = "d77b438c716f50c9227f3ef9208658421568558c1c5bfc0492bea4c74c6c317411a4d7675548d4beb740ac11afbeb67c6ddedc065724cf7b245cbf8bfbd7ab12c6d87cea828571a7db2c27435ce17c14abce92cc9489263bf9c629b6d3b0990232a222728de630752174ce8c980cecf36ac19d69309f3e2cf11d55a46bdca13873377b79f18dcaec0f8f4135ea9c5029388e143795dc3496e5b4c83547898323b9cee00fb955b63c8370d2fdcfdfa5db64b283e4daa4e11c91255ba61b3a1da2eb647191cdb9c538c4768b7fc484e0b7006692b818d5910a666226e3650b271cbb697eddffa963d2222ca84cfbb730b338e942b6dadc758b59e6456db5f4c0637c8adeec177fa50535869b355cada5618e78e95860a48e0f332a270ee18d9dc06c520625ff3d";
+
+    const EXTENDED_VECTOR_TOKEN: &str = "0f1e2d3c4b5a69788796a5b4c3d2e1f0";
+    const EXTENDED_VECTOR_ENCRYPTION_KEY: &str =
+        "f2a6cd47b89e666431f888511296060ab245b6f08e546d3d737d554c09e9170c";
+    const EXTENDED_VECTOR_MAC: &str =
+        "e68ff61dbb1f907ec58768df656387dfd898c3de6d688726337a3c172cb92dd2";
+
+    const EXTENDED_VECTOR_PLAINTEXT: &str = "BSMS 1.0\n/0/*,/1/*\nextended token record";
+
+    const EXTENDED_VECTOR_CIPHERTEXT: &str = "e68ff61dbb1f907ec58768df656387dfd898c3de6d688726337a3c172cb92dd2ae339ec279799af0cb20a4e5c978dfb66fcf0837d6f393226da43bed953a3eb29a73a15d01118d33";
+
+    #[test]
+    fn test_encryption_vectors() {
+        let token = hex::decode(VECTOR_TOKEN).unwrap();
+        let key = encryption_key(&token);
+        assert_eq!(hex::encode(key), VECTOR_ENCRYPTION_KEY);
+
+        let mac = mac(&key, VECTOR_TOKEN, VECTOR_PLAINTEXT.as_bytes());
+        assert_eq!(hex::encode(mac), VECTOR_MAC);
+
+        let encrypted = encrypt(VECTOR_TOKEN, VECTOR_PLAINTEXT).unwrap();
+        assert_eq!(encrypted, VECTOR_CIPHERTEXT);
+        assert_eq!(decrypt(VECTOR_TOKEN, VECTOR_CIPHERTEXT).unwrap(), VECTOR_PLAINTEXT);
+
+        let record = BsmsRecord::try_from_str(VECTOR_CIPHERTEXT).unwrap();
+        assert!(record.is_encrypted());
+        assert!(BsmsRecord::try_from_plaintext(VECTOR_CIPHERTEXT).is_err());
+    }
+
+    #[test]
+    fn test_extended_encryption_vectors() {
+        let token = hex::decode(EXTENDED_VECTOR_TOKEN).unwrap();
+        let key = encryption_key(&token);
+        assert_eq!(hex::encode(key), EXTENDED_VECTOR_ENCRYPTION_KEY);
+
+        let mac = mac(&key, EXTENDED_VECTOR_TOKEN, EXTENDED_VECTOR_PLAINTEXT.as_bytes());
+        assert_eq!(hex::encode(mac), EXTENDED_VECTOR_MAC);
+
+        let encrypted = encrypt(EXTENDED_VECTOR_TOKEN, EXTENDED_VECTOR_PLAINTEXT).unwrap();
+        assert_eq!(encrypted, EXTENDED_VECTOR_CIPHERTEXT);
+        assert_eq!(
+            decrypt(EXTENDED_VECTOR_TOKEN, EXTENDED_VECTOR_CIPHERTEXT).unwrap(),
+            EXTENDED_VECTOR_PLAINTEXT
+        );
+    }
+
+    #[test]
+    fn test_key_record_signature() {
+        let record = signer(1, "00", "Signer 1");
+        let key_record = BsmsKeyRecord::try_from_str(&record).unwrap();
+        assert!(key_record.verify(&Secp256k1::verification_only()).is_ok());
+
+        let forged = record.replace("Signer 1", "Signer 2");
+        let key_record = BsmsKeyRecord::try_from_str(&forged).unwrap();
+        assert_eq!(
+            key_record.verify(&Secp256k1::verification_only()),
+            Err(BsmsError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn test_coordinator_standard_encryption() {
+        let coordinator = BsmsCoordinator::try_new(2, 3, BsmsEncryption::Standard).unwrap();
+        let token = coordinator.tokens()[0].clone();
+
+        for seed in 1..=3 {
+            let record = signer(seed, &token, &format!("Signer {seed}"));
+            let encrypted = BsmsRecord::try_from_str(&encrypt(&token, &record).unwrap()).unwrap();
+            assert_eq!(coordinator.add_key_record(&encrypted).unwrap(), seed as u32);
+        }
+
+        assert!(coordinator.is_complete());
+        assert_eq!(coordinator.signers()[0].description, "Signer 1");
+
+        let descriptor_record =
+            coordinator.descriptor_record_for_network(Network::Testnet).unwrap();
+        assert!(descriptor_record.descriptor.starts_with("wsh(sortedmulti(2,"));
+        assert!(descriptor_record.first_address.starts_with("tb1q"));
+        assert_eq!(descriptor_record.records.len(), 1);
+
+        // signers decrypt the descriptor record with the same token
+        let record = decrypt(&token, &descriptor_record.records[0]).unwrap();
+        let lines = record.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], BSMS_VERSION);
+        assert_eq!(lines[2], PATH_RESTRICTIONS);
+        assert_eq!(lines[3], descriptor_record.first_address);
+
+        let export = coordinator.multisig_export().unwrap();
+        assert_eq!(export.config.threshold, 2);
+        assert_eq!(export.config.cosigners.len(), 3);
+    }
+
+    #[test]
+    fn test_coordinator_rejects_bad_records() {
+        let coordinator = BsmsCoordinator::try_new(2, 2, BsmsEncryption::NoEncryption).unwrap();
+
+        let wrong_token = BsmsRecord::try_from_str(&signer(1, "abcdef0123456789", "")).unwrap();
+        assert_eq!(coordinator.add_key_record(&wrong_token), Err(BsmsError::TokenMismatch));
+
+        let record = BsmsRecord::try_from_str(&signer(1, "00", "Signer 1")).unwrap();
+        assert_eq!(coordinator.add_key_record(&record), Ok(1));
+        assert!(matches!(coordinator.add_key_record(&record), Err(BsmsError::DuplicateSigner(_))));
+
+        assert_eq!(coordinator.descriptor_template(), Err(BsmsError::MissingSigners(1)));
+    }
+
+    #[test]
+    fn test_coordinator_rejects_plaintext_when_encrypted() {
+        let coordinator = BsmsCoordinator::try_new(2, 2, BsmsEncryption::Standard).unwrap();
+        let token = coordinator.tokens()[0].clone();
+
+        let plaintext = BsmsRecord::try_from_str(&signer(1, &token, "")).unwrap();
+        assert_eq!(coordinator.add_key_record(&plaintext), Err(BsmsError::NotEncrypted));
+        assert_eq!(coordinator.descriptor_template(), Err(BsmsError::MissingSigners(2)));
+    }
+
+    #[test]
+    fn test_extended_encryption_token_per_signer() {
+        let coordinator = BsmsCoordinator::try_new(2, 2, BsmsEncryption::Extended).unwrap();
+        let tokens = coordinator.tokens();
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[0].len(), 32);
+
+        for (seed, token) in tokens.iter().enumerate() {
+            let record = signer(seed as u8 + 1, token, "");
+            let encrypted = BsmsRecord::try_from_str(&encrypt(token, &record).unwrap()).unwrap();
+            coordinator.add_key_record(&encrypted).unwrap();
+        }
+
+        let descriptor_record =
+            coordinator.descriptor_record_for_network(Network::Testnet).unwrap();
+        assert_eq!(descriptor_record.records.len(), 2);
+        assert!(decrypt(&tokens[1], &descriptor_record.records[1]).is_ok());
+    }
+}
@@ -48,6 +48,7 @@ impl From<GlobalConfigKey> for &'static str {
             GlobalConfigKey::SelectedNode(Network::Testnet) => "selected_node_testnet",
             GlobalConfigKey::SelectedNode(Network::Testnet4) => "selected_node_testnet4",
             GlobalConfigKey::SelectedNode(Network::Signet) => "selected_node_signet",
+            GlobalConfigKey::SelectedNode(Network::Regtest) => "selected_node_regtest",
             GlobalConfigKey::ColorScheme => "color_scheme",
             GlobalConfigKey::AuthType => "auth_type",
             GlobalConfigKey::HashedPinCode => "hashed_pin_code",
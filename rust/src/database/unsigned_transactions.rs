@@ -6,7 +6,7 @@ use super::Error;
 
 use crate::transaction::TxId;
 use cove_types::WalletId;
-use cove_types::confirm::ConfirmDetails;
+use cove_types::confirm::{AddressAndAmount, ConfirmDetails};
 use cove_types::redb::Json;
 
 pub const MAIN_TABLE: TableDefinition<TxId, Json<UnsignedTransactionRecord>> =
@@ -76,6 +76,16 @@ impl UnsignedTransactionsTable {
         Ok(())
     }
 
+    /// Replace an already saved transaction, ex. after adding a cosigner's signatures
+    pub fn update_tx(&self, record: UnsignedTransactionRecord) -> Result<(), Error> {
+        let tx_id = record.tx_id;
+        if self.get(&tx_id)?.is_none() {
+            return Err(UnsignedTransactionsTableError::NoRecordFound.into());
+        }
+
+        self.set(tx_id, record)
+    }
+
     pub fn delete_tx(&self, tx_id: &TxId) -> Result<UnsignedTransactionRecord, Error> {
         let record = self.get(tx_id)?.ok_or(UnsignedTransactionsTableError::NoRecordFound)?;
 
@@ -239,6 +249,11 @@ impl UnsignedTransactionRecord {
         self.confirm_details.clone()
     }
 
+    #[uniffi::method]
+    pub fn recipients(&self) -> Vec<AddressAndAmount> {
+        self.confirm_details.recipients()
+    }
+
     #[uniffi::method]
     pub fn created_at(&self) -> u64 {
         self.created_at
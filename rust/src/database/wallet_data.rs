@@ -1,6 +1,7 @@
 pub mod label;
 
 use std::{
+    collections::BTreeMap,
     path::{Path, PathBuf},
     sync::Arc,
 };
@@ -16,7 +17,8 @@ use crate::wallet::{WalletAddressType, metadata::WalletId};
 use cove_common::consts::WALLET_DATA_DIR;
 use cove_types::redb::Json;
 
-use ahash::AHashMap as HashMap;
+use ahash::{AHashMap as HashMap, HashSet};
+use bitcoin::{OutPoint, Txid};
 
 pub static DATABASE_CONNECTIONS: Lazy<RwLock<HashMap<WalletId, Arc<redb::Database>>>> =
     Lazy::new(|| RwLock::new(HashMap::new()));
@@ -38,11 +40,19 @@ const TABLE: TableDefinition<&'static str, Json<WalletData>> =
 pub enum WalletData {
     /// number of addresses scanned
     ScanState(ScanState),
+
+    /// utxos the user has frozen, these are never picked when building a transaction
+    FrozenUtxos(Vec<OutPoint>),
+
+    /// replacement transactions we built, paired with the transaction they replace
+    Replacements(Vec<(Txid, Txid)>),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, uniffi::Enum)]
 pub enum WalletDataKey {
     ScanState(WalletAddressType),
+    FrozenUtxos,
+    Replacements,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, uniffi::Enum)]
@@ -134,6 +144,65 @@ impl WalletDataDb {
         self.set(key, value)
     }
 
+    pub fn get_frozen_utxos(&self) -> Result<HashSet<OutPoint>> {
+        let value = self.get(WalletDataKey::FrozenUtxos)?;
+
+        let Some(WalletData::FrozenUtxos(frozen)) = value else {
+            return Ok(HashSet::default());
+        };
+
+        Ok(frozen.into_iter().collect())
+    }
+
+    pub fn set_frozen_utxos(&self, frozen: HashSet<OutPoint>) -> Result<()> {
+        debug!("setting frozen utxos, count: {}", frozen.len());
+
+        // sort so the stored value is stable
+        let mut frozen = frozen.into_iter().collect::<Vec<_>>();
+        frozen.sort();
+
+        self.set(WalletDataKey::FrozenUtxos, WalletData::FrozenUtxos(frozen))
+    }
+
+    pub fn freeze_utxos(&self, outpoints: impl IntoIterator<Item = OutPoint>) -> Result<()> {
+        let mut frozen = self.get_frozen_utxos()?;
+        frozen.extend(outpoints);
+        self.set_frozen_utxos(frozen)
+    }
+
+    pub fn unfreeze_utxos(&self, outpoints: impl IntoIterator<Item = OutPoint>) -> Result<()> {
+        let mut frozen = self.get_frozen_utxos()?;
+        for outpoint in outpoints {
+            frozen.remove(&outpoint);
+        }
+
+        self.set_frozen_utxos(frozen)
+    }
+
+    /// Replacement transactions mapped to the transaction they replace
+    pub fn get_replacements(&self) -> Result<BTreeMap<Txid, Txid>> {
+        let value = self.get(WalletDataKey::Replacements)?;
+
+        let Some(WalletData::Replacements(replacements)) = value else {
+            return Ok(BTreeMap::new());
+        };
+
+        Ok(replacements.into_iter().collect())
+    }
+
+    pub fn set_replacements(&self, replacements: BTreeMap<Txid, Txid>) -> Result<()> {
+        debug!("setting replacements, count: {}", replacements.len());
+
+        let replacements = replacements.into_iter().collect::<Vec<_>>();
+        self.set(WalletDataKey::Replacements, WalletData::Replacements(replacements))
+    }
+
+    pub fn add_replacement(&self, replacement: Txid, original: Txid) -> Result<()> {
+        let mut replacements = self.get_replacements()?;
+        replacements.insert(replacement, original);
+        self.set_replacements(replacements)
+    }
+
     fn get(&self, key: WalletDataKey) -> Result<Option<WalletData>> {
         let table = self.read_table()?;
 
@@ -242,6 +311,9 @@ impl WalletDataKey {
                 "scan_state_wrapped_segwit"
             }
             WalletDataKey::ScanState(WalletAddressType::Legacy) => "scan_state_legacy",
+            WalletDataKey::ScanState(WalletAddressType::Taproot) => "scan_state_taproot",
+            WalletDataKey::FrozenUtxos => "frozen_utxos",
+            WalletDataKey::Replacements => "replacements",
         }
     }
 }
@@ -257,3 +329,46 @@ impl From<ScanningInfo> for ScanState {
         Self::Scanning(info)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr as _;
+
+    #[test]
+    fn test_freeze_and_unfreeze_utxos() {
+        let db = WalletDataDb::new_test(WalletId::preview_new_random());
+        assert!(db.get_frozen_utxos().unwrap().is_empty());
+
+        let first = OutPoint::from_str(
+            "d97bf8892657980426c879e4ab2001f09342f1ab61cfa602741a7715a3d60290:0",
+        )
+        .unwrap();
+
+        let second = OutPoint { vout: 1, ..first };
+
+        db.freeze_utxos([first, second]).unwrap();
+        let frozen = db.get_frozen_utxos().unwrap();
+        assert_eq!(frozen.len(), 2);
+
+        db.unfreeze_utxos([first]).unwrap();
+        let frozen = db.get_frozen_utxos().unwrap();
+        assert_eq!(frozen.len(), 1);
+        assert!(frozen.contains(&second));
+    }
+
+    #[test]
+    fn test_replacements_are_persisted() {
+        let db = WalletDataDb::new_test(WalletId::preview_new_random());
+        assert!(db.get_replacements().unwrap().is_empty());
+
+        let original = Txid::from_str(&"ab".repeat(32)).unwrap();
+        let replacement = Txid::from_str(&"cd".repeat(32)).unwrap();
+
+        db.add_replacement(replacement, original).unwrap();
+        assert_eq!(db.get_replacements().unwrap().get(&replacement), Some(&original));
+
+        db.set_replacements(BTreeMap::new()).unwrap();
+        assert!(db.get_replacements().unwrap().is_empty());
+    }
+}
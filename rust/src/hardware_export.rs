@@ -1,21 +1,36 @@
 use std::sync::Arc;
 
-use derive_more::derive::{AsRef, Deref, From, Into};
+use crate::{multisig::MultisigExport, policy::MiniscriptExport};
 
-#[derive(Debug, Clone, Deref, From, Into, AsRef, uniffi::Object)]
-pub struct HardwareExport(Arc<pubport::Format>);
+#[derive(Debug, Clone, uniffi::Object)]
+pub enum HardwareExport {
+    SingleSig(Arc<pubport::Format>),
+    Multisig(Arc<MultisigExport>),
+    Miniscript(Arc<MiniscriptExport>),
+}
 
 impl HardwareExport {
     pub fn new(format: pubport::Format) -> Self {
-        Self(Arc::new(format))
+        Self::SingleSig(Arc::new(format))
     }
+}
 
-    pub fn format(&self) -> &pubport::Format {
-        self.0.as_ref()
+#[uniffi::export]
+impl HardwareExport {
+    /// ex. "2 of 3", `None` for single sig exports
+    pub fn multisig_policy(&self) -> Option<String> {
+        match self {
+            Self::SingleSig(_) | Self::Miniscript(_) => None,
+            Self::Multisig(export) => Some(export.policy()),
+        }
     }
 
-    pub fn into_format(self) -> pubport::Format {
-        Arc::unwrap_or_clone(self.0)
+    /// The descriptor to show before importing, `None` unless its a miniscript policy
+    pub fn miniscript_descriptor(&self) -> Option<String> {
+        match self {
+            Self::Miniscript(export) => Some(export.descriptor()),
+            Self::SingleSig(_) | Self::Multisig(_) => None,
+        }
     }
 }
 
@@ -24,3 +39,15 @@ impl From<pubport::Format> for HardwareExport {
         Self::new(format)
     }
 }
+
+impl From<MultisigExport> for HardwareExport {
+    fn from(export: MultisigExport) -> Self {
+        Self::Multisig(Arc::new(export))
+    }
+}
+
+impl From<MiniscriptExport> for HardwareExport {
+    fn from(export: MiniscriptExport) -> Self {
+        Self::Miniscript(Arc::new(export))
+    }
+}
@@ -1,7 +1,7 @@
 #![allow(dead_code)]
 use std::str::FromStr as _;
 
-use bdk_wallet::bitcoin::bip32::{DerivationPath, Fingerprint};
+use bdk_wallet::bitcoin::bip32::{ChildNumber, DerivationPath, Fingerprint};
 use bdk_wallet::chain::miniscript::descriptor::DescriptorType;
 use bdk_wallet::descriptor::ExtendedDescriptor;
 use bdk_wallet::keys::bip39::Mnemonic;
@@ -12,17 +12,20 @@ use bdk_wallet::{CreateParams, KeychainKind};
 use bdk_wallet::{
     keys::{DescriptorPublicKey as BdkDescriptorPublicKey, KeyMap},
     miniscript::descriptor::{DescriptorXKey, Wildcard},
-    template::{Bip44, Bip49, Bip84, Bip84Public, DescriptorTemplate as _},
+    template::{Bip84Public, DescriptorTemplate as _, P2Pkh, P2TR, P2Wpkh, P2Wpkh_P2Sh},
 };
 use bitcoin::bip32::Xpub;
 use bitcoin::secp256k1;
 use cove_bdk::descriptor_ext::DescriptorExt as _;
 
-use crate::tap_card::tap_signer_reader::DeriveInfo;
+use crate::{tap_card::tap_signer_reader::DeriveInfo, wallet::WalletAddressType};
 use cove_types::Network;
 
 pub type Seed = [u8; 64];
 
+/// Accounts are hardened, so the index must be below 2^31
+pub const MAX_ACCOUNT: u32 = (1 << 31) - 1;
+
 #[derive(Debug, Clone, derive_more::Display, derive_more::From, derive_more::FromStr)]
 pub struct DescriptorSecretKey(pub(crate) BdkDescriptorSecretKey);
 
@@ -85,13 +88,19 @@ impl Descriptors {
     pub fn new_from_tap_signer(derive: &DeriveInfo) -> Result<Self, Error> {
         use bitcoin::{
             NetworkKind,
-            bip32::{ChainCode, ChildNumber, Xpub},
+            bip32::{ChainCode, Xpub},
             secp256k1::PublicKey,
         };
 
-        // dept is always 3 and always the first (0) child, derives the standard derivation path
+        let path = match derive.path.as_slice() {
+            [84, coin @ (0 | 1), account] => [84, *coin, *account],
+            path => return Err(Error::InvalidBip84Path(path.to_vec())),
+        };
+
+        // depth is always 3 and the child is the account, derives the standard derivation path
         let depth = 3;
-        let child_number = ChildNumber::Hardened { index: 0 };
+        let child_number = ChildNumber::from_hardened_idx(path[2])
+            .map_err(|_| Error::InvalidBip84Path(path.to_vec()))?;
 
         // using the master fingerprint as the parent fingerprint )
         let master_fingerprint = derive.master_fingerprint();
@@ -112,12 +121,6 @@ impl Descriptors {
             chain_code,
         };
 
-        let path = match derive.path.as_slice() {
-            [84, 0, 0] => [84, 0, 0],
-            [84, 1, 0] => [84, 1, 0],
-            path => return Err(Error::InvalidBip84Path(path.to_vec())),
-        };
-
         Self::try_new_bip84(xpub, path, master_fingerprint)
     }
 
@@ -127,8 +130,9 @@ impl Descriptors {
         master_fingerprint: Fingerprint,
     ) -> Result<Self, Error> {
         let derivation_path = match path {
-            [84, 0, 0] => "84h/0h/0h",
-            [84, 1, 0] => "84h/1h/0h",
+            [84, coin @ (0 | 1), account] if account <= MAX_ACCOUNT => {
+                format!("84h/{coin}h/{account}h")
+            }
             path => return Err(Error::InvalidBip84Path(path.to_vec())),
         };
 
@@ -139,6 +143,27 @@ impl Descriptors {
         Ok(Self::from(desc))
     }
 
+    /// The address type of the external descriptor, `None` for non single sig descriptors
+    pub fn address_type(&self) -> Option<WalletAddressType> {
+        match self.external.extended_descriptor.desc_type() {
+            DescriptorType::Wpkh => Some(WalletAddressType::NativeSegwit),
+            DescriptorType::ShWpkh => Some(WalletAddressType::WrappedSegwit),
+            DescriptorType::Pkh => Some(WalletAddressType::Legacy),
+            DescriptorType::Tr => Some(WalletAddressType::Taproot),
+            _ => None,
+        }
+    }
+
+    /// The BIP32 account from the key origin, `m/purpose'/coin'/account'`
+    pub fn account(&self) -> Option<u32> {
+        let (_, path) = self.external.origin().ok()?;
+
+        match path.as_ref() {
+            [_, _, ChildNumber::Hardened { index }] => Some(*index),
+            _ => None,
+        }
+    }
+
     pub fn fingerprint(&self) -> Option<Fingerprint> {
         let pub_key = self.external.descriptor_public_key().ok()?;
         let fingerprint = pub_key.master_fingerprint();
@@ -149,6 +174,75 @@ impl Descriptors {
 
         Some(fingerprint)
     }
+
+    /// Parse a watch-only miniscript descriptor, `wsh(..)` or `tr(..)` with script paths
+    ///
+    /// Accepts a multipath `<0;1>` descriptor, a single `/0/*` descriptor (change uses `/1/*`),
+    /// or the external and internal descriptors on separate lines
+    pub fn try_from_miniscript(descriptor: &str) -> Result<Self, Error> {
+        let lines = descriptor.lines().map(str::trim).filter(|line| !line.is_empty());
+        let lines = lines.collect::<Vec<_>>();
+
+        let (external, internal) = match lines.as_slice() {
+            [external, internal] => {
+                (ExtendedDescriptor::from_str(external)?, ExtendedDescriptor::from_str(internal)?)
+            }
+            [descriptor] => split_external_internal(ExtendedDescriptor::from_str(descriptor)?)?,
+            _ => {
+                let error = "expected one multipath descriptor, or an external and internal one";
+                return Err(Error::UnsupportedDescriptor(error.to_string()));
+            }
+        };
+
+        match &external {
+            ExtendedDescriptor::Wsh(_) => {}
+            ExtendedDescriptor::Tr(tr) if tr.tap_tree().is_some() => {}
+            _ => return Err(Error::UnsupportedDescriptorType(external.desc_type())),
+        }
+
+        if !external.has_wildcard() || !internal.has_wildcard() {
+            let error = "descriptor keys must end in a wildcard, ex. /<0;1>/*";
+            return Err(Error::UnsupportedDescriptor(error.to_string()));
+        }
+
+        external.sanity_check()?;
+        internal.sanity_check()?;
+
+        Ok(Self::new_from_public(external, internal))
+    }
+
+    /// A `wsh` or `tr` descriptor with script paths, not one of the standard single sig ones
+    pub fn is_miniscript(&self) -> bool {
+        match &self.external.extended_descriptor {
+            ExtendedDescriptor::Wsh(_) => true,
+            ExtendedDescriptor::Tr(tr) => tr.tap_tree().is_some(),
+            _ => false,
+        }
+    }
+}
+
+fn split_external_internal(
+    descriptor: ExtendedDescriptor,
+) -> Result<(ExtendedDescriptor, ExtendedDescriptor), Error> {
+    if descriptor.is_multipath() {
+        let descriptors = descriptor.into_single_descriptors()?;
+        let [external, internal] = <[_; 2]>::try_from(descriptors).map_err(|_| {
+            Error::UnsupportedDescriptor("multipath descriptor must have two paths".to_string())
+        })?;
+
+        return Ok((external, internal));
+    }
+
+    // no change descriptor, use the same keys on the `/1/*` branch
+    let string = descriptor.to_string();
+    let string = string.split('#').next().unwrap_or_default();
+    if !string.contains("/0/*") {
+        let error = "missing change descriptor, use /<0;1>/* or add a /1/* descriptor";
+        return Err(Error::UnsupportedDescriptor(error.to_string()));
+    }
+
+    let internal = ExtendedDescriptor::from_str(&string.replace("/0/*", "/1/*"))?;
+    Ok((descriptor, internal))
 }
 
 impl Descriptor {
@@ -192,14 +286,16 @@ impl Descriptor {
         secret_key: &DescriptorSecretKey,
         keychain_kind: KeychainKind,
         network: Network,
+        account: u32,
     ) -> Self {
         let derivable_key = &secret_key.0;
 
         match derivable_key {
             BdkDescriptorSecretKey::XPrv(descriptor_x_key) => {
                 let derivable_key = descriptor_x_key.xkey;
+                let path = account_path(84, keychain_kind, network, account);
                 let (extended_descriptor, key_map, _) =
-                    Bip84(derivable_key, keychain_kind).build(network.into()).unwrap();
+                    P2Wpkh((derivable_key, path)).build(network.into()).unwrap();
 
                 Self { extended_descriptor, key_map }
             }
@@ -250,6 +346,7 @@ impl Descriptor {
         secret_key: &DescriptorSecretKey,
         keychain_kind: KeychainKind,
         network: Network,
+        account: u32,
     ) -> Self {
         let derivable_key = &secret_key.0;
 
@@ -259,8 +356,9 @@ impl Descriptor {
             }
             BdkDescriptorSecretKey::XPrv(descriptor_x_key) => {
                 let derivable_key = descriptor_x_key.xkey;
+                let path = account_path(49, keychain_kind, network, account);
                 let (extended_descriptor, key_map, _) =
-                    Bip49(derivable_key, keychain_kind).build(network.into()).unwrap();
+                    P2Wpkh_P2Sh((derivable_key, path)).build(network.into()).unwrap();
                 Self { extended_descriptor, key_map }
             }
             BdkDescriptorSecretKey::MultiXPrv(_) => {
@@ -274,6 +372,33 @@ impl Descriptor {
         secret_key: &DescriptorSecretKey,
         keychain_kind: KeychainKind,
         network: Network,
+        account: u32,
+    ) -> Self {
+        let derivable_key = &secret_key.0;
+
+        match derivable_key {
+            BdkDescriptorSecretKey::Single(_) => {
+                unreachable!()
+            }
+            BdkDescriptorSecretKey::XPrv(descriptor_x_key) => {
+                let derivable_key = descriptor_x_key.xkey;
+                let path = account_path(44, keychain_kind, network, account);
+                let (extended_descriptor, key_map, _) =
+                    P2Pkh((derivable_key, path)).build(network.into()).unwrap();
+                Self { extended_descriptor, key_map }
+            }
+            BdkDescriptorSecretKey::MultiXPrv(_) => {
+                unreachable!()
+            }
+        }
+    }
+
+    /// BIP86 for P2TR key path spends (Taproot)
+    pub(crate) fn new_bip86(
+        secret_key: &DescriptorSecretKey,
+        keychain_kind: KeychainKind,
+        network: Network,
+        account: u32,
     ) -> Self {
         let derivable_key = &secret_key.0;
 
@@ -283,8 +408,9 @@ impl Descriptor {
             }
             BdkDescriptorSecretKey::XPrv(descriptor_x_key) => {
                 let derivable_key = descriptor_x_key.xkey;
+                let path = account_path(86, keychain_kind, network, account);
                 let (extended_descriptor, key_map, _) =
-                    Bip44(derivable_key, keychain_kind).build(network.into()).unwrap();
+                    P2TR((derivable_key, path)).build(network.into()).unwrap();
                 Self { extended_descriptor, key_map }
             }
             BdkDescriptorSecretKey::MultiXPrv(_) => {
@@ -298,6 +424,34 @@ impl Descriptor {
     }
 }
 
+/// `m/purpose'/coin'/account'/change`, the same path the BDK bip templates use for account 0
+fn account_path(
+    purpose: u32,
+    keychain_kind: KeychainKind,
+    network: Network,
+    account: u32,
+) -> DerivationPath {
+    let coin = match network {
+        Network::Bitcoin => 0,
+        _ => 1,
+    };
+
+    let change = match keychain_kind {
+        KeychainKind::External => 0,
+        KeychainKind::Internal => 1,
+    };
+
+    let hardened = |index| ChildNumber::from_hardened_idx(index).expect("valid hardened index");
+    let path = vec![
+        hardened(purpose),
+        hardened(coin),
+        hardened(account),
+        ChildNumber::from_normal_idx(change).expect("valid index"),
+    ];
+
+    DerivationPath::from(path)
+}
+
 impl DescriptorSecretKey {
     pub(crate) fn new(network: Network, mnemonic: Mnemonic, passphrase: Option<String>) -> Self {
         let seed: Seed = mnemonic.to_seed(passphrase.as_deref().unwrap_or(""));
@@ -434,4 +588,118 @@ mod tests {
         let parsed_descriptors = Descriptors::new_from_tap_signer(&derive_info).unwrap();
         assert!(parsed_descriptors.external.xpub().is_some());
     }
+
+    #[test]
+    fn test_bip86_descriptors_match_test_vectors() {
+        let mnemonic = Mnemonic::from_str(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+        )
+        .unwrap();
+
+        let secret_key = DescriptorSecretKey::new(Network::Bitcoin, mnemonic, None);
+        let descriptors = Descriptors {
+            external: Descriptor::new_bip86(
+                &secret_key,
+                KeychainKind::External,
+                Network::Bitcoin,
+                0,
+            ),
+            internal: Descriptor::new_bip86(
+                &secret_key,
+                KeychainKind::Internal,
+                Network::Bitcoin,
+                0,
+            ),
+        };
+
+        assert_eq!(descriptors.address_type(), Some(WalletAddressType::Taproot));
+        assert_eq!(descriptors.origin().unwrap(), "tr([73c5da0a/86'/0'/0'])");
+
+        let wallet = descriptors
+            .into_create_params()
+            .network(bitcoin::Network::Bitcoin)
+            .create_wallet_no_persist()
+            .unwrap();
+
+        let receive = wallet.peek_address(KeychainKind::External, 0).address.to_string();
+        assert_eq!(receive, "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr");
+
+        let change = wallet.peek_address(KeychainKind::Internal, 0).address.to_string();
+        assert_eq!(change, "bc1p3qkhfews2uk44qtvauqyr2ttdsw7svhkl9nkm9s9c3x4ax5h60wqwruhk7");
+    }
+
+    #[test]
+    fn test_account_descriptors() {
+        use bdk_wallet::template::Bip84;
+
+        let mnemonic = Mnemonic::from_str(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+        )
+        .unwrap();
+
+        let secret_key = DescriptorSecretKey::new(Network::Bitcoin, mnemonic, None);
+        let BdkDescriptorSecretKey::XPrv(xprv) = &secret_key.0 else { panic!("xprv") };
+
+        // account 0 is the same wallet the bip templates create
+        let (template, _, _) =
+            Bip84(xprv.xkey, KeychainKind::External).build(bitcoin::Network::Bitcoin).unwrap();
+        let account_0 =
+            Descriptor::new_bip84(&secret_key, KeychainKind::External, Network::Bitcoin, 0);
+        assert_eq!(account_0.extended_descriptor, template);
+
+        let descriptors = Descriptors {
+            external: Descriptor::new_bip84(
+                &secret_key,
+                KeychainKind::External,
+                Network::Bitcoin,
+                1,
+            ),
+            internal: Descriptor::new_bip84(
+                &secret_key,
+                KeychainKind::Internal,
+                Network::Bitcoin,
+                1,
+            ),
+        };
+
+        assert_eq!(descriptors.account(), Some(1));
+        assert_eq!(descriptors.origin().unwrap(), "wpkh([73c5da0a/84'/0'/1'])");
+        assert_ne!(descriptors.external.extended_descriptor, template);
+    }
+
+    #[test]
+    fn test_miniscript_descriptor_parse() {
+        let primary = "[73c5da0a/48h/1h/0h/2h]tpubDE2qaB2bR6mCJycd41cGvu4JFP2gXFTGyLb8nmhZcEqJY7JabfnPG6bXcuTekjpo6No3ZkjZYaPe7r7TBJJGR2mVRsC3hV2NHmEV2JwKFDs";
+        let recovery = "[f245ae38/48h/1h/0h/2h]tpubDE2qaB2bR6mCKJ2ztrMeDgejB6d7UZb6JKxKD5azHhvchQhYBmtuST8umbRZxm8kGhUmcLwaozs7NtXoU8udGoXoGpEJ2D1NpFcyozjELRe";
+
+        let multipath = format!(
+            "wsh(or_d(pk({primary}/<0;1>/*),and_v(v:pk({recovery}/<0;1>/*),older(26280))))"
+        );
+        let descriptors = Descriptors::try_from_miniscript(&multipath).unwrap();
+        assert!(descriptors.is_miniscript());
+        assert_eq!(descriptors.address_type(), None);
+
+        // the same wallet, written as a single receive descriptor
+        let single = multipath.replace("<0;1>", "0");
+        assert_eq!(Descriptors::try_from_miniscript(&single).unwrap(), descriptors);
+
+        // standard single sig descriptors are not miniscript wallets
+        assert!(Descriptors::try_from_miniscript(desc()).is_err());
+
+        // no change branch to derive
+        let no_change = multipath.replace("/<0;1>/*", "/*");
+        assert!(Descriptors::try_from_miniscript(&no_change).is_err());
+    }
+
+    #[test]
+    fn test_address_type_from_imported_descriptor() {
+        let descriptors: Descriptors =
+            pubport::descriptor::Descriptors::try_from_line(desc()).unwrap().into();
+        assert_eq!(descriptors.address_type(), Some(WalletAddressType::NativeSegwit));
+
+        let tr = "tr([73c5da0a/86h/0h/0h]xpub6BgBgsespWvERF3LHQu6CnqdvfEvtMcQjYrcRzx53QJjSxarj2afYWcLteoGVky7D3UKDP9QyrLprQ3VCECoY49yfdDEHGCtMMj92pReUsQ/<0;1>/*)";
+        let descriptors: Descriptors =
+            pubport::descriptor::Descriptors::try_from_line(tr).unwrap().into();
+        assert_eq!(descriptors.address_type(), Some(WalletAddressType::Taproot));
+    }
 }
@@ -172,6 +172,39 @@ impl LabelManager {
         Ok(())
     }
 
+    /// Label one of our receive addresses, ex: from a payment request
+    pub fn insert_or_update_address_label(
+        &self,
+        address: Arc<Address>,
+        label: String,
+    ) -> Result<()> {
+        let label = label.trim();
+        if label.is_empty() {
+            return Ok(());
+        }
+
+        let args = AddressArgs {
+            address: Arc::unwrap_or_clone(address),
+            change_address: None,
+            direction: TransactionDirection::Incoming,
+        };
+
+        self.insert_or_update_address_records(label, args)?;
+        Ok(())
+    }
+
+    /// Set the label for a transaction that hasn't been broadcast yet, ex: from a BIP21 label or
+    /// message, does not replace a label that is already set
+    pub fn prefill_transaction_label(&self, tx_id: Arc<TxId>, label: String) -> Result<()> {
+        let label = label.trim();
+        if label.is_empty() || self.transaction_label(tx_id.clone()).is_some() {
+            return Ok(());
+        }
+
+        self.insert_or_update_transaction_label(&tx_id, label.to_string(), None)?;
+        Ok(())
+    }
+
     pub fn delete_labels_for_txn(&self, tx_id: Arc<TxId>) -> Result<(), LabelManagerError> {
         let Some(txn_label) = self
             .db
@@ -225,15 +258,40 @@ impl LabelManager {
         Ok(())
     }
 
+    /// Copy the transaction label from a replaced transaction to its replacement
+    pub fn copy_labels_for_txn(
+        &self,
+        from_tx_id: Arc<TxId>,
+        to_tx_id: Arc<TxId>,
+    ) -> Result<(), LabelManagerError> {
+        let Some(txn_label) = self
+            .db
+            .labels
+            .get_txn_label_record(from_tx_id.0)
+            .map_err(|e| LabelManagerError::Get(e.to_string()))?
+        else {
+            return Ok(());
+        };
+
+        let Record { item, timestamps } = txn_label;
+        let copied = TransactionRecord { ref_: to_tx_id.0, label: item.label, origin: item.origin };
+
+        self.db
+            .labels
+            .insert_label_with_timestamps(copied, timestamps)
+            .map_err(|e| LabelManagerError::Save(e.to_string()))?;
+
+        Ok(())
+    }
+
     #[uniffi::method(name = "importLabels")]
     pub fn _import_labels(&self, labels: Arc<Bip329Labels>) -> Result<(), LabelManagerError> {
-        let labels = Arc::unwrap_or_clone(labels);
-        self.import_labels(labels.0)
+        self.import_labels(Arc::unwrap_or_clone(labels))
     }
 
     pub fn import(&self, jsonl: &str) -> Result<(), LabelManagerError> {
-        let labels =
-            Labels::try_from_str(jsonl).map_err(|e| LabelManagerError::Parse(e.to_string()))?;
+        let labels = Bip329Labels::try_from_str(jsonl)
+            .map_err(|e| LabelManagerError::Parse(e.to_string()))?;
 
         self.import_labels(labels)
     }
@@ -242,18 +300,60 @@ impl LabelManager {
         let labels =
             self.db.labels.all_labels().map_err(|e| LabelManagerError::Get(e.to_string()))?;
 
-        let labels = labels.export().map_err(|e| LabelManagerError::Export(e.to_string()))?;
+        let mut frozen =
+            self.db.get_frozen_utxos().map_err(|e| LabelManagerError::Get(e.to_string()))?;
+
+        // frozen utxos are exported as non spendable outputs
+        let mut labels = labels
+            .into_iter()
+            .map(|label| match label {
+                Label::Output(mut output) => {
+                    output.spendable = !frozen.remove(&output.ref_);
+                    Label::Output(output)
+                }
+                label => label,
+            })
+            .collect::<Vec<Label>>();
+
+        // frozen utxos without an output label still need a record to carry the flag
+        let mut frozen_without_label = frozen.into_iter().collect::<Vec<_>>();
+        frozen_without_label.sort();
+
+        labels.extend(frozen_without_label.into_iter().map(|outpoint| {
+            Label::Output(OutputRecord { ref_: outpoint, label: None, spendable: false })
+        }));
+
+        let labels =
+            Labels::from(labels).export().map_err(|e| LabelManagerError::Export(e.to_string()))?;
 
         Ok(labels)
     }
 }
 
 impl LabelManager {
-    pub fn import_labels(&self, labels: impl Into<Labels>) -> Result<(), LabelManagerError> {
-        let labels = labels.into();
+    pub fn import_labels(&self, labels: Bip329Labels) -> Result<(), LabelManagerError> {
+        let Bip329Labels { labels, spendable } = labels;
+        let labels = labels.into_iter().collect::<Vec<Label>>();
+
+        // the bip329 `spendable` flag on outputs is how frozen utxos are shared between wallets,
+        // outputs without the flag keep their current frozen state
+        let (to_freeze, to_unfreeze): (Vec<_>, Vec<_>) =
+            spendable.into_iter().partition(|(_, spendable)| !spendable);
 
         self.db.labels.insert_labels(labels).map_err(|e| LabelManagerError::Save(e.to_string()))?;
 
+        if !to_freeze.is_empty() {
+            self.db
+                .freeze_utxos(to_freeze.into_iter().map(|(outpoint, _)| outpoint))
+                .map_err(|e| LabelManagerError::Save(e.to_string()))?;
+        }
+
+        if !to_unfreeze.is_empty() {
+            self.db
+                .unfreeze_utxos(to_unfreeze.into_iter().map(|(outpoint, _)| outpoint))
+                .map_err(|e| LabelManagerError::Save(e.to_string()))?;
+        }
+
         Ok(())
     }
 
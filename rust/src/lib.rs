@@ -6,6 +6,8 @@ mod router;
 mod auth;
 mod autocomplete;
 mod bdk_store;
+mod bip85;
+mod bsms;
 mod build;
 mod converter;
 mod fee_client;
@@ -16,21 +18,31 @@ mod historical_price_service;
 mod keys;
 mod label_manager;
 mod manager;
+mod message_signing;
 mod mnemonic;
 mod multi_format;
 mod multi_qr;
+mod multisig;
 mod node;
 mod node_connect;
 mod pending_wallet;
+mod policy;
+mod psbt_combiner;
+mod psbt_match;
+mod psbt_verify;
 mod push_tx;
+#[cfg(test)]
+mod regtest;
 mod reporting;
 mod seed_qr;
 mod send_flow;
+mod sweep;
 mod tap_card;
 mod task;
 mod transaction;
 mod transaction_watcher;
 mod unblock;
+mod ur;
 mod wallet;
 mod wallet_scanner;
 mod word_validator;
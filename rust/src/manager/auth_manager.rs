@@ -1,4 +1,7 @@
-use std::sync::{Arc, LazyLock};
+use std::{
+    sync::{Arc, LazyLock},
+    time::{Duration, Instant},
+};
 
 use cove_macros::impl_default_for;
 use flume::{Receiver, Sender};
@@ -17,6 +20,9 @@ type Message = AuthManagerReconcileMessage;
 
 pub static AUTH_MANAGER: LazyLock<Arc<RustAuthManager>> = LazyLock::new(RustAuthManager::init);
 
+/// How long an auth check unlocks sensitive actions, ex. deriving BIP85 child seeds
+const SENSITIVE_ACTION_WINDOW: Duration = Duration::from_secs(60);
+
 #[derive(Debug, Clone, Hash, Eq, PartialEq, uniffi::Enum)]
 #[allow(clippy::enum_variant_names)]
 pub enum AuthManagerReconcileMessage {
@@ -36,12 +42,24 @@ pub enum AuthManagerAction {
     DisableDecoyPin,
 }
 
+/// How the user authenticated before a sensitive action
+#[derive(Debug, Clone, Hash, Eq, PartialEq, uniffi::Enum)]
+pub enum SensitiveActionAuth {
+    Pin(String),
+    /// Result of the biometric check the frontend just ran, it can't be checked from here
+    Biometric {
+        authenticated: bool,
+    },
+}
+
 #[derive(Clone, Debug, uniffi::Object)]
 pub struct RustAuthManager {
     #[allow(dead_code)]
     pub state: Arc<RwLock<AuthManagerState>>,
     pub reconciler: Sender<AuthManagerReconcileMessage>,
     pub reconcile_receiver: Arc<Receiver<AuthManagerReconcileMessage>>,
+    /// Last time the user authenticated for a sensitive action
+    sensitive_action_authorized_at: Arc<RwLock<Option<Instant>>>,
 }
 
 #[derive(Clone, Debug, uniffi::Record)]
@@ -62,6 +80,18 @@ pub enum AuthManagerError {
 
     #[error("There was a database error: {0}")]
     DatabaseError(#[from] database::Error),
+
+    #[error("Set up a PIN or Face ID to use this feature")]
+    AuthNotEnabled,
+
+    #[error("Incorrect PIN")]
+    IncorrectPin,
+
+    #[error("Face ID did not succeed, please try again")]
+    BiometricFailed,
+
+    #[error("Please authenticate again to continue")]
+    AuthRequired,
 }
 
 #[uniffi::export]
@@ -96,6 +126,7 @@ impl RustAuthManager {
             state: Arc::new(RwLock::new(AuthManagerState::new())),
             reconciler: sender,
             reconcile_receiver: Arc::new(receiver),
+            sensitive_action_authorized_at: Arc::new(RwLock::new(None)),
         }
         .into()
     }
@@ -209,6 +240,47 @@ impl RustAuthManager {
         Updater::send_update(AppStateReconcileMessage::WalletModeChanged(WalletMode::Main));
     }
 
+    // MARK: SENSITIVE ACTIONS
+
+    /// Unlock sensitive actions for a short time, after the user authenticated with one of the
+    /// methods they have enabled
+    pub fn authorize_sensitive_action(&self, auth: SensitiveActionAuth) -> Result<()> {
+        let auth_type = self.auth_type();
+        if auth_type == AuthType::None || self.is_in_decoy_mode() {
+            return Err(AuthManagerError::AuthNotEnabled);
+        }
+
+        match auth {
+            SensitiveActionAuth::Pin(pin) => {
+                if !matches!(auth_type, AuthType::Pin | AuthType::Both) {
+                    return Err(AuthManagerError::AuthNotEnabled);
+                }
+
+                if !AuthPin::new().check(&pin) {
+                    return Err(AuthManagerError::IncorrectPin);
+                }
+            }
+
+            SensitiveActionAuth::Biometric { authenticated } => {
+                if !matches!(auth_type, AuthType::Biometric | AuthType::Both) {
+                    return Err(AuthManagerError::AuthNotEnabled);
+                }
+
+                if !authenticated {
+                    return Err(AuthManagerError::BiometricFailed);
+                }
+            }
+        }
+
+        *self.sensitive_action_authorized_at.write() = Some(Instant::now());
+        Ok(())
+    }
+
+    /// Lock sensitive actions again, ex. when leaving the screen
+    pub fn revoke_sensitive_action(&self) {
+        *self.sensitive_action_authorized_at.write() = None;
+    }
+
     // MARK: WIPE DATA PIN
 
     /// Check if the wipe data pin is enabled
@@ -352,6 +424,16 @@ impl RustAuthManager {
 }
 
 impl RustAuthManager {
+    /// Errors unless the user authenticated for a sensitive action in the last minute
+    pub fn check_sensitive_action_authorized(&self) -> Result<()> {
+        let authorized_at = *self.sensitive_action_authorized_at.read();
+
+        match authorized_at {
+            Some(at) if at.elapsed() < SENSITIVE_ACTION_WINDOW => Ok(()),
+            _ => Err(AuthManagerError::AuthRequired),
+        }
+    }
+
     fn check_wipe_data_pin(&self, pin: &str) -> bool {
         if pin.is_empty() {
             return false;
@@ -17,11 +17,12 @@ use parking_lot::Mutex;
 
 use crate::task;
 use crate::{
+    database::wallet_data::WalletDataDb,
     manager::deferred_sender::{self, DeferredSender},
     wallet::metadata::WalletMetadata,
 };
 use flume::Receiver;
-use tracing::trace;
+use tracing::{error, trace};
 
 use super::deferred_sender::MessageSender;
 
@@ -68,6 +69,9 @@ pub enum CoinControlManagerAction {
 
     NotifySelectedUtxosChanged(Vec<Arc<OutPoint>>),
     NotifySearchChanged(String),
+
+    FreezeUtxos(Vec<Arc<OutPoint>>),
+    UnfreezeUtxos(Vec<Arc<OutPoint>>),
 }
 
 #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, uniffi::Enum)]
@@ -206,6 +210,8 @@ impl RustCoinControlManager {
             Action::NotifySelectedUtxosChanged(selected_utxos) => {
                 self.notify_selected_utxos_changed(selected_utxos);
             }
+            Action::FreezeUtxos(utxos) => self.set_utxos_frozen(utxos, true),
+            Action::UnfreezeUtxos(utxos) => self.set_utxos_frozen(utxos, false),
         }
     }
 }
@@ -218,6 +224,7 @@ impl RustCoinControlManager {
 
         state.sort_utxos(CoinControlListSort::Date(ListSortDirection::Descending));
         state.load_utxo_labels();
+        state.load_frozen_utxos();
 
         Self {
             state: Arc::new(Mutex::new(state)),
@@ -282,8 +289,9 @@ impl RustCoinControlManager {
 
         let old_selected_utxos = self.state.lock().selected_utxos.clone();
 
+        // frozen utxos can't be spent, so don't select them
         let new_selected_utxos = if old_selected_utxos.is_empty() {
-            self.utxos().into_iter().map(|utxo| utxo.outpoint).collect()
+            self.utxos().into_iter().filter(|utxo| !utxo.frozen).map(|utxo| utxo.outpoint).collect()
         } else {
             vec![]
         };
@@ -303,6 +311,44 @@ impl RustCoinControlManager {
         self.reconciler.send(Message::UpdateTotalSelectedAmount(total_value));
     }
 
+    fn set_utxos_frozen(self: Arc<Self>, utxos: Vec<Arc<OutPoint>>, frozen: bool) {
+        let mut sender = DeferredSender::new(self.reconciler.clone());
+
+        let wallet_id = self.state.lock().wallet_id.clone();
+        let db = WalletDataDb::new_or_existing(wallet_id);
+        let outpoints = utxos.iter().map(|outpoint| bitcoin::OutPoint::from(outpoint.as_ref()));
+
+        let result = if frozen { db.freeze_utxos(outpoints) } else { db.unfreeze_utxos(outpoints) };
+
+        if let Err(error) = result {
+            return error!("unable to update frozen utxos: {error}");
+        }
+
+        self.state.lock().load_frozen_utxos();
+        sender.queue(Message::UpdateUtxos(self.utxos()));
+
+        if !frozen {
+            return;
+        }
+
+        // a frozen utxo can't stay selected
+        let frozen_utxos: HashSet<Arc<OutPoint>> = utxos.into_iter().collect();
+        let selected_utxos = {
+            let mut state = self.state.lock();
+            let before = state.selected_utxos.len();
+            state.selected_utxos.retain(|outpoint| !frozen_utxos.contains(outpoint));
+
+            if before == state.selected_utxos.len() {
+                return;
+            }
+
+            state.selected_utxos.clone()
+        };
+
+        let total_value = self.total_value_of_utxos(&selected_utxos).into();
+        sender.queue(Message::UpdateSelectedUtxos { utxos: selected_utxos, total_value });
+    }
+
     fn notify_search_changed(self: Arc<Self>, search: String) {
         if search == self.state.lock().search {
             return;
@@ -7,6 +7,7 @@ use cove_types::{
     unit::Unit,
     utxo::{Utxo, UtxoType},
 };
+use tracing::error;
 
 use crate::{database::wallet_data::WalletDataDb, wallet::metadata::WalletMetadata};
 
@@ -90,6 +91,32 @@ impl State {
         });
     }
 
+    pub fn load_frozen_utxos(&mut self) {
+        let db = WalletDataDb::new_or_existing(self.wallet_id.clone());
+        let frozen = match db.get_frozen_utxos() {
+            Ok(frozen) => frozen,
+            Err(error) => {
+                error!("unable to load frozen utxos: {error}");
+                return;
+            }
+        };
+
+        self.set_frozen(|outpoint| frozen.contains(outpoint));
+    }
+
+    /// Update the frozen flag on every utxo, including the search results
+    pub fn set_frozen(&mut self, is_frozen: impl Fn(&bitcoin::OutPoint) -> bool) {
+        let search_utxos = match &mut self.filtered_utxos {
+            FilteredUtxos::All => None,
+            FilteredUtxos::Search(utxos) => Some(utxos),
+        };
+
+        self.utxos.iter_mut().chain(search_utxos.into_iter().flatten()).for_each(|utxo| {
+            let outpoint: bitcoin::OutPoint = utxo.outpoint.as_ref().into();
+            utxo.frozen = is_frozen(&outpoint);
+        });
+    }
+
     pub fn sort_utxos(&mut self, sort: ListSort) {
         let utxos = match &mut self.filtered_utxos {
             FilteredUtxos::All => &mut self.utxos,
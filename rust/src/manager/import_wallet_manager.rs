@@ -7,6 +7,7 @@ use parking_lot::RwLock;
 use crate::{
     database::{self, Database},
     keychain::KeychainError,
+    keys::MAX_ACCOUNT,
     mnemonic::MnemonicExt as _,
     wallet::{
         Wallet,
@@ -65,6 +66,9 @@ pub enum ImportWalletError {
 
     #[error("failed to create wallet: {0}")]
     BdkError(String),
+
+    #[error("invalid account {0}, must be less than 2^31")]
+    InvalidAccount(u32),
 }
 
 pub type Error = ImportWalletError;
@@ -94,9 +98,17 @@ impl RustImportWalletManager {
         });
     }
 
-    /// Import wallet view from entered words
-    #[uniffi::method]
-    pub fn import_wallet(&self, entered_words: Vec<Vec<String>>) -> Result<WalletMetadata, Error> {
+    /// Import wallet view from entered words, `account` is the BIP32 account to use
+    #[uniffi::method(default(account = 0))]
+    pub fn import_wallet(
+        &self,
+        entered_words: Vec<Vec<String>>,
+        account: u32,
+    ) -> Result<WalletMetadata, Error> {
+        if account > MAX_ACCOUNT {
+            return Err(ImportWalletError::InvalidAccount(account));
+        }
+
         let words = entered_words.into_iter().flatten().collect::<Vec<String>>().join(" ");
 
         let mnemonic = Mnemonic::parse_in_normalized(Language::English, &words)
@@ -113,6 +125,7 @@ impl RustImportWalletManager {
             .map(|wallets| {
                 wallets
                     .into_iter()
+                    .filter(|wallet_metadata| wallet_metadata.account == account)
                     .filter_map(|wallet_metadata| {
                         let fingerprint = Fingerprint::try_new(&wallet_metadata.id).ok()?;
                         Some((wallet_metadata.id, fingerprint))
@@ -128,9 +141,14 @@ impl RustImportWalletManager {
         // get current number of wallets and add one;
         let number_of_wallets = Database::global().wallets.len(network, mode).unwrap_or(0);
 
-        let name = format!("Wallet {}", number_of_wallets + 1);
-        let wallet_metadata =
+        let name = match account {
+            0 => format!("Wallet {}", number_of_wallets + 1),
+            account => format!("Wallet {} Account {account}", number_of_wallets + 1),
+        };
+
+        let mut wallet_metadata =
             WalletMetadata::new_imported_from_mnemonic(name, network, fingerprint);
+        wallet_metadata.account = account;
 
         Wallet::try_new_persisted_and_selected(wallet_metadata.clone(), mnemonic.clone(), None)
             .map_err(|e| ImportWalletError::WalletImportError(e.to_string()))?;
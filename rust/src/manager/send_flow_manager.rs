@@ -3,6 +3,7 @@ pub mod amount_or_max;
 pub mod btc_on_change;
 pub mod error;
 pub mod fiat_on_change;
+pub mod recipient;
 mod sanitize;
 pub mod state;
 
@@ -42,6 +43,7 @@ use error::SendFlowError;
 use fiat_on_change::FiatOnChangeHandler;
 use flume::Receiver;
 use parking_lot::Mutex;
+use recipient::Recipient;
 use state::{CoinControlMode, EnterMode, SendFlowManagerState, State};
 use tracing::{debug, trace, warn};
 
@@ -101,6 +103,8 @@ pub enum SendFlowManagerReconcileMessage {
 
     UpdateFocusField(Option<SetAmountFocusField>),
 
+    UpdateRecipients(Vec<Recipient>),
+
     UpdateSelectedFeeRate(Arc<FeeRateOptionWithTotalFee>),
     UpdateFeeRateOptions(Arc<FeeRateOptionsWithTotalFee>),
 
@@ -124,6 +128,10 @@ pub enum SendFlowManagerAction {
     SetCoinControlMode(Vec<Utxo>),
     DisableCoinControlMode,
 
+    // batch send, add the entered address and amount as a recipient, or remove one
+    AddRecipient,
+    RemoveRecipient(u32),
+
     SelectFeeRate(Arc<FeeRateOptionWithTotalFee>),
 
     // front end changing text fields
@@ -141,6 +149,7 @@ pub enum SendFlowManagerAction {
     // starting with an amount and address from scan
     NotifyAddressChanged(Arc<Address>),
     NotifyAmountChanged(Arc<Amount>),
+    NotifyTransactionLabelChanged(Option<String>),
 
     // notify coin control custom amount changed
     NotifyCoinControlAmountChanged(f64),
@@ -245,6 +254,11 @@ impl RustSendFlowManager {
         }
     }
 
+    #[uniffi::method]
+    pub fn recipients(&self) -> Vec<Recipient> {
+        self.state.lock().recipients.clone()
+    }
+
     #[uniffi::method]
     pub fn amount_sats(&self) -> u64 {
         self.state.lock().amount_sats.unwrap_or(0)
@@ -447,7 +461,11 @@ impl RustSendFlowManager {
             .trusted_spendable()
             .to_sat();
 
-        if spendable_balance < amount {
+        // other recipients in a batch send are paid from the same balance
+        let other_recipients_sats =
+            recipient::total_fixed_amount(&self.state.lock().recipients).as_sats();
+
+        if spendable_balance < amount + other_recipients_sats {
             let is_max_selected = self.state.lock().max_selected.is_some();
             if is_max_selected {
                 let me = self.clone();
@@ -582,6 +600,10 @@ impl RustSendFlowManager {
 
             Action::NotifyAmountChanged(amount) => self.handle_amount_changed(*amount),
 
+            Action::NotifyTransactionLabelChanged(label) => {
+                self.state.lock().transaction_label = label;
+            }
+
             Action::NotifyFocusFieldChanged { old, new } => {
                 self.handle_focus_field_changed(old, new)
             }
@@ -596,6 +618,9 @@ impl RustSendFlowManager {
                 self.handle_entering_address_changed(string);
             }
 
+            Action::AddRecipient => self.add_recipient(),
+            Action::RemoveRecipient(index) => self.remove_recipient(index as usize),
+
             Action::DisableCoinControlMode => self.disable_coin_control_mode(),
             Action::SetCoinControlMode(utxos) => self.set_coin_control_mode(utxos),
             Action::NotifyCoinControlAmountChanged(amount) => {
@@ -809,6 +834,56 @@ impl RustSendFlowManager {
         });
     }
 
+    fn add_recipient(self: &Arc<Self>) {
+        if self.state.lock().mode.is_coin_control() {
+            return self.send_alert(SendFlowError::BatchWithCoinControl);
+        }
+
+        if !self.validate_address(true) || !self.validate_amount(true) {
+            return;
+        }
+
+        let Some(recipient) = self.state.lock().current_recipient() else {
+            return self.send_alert(SendFlowError::InvalidNumber);
+        };
+
+        let has_max_recipient = self.state.lock().recipients.iter().any(Recipient::is_max);
+        if recipient.is_max() && has_max_recipient {
+            return self.send_alert(SendFlowError::MultipleMaxRecipients);
+        }
+
+        let recipients = {
+            let mut state = self.state.lock();
+            state.recipients.push(recipient);
+            state.recipients.clone()
+        };
+
+        self.reconciler.send(Message::UpdateRecipients(recipients));
+
+        // start entering the next recipient
+        self.clear_send_amount();
+        self.clear_address();
+
+        self.state.lock().focus_field = Some(SetAmountFocusField::Address);
+        self.reconciler.send(Message::UpdateFocusField(Some(SetAmountFocusField::Address)));
+    }
+
+    fn remove_recipient(self: &Arc<Self>, index: usize) {
+        let recipients = {
+            let mut state = self.state.lock();
+            if index >= state.recipients.len() {
+                warn!("remove_recipient: index {index} out of bounds");
+                return;
+            }
+
+            state.recipients.remove(index);
+            state.recipients.clone()
+        };
+
+        self.reconciler.send(Message::UpdateRecipients(recipients));
+        self.sync_wrap_get_or_update_fee_rate_options();
+    }
+
     fn clear_send_amount(self: &Arc<Self>) {
         {
             let mut state = self.state.lock();
@@ -1074,18 +1149,22 @@ impl RustSendFlowManager {
         }
 
         let utxo_list = Arc::new(UtxoList::from(utxos));
-        let total_minus_fees = {
+        let (total_minus_fees, cleared_recipients) = {
             let mut state = self.state.lock();
             let total_fee_sats =
                 state.selected_fee_rate.as_ref().map(|fee_rate| fee_rate.total_fee.as_sats());
 
-            state.mode = EnterMode::coin_control_max(utxo_list.clone());
+            let cleared_recipients = state.enter_coin_control(utxo_list.clone());
             let total_minus_fees =
                 utxo_list.total.as_sats().saturating_sub(total_fee_sats.unwrap_or(1000));
 
-            Amount::from_sat(total_minus_fees)
+            (Amount::from_sat(total_minus_fees), cleared_recipients)
         };
 
+        if cleared_recipients {
+            self.reconciler.send(Message::UpdateRecipients(vec![]));
+        }
+
         // update the fee rate options for utxos
         let me = self.clone();
         task::spawn(async move {
@@ -1136,7 +1215,7 @@ impl RustSendFlowManager {
     ) -> Result<Psbt> {
         debug!("build_psbt_for_amount");
 
-        let (amount, address) = {
+        let recipients = {
             let state = self.state.lock();
 
             let amount_sats = amount
@@ -1153,20 +1232,13 @@ impl RustSendFlowManager {
                 .or_else(|| state.address.clone().map(|address| address.as_ref().clone()))
                 .ok_or_else(|| Error::UnableToBuildTxn("no address".to_string()))?;
 
-            (amount, address)
+            let mut recipients = state.recipients.clone();
+            recipients.push(Recipient { address: Arc::new(address), amount });
+            recipients
         };
 
         let actor = self.wallet_actor();
-        let psbt = match amount {
-            AmountOrMax::Amount(amount) => {
-                let amount = amount.as_ref().0;
-                call!(actor.build_ephemeral_tx(amount, address, fee_rate)).await.unwrap()
-            }
-
-            AmountOrMax::Max => {
-                call!(actor.build_ephemeral_drain_tx(address, fee_rate)).await.unwrap()
-            }
-        }?;
+        let psbt = call!(actor.build_ephemeral_tx(recipients, fee_rate)).await.unwrap()?;
 
         Ok(psbt.into())
     }
@@ -1221,9 +1293,13 @@ impl RustSendFlowManager {
         let mut sender = DeferredSender::new(self.reconciler.clone());
 
         // access the mutex once
-        let (address, fee_rate_options, selected_fee_rate, selected_fee_rate_base) = {
+        let (address, fee_rate_options, selected_fee_rate, selected_fee_rate_base, recipients) = {
             let state = self.state.lock();
 
+            if state.recipients.iter().any(Recipient::is_max) {
+                return Err(Error::MultipleMaxRecipients);
+            }
+
             let address = state.address.clone();
             let address_string = &state.entering_address;
 
@@ -1236,8 +1312,9 @@ impl RustSendFlowManager {
             let fee_rate_options = state.fee_rate_options.clone();
             let selected_fee_rate = state.selected_fee_rate.clone();
             let address = address.ok_or(Error::InvalidAddress(address_string.to_string()))?;
+            let recipients = state.recipients.clone();
 
-            (address, fee_rate_options, selected_fee_rate, selected_fee_rate_base)
+            (address, fee_rate_options, selected_fee_rate, selected_fee_rate_base, recipients)
         };
 
         if fee_rate_options.is_none() {
@@ -1258,13 +1335,17 @@ impl RustSendFlowManager {
         }
 
         let fee_rate = fee_rate.unwrap_or_else(|| FeeRate::from_sat_per_vb(50.0));
-        let psbt: Psbt = call!(wallet_actor.build_ephemeral_drain_tx(address, fee_rate))
+        // the max is whats left over after paying the other recipients of a batch send
+        let other_recipients_total = recipient::total_fixed_amount(&recipients);
+        let recipients = recipients.into_iter().chain([Recipient::max(address)]).collect();
+
+        let psbt: Psbt = call!(wallet_actor.build_ephemeral_tx(recipients, fee_rate))
             .await
             .unwrap()
             .map_err(|error| Error::UnableToGetMaxSend(error.to_string()))?
             .into();
 
-        let total = Arc::new(psbt.output_total_amount());
+        let total = Arc::new(psbt.output_total_amount() - other_recipients_total);
         trace!("psbt: {psbt:?}, total: {total:?}, fee_rate: {fee_rate:?}");
 
         self.state.lock().max_selected = Some(total.clone());
@@ -1409,6 +1490,11 @@ impl RustSendFlowManager {
             return self.send_alert(error);
         }
 
+        // keep the label or message from the uri, to label the transaction once its created
+        if let Some(label) = address_with_network.transaction_label() {
+            self.state.lock().transaction_label = Some(label);
+        }
+
         // set address
         let address = Arc::new(address_with_network.address);
 
@@ -1492,10 +1578,15 @@ impl RustSendFlowManager {
         let send_mode = self.state.lock().mode.clone();
         let manager = self.wallet_manager.clone();
 
+        let mut recipients = self.state.lock().recipients.clone();
+        recipients.push(Recipient::new(address.clone(), amount));
+
+        let transaction_label = self.state.lock().transaction_label.clone();
+
         task::spawn(async move {
             let confirm_details = match send_mode {
                 EnterMode::SetAmount => {
-                    manager.confirm_txn(amount, address, selected_fee_rate.fee_rate).await
+                    manager.confirm_txn(recipients, selected_fee_rate.fee_rate).await
                 }
                 EnterMode::CoinControl(coin_control) => {
                     let amount = match coin_control.is_max_selected {
@@ -1524,8 +1615,23 @@ impl RustSendFlowManager {
 
             let details = Arc::new(details);
 
+            if let Some(label) = transaction_label {
+                let label_manager = manager.label_manager();
+                if let Err(error) =
+                    label_manager.prefill_transaction_label(details.id().into(), label)
+                {
+                    warn!("unable to set transaction label: {error}");
+                }
+            }
+
             // save the unsigned transaction if its a cold wallet
-            if matches!(wallet_type, WalletType::Cold | WalletType::XpubOnly) {
+            if matches!(
+                wallet_type,
+                WalletType::Cold
+                    | WalletType::XpubOnly
+                    | WalletType::Multisig
+                    | WalletType::Miniscript
+            ) {
                 if let Err(e) = manager.save_unsigned_transaction(details.clone()) {
                     let error = SendFlowError::UnableToSaveUnsignedTransaction(e.to_string());
                     me.send_alert_async(error).await;
@@ -1535,7 +1641,10 @@ impl RustSendFlowManager {
             // update the route send the frontend to the proper next screen
             let next_route = match wallet_type {
                 WalletType::Hot => RouteFactory::new().send_confirm(wallet_id, details, None, None),
-                WalletType::Cold | WalletType::XpubOnly => {
+                WalletType::Cold
+                | WalletType::XpubOnly
+                | WalletType::Multisig
+                | WalletType::Miniscript => {
                     RouteFactory::new().send_hardware_export(wallet_id, details)
                 }
                 WalletType::WatchOnly => {
@@ -1703,20 +1812,25 @@ impl RustSendFlowManager {
 
         let amount_for_fee_calc = Amount::from_sat(amount_sats_for_fee_calc);
         let max_selected = self.state.lock().max_selected.clone();
+        let other_recipients = self.state.lock().recipients.clone();
 
         let new_fee_rate_options = match (max_selected, &mode) {
-            (Some(_), _) => {
+            (Some(_), EnterMode::CoinControl(_)) => {
                 call!(wallet_actor.fee_rate_options_with_total_fee_for_drain(
                     fee_rate_options_base,
                     address.clone()
                 ))
             }
-            (None, EnterMode::SetAmount) => {
-                call!(wallet_actor.fee_rate_options_with_total_fee(
-                    fee_rate_options_base,
-                    amount_for_fee_calc.into(),
-                    address.clone()
-                ))
+            (max_selected, EnterMode::SetAmount) => {
+                let recipient = match max_selected {
+                    Some(_) => Recipient::max(address.clone()),
+                    None => Recipient::new(address.clone(), amount_for_fee_calc),
+                };
+
+                let recipients = other_recipients.into_iter().chain([recipient]).collect();
+                call!(
+                    wallet_actor.fee_rate_options_with_total_fee(fee_rate_options_base, recipients)
+                )
             }
             (None, EnterMode::CoinControl(cc)) => {
                 call!(wallet_actor.fee_rate_options_with_total_fee_for_manual(
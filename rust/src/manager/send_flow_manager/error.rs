@@ -34,6 +34,12 @@ pub enum SendFlowError {
     #[error("unable to get fee rate")]
     UnableToGetFeeRate,
 
+    #[error("only one recipient can send the max remaining amount")]
+    MultipleMaxRecipients,
+
+    #[error("sending to multiple recipients is not supported with coin control")]
+    BatchWithCoinControl,
+
     #[error("unable to build txn: {0}")]
     UnableToBuildTxn(String),
 
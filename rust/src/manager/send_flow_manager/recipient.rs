@@ -0,0 +1,42 @@
+use std::sync::Arc;
+
+use cove_types::amount::Amount;
+
+use super::amount_or_max::AmountOrMax;
+use crate::wallet::Address;
+
+/// One output of a send, a batch send has many of these but only one can be `Max`,
+/// the max recipient gets whatever is left after the other recipients and fees
+#[derive(Debug, Clone, PartialEq, Eq, Hash, uniffi::Record)]
+pub struct Recipient {
+    pub address: Arc<Address>,
+    pub amount: AmountOrMax,
+}
+
+impl Recipient {
+    pub fn new(address: impl Into<Arc<Address>>, amount: Amount) -> Self {
+        Self { address: address.into(), amount: AmountOrMax::Amount(Arc::new(amount)) }
+    }
+
+    pub fn max(address: impl Into<Arc<Address>>) -> Self {
+        Self { address: address.into(), amount: AmountOrMax::Max }
+    }
+
+    pub fn is_max(&self) -> bool {
+        matches!(self.amount, AmountOrMax::Max)
+    }
+
+    /// The fixed amount for this recipient, `None` if it is the max remaining recipient
+    pub fn amount(&self) -> Option<Amount> {
+        match &self.amount {
+            AmountOrMax::Amount(amount) => Some(**amount),
+            AmountOrMax::Max => None,
+        }
+    }
+}
+
+/// Total of all the fixed amount recipients
+pub fn total_fixed_amount(recipients: &[Recipient]) -> Amount {
+    let sats = recipients.iter().filter_map(Recipient::amount).map(|amount| amount.as_sats()).sum();
+    Amount::from_sat(sats)
+}
@@ -8,7 +8,7 @@ use cove_types::{
     utxo::UtxoList,
 };
 
-use super::SetAmountFocusField;
+use super::{SetAmountFocusField, recipient::Recipient};
 use crate::{
     app::App,
     database::Database,
@@ -44,6 +44,12 @@ pub struct SendFlowManagerState {
     pub address: Option<Arc<Address>>,
     pub focus_field: Option<SetAmountFocusField>,
 
+    /// recipients already added for a batch send, not including the one being entered
+    pub recipients: Vec<Recipient>,
+
+    /// label for the transaction, from the label or message of a scanned BIP21 URI
+    pub transaction_label: Option<String>,
+
     pub selected_fee_rate: Option<Arc<FeeRateOptionWithTotalFee>>,
     pub fee_rate_options: Option<Arc<FeeRateOptionsWithTotalFee>>,
 }
@@ -133,6 +139,8 @@ impl SendFlowManagerState {
             max_selected: None,
             focus_field: None,
             address: None,
+            recipients: Vec::new(),
+            transaction_label: None,
             selected_fee_rate: None,
             wallet_balance: None,
             fee_rate_options: None,
@@ -141,6 +149,28 @@ impl SendFlowManagerState {
             init_complete: false,
         }
     }
+
+    /// The recipient currently being entered, once both the address and amount are set
+    pub fn current_recipient(&self) -> Option<Recipient> {
+        let address = self.address.clone()?;
+        if self.max_selected.is_some() {
+            return Some(Recipient::max(address));
+        }
+
+        let amount = Amount::from_sat(self.amount_sats?);
+        Some(Recipient::new(address, amount))
+    }
+
+    /// Coin control sends the selected UTXOs to a single recipient, recipients already added for
+    /// a batch send would be left out, so they are cleared. Returns true if any were cleared
+    pub fn enter_coin_control(&mut self, utxo_list: Arc<UtxoList>) -> bool {
+        self.mode = EnterMode::coin_control_max(utxo_list);
+
+        let had_recipients = !self.recipients.is_empty();
+        self.recipients.clear();
+
+        had_recipients
+    }
 }
 
 impl From<SendFlowManagerState> for State {
@@ -154,3 +184,56 @@ impl From<Arc<Mutex<SendFlowManagerState>>> for State {
         Self(state)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> SendFlowManagerState {
+        SendFlowManagerState {
+            metadata: WalletMetadata::preview_new(),
+            fee_rate_options_base: None,
+            btc_price_in_fiat: None,
+            selected_fiat_currency: FiatCurrency::default(),
+            first_address: None,
+            wallet_balance: None,
+            init_complete: true,
+            mode: EnterMode::SetAmount,
+            entering_btc_amount: String::new(),
+            entering_fiat_amount: String::new(),
+            entering_address: String::new(),
+            amount_sats: None,
+            amount_fiat: None,
+            max_selected: None,
+            address: None,
+            focus_field: None,
+            recipients: Vec::new(),
+            transaction_label: None,
+            selected_fee_rate: None,
+            fee_rate_options: None,
+        }
+    }
+
+    #[test]
+    fn test_coin_control_clears_batch_recipients() {
+        let mut state = state();
+        state.recipients.push(Recipient::new(Address::preview_new(), Amount::from_sat(10_000)));
+        state.recipients.push(Recipient::max(Address::preview_new()));
+
+        let cleared = state.enter_coin_control(Arc::new(UtxoList::from(vec![])));
+
+        assert!(cleared);
+        assert!(state.recipients.is_empty());
+        assert!(state.mode.is_coin_control());
+    }
+
+    #[test]
+    fn test_coin_control_without_recipients() {
+        let mut state = state();
+
+        let cleared = state.enter_coin_control(Arc::new(UtxoList::from(vec![])));
+
+        assert!(!cleared);
+        assert!(state.mode.is_coin_control());
+    }
+}
@@ -6,7 +6,7 @@ use std::{
 };
 
 use act_zero::{Addr, call, send};
-use actor::WalletActor;
+use actor::{ReplacementKind, WalletActor};
 use flume::Receiver;
 use parking_lot::RwLock;
 use tap::TapFallible as _;
@@ -15,7 +15,7 @@ use tracing::{debug, error, warn};
 use cove_util::format::NumberFormatter as _;
 
 use crate::{
-    app::FfiApp,
+    app::{AppAction, FfiApp},
     converter::{Converter, ConverterError},
     database::{Database, error::DatabaseError},
     fee_client::{FEE_CLIENT, FEES, FeeResponse},
@@ -24,10 +24,19 @@ use crate::{
         client::{FIAT_CLIENT, PriceResponse},
     },
     keychain::{Keychain, KeychainError},
+    keys::MAX_ACCOUNT,
     label_manager::LabelManager,
+    message_signing::{self, MessageSignatureFormat},
+    multisig::{MultisigConfig, MultisigSignatureStatus},
+    node::client::NodeClient,
+    policy::SpendingPath,
     psbt::Psbt,
+    psbt_combiner::{self, PsbtCombineError},
+    psbt_match,
+    psbt_verify::PsbtVerification,
     reporting::HistoricalFiatPriceReport,
-    router::Route,
+    router::{Route, RouteFactory},
+    sweep::SweepKey,
     tap_card::tap_signer_reader::DeriveInfo,
     task::{self, spawn_actor},
     transaction::{
@@ -37,23 +46,31 @@ use crate::{
     wallet::{
         Address, AddressInfo, Wallet, WalletAddressType, WalletError,
         balance::Balance,
+        export::{WalletExport, WalletExportFormat},
         fingerprint::Fingerprint,
-        metadata::{DiscoveryState, FiatOrBtc, WalletColor, WalletId, WalletMetadata},
+        metadata::{
+            CoinSelectionStrategy, DiscoveryState, FiatOrBtc, WalletColor, WalletId,
+            WalletMetadata, WalletType,
+        },
     },
-    wallet_scanner::{ScannerResponse, WalletScanner},
+    wallet_scanner::{self, ScannerResponse, WalletScanner},
     word_validator::WordValidator,
 };
 
 use cove_types::{
-    address::AddressInfoWithDerivation,
-    confirm::{ConfirmDetails, SplitOutput},
+    address::{AddressInfoWithDerivation, bip21::Bip21},
+    confirm::{ConfirmDetails, CpfpConfirmDetails, SplitOutput},
+    consolidation::ConsolidationPlan,
+};
+use cove_types::{
+    confirm::AddressAndAmount,
+    fees::{BdkFeeRate, FeeRateOption, FeeRateOptions, FeeRateOptionsWithTotalFee},
 };
-use cove_types::{confirm::AddressAndAmount, fees::FeeRateOptions};
 
 use super::{
     coin_control_manager::RustCoinControlManager,
     deferred_sender::{self, MessageSender},
-    send_flow_manager::RustSendFlowManager,
+    send_flow_manager::{RustSendFlowManager, recipient::Recipient},
 };
 
 type Action = WalletManagerAction;
@@ -81,6 +98,12 @@ pub enum WalletManagerReconcileMessage {
     UnsignedTransactionsChanged,
 
     SendFlowError(SendFlowErrorAlert),
+
+    /// one version of a replaced (fee bumped or cancelled) transaction was confirmed
+    ReplacementConfirmed {
+        confirmed: Arc<TxId>,
+        replaced: Arc<TxId>,
+    },
 }
 
 #[derive(Debug, Clone, Hash, Eq, PartialEq, uniffi::Enum)]
@@ -94,6 +117,9 @@ pub enum WalletManagerAction {
     ToggleFiatOrBtc,
     ToggleFiatBtcPrimarySecondary,
     ToggleShowLabels,
+    UpdateCoinSelection(CoinSelectionStrategy),
+    /// `None` goes back to using the first available path
+    SelectSpendingPath(Option<u32>),
     SelectCurrentWalletAddressType,
     SelectDifferentWalletAddressType(WalletAddressType),
     SelectedWalletDisappeared,
@@ -113,6 +139,14 @@ pub enum WalletErrorAlert {
     NoBalance,
 }
 
+/// Amount for a payment request, fiat is converted using the current price of the selected currency
+#[derive(Debug, Clone, PartialEq, uniffi::Enum)]
+pub enum PaymentRequestAmount {
+    Btc(f64),
+    Sats(u64),
+    Fiat(f64),
+}
+
 #[derive(Debug, Clone, Hash, Eq, PartialEq, uniffi::Enum)]
 pub enum SendFlowErrorAlert {
     SignAndBroadcast(String),
@@ -223,6 +257,45 @@ pub enum WalletManagerError {
 
     #[error("Unable to add UTXOs to PSBT: {0}")]
     AddUtxosError(String),
+
+    #[error("Unable to bump fee: {0}")]
+    FeeBumpError(String),
+
+    #[error("Unable to build child-pays-for-parent transaction: {0}")]
+    CpfpError(String),
+
+    #[error("Unable to cancel transaction: {0}")]
+    CancelTxError(String),
+
+    #[error("Unable to create payment request: {0}")]
+    PaymentRequestError(String),
+
+    #[error("UTXO {0} is frozen, unfreeze it to spend it")]
+    SpendFrozenUtxo(String),
+
+    #[error("Unable to consolidate UTXOs: {0}")]
+    ConsolidationError(String),
+
+    #[error("Unable to sweep private key: {0}")]
+    SweepError(String),
+
+    #[error("Unable to add multisig signatures: {0}")]
+    MultisigSignatureError(String),
+
+    #[error("Unable to use spending path: {0}")]
+    SpendingPathError(String),
+
+    #[error("Unable to add account: {0}")]
+    AddAccountError(String),
+
+    #[error("Unable to export wallet: {0}")]
+    ExportError(String),
+
+    #[error("Unable to verify signed PSBT: {0}")]
+    VerifyPsbtError(String),
+
+    #[error("Unable to sign message: {0}")]
+    SignMessageError(String),
 }
 
 #[uniffi::export(async_runtime = "tokio")]
@@ -377,6 +450,25 @@ impl RustWalletManager {
         Ok(csv.into_string())
     }
 
+    /// Export the wallet's public descriptors, to watch or co-sign from another wallet
+    #[uniffi::method]
+    pub async fn export_wallet(
+        &self,
+        format: WalletExportFormat,
+    ) -> Result<Arc<WalletExport>, Error> {
+        let mut source = call!(self.actor.export_source())
+            .await
+            .map_err(|_| Error::ExportError("unable to get wallet descriptors".to_string()))?;
+
+        // the name can change after the actor loaded the wallet
+        source.name = self.metadata.read().name.clone();
+
+        let export = WalletExport::try_new(format, &source)
+            .map_err(|error| Error::ExportError(error.to_string()))?;
+
+        Ok(Arc::new(export))
+    }
+
     #[uniffi::method]
     pub async fn first_address(&self) -> Result<AddressInfo, Error> {
         let address_info = call!(self.actor.address_at(0))
@@ -466,6 +558,62 @@ impl RustWalletManager {
         Ok(())
     }
 
+    /// Merge the signatures from a cosigner's signed PSBT into the saved unsigned transaction,
+    /// returns how many of the required signatures are now present
+    #[uniffi::method]
+    pub fn add_multisig_signatures(
+        &self,
+        psbt: Arc<Psbt>,
+    ) -> Result<MultisigSignatureStatus, Error> {
+        let config = self.multisig_config()?;
+        let tx_id = psbt.tx_id();
+
+        let db = Database::global().unsigned_transactions();
+        let mut record = db.get_tx(&tx_id)?.ok_or_else(|| {
+            Error::MultisigSignatureError("PSBT is not for a transaction in this wallet".into())
+        })?;
+
+        let saved = record.confirm_details.psbt.0.clone();
+        let combined = psbt_combiner::combine([saved, Arc::unwrap_or_clone(psbt).0])
+            .map_err(|error| Error::MultisigSignatureError(error.to_string()))?;
+
+        let status = config.signature_status(&combined);
+        let progress = psbt_combiner::progress(&combined);
+        debug!(
+            "multisig signatures {} of {}, {} inputs ready",
+            status.collected, status.required, progress.ready_inputs
+        );
+
+        record.confirm_details.psbt = combined.into();
+        db.update_tx(record)?;
+
+        self.reconciler.send(Message::UnsignedTransactionsChanged);
+
+        Ok(status)
+    }
+
+    #[uniffi::method]
+    pub fn multisig_signature_status(
+        &self,
+        tx_id: Arc<TxId>,
+    ) -> Result<MultisigSignatureStatus, Error> {
+        let config = self.multisig_config()?;
+        let psbt = self.multisig_signed_psbt(tx_id)?;
+
+        Ok(config.signature_status(&psbt.0))
+    }
+
+    /// The unsigned transaction's PSBT with every signature collected so far
+    #[uniffi::method]
+    pub fn multisig_signed_psbt(&self, tx_id: Arc<TxId>) -> Result<Arc<Psbt>, Error> {
+        let record =
+            Database::global().unsigned_transactions().get_tx(tx_id.as_ref())?.ok_or_else(
+                || Error::MultisigSignatureError("unsigned transaction not found".into()),
+            )?;
+
+        Ok(Arc::new(record.confirm_details.psbt))
+    }
+
     #[uniffi::method]
     pub async fn balance(&self) -> Balance {
         call!(self.actor.balance()).await.unwrap_or_default()
@@ -495,6 +643,8 @@ impl RustWalletManager {
             error!("unable to delete unsigned transaction record: {error}");
         }
 
+        self.copy_labels_if_replacement(tx_id).await;
+
         self.force_wallet_scan().await;
 
         Ok(())
@@ -618,6 +768,15 @@ impl RustWalletManager {
         Ok(height as u32)
     }
 
+    /// Spending paths of a miniscript policy wallet, and if they can be used at the current height
+    #[uniffi::method]
+    pub async fn spending_paths(&self) -> Result<Vec<SpendingPath>, Error> {
+        let height = self.current_block_height().await?;
+        let paths = call!(self.actor.spending_paths(height)).await.unwrap()?;
+
+        Ok(paths)
+    }
+
     #[uniffi::method]
     pub async fn transaction_details(&self, tx_id: Arc<TxId>) -> Result<TransactionDetails, Error> {
         let tx_id = Arc::unwrap_or_clone(tx_id);
@@ -656,6 +815,55 @@ impl RustWalletManager {
         Ok(address)
     }
 
+    /// Receive addresses that have been paid, most recent first
+    #[uniffi::method]
+    pub async fn used_addresses(&self) -> Result<Vec<AddressInfoWithDerivation>, Error> {
+        let addresses =
+            call!(self.actor.used_addresses()).await.map_err(|_| Error::ActorNotFound)?;
+
+        Ok(addresses)
+    }
+
+    /// Build a BIP21 `bitcoin:` URI for one of our addresses, the label is saved as the address
+    /// label so any transaction paying it is labelled
+    #[uniffi::method]
+    pub async fn payment_request_uri(
+        &self,
+        address: Arc<Address>,
+        amount: Option<PaymentRequestAmount>,
+        label: Option<String>,
+        message: Option<String>,
+    ) -> Result<String, Error> {
+        let amount = match amount {
+            None => None,
+            Some(PaymentRequestAmount::Sats(sats)) => Some(Amount::from_sat(sats)),
+            Some(PaymentRequestAmount::Btc(btc)) => {
+                let amount = Amount::from_btc(btc)
+                    .map_err(|error| Error::PaymentRequestError(error.to_string()))?;
+                Some(amount)
+            }
+            Some(PaymentRequestAmount::Fiat(fiat)) => {
+                let prices = FIAT_CLIENT.get_or_fetch_prices().await.map_err(|error| {
+                    Error::PaymentRequestError(format!("unable to get prices: {error}"))
+                })?;
+
+                let currency = self.selected_fiat_currency();
+                Some(Converter::new().convert_from_fiat(fiat, currency, prices))
+            }
+        };
+
+        if let Some(label) = label.as_ref().filter(|label| !label.trim().is_empty()) {
+            self.label_manager
+                .insert_or_update_address_label(address.clone(), label.clone())
+                .map_err(|error| Error::PaymentRequestError(error.to_string()))?;
+        }
+
+        let address = Arc::unwrap_or_clone(address);
+        let request = Bip21::new_request(address, amount, label, message);
+
+        Ok(request.to_string())
+    }
+
     /// Get address at the given index
     #[uniffi::method]
     pub async fn address_at(&self, index: u32) -> Result<AddressInfo, Error> {
@@ -737,6 +945,75 @@ impl RustWalletManager {
         });
     }
 
+    /// Accounts of this wallet's seed that have been used before, and aren't added yet
+    ///
+    /// Checks native segwit, the address type new account wallets are created with
+    #[uniffi::method]
+    pub async fn discover_accounts(&self) -> Result<Vec<u32>, Error> {
+        let metadata = self.metadata.read().clone();
+        let mnemonic = Keychain::global()
+            .get_wallet_key(&self.id)?
+            .ok_or_else(|| Error::AddAccountError("wallet has no secret words".to_string()))?;
+
+        let node = Database::global().global_config.selected_node();
+        let client = NodeClient::new(&node)
+            .await
+            .map_err(|error| Error::NodeConnectionFailed(error.to_string()))?;
+
+        let used_accounts = wallet_scanner::find_used_accounts(
+            &mnemonic,
+            metadata.network.into(),
+            WalletAddressType::NativeSegwit,
+            &client,
+        )
+        .await
+        .map_err(|error| Error::WalletScanError(error.to_string()))?;
+
+        let added_accounts = self.added_accounts();
+        let accounts =
+            used_accounts.into_iter().filter(|account| !added_accounts.contains(account)).collect();
+
+        Ok(accounts)
+    }
+
+    /// Add another account of this wallet's seed as its own wallet, and select it
+    #[uniffi::method]
+    pub fn add_account(&self, account: u32) -> Result<WalletMetadata, Error> {
+        let parent = self.metadata.read().clone();
+        if parent.wallet_type != WalletType::Hot {
+            let error = "only wallets with secret words can add accounts";
+            return Err(Error::AddAccountError(error.to_string()));
+        }
+
+        if account > MAX_ACCOUNT {
+            let error = "account must be less than 2^31";
+            return Err(Error::AddAccountError(error.to_string()));
+        }
+
+        let fingerprint = parent
+            .master_fingerprint
+            .clone()
+            .ok_or_else(|| Error::AddAccountError("wallet has no fingerprint".to_string()))?;
+
+        if self.added_accounts().contains(&account) {
+            return Err(Error::AddAccountError(format!("account {account} is already added")));
+        }
+
+        let mnemonic = Keychain::global()
+            .get_wallet_key(&self.id)?
+            .ok_or_else(|| Error::AddAccountError("wallet has no secret words".to_string()))?;
+
+        let name = format!("{} Account {account}", parent.name);
+        let mut metadata = WalletMetadata::new(name, Some(fingerprint));
+        metadata.account = account;
+        metadata.verified = parent.verified;
+
+        let wallet = Wallet::try_new_persisted_and_selected(metadata, mnemonic, None)
+            .map_err(|error| Error::AddAccountError(error.to_string()))?;
+
+        Ok(wallet.metadata)
+    }
+
     #[uniffi::method]
     pub fn mark_wallet_as_verified(&self) -> Result<(), Error> {
         {
@@ -835,6 +1112,33 @@ impl RustWalletManager {
         });
     }
 
+    /// Confirm details for a PSBT from somewhere else, merged with our saved unsigned transaction
+    /// for the same txid so signatures collected so far are kept
+    #[uniffi::method]
+    pub async fn confirm_details_for_psbt(&self, psbt: Arc<Psbt>) -> Result<ConfirmDetails, Error> {
+        use bdk_wallet::psbt::PsbtUtils as _;
+
+        let mut psbt = Arc::unwrap_or_clone(psbt).0;
+        let tx_id = psbt.unsigned_tx.compute_txid().into();
+
+        if let Some(record) = Database::global().unsigned_transactions().get_tx(&tx_id)? {
+            match psbt_combiner::combine([record.confirm_details.psbt.0, psbt.clone()]) {
+                Ok(combined) => psbt = combined,
+                Err(error) => warn!("unable to merge with saved unsigned transaction: {error}"),
+            }
+        }
+
+        let progress = psbt_combiner::progress(&psbt);
+        debug!("PSBT has {} of {} inputs ready", progress.ready_inputs, progress.inputs.len());
+
+        let fee_rate = psbt.fee_rate().ok_or_else(|| {
+            Error::GetConfirmDetailsError("unable to get fee rate, PSBT is missing UTXOs".into())
+        })?;
+
+        let details = call!(self.actor.get_confirm_details(psbt, fee_rate)).await.unwrap()?;
+        Ok(details)
+    }
+
     /// Finalize a signed PSBT
     #[uniffi::method]
     pub async fn finalize_psbt(&self, psbt: Arc<Psbt>) -> Result<BitcoinTransaction, Error> {
@@ -845,6 +1149,337 @@ impl RustWalletManager {
         Ok(BitcoinTransaction::from(transaction))
     }
 
+    /// Independently check a signed PSBT against the confirmed details before approving it
+    #[uniffi::method]
+    pub async fn verify_signed_psbt(
+        &self,
+        details: Arc<ConfirmDetails>,
+        psbt: Arc<Psbt>,
+    ) -> Result<PsbtVerification, Error> {
+        // outputs to our other wallets are checked against their saved descriptors
+        let others = psbt_match::saved_wallet_descriptors()
+            .map_err(|error| Error::VerifyPsbtError(error.to_string()))?
+            .into_iter()
+            .filter(|other| other.id != self.id)
+            .collect::<Vec<_>>();
+
+        let psbt = Arc::unwrap_or_clone(psbt).0;
+        let exported = details.psbt.0.clone();
+        let confirmed_fee = details.fee_total.0;
+
+        let verification =
+            call!(self.actor.verify_signed_psbt(psbt, exported, confirmed_fee, others))
+                .await
+                .map_err(|_| Error::ActorNotFound)?;
+
+        Ok(verification)
+    }
+
+    /// Sign a message with one of this wallet's addresses to prove ownership, hot wallets only
+    #[uniffi::method]
+    pub fn sign_message(
+        &self,
+        address: Arc<AddressInfoWithDerivation>,
+        message: String,
+        format: Option<MessageSignatureFormat>,
+    ) -> Result<String, Error> {
+        use crate::mnemonic::MnemonicExt as _;
+
+        let metadata = self.metadata.read().clone();
+        let mnemonic = Keychain::global()
+            .get_wallet_key(&self.id)?
+            .ok_or_else(|| Error::SignMessageError("wallet has no secret words".to_string()))?;
+
+        let descriptors = mnemonic.into_descriptors(
+            None,
+            metadata.network,
+            metadata.address_type,
+            metadata.account,
+        );
+
+        let info = &address.info;
+        message_signing::sign(
+            descriptors,
+            metadata.network.into(),
+            info.keychain,
+            info.index,
+            &info.address,
+            &message,
+            format,
+        )
+        .map_err(|error| Error::SignMessageError(error.to_string()))
+    }
+
+    /// The BIP322 `to_sign` PSBT for a hardware wallet to sign, export it like any other PSBT
+    /// then turn it into a signature with `message_signature_from_psbt`
+    #[uniffi::method]
+    pub async fn message_signing_psbt(
+        &self,
+        address: Arc<AddressInfoWithDerivation>,
+        message: String,
+    ) -> Result<Arc<Psbt>, Error> {
+        let source = call!(self.actor.export_source())
+            .await
+            .map_err(|_| Error::SignMessageError("unable to get wallet descriptors".to_string()))?;
+
+        let info = &address.info;
+        let descriptor = match info.keychain {
+            bdk_wallet::KeychainKind::External => &source.external,
+            bdk_wallet::KeychainKind::Internal => &source.internal,
+        };
+
+        let definite = message_signing::derive(descriptor, info.index)
+            .map_err(|error| Error::SignMessageError(error.to_string()))?;
+
+        if definite.script_pubkey() != info.address.script_pubkey() {
+            return Err(Error::SignMessageError("address is not derived by this wallet".into()));
+        }
+
+        let psbt = message_signing::to_sign_psbt(&definite, &message)
+            .map_err(|error| Error::SignMessageError(error.to_string()))?;
+
+        Ok(Arc::new(psbt.into()))
+    }
+
+    /// Combine the PSBTs signers sent back for the same transaction, then finalize
+    #[uniffi::method]
+    pub fn finalize_psbts(
+        &self,
+        psbts: Vec<Arc<Psbt>>,
+    ) -> Result<BitcoinTransaction, PsbtCombineError> {
+        let psbts = psbts.into_iter().map(|psbt| Arc::unwrap_or_clone(psbt).0);
+        let combined = psbt_combiner::combine(psbts)?;
+
+        let transaction = psbt_combiner::finalize(combined)?;
+        Ok(BitcoinTransaction::from(transaction))
+    }
+
+    /// Fee options for replacing an unconfirmed outgoing transaction, never below the minimum
+    /// fee rate the replacement needs to be relayed
+    #[uniffi::method]
+    pub async fn fee_bump_fee_rate_options(
+        &self,
+        tx_id: Arc<TxId>,
+    ) -> Result<FeeRateOptionsWithTotalFee, Error> {
+        let fee_rate_options = self.fee_rate_options().await?;
+        let tx_id = tx_id.as_ref().0;
+
+        let options = call!(self.actor.fee_rate_options_with_total_fee_for_replacement(
+            ReplacementKind::FeeBump,
+            fee_rate_options,
+            tx_id
+        ))
+        .await
+        .unwrap()?;
+
+        Ok(options)
+    }
+
+    /// Replace an unconfirmed outgoing transaction with one paying a higher fee rate (RBF)
+    ///
+    /// Hot wallets sign and broadcast the replacement right away, cold wallets save it as an
+    /// unsigned transaction and go to the hardware export screen to get it signed
+    #[uniffi::method]
+    pub async fn bump_fee(
+        &self,
+        tx_id: Arc<TxId>,
+        new_fee_rate: Arc<FeeRate>,
+    ) -> Result<(), Error> {
+        let tx_id = tx_id.as_ref().0;
+        let fee_rate: BdkFeeRate = Arc::unwrap_or_clone(new_fee_rate).into();
+
+        let (wallet_type, wallet_id) = {
+            let metadata = self.metadata.read();
+            (metadata.wallet_type, metadata.id.clone())
+        };
+
+        if wallet_type == WalletType::WatchOnly {
+            return Err(Error::FeeBumpError("watch only wallets can't sign".to_string()));
+        }
+
+        let psbt = call!(self.actor.build_fee_bump_tx(tx_id, fee_rate)).await.unwrap()?;
+        let details = call!(self.actor.get_confirm_details(psbt, fee_rate)).await.unwrap()?;
+
+        self.sign_and_broadcast_or_export(wallet_type, wallet_id, details).await
+    }
+
+    /// Fee options for cancelling an unconfirmed outgoing transaction
+    #[uniffi::method]
+    pub async fn cancel_fee_rate_options(
+        &self,
+        tx_id: Arc<TxId>,
+    ) -> Result<FeeRateOptionsWithTotalFee, Error> {
+        let fee_rate_options = self.fee_rate_options().await?;
+        let tx_id = tx_id.as_ref().0;
+
+        let options = call!(self.actor.fee_rate_options_with_total_fee_for_replacement(
+            ReplacementKind::Cancel,
+            fee_rate_options,
+            tx_id
+        ))
+        .await
+        .unwrap()?;
+
+        Ok(options)
+    }
+
+    /// Cancel an unconfirmed outgoing transaction, by replacing it with one that spends the same
+    /// inputs back to our own change address at a higher fee rate
+    ///
+    /// Hot wallets sign and broadcast the replacement right away, cold wallets save it as an
+    /// unsigned transaction and go to the hardware export screen to get it signed
+    #[uniffi::method]
+    pub async fn cancel_transaction(
+        &self,
+        tx_id: Arc<TxId>,
+        fee_rate: Arc<FeeRate>,
+    ) -> Result<(), Error> {
+        let tx_id = tx_id.as_ref().0;
+        let fee_rate: BdkFeeRate = Arc::unwrap_or_clone(fee_rate).into();
+
+        let (wallet_type, wallet_id) = {
+            let metadata = self.metadata.read();
+            (metadata.wallet_type, metadata.id.clone())
+        };
+
+        if wallet_type == WalletType::WatchOnly {
+            return Err(Error::CancelTxError("watch only wallets can't sign".to_string()));
+        }
+
+        let psbt = call!(self.actor.build_cancel_tx(tx_id, fee_rate)).await.unwrap()?;
+        let details = call!(self.actor.get_confirm_details(psbt, fee_rate)).await.unwrap()?;
+
+        self.sign_and_broadcast_or_export(wallet_type, wallet_id, details).await
+    }
+
+    /// Plan merging the small UTXOs, at or below `dust_threshold`, into a single UTXO at
+    /// `fee_rate`, savings are estimated against spending them later at `future_fee_rate`,
+    /// which defaults to the current fast fee rate
+    #[uniffi::method(default(future_fee_rate = None))]
+    pub async fn consolidation_plan(
+        &self,
+        fee_rate: Arc<FeeRate>,
+        dust_threshold: Arc<Amount>,
+        future_fee_rate: Option<Arc<FeeRate>>,
+    ) -> Result<ConsolidationPlan, Error> {
+        let fee_rate: BdkFeeRate = Arc::unwrap_or_clone(fee_rate).into();
+        let dust_threshold: bitcoin::Amount = Arc::unwrap_or_clone(dust_threshold).into();
+
+        let future_fee_rate: BdkFeeRate = match future_fee_rate {
+            Some(future_fee_rate) => Arc::unwrap_or_clone(future_fee_rate).into(),
+            None => self.fee_rate_options().await?.fast.fee_rate.into(),
+        };
+
+        let plan = call!(self.actor.consolidation_plan(fee_rate, future_fee_rate, dust_threshold))
+            .await
+            .unwrap()?;
+
+        Ok(plan)
+    }
+
+    /// Build the consolidation transaction for the plan, hot wallets sign and broadcast it,
+    /// hardware wallets go to the export screen, the app passes in the transaction label
+    #[uniffi::method(default(label = None))]
+    pub async fn consolidate_utxos(
+        &self,
+        plan: Arc<ConsolidationPlan>,
+        label: Option<String>,
+    ) -> Result<(), Error> {
+        let fee_rate: BdkFeeRate = plan.fee_rate.into();
+
+        let (wallet_type, wallet_id) = {
+            let metadata = self.metadata.read();
+            (metadata.wallet_type, metadata.id.clone())
+        };
+
+        if wallet_type == WalletType::WatchOnly {
+            return Err(Error::ConsolidationError("watch only wallets can't sign".to_string()));
+        }
+
+        let psbt =
+            call!(self.actor.build_consolidation_tx(plan.outpoints(), fee_rate)).await.unwrap()?;
+
+        let details = call!(self.actor.get_confirm_details(psbt, fee_rate)).await.unwrap()?;
+
+        if let Some(label) = label {
+            let label_manager = self.label_manager();
+            if let Err(error) = label_manager.prefill_transaction_label(details.id().into(), label)
+            {
+                warn!("unable to label consolidation transaction: {error}");
+            }
+        }
+
+        self.sign_and_broadcast_or_export(wallet_type, wallet_id, details).await
+    }
+
+    /// Total amount controlled by a private key, checked before asking the user to sweep it
+    #[uniffi::method]
+    pub async fn sweep_key_balance(&self, key: Arc<SweepKey>) -> Result<Amount, Error> {
+        let utxos = call!(self.actor.find_sweep_utxos(key)).await.unwrap()?;
+        let total = utxos.iter().map(|utxo| utxo.txout.value).sum::<bitcoin::Amount>();
+
+        Ok(total.into())
+    }
+
+    /// Sweep all the funds controlled by a private key (WIF or raw hex) into this wallet,
+    /// the key signs the transaction itself, so this works for every wallet type
+    ///
+    /// Goes to the confirm screen with the signed transaction, it's only broadcast from there
+    #[uniffi::method(default(label = None))]
+    pub async fn sweep_key(
+        &self,
+        key: Arc<SweepKey>,
+        fee_rate: Arc<FeeRateOption>,
+        label: Option<String>,
+    ) -> Result<(), Error> {
+        let fee_rate: BdkFeeRate = fee_rate.fee_rate.into();
+        let psbt = call!(self.actor.build_sweep_psbt(key, fee_rate)).await.unwrap()?;
+
+        // the unsigned tx has no script sigs, so its txid is wrong for legacy inputs
+        let transaction = psbt.clone().extract_tx_unchecked_fee_rate();
+        let tx_id = TxId::from(transaction.compute_txid());
+
+        let details = call!(self.actor.get_confirm_details(psbt, fee_rate)).await.unwrap()?;
+
+        if let Some(label) = label {
+            let label_manager = self.label_manager();
+            if let Err(error) = label_manager.prefill_transaction_label(Arc::new(tx_id), label) {
+                warn!("unable to label sweep transaction: {error}");
+            }
+        }
+
+        let wallet_id = self.metadata.read().id.clone();
+        let route = RouteFactory::new().send_confirm(
+            wallet_id,
+            Arc::new(details),
+            Some(Arc::new(transaction.into())),
+            None,
+        );
+
+        FfiApp::global().dispatch(AppAction::PushRoute(route));
+        Ok(())
+    }
+
+    /// Build a child-pays-for-parent transaction to speed up an unconfirmed incoming transaction,
+    /// the child pays enough that the parent and child together hit the target fee rate
+    #[uniffi::method]
+    pub async fn confirm_cpfp_txn(
+        &self,
+        parent_tx_id: Arc<TxId>,
+        target_fee_rate: Arc<FeeRate>,
+    ) -> Result<CpfpConfirmDetails, Error> {
+        let parent_tx_id = parent_tx_id.as_ref().0;
+        let fee_rate: BdkFeeRate = Arc::unwrap_or_clone(target_fee_rate).into();
+
+        let actor = self.actor.clone();
+        let psbt = call!(actor.build_ephemeral_cpfp_tx(parent_tx_id, fee_rate)).await.unwrap()?;
+
+        let details =
+            call!(actor.get_cpfp_confirm_details(parent_tx_id, psbt, fee_rate)).await.unwrap()?;
+
+        Ok(details)
+    }
+
     #[uniffi::method]
     pub async fn switch_to_different_wallet_address_type(
         &self,
@@ -856,7 +1491,9 @@ impl RustWalletManager {
                 let descriptors = match wallet_address_type {
                     WalletAddressType::WrappedSegwit => json.bip49.clone(),
                     WalletAddressType::Legacy => json.bip44.clone(),
-                    _ => {
+                    // hardware json exports don't include a taproot account
+                    WalletAddressType::Taproot => None,
+                    WalletAddressType::NativeSegwit => {
                         error!("trying to swtich to native segwit, but already segwit");
                         return Ok(());
                     }
@@ -988,6 +1625,16 @@ impl RustWalletManager {
                 metadata.show_labels = !metadata.show_labels;
             }
 
+            Action::UpdateCoinSelection(coin_selection) => {
+                let mut metadata = self.metadata.write();
+                metadata.coin_selection = coin_selection;
+            }
+
+            Action::SelectSpendingPath(spending_path) => {
+                let mut metadata = self.metadata.write();
+                metadata.spending_path = spending_path;
+            }
+
             Action::SelectedWalletDisappeared => {
                 send!(self.actor.stop_all_scans());
             }
@@ -1012,19 +1659,90 @@ impl RustWalletManager {
 }
 
 impl RustWalletManager {
+    fn multisig_config(&self) -> Result<MultisigConfig, Error> {
+        self.metadata
+            .read()
+            .multisig
+            .clone()
+            .ok_or_else(|| Error::MultisigSignatureError("not a multisig wallet".into()))
+    }
+
+    /// Accounts of this wallet's seed that are already added as wallets
+    fn added_accounts(&self) -> Vec<u32> {
+        let metadata = self.metadata.read().clone();
+        let Some(fingerprint) = metadata.master_fingerprint else { return vec![metadata.account] };
+
+        Database::global()
+            .wallets
+            .get_all(metadata.network, metadata.wallet_mode)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|wallet| wallet.master_fingerprint.as_ref() == Some(&fingerprint))
+            .map(|wallet| wallet.account)
+            .collect()
+    }
+
+    /// Hot wallets sign and broadcast the transaction right away, cold wallets save it as an
+    /// unsigned transaction and go to the hardware export screen to get it signed
+    ///
+    /// Callers reject watch only wallets before building the transaction
+    async fn sign_and_broadcast_or_export(
+        &self,
+        wallet_type: WalletType,
+        wallet_id: WalletId,
+        details: ConfirmDetails,
+    ) -> Result<(), Error> {
+        match wallet_type {
+            WalletType::Hot => {
+                let tx_id = details.psbt.tx_id();
+                let psbt = details.psbt.0;
+
+                call!(self.actor.sign_and_broadcast_transaction(psbt)).await.unwrap()?;
+                self.copy_labels_if_replacement(tx_id).await;
+
+                self.force_wallet_scan().await;
+            }
+
+            WalletType::Cold
+            | WalletType::XpubOnly
+            | WalletType::Multisig
+            | WalletType::Miniscript => {
+                let details = Arc::new(details);
+                self.save_unsigned_transaction(details.clone())?;
+
+                let route = RouteFactory::new().send_hardware_export(wallet_id, details);
+                FfiApp::global().dispatch(AppAction::PushRoute(route));
+            }
+
+            WalletType::WatchOnly => unreachable!("watch only wallets are rejected by the caller"),
+        }
+
+        Ok(())
+    }
+
+    /// If the broadcasted transaction replaced one of ours, copy its labels over, the original
+    /// keeps its labels until we know which one confirmed
+    async fn copy_labels_if_replacement(&self, tx_id: TxId) {
+        let Ok(Some(original_tx_id)) = call!(self.actor.replaced_tx_id(tx_id.0)).await else {
+            return;
+        };
+
+        if let Err(error) =
+            self.label_manager.copy_labels_for_txn(Arc::new(original_tx_id.into()), Arc::new(tx_id))
+        {
+            error!("unable to copy labels to replacement transaction: {error}");
+        }
+    }
+
     pub async fn confirm_txn(
         &self,
-        amount: Amount,
-        address: Arc<Address>,
+        recipients: Vec<Recipient>,
         fee_rate: FeeRate,
     ) -> Result<ConfirmDetails, Error> {
         let actor = self.actor.clone();
-
-        let amount = amount.into();
-        let address = Arc::unwrap_or_clone(address);
         let fee_rate = fee_rate.into();
 
-        let psbt = call!(actor.build_tx(amount, address, fee_rate)).await.unwrap()?;
+        let psbt = call!(actor.build_tx(recipients, fee_rate)).await.unwrap()?;
         let details = call!(self.actor.get_confirm_details(psbt, fee_rate)).await.unwrap()?;
 
         Ok(details)
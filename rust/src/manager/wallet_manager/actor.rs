@@ -1,45 +1,70 @@
 use crate::{
     database::{Database, wallet_data::WalletDataDb},
     historical_price_service::HistoricalPriceService,
-    manager::wallet_manager::{Error, SendFlowErrorAlert, WalletManagerError},
+    label_manager::LabelManager,
+    manager::{
+        send_flow_manager::recipient::Recipient,
+        wallet_manager::{Error, SendFlowErrorAlert, WalletManagerError},
+    },
     mnemonic,
     node::{
         client::{NodeClient, NodeClientOptions},
         client_builder::NodeClientBuilder,
     },
+    policy::{self, ChainState, SpendingPath},
+    psbt_match::WalletDescriptors,
+    psbt_verify::{self, PsbtVerification},
+    sweep::{SweepKey, SweepUtxo},
     transaction::{ConfirmedTransaction, FeeRate, Transaction, TransactionDetails, TxId},
     transaction_watcher::TransactionWatcher,
     wallet::{
-        Address, AddressInfo, Wallet, WalletAddressType, balance::Balance, metadata::BlockSizeLast,
+        Address, AddressInfo, Wallet, WalletAddressType,
+        balance::Balance,
+        export::ExportSource,
+        metadata::{BlockSizeLast, CoinSelectionStrategy, WalletType},
     },
 };
 use act_zero::{runtimes::tokio::spawn_actor, *};
 use act_zero_ext::into_actor_result;
-use ahash::HashMap;
+use ahash::{HashMap, HashSet};
 use bdk_wallet::{
     AddUtxoError, Utxo, WeightedUtxo,
     chain::{
-        BlockId, TxGraph,
+        BlockId, ChainPosition, ConfirmationBlockTime, TxGraph,
         bitcoin::Psbt,
         spk_client::{FullScanRequest, FullScanResponse, SyncRequestBuilder, SyncResponse},
     },
+    coin_selection::CoinSelectionAlgorithm,
     error::CreateTxError,
 };
-use bdk_wallet::{KeychainKind, LocalOutput, SignOptions, TxOrdering};
-use bitcoin::{Amount, FeeRate as BdkFeeRate, OutPoint, TxIn, Txid};
+use bdk_wallet::{
+    KeychainKind, LocalOutput, SignOptions, TxBuilder, TxOrdering, Wallet as BdkWallet,
+    descriptor::policy::Policy,
+};
+use bip329::{Label, TransactionRecord};
+use bitcoin::{Amount, FeeRate as BdkFeeRate, OutPoint, TxIn, Txid, Weight};
 use bitcoin::{Transaction as BdkTransaction, params::Params};
-use cove_bdk::coin_selection::CoveDefaultCoinSelection;
+use cove_bdk::coin_selection::{
+    AvoidChangeCoinSelection, CoveDefaultCoinSelection, LargestFirstCoinSelection,
+    OldestFirstCoinSelection, PrivacyCoinSelection, size_of_change,
+};
+use cove_bdk::fees::min_replacement_fee_rate;
 use cove_common::consts::{GAP_LIMIT, MIN_SEND_AMOUNT};
 use cove_types::{
     address::AddressInfoWithDerivation,
-    confirm::{AddressAndAmount, ConfirmDetails, ExtraItem, InputOutputDetails, SplitOutput},
-    fees::{FeeRateOptionWithTotalFee, FeeRateOptions, FeeRateOptionsWithTotalFee},
+    confirm::{
+        AddressAndAmount, ConfirmDetails, CpfpConfirmDetails, CpfpPackage, ExtraItem,
+        InputOutputDetails, SplitOutput,
+    },
+    consolidation::ConsolidationPlan,
+    fees::{FeeRateOption, FeeRateOptionWithTotalFee, FeeRateOptions, FeeRateOptionsWithTotalFee},
     utxo::{UtxoList, UtxoType},
 };
 use eyre::Result;
 use flume::Sender;
 use rand::Rng as _;
 use std::{
+    collections::BTreeMap,
     sync::Arc,
     time::{Duration, UNIX_EPOCH},
 };
@@ -50,6 +75,9 @@ use self::mnemonic::{Mnemonic, MnemonicExt as _};
 
 use super::{SingleOrMany, WalletManagerReconcileMessage};
 
+/// Keeps consolidation transactions well under the standard transaction weight limit
+const MAX_CONSOLIDATION_INPUTS: usize = 500;
+
 #[derive(Debug)]
 pub struct WalletActor {
     pub addr: WeakAddr<Self>,
@@ -103,6 +131,15 @@ impl FullScanType {
     }
 }
 
+/// How an unconfirmed outgoing transaction is replaced
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ReplacementKind {
+    /// Same recipients, higher fee rate
+    FeeBump,
+    /// Everything back to our own change address
+    Cancel,
+}
+
 #[async_trait::async_trait]
 impl Actor for WalletActor {
     async fn started(&mut self, addr: Addr<Self>) -> ActorResult<()> {
@@ -179,8 +216,14 @@ impl WalletActor {
     ) -> Result<Psbt, Error> {
         debug!("build_ephemeral_drain_tx for fee rate {}", fee.sat_per_vb());
         let script_pubkey = address.script_pubkey();
+        let frozen = self.frozen_utxos()?;
+        let options = self.tx_builder_options()?;
         let mut tx_builder = self.wallet.bdk.build_tx();
+        options.apply(&mut tx_builder);
 
+        frozen.into_iter().for_each(|outpoint| {
+            tx_builder.add_unspendable(outpoint);
+        });
         tx_builder.drain_wallet().drain_to(script_pubkey).fee_rate(fee.into());
         let psbt = tx_builder.finish().map_err(|err| Error::BuildTxError(err.to_string()))?;
         self.wallet.bdk.cancel_tx(&psbt.unsigned_tx);
@@ -188,39 +231,71 @@ impl WalletActor {
         Ok(psbt)
     }
 
-    /// Build a transaction
+    /// Build a single transaction paying every recipient in the list
+    ///
+    /// At most one recipient can be the max recipient, it receives everything left over after
+    /// the other recipients and the fee are paid
     #[into_actor_result]
     pub async fn build_tx(
         &mut self,
-        amount: Amount,
-        address: Address,
+        recipients: Vec<Recipient>,
         fee_rate: impl Into<BdkFeeRate>,
     ) -> Result<Psbt, Error> {
-        debug!("build_tx");
+        debug!("build_tx for {} recipients", recipients.len());
         let fee_rate = fee_rate.into();
-        let script_pubkey = address.script_pubkey();
 
-        let coin_selection = CoveDefaultCoinSelection::new(self.seed);
-        let mut tx_builder = self.wallet.bdk.build_tx().coin_selection(coin_selection);
+        if recipients.is_empty() {
+            return Err(Error::BuildTxError("no recipients".to_string()));
+        }
 
-        tx_builder.ordering(TxOrdering::Untouched);
-        tx_builder.add_recipient(script_pubkey, amount);
-        tx_builder.fee_rate(fee_rate);
+        if recipients.iter().filter(|recipient| recipient.is_max()).count() > 1 {
+            return Err(Error::BuildTxError(
+                "only one recipient can receive the max remaining amount".to_string(),
+            ));
+        }
 
-        let psbt = tx_builder.finish().map_err(|err| Error::BuildTxError(err.to_string()))?;
-        Ok(psbt)
+        // frozen utxos are excluded by the coin selection and marked unspendable so
+        // `drain_wallet` doesn't pick them up either
+        let frozen = self.frozen_utxos()?;
+
+        // taproot wallets have bigger change outputs than the segwit default assumes
+        let change_script = self.wallet.bdk.peek_address(KeychainKind::Internal, 0).script_pubkey();
+        let size_of_change = size_of_change(&change_script);
+
+        match self.coin_selection_strategy() {
+            CoinSelectionStrategy::Default => {
+                let coin_selection =
+                    CoveDefaultCoinSelection::new_with_size_of_change(self.seed, size_of_change)
+                        .with_frozen(frozen.iter().copied());
+
+                self.build_tx_with(coin_selection, recipients, fee_rate, frozen)
+            }
+            CoinSelectionStrategy::Privacy => {
+                let coin_selection = PrivacyCoinSelection::new(self.utxo_labels());
+                self.build_tx_with(coin_selection, recipients, fee_rate, frozen)
+            }
+            CoinSelectionStrategy::OldestFirst => {
+                self.build_tx_with(OldestFirstCoinSelection, recipients, fee_rate, frozen)
+            }
+            CoinSelectionStrategy::LargestFirst => {
+                self.build_tx_with(LargestFirstCoinSelection, recipients, fee_rate, frozen)
+            }
+            CoinSelectionStrategy::AvoidChange => {
+                let coin_selection = AvoidChangeCoinSelection::new(size_of_change);
+                self.build_tx_with(coin_selection, recipients, fee_rate, frozen)
+            }
+        }
     }
 
     /// Build a transaction but don't advance the change address index
     #[into_actor_result]
     pub async fn build_ephemeral_tx(
         &mut self,
-        amount: Amount,
-        address: Address,
+        recipients: Vec<Recipient>,
         fee: impl Into<BdkFeeRate>,
     ) -> Result<Psbt, Error> {
         debug!("build_ephemeral_tx");
-        let psbt = self.do_build_tx(amount, address, fee).await?;
+        let psbt = self.do_build_tx(recipients, fee).await?;
         self.wallet.bdk.cancel_tx(&psbt.unsigned_tx);
         Ok(psbt)
     }
@@ -239,7 +314,10 @@ impl WalletActor {
         let fee_rate = fee_rate.into();
         let send_amount = self.get_max_send_for_utxos(total_amount, &address, fee_rate, &utxos)?;
 
+        let options = self.tx_builder_options()?;
         let mut tx_builder = self.wallet.bdk.build_tx();
+        options.apply(&mut tx_builder);
+
         tx_builder.add_utxos(&utxos).map_err(|err| Error::AddUtxosError(err.to_string()))?;
         tx_builder.manually_selected_only();
         tx_builder.ordering(TxOrdering::Untouched);
@@ -265,6 +343,111 @@ impl WalletActor {
         Ok(psbt)
     }
 
+    /// Build a replacement for an unconfirmed outgoing transaction paying a higher fee rate
+    #[into_actor_result]
+    pub async fn build_fee_bump_tx(
+        &mut self,
+        tx_id: Txid,
+        fee_rate: impl Into<BdkFeeRate>,
+    ) -> Result<Psbt, Error> {
+        debug!("build_fee_bump_tx for {tx_id}");
+        self.replacement_psbt(ReplacementKind::FeeBump, tx_id, fee_rate.into(), true)
+    }
+
+    /// Build a replacement for an unconfirmed outgoing transaction that spends the same inputs
+    /// back to one of our change addresses at a higher fee rate, effectively cancelling it
+    #[into_actor_result]
+    pub async fn build_cancel_tx(
+        &mut self,
+        tx_id: Txid,
+        fee_rate: impl Into<BdkFeeRate>,
+    ) -> Result<Psbt, Error> {
+        debug!("build_cancel_tx for {tx_id}");
+        self.replacement_psbt(ReplacementKind::Cancel, tx_id, fee_rate.into(), true)
+    }
+
+    /// Build a fee bump or cancel transaction but don't advance the change address index
+    #[into_actor_result]
+    pub async fn build_ephemeral_replacement_tx(
+        &mut self,
+        kind: ReplacementKind,
+        tx_id: Txid,
+        fee_rate: impl Into<BdkFeeRate>,
+    ) -> Result<Psbt, Error> {
+        debug!("build_ephemeral_replacement_tx {kind:?}");
+        let psbt = self.replacement_psbt(kind, tx_id, fee_rate.into(), false)?;
+        self.wallet.bdk.cancel_tx(&psbt.unsigned_tx);
+        Ok(psbt)
+    }
+
+    /// The transaction the given transaction replaces, if it was broadcast as a replacement
+    pub async fn replaced_tx_id(&mut self, tx_id: Txid) -> ActorResult<Option<Txid>> {
+        Produces::ok(self.replacements().get(&tx_id).copied())
+    }
+
+    /// Build a child-pays-for-parent transaction, spends the wallet's unconfirmed outputs of the
+    /// parent to a fresh change address, paying enough so the parent and child together hit the
+    /// target fee rate
+    #[into_actor_result]
+    pub async fn build_cpfp_tx(
+        &mut self,
+        parent_tx_id: Txid,
+        target_fee_rate: impl Into<BdkFeeRate>,
+    ) -> Result<Psbt, Error> {
+        debug!("build_cpfp_tx for parent {parent_tx_id}");
+        let psbt = self.cpfp_psbt(parent_tx_id, target_fee_rate.into(), true)?;
+        self.wallet.persist()?;
+
+        Ok(psbt)
+    }
+
+    /// Build a child-pays-for-parent transaction but don't advance the change address index
+    #[into_actor_result]
+    pub async fn build_ephemeral_cpfp_tx(
+        &mut self,
+        parent_tx_id: Txid,
+        target_fee_rate: impl Into<BdkFeeRate>,
+    ) -> Result<Psbt, Error> {
+        debug!("build_ephemeral_cpfp_tx");
+        self.cpfp_psbt(parent_tx_id, target_fee_rate.into(), false)
+    }
+
+    #[into_actor_result]
+    pub async fn get_cpfp_confirm_details(
+        &mut self,
+        parent_tx_id: Txid,
+        psbt: Psbt,
+        target_fee_rate: BdkFeeRate,
+    ) -> Result<CpfpConfirmDetails, Error> {
+        let (parent_vsize, parent_fee) = self.unconfirmed_parent_vsize_and_fee(parent_tx_id)?;
+        let ancestors = self.unconfirmed_ancestors(parent_tx_id)?;
+
+        let child_fee = psbt.fee().map_err(|err| Error::CpfpError(err.to_string()))?;
+        let child_vsize = {
+            let tx = &psbt.unsigned_tx;
+            let outpoints = tx.input.iter().map(|input| input.previous_output).collect::<Vec<_>>();
+            let drain_script =
+                tx.output.first().map(|output| output.script_pubkey.clone()).ok_or_else(|| {
+                    Error::CpfpError("child transaction has no output".to_string())
+                })?;
+
+            estimate_child_vsize(&self.wallet.bdk, &outpoints, &drain_script)?
+        };
+
+        let details = self.do_get_confirm_details(psbt, target_fee_rate).await?;
+        let cpfp_details = CpfpConfirmDetails::new(
+            parent_tx_id.into(),
+            parent_vsize,
+            parent_fee.into(),
+            ancestors,
+            child_vsize,
+            child_fee.into(),
+            details,
+        );
+
+        Ok(cpfp_details)
+    }
+
     // cancel a transaction, reset the address & change address index
     pub async fn cancel_txn(&mut self, txn: BdkTransaction) {
         self.wallet.bdk.cancel_tx(&txn)
@@ -299,6 +482,56 @@ impl WalletActor {
         transactions
     }
 
+    fn transaction_ids(&self) -> HashSet<Txid> {
+        self.wallet.bdk.transactions().map(|tx| tx.tx_node.txid).collect()
+    }
+
+    /// Label the incoming transactions a scan found that weren't in the wallet before
+    fn label_new_incoming_transactions(&self, known: &HashSet<Txid>) {
+        for tx in self.wallet.bdk.transactions() {
+            if known.contains(&tx.tx_node.txid) {
+                continue;
+            }
+
+            let (sent, _received) = self.wallet.bdk.sent_and_received(&tx.tx_node.tx);
+            if sent == Amount::ZERO {
+                self.label_from_receive_address(&tx.tx_node.tx);
+            }
+        }
+    }
+
+    /// Incoming transactions without a label take the label of the address they paid, ex: the
+    /// label given to a payment request
+    fn label_from_receive_address(&self, tx: &BdkTransaction) {
+        let labels = &self.db.labels;
+        let tx_id = tx.compute_txid();
+
+        if labels.get_txn_label_record(tx_id).ok().flatten().is_some() {
+            return;
+        }
+
+        let params = Params::from(self.wallet.bdk.network());
+        let label = tx
+            .output
+            .iter()
+            .filter(|output| {
+                let keychain = self.wallet.bdk.derivation_of_spk(output.script_pubkey.clone());
+                matches!(keychain, Some((KeychainKind::External, _)))
+            })
+            .filter_map(|output| bitcoin::Address::from_script(&output.script_pubkey, &params).ok())
+            .find_map(|address| {
+                let record = labels.get_address_record(address.into_unchecked()).ok().flatten();
+                record.and_then(|record| record.item.label)
+            });
+
+        let Some(label) = label else { return };
+
+        let record = TransactionRecord { ref_: tx_id, label: Some(label), origin: None };
+        if let Err(error) = labels.insert_labels([Label::Transaction(record)]) {
+            warn!("unable to label incoming transaction {tx_id}: {error}");
+        }
+    }
+
     pub async fn split_transaction_outputs(
         &mut self,
         outputs: Vec<AddressAndAmount>,
@@ -318,23 +551,195 @@ impl WalletActor {
         Produces::ok(SplitOutput { external, internal })
     }
 
+    /// Propose merging the UTXOs at or below the dust threshold into one internal UTXO
+    ///
+    /// UTXOs worth less than the fee to spend them at `fee_rate` are left out, consolidating
+    /// them would cost more than they are worth
+    #[into_actor_result]
+    pub async fn consolidation_plan(
+        &mut self,
+        fee_rate: BdkFeeRate,
+        future_fee_rate: BdkFeeRate,
+        dust_threshold: Amount,
+    ) -> Result<ConsolidationPlan, Error> {
+        debug!("consolidation_plan fee_rate: {fee_rate:?}, dust_threshold: {dust_threshold}");
+        let frozen = self.frozen_utxos()?;
+
+        let mut small_utxos = self
+            .wallet
+            .bdk
+            .list_unspent()
+            .filter(|output| !frozen.contains(&output.outpoint))
+            .filter(|output| output.txout.value <= dust_threshold)
+            .map(|output| output.outpoint)
+            .collect::<Vec<_>>();
+
+        small_utxos.sort();
+
+        let weighted_utxos = self
+            .get_weighted_utxos(&small_utxos)
+            .map_err(|err| Error::ConsolidationError(err.to_string()))?;
+
+        let mut utxos = weighted_utxos
+            .into_iter()
+            .map(|weighted_utxo| {
+                let weight = TxIn::default().segwit_weight() + weighted_utxo.satisfaction_weight;
+                (weighted_utxo.utxo, weight)
+            })
+            .filter(|(utxo, weight)| utxo.txout().value > fee_rate * *weight)
+            .collect::<Vec<_>>();
+
+        // smallest first, they are the most expensive to spend later relative to their value
+        utxos.sort_by_key(|(utxo, _)| utxo.txout().value);
+        utxos.truncate(MAX_CONSOLIDATION_INPUTS);
+
+        if utxos.len() < 2 {
+            return Err(Error::ConsolidationError(
+                "need at least two small UTXOs to consolidate".to_string(),
+            ));
+        }
+
+        let outpoints = utxos.iter().map(|(utxo, _)| utxo.outpoint()).collect::<Vec<_>>();
+        let psbt = self.do_build_ephemeral_consolidation_tx(outpoints, fee_rate).await?;
+        let fee = psbt.fee().map_err(|err| Error::ConsolidationError(err.to_string()))?;
+
+        let input_vsizes =
+            utxos.iter().map(|(_, weight)| weight.to_vbytes_ceil()).collect::<Vec<_>>();
+
+        let consolidated_satisfaction_weight = self
+            .wallet
+            .bdk
+            .public_descriptor(KeychainKind::Internal)
+            .max_weight_to_satisfy()
+            .map_err(|err| Error::ConsolidationError(err.to_string()))?;
+
+        let consolidated_input_vsize =
+            (TxIn::default().segwit_weight() + consolidated_satisfaction_weight).to_vbytes_ceil();
+
+        let utxos = utxos
+            .into_iter()
+            .map(|(utxo, _)| (utxo.outpoint(), utxo.txout().value.into()))
+            .collect();
+
+        let plan = ConsolidationPlan::new(
+            utxos,
+            &input_vsizes,
+            consolidated_input_vsize,
+            fee_rate,
+            future_fee_rate,
+            fee.into(),
+        );
+
+        Ok(plan)
+    }
+
+    /// Build a transaction spending all the given UTXOs to a single internal address
+    #[into_actor_result]
+    pub async fn build_consolidation_tx(
+        &mut self,
+        utxos: Vec<OutPoint>,
+        fee_rate: impl Into<BdkFeeRate>,
+    ) -> Result<Psbt, Error> {
+        debug!("build_consolidation_tx for {} utxos", utxos.len());
+        let fee_rate = fee_rate.into();
+
+        let frozen = self.frozen_utxos()?;
+        if let Some(outpoint) = utxos.iter().find(|outpoint| frozen.contains(outpoint)) {
+            return Err(Error::SpendFrozenUtxo(outpoint.to_string()));
+        }
+
+        let address = self.wallet.bdk.next_unused_address(KeychainKind::Internal);
+        self.wallet.persist()?;
+
+        let options = self.tx_builder_options()?;
+        let mut tx_builder = self.wallet.bdk.build_tx();
+        options.apply(&mut tx_builder);
+
+        tx_builder.add_utxos(&utxos).map_err(|err| Error::AddUtxosError(err.to_string()))?;
+        tx_builder.manually_selected_only();
+        tx_builder.ordering(TxOrdering::Untouched);
+        tx_builder.drain_to(address.script_pubkey());
+        tx_builder.fee_rate(fee_rate);
+
+        let psbt = tx_builder.finish().map_err(|err| Error::ConsolidationError(err.to_string()))?;
+        Ok(psbt)
+    }
+
+    /// Build a consolidation transaction but don't advance the change address index
+    #[into_actor_result]
+    pub async fn build_ephemeral_consolidation_tx(
+        &mut self,
+        utxos: Vec<OutPoint>,
+        fee_rate: impl Into<BdkFeeRate>,
+    ) -> Result<Psbt, Error> {
+        debug!("build_ephemeral_consolidation_tx");
+        let psbt = self.do_build_consolidation_tx(utxos, fee_rate).await?;
+        self.wallet.bdk.cancel_tx(&psbt.unsigned_tx);
+        Ok(psbt)
+    }
+
+    /// Find the UTXOs controlled by a private key, across all the single key script types
+    #[into_actor_result]
+    pub async fn find_sweep_utxos(&mut self, key: Arc<SweepKey>) -> Result<Vec<SweepUtxo>, Error> {
+        key.check_network(self.wallet.network).map_err(|err| Error::SweepError(err.to_string()))?;
+
+        let node_client = self
+            .node_client()
+            .await
+            .map_err(|err| Error::SweepError(format!("unable to connect to node: {err}")))?;
+
+        let mut utxos = Vec::new();
+        for (script_type, script) in key.scripts() {
+            let found = node_client
+                .get_script_utxos(script)
+                .await
+                .map_err(|err| Error::SweepError(err.to_string()))?;
+
+            debug!("found {} utxos for {script_type:?}", found.len());
+            utxos.extend(found.into_iter().map(|(outpoint, txout)| SweepUtxo {
+                outpoint,
+                txout,
+                script_type,
+            }));
+        }
+
+        Ok(utxos)
+    }
+
+    /// Signed transaction sweeping all the funds controlled by a private key to the next address
+    /// in this wallet, as a finalized PSBT so it can be confirmed before it's broadcast
+    #[into_actor_result]
+    pub async fn build_sweep_psbt(
+        &mut self,
+        key: Arc<SweepKey>,
+        fee_rate: impl Into<BdkFeeRate>,
+    ) -> Result<Psbt, Error> {
+        let fee_rate = fee_rate.into();
+        let utxos = self.do_find_sweep_utxos(key.clone()).await?;
+
+        let address = self.wallet.bdk.next_unused_address(KeychainKind::External);
+        self.wallet.persist()?;
+
+        let psbt = key
+            .build_sweep_psbt(&utxos, address.script_pubkey(), fee_rate)
+            .map_err(|err| Error::SweepError(err.to_string()))?;
+
+        Ok(psbt)
+    }
+
     #[into_actor_result]
     pub async fn fee_rate_options_with_total_fee(
         &mut self,
         fee_rate_options: FeeRateOptions,
-        amount: Amount,
-        address: Address,
+        recipients: Vec<Recipient>,
     ) -> Result<FeeRateOptionsWithTotalFee, Error> {
         let fast_fee_rate = fee_rate_options.fast.fee_rate;
         let medium_fee_rate = fee_rate_options.medium.fee_rate;
         let slow_fee_rate = fee_rate_options.slow.fee_rate;
 
-        let fast_psbt = self.do_build_ephemeral_tx(amount, address.clone(), fast_fee_rate).await?;
-
-        let medium_psbt =
-            self.do_build_ephemeral_tx(amount, address.clone(), medium_fee_rate).await?;
-
-        let slow_psbt = self.do_build_ephemeral_tx(amount, address.clone(), slow_fee_rate).await?;
+        let fast_psbt = self.do_build_ephemeral_tx(recipients.clone(), fast_fee_rate).await?;
+        let medium_psbt = self.do_build_ephemeral_tx(recipients.clone(), medium_fee_rate).await?;
+        let slow_psbt = self.do_build_ephemeral_tx(recipients, slow_fee_rate).await?;
 
         let options = FeeRateOptionsWithTotalFee {
             fast: FeeRateOptionWithTotalFee::new(
@@ -453,6 +858,55 @@ impl WalletActor {
         Ok(options_with_fee)
     }
 
+    /// Fee options for replacing the given transaction, any option below the minimum replacement
+    /// fee rate is raised to it
+    #[into_actor_result]
+    pub async fn fee_rate_options_with_total_fee_for_replacement(
+        &mut self,
+        kind: ReplacementKind,
+        fee_rate_options: FeeRateOptions,
+        tx_id: Txid,
+    ) -> Result<FeeRateOptionsWithTotalFee, Error> {
+        let min_fee_rate: FeeRate = self.min_replacement_rate(kind, tx_id)?.into();
+        let raise = |option: FeeRateOption| FeeRateOption {
+            fee_speed: option.fee_speed,
+            fee_rate: option.fee_rate.max(min_fee_rate),
+        };
+
+        let options = FeeRateOptions {
+            fast: raise(fee_rate_options.fast),
+            medium: raise(fee_rate_options.medium),
+            slow: raise(fee_rate_options.slow),
+        };
+
+        let fast_psbt =
+            self.do_build_ephemeral_replacement_tx(kind, tx_id, options.fast.fee_rate).await?;
+
+        let medium_psbt =
+            self.do_build_ephemeral_replacement_tx(kind, tx_id, options.medium.fee_rate).await?;
+
+        let slow_psbt =
+            self.do_build_ephemeral_replacement_tx(kind, tx_id, options.slow.fee_rate).await?;
+
+        let options_with_fee = FeeRateOptionsWithTotalFee {
+            fast: FeeRateOptionWithTotalFee::new(
+                options.fast,
+                fast_psbt.fee().map_err(|e| Error::FeesError(e.to_string()))?,
+            ),
+            medium: FeeRateOptionWithTotalFee::new(
+                options.medium,
+                medium_psbt.fee().map_err(|e| Error::FeesError(e.to_string()))?,
+            ),
+            slow: FeeRateOptionWithTotalFee::new(
+                options.slow,
+                slow_psbt.fee().map_err(|e| Error::FeesError(e.to_string()))?,
+            ),
+            custom: None,
+        };
+
+        Ok(options_with_fee)
+    }
+
     #[into_actor_result]
     pub async fn get_confirm_details(
         &mut self,
@@ -477,10 +931,6 @@ impl WalletActor {
             .filter(|output| !self.wallet.bdk.is_mine(output.script_pubkey.clone()))
             .collect::<Vec<&bitcoin::TxOut>>();
 
-        if external_outputs.len() > 1 {
-            return Err(error("multiple address to send to found, not currently supported"));
-        }
-
         // if there is an external output, use that
         // otherwise this is a consolidation txn, sending to the same wallet so use the first output
         let output = external_outputs
@@ -493,7 +943,17 @@ impl WalletActor {
         let sending_to = bitcoin::Address::from_script(&output.script_pubkey, params)
             .map_err(|err| err_fmt("unable to get address from script", err))?;
 
-        let sending_amount = output.value;
+        // a batch transaction sends to every external output
+        let recipients = external_outputs
+            .iter()
+            .map(|output| AddressAndAmount::try_new(output, network.into()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| err_fmt("unable to get address from script", err))?;
+
+        let sending_amount = match external_outputs.is_empty() {
+            true => output.value,
+            false => external_outputs.iter().map(|output| output.value).sum(),
+        };
 
         if sending_amount == Amount::ZERO {
             return Err(error("zero amount"));
@@ -540,7 +1000,19 @@ impl WalletActor {
             })
             .collect();
 
-        let more_details = InputOutputDetails::new_with_labels(&psbt, network.into(), extras);
+        let mut more_details = InputOutputDetails::new_with_labels(&psbt, network.into(), extras);
+
+        // mark our own outputs so the external outputs can be told apart from change
+        for output in more_details.outputs.iter_mut() {
+            let script_pubkey = output.address.script_pubkey();
+            if let Some((keychain, _)) = self.wallet.bdk.derivation_of_spk(script_pubkey) {
+                output.utxo_type = Some(match keychain {
+                    KeychainKind::External => UtxoType::Output,
+                    KeychainKind::Internal => UtxoType::Change,
+                });
+            }
+        }
+
         let fee_percentage = fee.to_sat() * 100 / sending_amount.to_sat();
 
         let details = ConfirmDetails {
@@ -550,6 +1022,7 @@ impl WalletActor {
             fee_rate: fee_rate.into(),
             fee_percentage,
             sending_to: sending_to.into(),
+            recipients,
             psbt,
             more_details,
         };
@@ -575,8 +1048,9 @@ impl WalletActor {
             .tap_err(|error| error!("failed to get mnemonic for wallet: {error}"))
             .map_err(|_| err("failed to get mnemonic for wallet"))?;
 
+        let metadata = &self.wallet.metadata;
         let descriptors =
-            mnemonic.into_descriptors(None, network, self.wallet.metadata.address_type);
+            mnemonic.into_descriptors(None, network, metadata.address_type, metadata.account);
 
         let create_params = descriptors.into_create_params().network(network.into());
 
@@ -610,6 +1084,11 @@ impl WalletActor {
         &mut self,
         transaction: BdkTransaction,
     ) -> Result<(), Error> {
+        let tx_id = transaction.compute_txid();
+
+        // looked up before broadcasting, once the wallet syncs it only keeps one of the two
+        let replaced_tx_id = find_replaced_tx_id(&self.wallet.bdk, &transaction);
+
         self.check_node_connection().await.map_err(|error| {
             let error_string =
                 format!("failed to broadcast transaction, unable to connect to node: {error:?}");
@@ -631,6 +1110,17 @@ impl WalletActor {
                 Error::SignAndBroadcastError(error_string)
             })?;
 
+        // only saved now, a replacement that was built but never broadcast replaces nothing
+        // watch both versions of a replaced transaction, to find out which one gets confirmed
+        if let Some(original_tx_id) = replaced_tx_id {
+            if let Err(error) = self.db.add_replacement(tx_id, original_tx_id) {
+                error!("unable to save replacement {tx_id} for {original_tx_id}: {error}");
+            }
+
+            send!(self.addr.start_transaction_watcher(original_tx_id));
+            send!(self.addr.start_transaction_watcher(tx_id));
+        }
+
         Ok(())
     }
 
@@ -656,6 +1146,41 @@ impl WalletActor {
         Ok(tx)
     }
 
+    /// Check a signed PSBT against the one we exported, using the loaded wallet
+    pub async fn verify_signed_psbt(
+        &mut self,
+        psbt: Psbt,
+        exported: Psbt,
+        confirmed_fee: Amount,
+        others: Vec<WalletDescriptors>,
+    ) -> ActorResult<PsbtVerification> {
+        let verification =
+            psbt_verify::verify(&psbt, &exported, confirmed_fee, &self.wallet.bdk, &others);
+
+        Produces::ok(verification)
+    }
+
+    /// Public descriptors and the block of the first transaction, to export the wallet
+    pub async fn export_source(&mut self) -> ActorResult<ExportSource> {
+        let bdk = &self.wallet.bdk;
+
+        let birthday = bdk
+            .transactions()
+            .filter_map(|tx| match tx.chain_position {
+                ChainPosition::Confirmed { anchor, .. } => Some(anchor),
+                ChainPosition::Unconfirmed { .. } => None,
+            })
+            .min_by_key(|anchor| anchor.block_id.height);
+
+        Produces::ok(ExportSource {
+            name: self.wallet.metadata.name.clone(),
+            network: self.wallet.network,
+            external: bdk.public_descriptor(KeychainKind::External).clone(),
+            internal: bdk.public_descriptor(KeychainKind::Internal).clone(),
+            birthday,
+        })
+    }
+
     pub async fn address_at(&mut self, index: u32) -> ActorResult<AddressInfo> {
         let address = self.wallet.bdk.peek_address(KeychainKind::External, index);
         Produces::ok(address.into())
@@ -666,6 +1191,10 @@ impl WalletActor {
         Produces::ok(address)
     }
 
+    pub async fn used_addresses(&mut self) -> ActorResult<Vec<AddressInfoWithDerivation>> {
+        Produces::ok(self.wallet.used_addresses())
+    }
+
     pub async fn check_node_connection(&mut self) -> ActorResult<()> {
         self.node_client()
             .await?
@@ -752,6 +1281,12 @@ impl WalletActor {
         Produces::ok(block_height)
     }
 
+    /// Spending paths of a miniscript policy wallet, and if they can be used at `height`
+    #[into_actor_result]
+    pub async fn spending_paths(&mut self, height: u32) -> Result<Vec<SpendingPath>, Error> {
+        self.do_spending_paths(height)
+    }
+
     pub async fn switch_mnemonic_to_new_address_type(
         &mut self,
         address_type: WalletAddressType,
@@ -861,6 +1396,31 @@ impl WalletActor {
     pub async fn mark_transaction_found(&mut self, tx_id: Txid) -> ActorResult<()> {
         info!("marking transaction found: {tx_id}");
 
+        // one version of a replaced transaction confirmed, none of the others in its chain of
+        // replacements ever will
+        let (replaced, remaining) = split_replacement_chain(&self.replacements(), tx_id);
+        if !replaced.is_empty() {
+            if let Err(error) = self.db.set_replacements(remaining) {
+                error!("unable to remove replacements of {tx_id}: {error}");
+            }
+        }
+
+        // labels were copied to each replacement when it was broadcast, only now do we know
+        // which versions they no longer belong to
+        let label_manager = LabelManager::new(self.wallet.id.clone());
+        for replaced in replaced {
+            self.transaction_watchers.remove(&replaced);
+
+            if let Err(error) = label_manager.delete_labels_for_txn(Arc::new(replaced.into())) {
+                error!("unable to delete labels for replaced transaction {replaced}: {error}");
+            }
+
+            self.send(WalletManagerReconcileMessage::ReplacementConfirmed {
+                confirmed: Arc::new(tx_id.into()),
+                replaced: Arc::new(replaced.into()),
+            });
+        }
+
         // update the height
         self.update_block_id().await?;
 
@@ -961,6 +1521,11 @@ impl WalletActor {
     ) -> Result<Amount, Error> {
         let fee_rate = fee_rate.into();
 
+        let frozen = self.frozen_utxos()?;
+        if let Some(outpoint) = utxos.iter().find(|outpoint| frozen.contains(outpoint)) {
+            return Err(Error::SpendFrozenUtxo(outpoint.to_string()));
+        }
+
         let (utxo_total_amount, fee_estimate) = {
             let mut utxo_total_amount = Amount::ZERO;
             let mut total_fee_amount = Amount::ZERO;
@@ -1047,28 +1612,357 @@ impl WalletActor {
         Ok(max_send_amount)
     }
 
-    fn get_weighted_utxos(
-        &self,
-        outpoints: &[bitcoin::OutPoint],
-    ) -> Result<Vec<WeightedUtxo>, AddUtxoError> {
-        outpoints
-            .iter()
-            .map(|outpoint| {
-                self.wallet.bdk.get_utxo(*outpoint).ok_or(AddUtxoError::UnknownUtxo(*outpoint)).map(
-                    |output| WeightedUtxo {
-                        satisfaction_weight: self
-                            .wallet
-                            .bdk
-                            .public_descriptor(output.keychain)
-                            .max_weight_to_satisfy()
-                            .unwrap(),
-                        utxo: Utxo::Local(output),
-                    },
-                )
+    fn build_tx_with(
+        &mut self,
+        coin_selection: impl CoinSelectionAlgorithm,
+        recipients: Vec<Recipient>,
+        fee_rate: BdkFeeRate,
+        frozen: HashSet<OutPoint>,
+    ) -> Result<Psbt, Error> {
+        let options = self.tx_builder_options()?;
+        build_batch_tx(&mut self.wallet.bdk, coin_selection, recipients, fee_rate, options, frozen)
+    }
+
+    /// Read before the tx builder borrows the wallet
+    fn tx_builder_options(&mut self) -> Result<TxBuilderOptions, Error> {
+        let wallet_type = self.wallet.metadata.wallet_type;
+        let mut options = TxBuilderOptions {
+            add_global_xpubs: matches!(wallet_type, WalletType::Multisig | WalletType::Miniscript),
+            policy_paths: Vec::new(),
+            unspendable: Vec::new(),
+        };
+
+        if wallet_type != WalletType::Miniscript {
+            return Ok(options);
+        }
+
+        let index = self.selected_spending_path()?;
+        for keychain in [KeychainKind::External, KeychainKind::Internal] {
+            let Some(policy) = self.policy(keychain)? else { continue };
+            let path = policy::policy_path(&policy, index)
+                .map_err(|error| Error::SpendingPathError(error.to_string()))?;
+
+            if let Some(path) = path {
+                options.policy_paths.push((path, keychain));
+            }
+        }
+
+        options.unspendable = self.timelocked_utxos(index)?;
+        Ok(options)
+    }
+
+    /// UTXOs that can't be spent on the spending path yet, `older(n)` needs `n` confirmations on
+    /// every coin the transaction spends
+    fn timelocked_utxos(&mut self, index: u32) -> Result<Vec<OutPoint>, Error> {
+        let height = self.last_height_fetched().map(|(_, height)| height as u32).unwrap_or(0);
+        let Some(path) = self.do_spending_paths(height)?.into_iter().find(|p| p.index == index)
+        else {
+            return Ok(Vec::new());
+        };
+
+        let chain = self.chain_state(height);
+        let timelocked = self
+            .wallet
+            .bdk
+            .list_unspent()
+            .filter(|utxo| {
+                let confirmed = confirmed_at(&utxo.chain_position);
+                !path.timelocks.iter().all(|timelock| timelock.is_met_by_utxo(chain, confirmed))
+            })
+            .map(|utxo| utxo.outpoint)
+            .collect();
+
+        Ok(timelocked)
+    }
+
+    fn policy(&self, keychain: KeychainKind) -> Result<Option<Policy>, Error> {
+        self.wallet
+            .bdk
+            .policies(keychain)
+            .map_err(|error| Error::SpendingPathError(error.to_string()))
+    }
+
+    /// The path the user picked, or the first one that can be used at the last fetched height
+    fn selected_spending_path(&mut self) -> Result<u32, Error> {
+        let selected = Database::global()
+            .wallets()
+            .get(&self.wallet.id, self.wallet.network, self.wallet.metadata.wallet_mode)
+            .ok()
+            .flatten()
+            .and_then(|metadata| metadata.spending_path);
+
+        if let Some(index) = selected {
+            return Ok(index);
+        }
+
+        let height = self.last_height_fetched().map(|(_, height)| height as u32).unwrap_or(0);
+        let first_available = self
+            .do_spending_paths(height)?
+            .into_iter()
+            .find(|path| path.is_available)
+            .map(|path| path.index);
+
+        Ok(first_available.unwrap_or(0))
+    }
+
+    fn do_spending_paths(&self, height: u32) -> Result<Vec<SpendingPath>, Error> {
+        let Some(policy) = self.policy(KeychainKind::External)? else {
+            return Ok(Vec::new());
+        };
+
+        let utxos = self
+            .wallet
+            .bdk
+            .list_unspent()
+            .map(|utxo| (utxo.txout.value, confirmed_at(&utxo.chain_position)))
+            .collect::<Vec<_>>();
+
+        Ok(policy::spending_paths(&policy, self.chain_state(height), &utxos))
+    }
+
+    /// What timelocks are checked against, every spending path check uses this so they all see
+    /// the same clock
+    fn chain_state(&self, height: u32) -> ChainState {
+        let oldest_utxo = self
+            .wallet
+            .bdk
+            .list_unspent()
+            .filter_map(|utxo| confirmed_at(&utxo.chain_position))
+            .min();
+
+        let now = jiff::Timestamp::now().as_second() as u64;
+        ChainState { height, now, oldest_utxo }
+    }
+
+    /// The coin selection the user chose for this wallet, read from the database so changes
+    /// made after the wallet was loaded are picked up
+    fn coin_selection_strategy(&self) -> CoinSelectionStrategy {
+        Database::global()
+            .wallets()
+            .get(&self.wallet.id, self.wallet.network, self.wallet.metadata.wallet_mode)
+            .ok()
+            .flatten()
+            .map(|metadata| metadata.coin_selection)
+            .unwrap_or(self.wallet.metadata.coin_selection)
+    }
+
+    /// Labels for our unspent outputs, the transaction label or the address label
+    fn utxo_labels(&self) -> Vec<(OutPoint, String)> {
+        let labels = &self.db.labels;
+        let params = Params::from(self.wallet.bdk.network());
+
+        self.wallet
+            .bdk
+            .list_unspent()
+            .filter_map(|output| {
+                let txn_label = labels
+                    .get_txn_label_record(output.outpoint.txid)
+                    .ok()
+                    .flatten()
+                    .and_then(|record| record.item.label);
+
+                let label = txn_label.or_else(|| {
+                    let address =
+                        bitcoin::Address::from_script(&output.txout.script_pubkey, &params).ok()?;
+
+                    labels
+                        .get_address_record(address.into_unchecked())
+                        .ok()
+                        .flatten()
+                        .and_then(|record| record.item.label)
+                })?;
+
+                Some((output.outpoint, label))
             })
             .collect()
     }
 
+    /// UTXOs the user has frozen, these are never spent by any transaction we build
+    fn frozen_utxos(&self) -> Result<HashSet<OutPoint>, Error> {
+        self.db.get_frozen_utxos().map_err(|err| Error::BuildTxError(err.to_string()))
+    }
+
+    fn get_weighted_utxos(
+        &self,
+        outpoints: &[bitcoin::OutPoint],
+    ) -> Result<Vec<WeightedUtxo>, AddUtxoError> {
+        weighted_utxos(&self.wallet.bdk, outpoints)
+    }
+
+    /// Child-pays-for-parent for an unconfirmed transaction, the change address is only revealed
+    /// when `reveal` is set
+    fn cpfp_psbt(
+        &mut self,
+        parent_tx_id: Txid,
+        target_fee_rate: BdkFeeRate,
+        reveal: bool,
+    ) -> Result<Psbt, Error> {
+        let package = self.unconfirmed_package(parent_tx_id)?;
+        let frozen = self.frozen_utxos()?;
+        let options = self.tx_builder_options()?;
+
+        build_cpfp(
+            &mut self.wallet.bdk,
+            parent_tx_id,
+            package,
+            target_fee_rate,
+            options,
+            frozen,
+            reveal,
+        )
+    }
+
+    /// Fee bump or cancel replacement for an unconfirmed outgoing transaction, the cancel's change
+    /// address is only revealed when `reveal` is set
+    fn replacement_psbt(
+        &mut self,
+        kind: ReplacementKind,
+        tx_id: Txid,
+        fee_rate: BdkFeeRate,
+        reveal: bool,
+    ) -> Result<Psbt, Error> {
+        let frozen = self.frozen_utxos()?;
+        let options = self.tx_builder_options()?;
+
+        let psbt = build_replacement(
+            &mut self.wallet.bdk,
+            kind,
+            tx_id,
+            fee_rate,
+            options,
+            frozen,
+            reveal,
+        )?;
+
+        if reveal {
+            self.wallet.persist()?;
+        }
+
+        Ok(psbt)
+    }
+
+    /// Broadcast replacement transactions mapped to the transaction they replace, saved so we
+    /// still know which one confirmed after a restart
+    fn replacements(&self) -> BTreeMap<Txid, Txid> {
+        self.db.get_replacements().unwrap_or_else(|error| {
+            error!("unable to get replacements: {error}");
+            BTreeMap::new()
+        })
+    }
+
+    /// The lowest fee rate a replacement for the given transaction can use
+    ///
+    /// A fee bump keeps the same inputs and outputs so it's about the same size as the original,
+    /// a cancel has a single change output so it's smaller and needs a higher fee rate
+    fn min_replacement_rate(
+        &self,
+        kind: ReplacementKind,
+        tx_id: Txid,
+    ) -> Result<BdkFeeRate, Error> {
+        let tx = self
+            .wallet
+            .bdk
+            .get_tx(tx_id)
+            .ok_or_else(|| Error::FeeBumpError("transaction not found".to_string()))?
+            .tx_node
+            .tx;
+
+        let original_fee = self
+            .wallet
+            .bdk
+            .calculate_fee(&tx)
+            .map_err(|err| Error::FeeBumpError(err.to_string()))?;
+
+        let original_vsize = tx.vsize() as u64;
+        let replacement_vsize = match kind {
+            ReplacementKind::FeeBump => original_vsize,
+            ReplacementKind::Cancel => {
+                let change_script =
+                    self.wallet.bdk.peek_address(KeychainKind::Internal, 0).script_pubkey();
+
+                let change = bitcoin::TxOut { value: Amount::ZERO, script_pubkey: change_script };
+                let outputs = tx.output.iter().map(|output| output.weight()).sum::<Weight>();
+
+                (tx.weight() - outputs + change.weight()).to_vbytes_ceil()
+            }
+        };
+
+        Ok(min_replacement_fee_rate(original_fee, original_vsize, replacement_vsize))
+    }
+
+    /// Virtual size and fee of a transaction in the wallet that is not confirmed yet
+    fn unconfirmed_parent_vsize_and_fee(&self, tx_id: Txid) -> Result<(u64, Amount), Error> {
+        let tx = self
+            .wallet
+            .bdk
+            .get_tx(tx_id)
+            .ok_or_else(|| Error::CpfpError("transaction not found".to_string()))?;
+
+        if tx.chain_position.is_confirmed() {
+            return Err(Error::CpfpError("transaction is already confirmed".to_string()));
+        }
+
+        let fee = self
+            .wallet
+            .bdk
+            .calculate_fee(&tx.tx_node.tx)
+            .map_err(|err| Error::CpfpError(err.to_string()))?;
+
+        Ok((tx.tx_node.tx.vsize() as u64, fee))
+    }
+
+    /// Unconfirmed ancestors of the parent that are in the wallet's graph, the child has to pay
+    /// for them too for the package to reach the target fee rate
+    ///
+    /// Ancestors that aren't ours are not in the graph and can't be accounted for
+    fn unconfirmed_ancestors(&self, parent_tx_id: Txid) -> Result<CpfpPackage, Error> {
+        let parent = self
+            .wallet
+            .bdk
+            .get_tx(parent_tx_id)
+            .ok_or_else(|| Error::CpfpError("transaction not found".to_string()))?;
+
+        let mut ancestors = CpfpPackage::default();
+        let mut seen = HashSet::default();
+        let mut queue = parent
+            .tx_node
+            .tx
+            .input
+            .iter()
+            .map(|input| input.previous_output.txid)
+            .collect::<Vec<_>>();
+
+        while let Some(tx_id) = queue.pop() {
+            if !seen.insert(tx_id) {
+                continue;
+            }
+
+            let Some(tx) = self.wallet.bdk.get_tx(tx_id) else { continue };
+            if tx.chain_position.is_confirmed() {
+                continue;
+            }
+
+            let fee = self.wallet.bdk.calculate_fee(&tx.tx_node.tx).map_err(|err| {
+                Error::CpfpError(format!(
+                    "unable to get fee of unconfirmed ancestor {tx_id}: {err}"
+                ))
+            })?;
+
+            ancestors = ancestors.with(tx.tx_node.tx.vsize() as u64, fee);
+            queue.extend(tx.tx_node.tx.input.iter().map(|input| input.previous_output.txid));
+        }
+
+        Ok(ancestors)
+    }
+
+    /// The parent together with its unconfirmed ancestors
+    fn unconfirmed_package(&self, parent_tx_id: Txid) -> Result<CpfpPackage, Error> {
+        let (parent_vsize, parent_fee) = self.unconfirmed_parent_vsize_and_fee(parent_tx_id)?;
+        let ancestors = self.unconfirmed_ancestors(parent_tx_id)?;
+
+        Ok(ancestors.with(parent_vsize, parent_fee))
+    }
+
     // after the initial full scan is complete, do a much for comprehensive scan of the wallet
     // this is slower, but we want to be able to see all transactions in the UI, so scan the next
     // 150 addresses
@@ -1191,8 +2085,10 @@ impl WalletActor {
 
         match full_scan_result {
             Ok(full_scan_result) => {
+                let known = self.transaction_ids();
                 self.wallet.bdk.apply_update(full_scan_result)?;
                 self.wallet.persist()?;
+                self.label_new_incoming_transactions(&known);
             }
             Err(error) => {
                 self.state = ActorState::FailedFullScan(full_scan_type);
@@ -1226,8 +2122,10 @@ impl WalletActor {
         }
 
         let sync_result = scan_result?;
+        let known = self.transaction_ids();
         self.wallet.bdk.apply_update(sync_result)?;
         self.wallet.persist()?;
+        self.label_new_incoming_transactions(&known);
         self.save_last_scan_finished();
 
         self.notify_scan_complete().await?;
@@ -1244,8 +2142,10 @@ impl WalletActor {
         }
 
         let scan_result: SyncResponse = scan_result?;
+        let known = self.transaction_ids();
         self.wallet.bdk.apply_update(scan_result)?;
         self.wallet.persist()?;
+        self.label_new_incoming_transactions(&known);
 
         // get and send transactions
         let transactions = self.transactions().await?.await?;
@@ -1395,3 +2295,719 @@ impl Drop for WalletActor {
         debug!("[DROP] Wallet Actor for {}", self.wallet.id);
     }
 }
+
+/// Height and time a wallet output was confirmed at, `None` while unconfirmed
+fn confirmed_at(chain_position: &ChainPosition<ConfirmationBlockTime>) -> Option<(u32, u64)> {
+    match chain_position {
+        ChainPosition::Confirmed { anchor, .. } => {
+            Some((anchor.block_id.height, anchor.confirmation_time))
+        }
+        ChainPosition::Unconfirmed { .. } => None,
+    }
+}
+
+/// One transaction paying every recipient, the max recipient drains whatever is left
+fn build_batch_tx(
+    bdk: &mut BdkWallet,
+    coin_selection: impl CoinSelectionAlgorithm,
+    recipients: Vec<Recipient>,
+    fee_rate: BdkFeeRate,
+    options: TxBuilderOptions,
+    frozen: HashSet<OutPoint>,
+) -> Result<Psbt, Error> {
+    let mut tx_builder = bdk.build_tx().coin_selection(coin_selection);
+    options.apply(&mut tx_builder);
+
+    tx_builder.ordering(TxOrdering::Untouched);
+    frozen.into_iter().for_each(|outpoint| {
+        tx_builder.add_unspendable(outpoint);
+    });
+
+    for recipient in recipients {
+        let script_pubkey = recipient.address.script_pubkey();
+        match recipient.amount() {
+            Some(amount) => {
+                tx_builder.add_recipient(script_pubkey, amount.into());
+            }
+            None => {
+                tx_builder.drain_wallet().drain_to(script_pubkey);
+            }
+        }
+    }
+
+    tx_builder.fee_rate(fee_rate);
+
+    let psbt = tx_builder.finish().map_err(|err| Error::BuildTxError(err.to_string()))?;
+    Ok(psbt)
+}
+
+/// Fee bump or cancel replacement for an unconfirmed outgoing transaction, a cancel drops the
+/// recipients and sends everything to the next change address
+///
+/// The change address is only revealed and marked used when `reveal` is set and the build worked,
+/// so fee previews don't use up change addresses
+fn build_replacement(
+    bdk: &mut BdkWallet,
+    kind: ReplacementKind,
+    tx_id: Txid,
+    fee_rate: BdkFeeRate,
+    options: TxBuilderOptions,
+    frozen: HashSet<OutPoint>,
+    reveal: bool,
+) -> Result<Psbt, Error> {
+    let error = |err: &dyn std::fmt::Display| match kind {
+        ReplacementKind::FeeBump => Error::FeeBumpError(err.to_string()),
+        ReplacementKind::Cancel => Error::CancelTxError(err.to_string()),
+    };
+
+    let change_address = match kind {
+        ReplacementKind::FeeBump => None,
+        ReplacementKind::Cancel => {
+            check_cancellable(bdk, tx_id)?;
+            Some(peek_change_address(bdk))
+        }
+    };
+
+    let mut tx_builder = bdk.build_fee_bump(tx_id).map_err(|err| error(&err))?;
+    options.apply(&mut tx_builder);
+    frozen.into_iter().for_each(|outpoint| {
+        tx_builder.add_unspendable(outpoint);
+    });
+
+    if let Some(change_address) = &change_address {
+        tx_builder.set_recipients(vec![]);
+        tx_builder.drain_to(change_address.script_pubkey());
+    }
+
+    tx_builder.fee_rate(fee_rate);
+    let psbt = tx_builder.finish().map_err(|err| error(&err))?;
+
+    if let Some(change_address) = change_address.filter(|_| reveal) {
+        use_change_address(bdk, change_address.index);
+    }
+
+    Ok(psbt)
+}
+
+/// Only unconfirmed outgoing transactions that signal replace-by-fee can be cancelled
+fn check_cancellable(bdk: &BdkWallet, tx_id: Txid) -> Result<(), Error> {
+    let tx = bdk
+        .get_tx(tx_id)
+        .ok_or_else(|| Error::CancelTxError("transaction not found".to_string()))?;
+
+    if tx.chain_position.is_confirmed() {
+        return Err(Error::CancelTxError("transaction is already confirmed".to_string()));
+    }
+
+    let (sent, _received) = bdk.sent_and_received(&tx.tx_node.tx);
+    if sent == Amount::ZERO {
+        return Err(Error::CancelTxError(
+            "only outgoing transactions can be cancelled".to_string(),
+        ));
+    }
+
+    // BDK only builds replacements for transactions that opted in to BIP125, full-RBF nodes
+    // would accept the replacement either way but we can't rely on every node running it
+    if !tx.tx_node.tx.is_explicitly_rbf() {
+        return Err(Error::CancelTxError("transaction does not signal replace-by-fee".to_string()));
+    }
+
+    Ok(())
+}
+
+/// The unconfirmed wallet transaction this one replaces, the one spending any of the same inputs
+fn find_replaced_tx_id(bdk: &BdkWallet, tx: &BdkTransaction) -> Option<Txid> {
+    let tx_id = tx.compute_txid();
+    let inputs = tx.input.iter().map(|input| input.previous_output).collect::<HashSet<_>>();
+
+    bdk.transactions()
+        .filter(|wallet_tx| !wallet_tx.chain_position.is_confirmed())
+        .filter(|wallet_tx| wallet_tx.tx_node.txid != tx_id)
+        .find(|wallet_tx| {
+            let other = &wallet_tx.tx_node.tx;
+            other.input.iter().any(|input| inputs.contains(&input.previous_output))
+        })
+        .map(|wallet_tx| wallet_tx.tx_node.txid)
+}
+
+/// Every other transaction in the chain of replacements the confirmed transaction is part of,
+/// and the replacements left once that chain is dropped
+///
+/// Replacements can be replaced again (A -> B -> C), whichever one confirms, all the others in
+/// the chain never will
+fn split_replacement_chain(
+    replacements: &BTreeMap<Txid, Txid>,
+    confirmed: Txid,
+) -> (Vec<Txid>, BTreeMap<Txid, Txid>) {
+    let mut chain = HashSet::from_iter([confirmed]);
+    let mut queue = vec![confirmed];
+
+    while let Some(tx_id) = queue.pop() {
+        for (replacement, original) in replacements {
+            let linked = if *replacement == tx_id {
+                *original
+            } else if *original == tx_id {
+                *replacement
+            } else {
+                continue;
+            };
+
+            if chain.insert(linked) {
+                queue.push(linked);
+            }
+        }
+    }
+
+    let remaining = replacements
+        .iter()
+        .filter(|(replacement, _)| !chain.contains(*replacement))
+        .map(|(replacement, original)| (*replacement, *original))
+        .collect::<BTreeMap<_, _>>();
+
+    let mut replaced = chain.into_iter().filter(|tx_id| *tx_id != confirmed).collect::<Vec<_>>();
+    replaced.sort();
+
+    (replaced, remaining)
+}
+
+/// Child-pays-for-parent spending the wallet's unconfirmed outputs of the parent to the next change
+/// address, paying enough so the parent package and child together hit the target fee rate
+///
+/// The change address is only revealed and marked used when `reveal` is set and the build worked,
+/// so fee previews and failed builds don't use up change addresses
+fn build_cpfp(
+    bdk: &mut BdkWallet,
+    parent_tx_id: Txid,
+    package: CpfpPackage,
+    target_fee_rate: BdkFeeRate,
+    options: TxBuilderOptions,
+    frozen: HashSet<OutPoint>,
+    reveal: bool,
+) -> Result<Psbt, Error> {
+    if package.child_fee(0, target_fee_rate).is_none() {
+        return Err(Error::CpfpError(
+            "transaction already pays at least the target fee rate".to_string(),
+        ));
+    }
+
+    let outpoints = bdk
+        .list_unspent()
+        .filter(|utxo| utxo.outpoint.txid == parent_tx_id)
+        .map(|utxo| utxo.outpoint)
+        .filter(|outpoint| !frozen.contains(outpoint))
+        .collect::<Vec<OutPoint>>();
+
+    if outpoints.is_empty() {
+        return Err(Error::CpfpError("no spendable outputs in transaction".to_string()));
+    }
+
+    let change_address = peek_change_address(bdk);
+    let drain_script = change_address.script_pubkey();
+
+    let child_vsize = estimate_child_vsize(bdk, &outpoints, &drain_script)?;
+    let child_fee = package
+        .child_fee(child_vsize, target_fee_rate)
+        .ok_or_else(|| Error::CpfpError("fee overflow".to_string()))?;
+
+    let mut tx_builder = bdk.build_tx();
+    options.apply(&mut tx_builder);
+
+    tx_builder.add_utxos(&outpoints).map_err(|err| Error::AddUtxosError(err.to_string()))?;
+    tx_builder.manually_selected_only();
+    tx_builder.drain_to(drain_script);
+    tx_builder.fee_absolute(child_fee);
+
+    let psbt = tx_builder.finish().map_err(|err| Error::CpfpError(err.to_string()))?;
+    if reveal {
+        use_change_address(bdk, change_address.index);
+    }
+
+    Ok(psbt)
+}
+
+/// The change address `next_unused_address` would give out, without revealing it
+fn peek_change_address(bdk: &BdkWallet) -> bdk_wallet::AddressInfo {
+    let keychain = KeychainKind::Internal;
+    bdk.list_unused_addresses(keychain)
+        .next()
+        .unwrap_or_else(|| bdk.peek_address(keychain, bdk.next_derivation_index(keychain)))
+}
+
+/// Reveal a change address from [`peek_change_address`] and mark it used, like the change BDK
+/// picks itself, the caller persists the wallet
+fn use_change_address(bdk: &mut BdkWallet, index: u32) {
+    bdk.reveal_addresses_to(KeychainKind::Internal, index).for_each(drop);
+    bdk.mark_used(KeychainKind::Internal, index);
+}
+
+fn weighted_utxos(
+    bdk: &BdkWallet,
+    outpoints: &[bitcoin::OutPoint],
+) -> Result<Vec<WeightedUtxo>, AddUtxoError> {
+    outpoints
+        .iter()
+        .map(|outpoint| {
+            bdk.get_utxo(*outpoint).ok_or(AddUtxoError::UnknownUtxo(*outpoint)).map(|output| {
+                WeightedUtxo {
+                    satisfaction_weight: bdk
+                        .public_descriptor(output.keychain)
+                        .max_weight_to_satisfy()
+                        .unwrap(),
+                    utxo: Utxo::Local(output),
+                }
+            })
+        })
+        .collect()
+}
+
+/// Estimated virtual size of a signed transaction spending the given utxos to a single output
+fn estimate_child_vsize(
+    bdk: &BdkWallet,
+    outpoints: &[bitcoin::OutPoint],
+    drain_script: &bitcoin::Script,
+) -> Result<u64, Error> {
+    let weighted_utxos =
+        weighted_utxos(bdk, outpoints).map_err(|err| Error::AddUtxosError(err.to_string()))?;
+
+    let output = bitcoin::TxOut { value: Amount::ZERO, script_pubkey: drain_script.into() };
+
+    // version, locktime, input and output counts plus the segwit marker and flag
+    let mut weight = Weight::from_wu(4 * 10 + 2) + output.weight();
+    for weighted_utxo in weighted_utxos {
+        weight += TxIn::default().segwit_weight() + weighted_utxo.satisfaction_weight;
+    }
+
+    Ok(weight.to_vbytes_ceil())
+}
+
+/// Settings every tx builder needs
+#[derive(Default)]
+struct TxBuilderOptions {
+    /// Multisig and policy PSBTs carry every xpub, so each device can verify the change
+    add_global_xpubs: bool,
+    policy_paths: Vec<(BTreeMap<String, Vec<usize>>, KeychainKind)>,
+    /// Coins still locked by a relative timelock on the selected spending path
+    unspendable: Vec<OutPoint>,
+}
+
+impl TxBuilderOptions {
+    fn apply<Cs>(self, tx_builder: &mut TxBuilder<'_, Cs>) {
+        if self.add_global_xpubs {
+            tx_builder.add_global_xpubs();
+        }
+
+        for (path, keychain) in self.policy_paths {
+            tx_builder.policy_path(path, keychain);
+        }
+
+        for outpoint in self.unspendable {
+            tx_builder.add_unspendable(outpoint);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr as _;
+
+    use bitcoin::{Network, ScriptBuf};
+
+    use super::*;
+    use crate::mnemonic::MnemonicExt as _;
+
+    const WORDS: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    /// In memory wallet that can sign for itself
+    fn wallet() -> BdkWallet {
+        let mnemonic = bip39::Mnemonic::from_str(WORDS).unwrap();
+        mnemonic
+            .into_descriptors(None, Network::Bitcoin, WalletAddressType::NativeSegwit, 0)
+            .into_create_params()
+            .network(Network::Bitcoin)
+            .create_wallet_no_persist()
+            .unwrap()
+    }
+
+    fn rate(sat_per_vb: u64) -> BdkFeeRate {
+        BdkFeeRate::from_sat_per_vb_unchecked(sat_per_vb)
+    }
+
+    fn external_script() -> ScriptBuf {
+        ScriptBuf::from_hex(&format!("0014{}", "11".repeat(20))).unwrap()
+    }
+
+    /// Unconfirmed payment from outside the wallet to the next receive address
+    fn receive(wallet: &mut BdkWallet, sats: u64) -> BdkTransaction {
+        let address = wallet.next_unused_address(KeychainKind::External);
+        let previous_output =
+            OutPoint { txid: Txid::from_str(&"ab".repeat(32)).unwrap(), vout: address.index };
+
+        let tx = BdkTransaction {
+            version: bitcoin::transaction::Version::TWO,
+            lock_time: bitcoin::absolute::LockTime::ZERO,
+            input: vec![TxIn { previous_output, ..Default::default() }],
+            output: vec![bitcoin::TxOut {
+                value: Amount::from_sat(sats),
+                script_pubkey: address.script_pubkey(),
+            }],
+        };
+
+        wallet.apply_unconfirmed_txs([(tx.clone(), 1_700_000_000)]);
+        tx
+    }
+
+    fn sign(wallet: &BdkWallet, mut psbt: Psbt) -> BdkTransaction {
+        assert!(wallet.sign(&mut psbt, SignOptions::default()).unwrap());
+        psbt.extract_tx().unwrap()
+    }
+
+    /// Signs the PSBT and puts the transaction in the wallet's mempool view
+    fn broadcast(wallet: &mut BdkWallet, psbt: Psbt, last_seen: u64) -> BdkTransaction {
+        let tx = sign(wallet, psbt);
+        wallet.apply_unconfirmed_txs([(tx.clone(), last_seen)]);
+        tx
+    }
+
+    /// Unconfirmed send to an address outside the wallet
+    fn send(wallet: &mut BdkWallet, sats: u64, fee_rate: u64) -> BdkTransaction {
+        let mut tx_builder = wallet.build_tx();
+        tx_builder.add_recipient(external_script(), Amount::from_sat(sats));
+        tx_builder.fee_rate(rate(fee_rate));
+        let psbt = tx_builder.finish().unwrap();
+
+        broadcast(wallet, psbt, 1_700_000_100)
+    }
+
+    fn inputs(tx: &BdkTransaction) -> Vec<OutPoint> {
+        tx.input.iter().map(|input| input.previous_output).collect()
+    }
+
+    fn fee_bump(wallet: &mut BdkWallet, tx_id: Txid, fee_rate: u64) -> Result<Psbt, Error> {
+        build_replacement(
+            wallet,
+            ReplacementKind::FeeBump,
+            tx_id,
+            rate(fee_rate),
+            TxBuilderOptions::default(),
+            HashSet::default(),
+            false,
+        )
+    }
+
+    fn cancel(wallet: &mut BdkWallet, tx_id: Txid, reveal: bool) -> Result<Psbt, Error> {
+        build_replacement(
+            wallet,
+            ReplacementKind::Cancel,
+            tx_id,
+            rate(10),
+            TxBuilderOptions::default(),
+            HashSet::default(),
+            reveal,
+        )
+    }
+
+    #[test]
+    fn test_fee_bump_keeps_inputs_and_recipient() {
+        let mut wallet = wallet();
+        receive(&mut wallet, 100_000);
+        let original = send(&mut wallet, 50_000, 2);
+        let original_fee = wallet.calculate_fee(&original).unwrap();
+
+        let psbt = fee_bump(&mut wallet, original.compute_txid(), 10).unwrap();
+        assert_eq!(inputs(&psbt.unsigned_tx), inputs(&original));
+
+        let payment =
+            bitcoin::TxOut { value: Amount::from_sat(50_000), script_pubkey: external_script() };
+        assert!(psbt.unsigned_tx.output.contains(&payment));
+
+        let bump = broadcast(&mut wallet, psbt, 1_700_000_200);
+        let fee = wallet.calculate_fee(&bump).unwrap();
+        assert!(fee > original_fee);
+        assert!(fee.to_sat() >= 10 * bump.vsize() as u64);
+    }
+
+    #[test]
+    fn test_fee_bump_needs_higher_rate() {
+        let mut wallet = wallet();
+        receive(&mut wallet, 100_000);
+        let original = send(&mut wallet, 50_000, 5);
+
+        let error = fee_bump(&mut wallet, original.compute_txid(), 3).unwrap_err();
+        assert!(matches!(error, Error::FeeBumpError(_)));
+    }
+
+    #[test]
+    fn test_fee_bump_unknown_transaction() {
+        let mut wallet = wallet();
+        receive(&mut wallet, 100_000);
+
+        let tx_id = Txid::from_str(&"cd".repeat(32)).unwrap();
+        assert!(matches!(fee_bump(&mut wallet, tx_id, 10), Err(Error::FeeBumpError(_))));
+    }
+
+    fn cpfp(
+        wallet: &mut BdkWallet,
+        parent: &BdkTransaction,
+        package: CpfpPackage,
+        frozen: HashSet<OutPoint>,
+        reveal: bool,
+    ) -> Result<Psbt, Error> {
+        let options = TxBuilderOptions::default();
+        build_cpfp(wallet, parent.compute_txid(), package, rate(10), options, frozen, reveal)
+    }
+
+    /// Incoming parent paying 1 sat/vB
+    fn parent_package(parent: &BdkTransaction) -> CpfpPackage {
+        let vsize = parent.vsize() as u64;
+        CpfpPackage::default().with(vsize, Amount::from_sat(vsize))
+    }
+
+    #[test]
+    fn test_cpfp_spends_parent_outputs_to_change() {
+        let mut wallet = wallet();
+        let parent = receive(&mut wallet, 100_000);
+        let package = parent_package(&parent);
+        let change_index = wallet.next_derivation_index(KeychainKind::Internal);
+
+        let psbt = cpfp(&mut wallet, &parent, package, HashSet::default(), false).unwrap();
+        let change = wallet.peek_address(KeychainKind::Internal, change_index);
+
+        assert_eq!(inputs(&psbt.unsigned_tx), vec![OutPoint::new(parent.compute_txid(), 0)]);
+        assert_eq!(psbt.unsigned_tx.output.len(), 1);
+        assert_eq!(psbt.unsigned_tx.output[0].script_pubkey, change.script_pubkey());
+
+        // parent and child together pay the target rate
+        let child = broadcast(&mut wallet, psbt, 1_700_000_100);
+        let child_fee = wallet.calculate_fee(&child).unwrap();
+        let total_vsize = parent.vsize() as u64 + child.vsize() as u64;
+        assert!(parent.vsize() as u64 + child_fee.to_sat() >= 10 * total_vsize);
+    }
+
+    #[test]
+    fn test_cpfp_only_reveals_change_when_asked() {
+        let mut wallet = wallet();
+        let parent = receive(&mut wallet, 100_000);
+        let package = parent_package(&parent);
+        let change_index = wallet.next_derivation_index(KeychainKind::Internal);
+
+        // fee previews leave the change index alone, so every preview uses the same address
+        let preview = cpfp(&mut wallet, &parent, package, HashSet::default(), false).unwrap();
+        let again = cpfp(&mut wallet, &parent, package, HashSet::default(), false).unwrap();
+        assert_eq!(preview.unsigned_tx.output, again.unsigned_tx.output);
+        assert_eq!(wallet.next_derivation_index(KeychainKind::Internal), change_index);
+
+        // the build that gets signed reveals the address it sends to
+        let psbt = cpfp(&mut wallet, &parent, package, HashSet::default(), true).unwrap();
+        assert_eq!(psbt.unsigned_tx.output, preview.unsigned_tx.output);
+        assert_eq!(wallet.next_derivation_index(KeychainKind::Internal), change_index + 1);
+    }
+
+    #[test]
+    fn test_cpfp_failures_leave_change_alone() {
+        let mut wallet = wallet();
+        let parent = receive(&mut wallet, 100_000);
+        let change_index = wallet.next_derivation_index(KeychainKind::Internal);
+
+        // already at the target rate
+        let vsize = parent.vsize() as u64;
+        let package = CpfpPackage::default().with(vsize, Amount::from_sat(vsize * 20));
+        let result = cpfp(&mut wallet, &parent, package, HashSet::default(), true);
+        assert!(matches!(result, Err(Error::CpfpError(_))));
+
+        // the only output is frozen
+        let frozen = HashSet::from_iter([OutPoint::new(parent.compute_txid(), 0)]);
+        let result = cpfp(&mut wallet, &parent, parent_package(&parent), frozen, true);
+        assert!(matches!(result, Err(Error::CpfpError(_))));
+
+        assert_eq!(wallet.next_derivation_index(KeychainKind::Internal), change_index);
+    }
+
+    #[test]
+    fn test_cancel_sends_everything_to_change() {
+        let mut wallet = wallet();
+        receive(&mut wallet, 100_000);
+        let original = send(&mut wallet, 50_000, 2);
+        let change_index = wallet.next_derivation_index(KeychainKind::Internal);
+
+        let psbt = cancel(&mut wallet, original.compute_txid(), false).unwrap();
+        let change = wallet.peek_address(KeychainKind::Internal, change_index);
+
+        assert_eq!(inputs(&psbt.unsigned_tx), inputs(&original));
+        assert_eq!(psbt.unsigned_tx.output.len(), 1);
+        assert_eq!(psbt.unsigned_tx.output[0].script_pubkey, change.script_pubkey());
+    }
+
+    #[test]
+    fn test_cancel_only_reveals_change_when_asked() {
+        let mut wallet = wallet();
+        receive(&mut wallet, 100_000);
+        let original = send(&mut wallet, 50_000, 2);
+        let change_index = wallet.next_derivation_index(KeychainKind::Internal);
+
+        // fee previews leave the change index alone
+        let preview = cancel(&mut wallet, original.compute_txid(), false).unwrap();
+        let again = cancel(&mut wallet, original.compute_txid(), false).unwrap();
+        assert_eq!(preview.unsigned_tx.output, again.unsigned_tx.output);
+        assert_eq!(wallet.next_derivation_index(KeychainKind::Internal), change_index);
+
+        let psbt = cancel(&mut wallet, original.compute_txid(), true).unwrap();
+        assert_eq!(psbt.unsigned_tx.output, preview.unsigned_tx.output);
+        assert_eq!(wallet.next_derivation_index(KeychainKind::Internal), change_index + 1);
+    }
+
+    #[test]
+    fn test_cancel_only_outgoing() {
+        let mut wallet = wallet();
+        let incoming = receive(&mut wallet, 100_000);
+        let change_index = wallet.next_derivation_index(KeychainKind::Internal);
+
+        let result = cancel(&mut wallet, incoming.compute_txid(), true);
+        assert!(matches!(result, Err(Error::CancelTxError(_))));
+        assert_eq!(wallet.next_derivation_index(KeychainKind::Internal), change_index);
+    }
+
+    #[test]
+    fn test_find_replaced_tx_id() {
+        let mut wallet = wallet();
+        receive(&mut wallet, 100_000);
+        let original = send(&mut wallet, 50_000, 2);
+
+        // a build that's never broadcast replaces nothing until it is
+        let bump = sign(&wallet, fee_bump(&mut wallet, original.compute_txid(), 5).unwrap());
+        assert_eq!(find_replaced_tx_id(&wallet, &bump), Some(original.compute_txid()));
+
+        // spending other coins, even the original's change, isn't a replacement
+        receive(&mut wallet, 100_000);
+        let mut tx_builder = wallet.build_tx();
+        tx_builder.add_recipient(external_script(), Amount::from_sat(140_000));
+        tx_builder.fee_rate(rate(2));
+        let other = sign(&wallet, tx_builder.finish().unwrap());
+        assert_eq!(find_replaced_tx_id(&wallet, &other), None);
+    }
+
+    #[test]
+    fn test_two_step_bump_drops_whole_chain() {
+        let mut wallet = wallet();
+        receive(&mut wallet, 100_000);
+
+        // A -> B -> C, each replacement saved when it's broadcast
+        let mut replacements = BTreeMap::new();
+        let a = send(&mut wallet, 50_000, 2);
+
+        let b = sign(&wallet, fee_bump(&mut wallet, a.compute_txid(), 5).unwrap());
+        replacements.insert(b.compute_txid(), find_replaced_tx_id(&wallet, &b).unwrap());
+        wallet.apply_unconfirmed_txs([(b.clone(), 1_700_000_200)]);
+
+        let c = sign(&wallet, fee_bump(&mut wallet, b.compute_txid(), 10).unwrap());
+        replacements.insert(c.compute_txid(), find_replaced_tx_id(&wallet, &c).unwrap());
+        wallet.apply_unconfirmed_txs([(c.clone(), 1_700_000_300)]);
+
+        let chain = [a.compute_txid(), b.compute_txid(), c.compute_txid()];
+        assert_eq!(replacements.get(&chain[1]), Some(&chain[0]));
+        assert_eq!(replacements.get(&chain[2]), Some(&chain[1]));
+
+        let unrelated =
+            (Txid::from_str(&"01".repeat(32)).unwrap(), Txid::from_str(&"02".repeat(32)).unwrap());
+        replacements.insert(unrelated.0, unrelated.1);
+
+        // whichever version confirms, the other two never will
+        for confirmed in chain {
+            let (replaced, remaining) = split_replacement_chain(&replacements, confirmed);
+
+            let mut expected =
+                chain.into_iter().filter(|tx_id| *tx_id != confirmed).collect::<Vec<_>>();
+            expected.sort();
+
+            assert_eq!(replaced, expected);
+            assert_eq!(remaining, BTreeMap::from([unrelated]));
+        }
+
+        let (replaced, remaining) = split_replacement_chain(&replacements, unrelated.1);
+        assert_eq!(replaced, vec![unrelated.0]);
+        assert_eq!(remaining.len(), 2);
+
+        let confirmed = Txid::from_str(&"03".repeat(32)).unwrap();
+        let (replaced, remaining) = split_replacement_chain(&replacements, confirmed);
+        assert!(replaced.is_empty());
+        assert_eq!(remaining, replacements);
+    }
+
+    /// Address outside the wallet, `byte` makes it unique
+    fn external_address(byte: u8) -> Address {
+        let script = ScriptBuf::from_hex(&format!("0014{}", format!("{byte:02x}").repeat(20)));
+        let address = bitcoin::Address::from_script(&script.unwrap(), Network::Bitcoin).unwrap();
+        Address::new(address)
+    }
+
+    fn sats(sats: u64) -> cove_types::amount::Amount {
+        cove_types::amount::Amount::from_sat(sats)
+    }
+
+    fn batch(
+        wallet: &mut BdkWallet,
+        recipients: Vec<Recipient>,
+        frozen: HashSet<OutPoint>,
+    ) -> Result<Psbt, Error> {
+        let options = TxBuilderOptions::default();
+        build_batch_tx(wallet, LargestFirstCoinSelection, recipients, rate(2), options, frozen)
+    }
+
+    fn pays(psbt: &Psbt, address: &Address, sats: u64) -> bool {
+        let payment = bitcoin::TxOut {
+            value: Amount::from_sat(sats),
+            script_pubkey: address.script_pubkey(),
+        };
+
+        psbt.unsigned_tx.output.contains(&payment)
+    }
+
+    #[test]
+    fn test_batch_pays_every_recipient() {
+        let mut wallet = wallet();
+        receive(&mut wallet, 100_000);
+        receive(&mut wallet, 50_000);
+
+        let recipients = vec![
+            Recipient::new(external_address(1), sats(20_000)),
+            Recipient::new(external_address(2), sats(30_000)),
+            Recipient::new(external_address(3), sats(40_000)),
+        ];
+
+        let psbt = batch(&mut wallet, recipients.clone(), HashSet::default()).unwrap();
+        for recipient in &recipients {
+            let amount = recipient.amount().unwrap().as_sats();
+            assert!(pays(&psbt, &recipient.address, amount));
+        }
+
+        // one transaction with a single change output back to the wallet
+        assert_eq!(psbt.unsigned_tx.output.len(), recipients.len() + 1);
+        let change = psbt
+            .unsigned_tx
+            .output
+            .iter()
+            .filter(|output| wallet.is_mine(output.script_pubkey.clone()));
+        assert_eq!(change.count(), 1);
+    }
+
+    #[test]
+    fn test_batch_max_recipient_gets_the_rest() {
+        let mut wallet = wallet();
+        let spendable = receive(&mut wallet, 100_000);
+        let frozen = receive(&mut wallet, 50_000);
+
+        let recipients = vec![
+            Recipient::new(external_address(1), sats(20_000)),
+            Recipient::max(external_address(2)),
+        ];
+
+        let frozen = HashSet::from_iter([OutPoint::new(frozen.compute_txid(), 0)]);
+        let psbt = batch(&mut wallet, recipients, frozen).unwrap();
+        assert_eq!(inputs(&psbt.unsigned_tx), vec![OutPoint::new(spendable.compute_txid(), 0)]);
+
+        // no change, the max recipient gets everything after the other recipient and the fee
+        let fee = psbt.fee().unwrap().to_sat();
+        assert_eq!(psbt.unsigned_tx.output.len(), 2);
+        assert!(pays(&psbt, &external_address(1), 20_000));
+        assert!(pays(&psbt, &external_address(2), 100_000 - 20_000 - fee));
+    }
+}
@@ -0,0 +1,503 @@
+//! Sign and verify messages to prove ownership of an address, for exchanges and auditors
+//!
+//! Legacy `signmessage` (BIP137) for P2PKH and P2SH-P2WPKH, BIP322 simple and full for the rest.
+//! Cold wallets sign the BIP322 `to_sign` transaction as a normal PSBT
+
+use std::sync::Arc;
+
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use bdk_wallet::{
+    KeychainKind, SignOptions,
+    bitcoin::{
+        Address as BdkAddress, AddressType, Amount, CompressedPublicKey, Network, OutPoint,
+        PrivateKey, Psbt as BdkPsbt, PublicKey, ScriptBuf, Sequence, Transaction, TxIn, TxOut,
+        Witness,
+        absolute::LockTime,
+        bip32::ChildNumber,
+        consensus,
+        hashes::{Hash as _, HashEngine as _, sha256},
+        opcodes::{OP_0, all::OP_RETURN},
+        secp256k1::{
+            Message, Secp256k1,
+            ecdsa::{RecoverableSignature, RecoveryId},
+        },
+        sighash::Prevouts,
+        sign_message::signed_msg_hash,
+        transaction::Version,
+    },
+    descriptor::{DescriptorType, ExtendedDescriptor},
+    keys::DescriptorSecretKey,
+    miniscript::{DefiniteDescriptorKey, Descriptor, interpreter::Interpreter, psbt::PsbtExt as _},
+};
+use cove_types::address::Address;
+use tracing::debug;
+
+use crate::{keys::Descriptors, psbt::Psbt};
+
+type DefiniteDescriptor = Descriptor<DefiniteDescriptorKey>;
+
+const BIP322_TAG: &[u8] = b"BIP0322-signed-message";
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, uniffi::Enum)]
+pub enum MessageSignatureFormat {
+    Legacy,
+    Bip322Simple,
+    Bip322Full,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Error, thiserror::Error)]
+pub enum MessageSigningError {
+    #[error("{format:?} signatures are not supported for this address type")]
+    UnsupportedFormat { format: MessageSignatureFormat },
+
+    #[error("address is not derived by this wallet")]
+    NotOurAddress,
+
+    #[error("invalid signature: {0}")]
+    InvalidSignature(String),
+
+    #[error("unable to sign message: {0}")]
+    Signing(String),
+}
+
+type Error = MessageSigningError;
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl MessageSignatureFormat {
+    /// Legacy where wallets expect it, BIP322 simple otherwise
+    pub fn default_for(descriptor_type: DescriptorType) -> Self {
+        match descriptor_type {
+            DescriptorType::Pkh | DescriptorType::ShWpkh => Self::Legacy,
+            _ => Self::Bip322Simple,
+        }
+    }
+
+    fn check_supported(self, descriptor_type: DescriptorType) -> Result<()> {
+        let supported = match self {
+            Self::Legacy => matches!(descriptor_type, DescriptorType::Pkh | DescriptorType::ShWpkh),
+            // simple is only the witness, anything with a script sig needs full
+            Self::Bip322Simple => {
+                descriptor_type.segwit_version().is_some() && !is_wrapped(descriptor_type)
+            }
+            Self::Bip322Full => true,
+        };
+
+        match supported {
+            true => Ok(()),
+            false => Err(Error::UnsupportedFormat { format: self }),
+        }
+    }
+}
+
+fn is_wrapped(descriptor_type: DescriptorType) -> bool {
+    matches!(
+        descriptor_type,
+        DescriptorType::ShWpkh | DescriptorType::ShWsh | DescriptorType::ShWshSortedMulti
+    )
+}
+
+// MARK: BIP322
+
+/// Tagged hash of the message, committed to in the `to_spend` script sig
+pub fn message_hash(message: &str) -> sha256::Hash {
+    let tag = sha256::Hash::hash(BIP322_TAG);
+
+    let mut engine = sha256::Hash::engine();
+    engine.input(tag.as_ref());
+    engine.input(tag.as_ref());
+    engine.input(message.as_bytes());
+
+    sha256::Hash::from_engine(engine)
+}
+
+/// The virtual transaction paying to the address, committing to the message
+pub fn to_spend(script_pubkey: &ScriptBuf, message: &str) -> Transaction {
+    let hash = message_hash(message).to_byte_array();
+    let script_sig = ScriptBuf::builder().push_opcode(OP_0).push_slice(hash).into_script();
+
+    Transaction {
+        version: Version(0),
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::null(),
+            script_sig,
+            sequence: Sequence::ZERO,
+            witness: Witness::new(),
+        }],
+        output: vec![TxOut { value: Amount::ZERO, script_pubkey: script_pubkey.clone() }],
+    }
+}
+
+/// The virtual transaction spending `to_spend`, this is what actually gets signed
+pub fn to_sign(to_spend: &Transaction) -> Transaction {
+    Transaction {
+        version: Version(0),
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint { txid: to_spend.compute_txid(), vout: 0 },
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::ZERO,
+            witness: Witness::new(),
+        }],
+        output: vec![TxOut {
+            value: Amount::ZERO,
+            script_pubkey: ScriptBuf::builder().push_opcode(OP_RETURN).into_script(),
+        }],
+    }
+}
+
+/// `to_sign` as a PSBT with the key origins filled in, for hardware wallets
+pub fn to_sign_psbt(descriptor: &DefiniteDescriptor, message: &str) -> Result<BdkPsbt> {
+    let to_spend = to_spend(&descriptor.script_pubkey(), message);
+    let mut psbt = BdkPsbt::from_unsigned_tx(to_sign(&to_spend))
+        .map_err(|error| Error::Signing(error.to_string()))?;
+
+    psbt.inputs[0].witness_utxo = Some(to_spend.output[0].clone());
+    if descriptor.desc_type() != DescriptorType::Tr {
+        psbt.inputs[0].non_witness_utxo = Some(to_spend);
+    }
+
+    psbt.update_input_with_descriptor(0, descriptor)
+        .map_err(|error| Error::Signing(error.to_string()))?;
+
+    Ok(psbt)
+}
+
+/// Finalize a signed `to_sign` PSBT and encode it as a BIP322 signature
+pub fn signature_from_psbt(psbt: BdkPsbt, format: MessageSignatureFormat) -> Result<String> {
+    let secp = Secp256k1::verification_only();
+    let psbt = psbt.finalize(&secp).map_err(|(_, errors)| {
+        let errors = errors.iter().map(ToString::to_string).collect::<Vec<_>>();
+        Error::Signing(errors.join(", "))
+    })?;
+
+    let transaction = psbt.extract_tx_unchecked_fee_rate();
+
+    match format {
+        MessageSignatureFormat::Legacy => Err(Error::UnsupportedFormat { format }),
+        MessageSignatureFormat::Bip322Simple => {
+            Ok(BASE64.encode(consensus::serialize(&transaction.input[0].witness)))
+        }
+        MessageSignatureFormat::Bip322Full => Ok(BASE64.encode(consensus::serialize(&transaction))),
+    }
+}
+
+// MARK: legacy
+
+fn legacy_sign(key: &PrivateKey, descriptor_type: DescriptorType, message: &str) -> String {
+    let secp = Secp256k1::signing_only();
+    let digest = Message::from_digest(signed_msg_hash(message).to_byte_array());
+    let (recovery_id, signature) =
+        secp.sign_ecdsa_recoverable(&digest, &key.inner).serialize_compact();
+
+    // BIP137 header, 31 for compressed P2PKH and 35 for P2SH-P2WPKH
+    let base = match descriptor_type {
+        DescriptorType::ShWpkh => 35,
+        _ => 31,
+    };
+
+    let mut bytes = Vec::with_capacity(65);
+    bytes.push(base + recovery_id.to_i32() as u8);
+    bytes.extend_from_slice(&signature);
+
+    BASE64.encode(bytes)
+}
+
+fn legacy_verify(address: &BdkAddress, message: &str, bytes: &[u8]) -> Result<bool> {
+    let invalid = |error: &dyn std::fmt::Display| Error::InvalidSignature(error.to_string());
+
+    let header = bytes[0];
+    let recovery_id =
+        RecoveryId::from_i32(i32::from((header - 27) % 4)).map_err(|e| invalid(&e))?;
+    let signature =
+        RecoverableSignature::from_compact(&bytes[1..], recovery_id).map_err(|e| invalid(&e))?;
+
+    let digest = Message::from_digest(signed_msg_hash(message).to_byte_array());
+    let Ok(public_key) = Secp256k1::verification_only().recover_ecdsa(&digest, &signature) else {
+        return Ok(false);
+    };
+
+    // only the script is compared, so the network doesn't matter
+    let compressed = CompressedPublicKey(public_key);
+    let network = Network::Bitcoin;
+
+    let signed_by = match address.address_type() {
+        Some(AddressType::P2pkh) => {
+            let public_key = PublicKey { compressed: header >= 31, inner: public_key };
+            BdkAddress::p2pkh(public_key, network)
+        }
+        Some(AddressType::P2sh) => BdkAddress::p2shwpkh(&compressed, network),
+        Some(AddressType::P2wpkh) => BdkAddress::p2wpkh(&compressed, network),
+        _ => {
+            let format = MessageSignatureFormat::Legacy;
+            return Err(Error::UnsupportedFormat { format });
+        }
+    };
+
+    Ok(signed_by.script_pubkey() == address.script_pubkey())
+}
+
+// MARK: sign
+
+/// Sign with a hot wallet, checks the address really is at `index` before signing, without a
+/// format it picks the one wallets expect for the address type
+pub fn sign(
+    descriptors: Descriptors,
+    network: Network,
+    keychain: KeychainKind,
+    index: u32,
+    address: &BdkAddress,
+    message: &str,
+    format: Option<MessageSignatureFormat>,
+) -> Result<String> {
+    let descriptor = match keychain {
+        KeychainKind::External => &descriptors.external,
+        KeychainKind::Internal => &descriptors.internal,
+    };
+
+    let definite = derive(&descriptor.extended_descriptor, index)?;
+    if definite.script_pubkey() != address.script_pubkey() {
+        return Err(Error::NotOurAddress);
+    }
+
+    let descriptor_type = definite.desc_type();
+    let format = format.unwrap_or_else(|| MessageSignatureFormat::default_for(descriptor_type));
+    format.check_supported(descriptor_type)?;
+    debug!("signing message with {format:?} for {descriptor_type:?}");
+
+    if format == MessageSignatureFormat::Legacy {
+        let key = private_key_at(descriptor.key_map.values().next(), index)?;
+        return Ok(legacy_sign(&key, descriptor_type, message));
+    }
+
+    let mut psbt = to_sign_psbt(&definite, message)?;
+    let wallet = descriptors
+        .into_create_params()
+        .network(network)
+        .create_wallet_no_persist()
+        .map_err(|error| Error::Signing(error.to_string()))?;
+
+    let options =
+        SignOptions { trust_witness_utxo: true, try_finalize: false, ..Default::default() };
+    wallet.sign(&mut psbt, options).map_err(|error| Error::Signing(error.to_string()))?;
+
+    signature_from_psbt(psbt, format)
+}
+
+pub fn derive(descriptor: &ExtendedDescriptor, index: u32) -> Result<DefiniteDescriptor> {
+    descriptor.at_derivation_index(index).map_err(|error| Error::Signing(error.to_string()))
+}
+
+fn private_key_at(secret: Option<&DescriptorSecretKey>, index: u32) -> Result<PrivateKey> {
+    let Some(DescriptorSecretKey::XPrv(xkey)) = secret else {
+        return Err(Error::Signing("no extended private key for this wallet".into()));
+    };
+
+    let child = ChildNumber::from_normal_idx(index).map_err(|e| Error::Signing(e.to_string()))?;
+    let path = xkey.derivation_path.child(child);
+    let derived = xkey
+        .xkey
+        .derive_priv(&Secp256k1::signing_only(), &path)
+        .map_err(|error| Error::Signing(error.to_string()))?;
+
+    Ok(derived.to_priv())
+}
+
+// MARK: verify
+
+/// Verify a legacy, BIP322 simple or BIP322 full signature for any address
+pub fn verify(address: &BdkAddress, message: &str, signature: &str) -> Result<bool> {
+    let bytes = BASE64
+        .decode(signature.trim())
+        .map_err(|error| Error::InvalidSignature(error.to_string()))?;
+
+    if bytes.len() == 65 && (27..=42).contains(&bytes[0]) {
+        return legacy_verify(address, message, &bytes);
+    }
+
+    let to_spend = to_spend(&address.script_pubkey(), message);
+    let expected = to_sign(&to_spend);
+
+    let to_sign = match consensus::deserialize::<Transaction>(&bytes) {
+        Ok(full) => {
+            let spends_to_spend = full.input.len() == 1
+                && full.input[0].previous_output == expected.input[0].previous_output;
+
+            if !spends_to_spend || full.output != expected.output {
+                return Ok(false);
+            }
+
+            full
+        }
+        Err(_) => {
+            let witness = consensus::deserialize::<Witness>(&bytes)
+                .map_err(|error| Error::InvalidSignature(error.to_string()))?;
+
+            let mut simple = expected;
+            simple.input[0].witness = witness;
+            simple
+        }
+    };
+
+    Ok(spends(&to_sign, &to_spend.output[0]))
+}
+
+/// Run the script interpreter, `to_sign` has to be a valid spend of the `to_spend` output
+fn spends(to_sign: &Transaction, prevout: &TxOut) -> bool {
+    let secp = Secp256k1::verification_only();
+    let input = &to_sign.input[0];
+
+    let interpreter = Interpreter::from_txdata(
+        &prevout.script_pubkey,
+        &input.script_sig,
+        &input.witness,
+        input.sequence,
+        to_sign.lock_time,
+    );
+
+    let Ok(interpreter) = interpreter else { return false };
+
+    let prevouts = [prevout.clone()];
+    let prevouts = Prevouts::All(&prevouts);
+
+    let constraints = interpreter.iter(&secp, to_sign, 0, &prevouts).collect::<Vec<_>>();
+    !constraints.is_empty() && constraints.iter().all(Result::is_ok)
+}
+
+// MARK: ffi
+
+#[uniffi::export]
+fn verify_message(address: Arc<Address>, message: String, signature: String) -> Result<bool> {
+    verify(&address, &message, &signature)
+}
+
+/// Signature from the `to_sign` PSBT a hardware wallet signed
+#[uniffi::export]
+fn message_signature_from_psbt(psbt: Arc<Psbt>, format: MessageSignatureFormat) -> Result<String> {
+    signature_from_psbt(Arc::unwrap_or_clone(psbt).0, format)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr as _;
+
+    use super::*;
+    use crate::{mnemonic::MnemonicExt as _, wallet::WalletAddressType};
+
+    const WORDS: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    fn descriptors(address_type: WalletAddressType) -> Descriptors {
+        let mnemonic = bip39::Mnemonic::from_str(WORDS).unwrap();
+        mnemonic.into_descriptors(None, Network::Bitcoin, address_type, 0)
+    }
+
+    fn address(descriptors: &Descriptors, index: u32) -> BdkAddress {
+        let definite = derive(&descriptors.external.extended_descriptor, index).unwrap();
+        definite.address(Network::Bitcoin).unwrap()
+    }
+
+    #[test]
+    fn test_message_hash() {
+        // BIP322 test vectors
+        let empty = "c90c269c4f8fcbe6880f72a721ddfbf1914268a794cbb21cfafee13770ae19f1";
+        let hello = "f0eb03b1a75ac6d9847f55c624a99169b5dccba2a31f5b23bea77ba270de0a7a";
+
+        assert_eq!(message_hash("").to_string(), empty);
+        assert_eq!(message_hash("Hello World").to_string(), hello);
+    }
+
+    #[test]
+    fn test_verify_bip322_vector() {
+        // BIP322 test vector, private key L3VFeEujGtevx9w18HD1fhRbCH67Az2dpCymeRE1SoPK6XQtaN2k
+        let address = BdkAddress::from_str("bc1q9vza2e8x573nczrlzms0wvx3gsqjx7vavgkx0l")
+            .unwrap()
+            .assume_checked();
+
+        let signature = "AkcwRAIgZRfIY3p7/DoVTty6YZbWS71bc5Vct9p9Fia83eRmw2QCICK/ENGfwLtptFluMGs2KsqoNSk89pO7F29zJLUx9a/sASECx/EgAxlkQpQ9hYjgGu6EBCPMVPwVIVJqO4XCsMvViHI=";
+
+        assert!(verify(&address, "Hello World", signature).unwrap());
+        assert!(!verify(&address, "Hello World!", signature).unwrap());
+    }
+
+    #[test]
+    fn test_legacy_vectors() {
+        // bitcoinjs-message vectors, BIP137 headers as written by Bitcoin Core `signmessage` for
+        // P2PKH and Electrum for P2SH-P2WPKH, the nonce is RFC6979 so signing reproduces them
+        let key =
+            PrivateKey::from_wif("L4rK1yDtCWekvXuE6oXD9jCYfFNV2cWRpVuPLBcCU2z8TrisoyY1").unwrap();
+        let message = "This is an example of a signed message.";
+
+        let cases = [
+            (
+                DescriptorType::Pkh,
+                "1F3sAm6ZtwLAUnj7d38pGFxtP3RVEvtsbV",
+                "H9L5yLFjti0QTHhPyFrZCT1V/MMnBtXKmoiKDZ78NDBjERki6ZTQZdSMCtkgoNmp17By9ItJr8o7ChX0XxY91nk=",
+            ),
+            (
+                DescriptorType::ShWpkh,
+                "3DnW8JGpPViEZdpqat8qky1zc26EKbXnmM",
+                "I9L5yLFjti0QTHhPyFrZCT1V/MMnBtXKmoiKDZ78NDBjERki6ZTQZdSMCtkgoNmp17By9ItJr8o7ChX0XxY91nk=",
+            ),
+        ];
+
+        for (descriptor_type, address, signature) in cases {
+            let address = BdkAddress::from_str(address).unwrap().assume_checked();
+
+            assert_eq!(legacy_sign(&key, descriptor_type, message), signature);
+            assert!(verify(&address, message, signature).unwrap(), "{descriptor_type:?}");
+            assert!(!verify(&address, "This is an example", signature).unwrap());
+        }
+
+        // the header says compressed, so it doesn't prove ownership of the uncompressed address
+        let other = BdkAddress::from_str("1HZwkjkeaoZfTSaJxDw6aKkxp45agDiEzN").unwrap();
+        assert!(!verify(&other.assume_checked(), message, cases[0].2).unwrap());
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        use MessageSignatureFormat as F;
+
+        let cases = [
+            (WalletAddressType::Legacy, F::Legacy),
+            (WalletAddressType::WrappedSegwit, F::Legacy),
+            (WalletAddressType::WrappedSegwit, F::Bip322Full),
+            (WalletAddressType::NativeSegwit, F::Bip322Simple),
+            (WalletAddressType::NativeSegwit, F::Bip322Full),
+            (WalletAddressType::Taproot, F::Bip322Simple),
+        ];
+
+        for (address_type, format) in cases {
+            let address = address(&descriptors(address_type), 3);
+            let signature = sign(
+                descriptors(address_type),
+                Network::Bitcoin,
+                KeychainKind::External,
+                3,
+                &address,
+                "proof of reserves",
+                Some(format),
+            )
+            .unwrap();
+
+            assert!(verify(&address, "proof of reserves", &signature).unwrap(), "{format:?}");
+            assert!(!verify(&address, "proof of reserve", &signature).unwrap(), "{format:?}");
+        }
+    }
+
+    #[test]
+    fn test_sign_errors() {
+        let descriptors = descriptors(WalletAddressType::NativeSegwit);
+        let address = address(&descriptors, 3);
+        let sign_at = |index, format| {
+            let descriptors = self::descriptors(WalletAddressType::NativeSegwit);
+            let keychain = KeychainKind::External;
+            sign(descriptors, Network::Bitcoin, keychain, index, &address, "hi", Some(format))
+        };
+
+        assert_eq!(sign_at(4, MessageSignatureFormat::Bip322Simple), Err(Error::NotOurAddress));
+        assert_eq!(
+            sign_at(3, MessageSignatureFormat::Legacy),
+            Err(Error::UnsupportedFormat { format: MessageSignatureFormat::Legacy })
+        );
+    }
+}
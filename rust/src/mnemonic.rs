@@ -61,6 +61,7 @@ pub trait MnemonicExt {
         passphrase: Option<String>,
         network: impl Into<cove_types::Network>,
         wallet_address_type: WalletAddressType,
+        account: u32,
     ) -> Descriptors;
 
     fn xpub(&self, network: Network) -> Xpub;
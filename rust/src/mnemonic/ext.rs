@@ -12,6 +12,7 @@ impl MnemonicExt for bip39::Mnemonic {
         passphrase: Option<String>,
         network: impl Into<cove_types::Network>,
         address_type: WalletAddressType,
+        account: u32,
     ) -> Descriptors {
         use crate::keys::{Descriptor, DescriptorSecretKey};
 
@@ -22,15 +23,27 @@ impl MnemonicExt for bip39::Mnemonic {
             WalletAddressType::NativeSegwit => Descriptor::new_bip84,
             WalletAddressType::WrappedSegwit => Descriptor::new_bip49,
             WalletAddressType::Legacy => Descriptor::new_bip44,
+            WalletAddressType::Taproot => Descriptor::new_bip86,
         };
 
-        let descriptor =
-            new_descriptor(&descriptor_secret_key, bdk_wallet::KeychainKind::External, network);
+        // taproot wallets keep their change in taproot too, so every spend is a key path spend
+        let new_change_descriptor = match address_type {
+            WalletAddressType::Taproot => Descriptor::new_bip86,
+            _ => Descriptor::new_bip84,
+        };
+
+        let descriptor = new_descriptor(
+            &descriptor_secret_key,
+            bdk_wallet::KeychainKind::External,
+            network,
+            account,
+        );
 
-        let change_descriptor = Descriptor::new_bip84(
+        let change_descriptor = new_change_descriptor(
             &descriptor_secret_key,
             bdk_wallet::KeychainKind::Internal,
             network,
+            account,
         );
 
         Descriptors { external: descriptor, internal: change_descriptor }
@@ -50,8 +63,9 @@ impl MnemonicExt for Mnemonic {
         passphrase: Option<String>,
         network: impl Into<cove_types::Network>,
         address_type: WalletAddressType,
+        account: u32,
     ) -> Descriptors {
-        self.0.into_descriptors(passphrase, network, address_type)
+        self.0.into_descriptors(passphrase, network, address_type, account)
     }
 
     fn xpub(&self, network: Network) -> Xpub {
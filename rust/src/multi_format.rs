@@ -1,12 +1,20 @@
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc};
+
+use bitcoin::OutPoint;
 
 use cove_nfc::message::NfcMessage;
 use tracing::{debug, warn};
 
 use crate::{
+    bsms::BsmsRecord,
     hardware_export::HardwareExport,
     mnemonic::ParseMnemonic as _,
+    multisig::MultisigExport,
+    policy::MiniscriptExport,
+    psbt::Psbt,
+    sweep::SweepKey,
     transaction::ffi::BitcoinTransaction,
+    ur::{UrError, UrPart, UrPayload},
     wallet::{AddressWithNetwork, address::AddressError},
 };
 
@@ -23,11 +31,17 @@ pub enum MultiFormat {
     HardwareExport(Arc<HardwareExport>),
     Mnemonic(Arc<crate::mnemonic::Mnemonic>),
     Transaction(Arc<crate::transaction::ffi::BitcoinTransaction>),
+    /// PSBT to sign or finish, from a coordinator or another device
+    Psbt(Arc<Psbt>),
     Bip329Labels(Arc<Bip329Labels>),
     /// TAPSIGNER has not been initialized yet
     TapSignerReady(Arc<cove_tap_card::TapSigner>),
     /// TAPSIGNER has not been initialized yet
     TapSignerUnused(Arc<cove_tap_card::TapSigner>),
+    /// WIF or raw hex private key, to sweep funds from
+    PrivateKey(Arc<SweepKey>),
+    /// BIP129 signer key record, possibly encrypted, for the multisig setup coordinator
+    BsmsRecord(Arc<BsmsRecord>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, uniffi::Error, thiserror::Error)]
@@ -38,14 +52,22 @@ pub enum MultiFormatError {
     #[error("Address is not supported for any network")]
     UnsupportedNetworkAddress,
 
+    #[error("Invalid payment URI: {0}")]
+    InvalidBip21(String),
+
     #[error(
         "Not a valid format, we only support addresses, SeedQr, mnemonic, descriptors and XPUBs"
     )]
     UnrecognizedFormat,
 
-    #[error("UR format not supported, please use a plain QR or a BBQr")]
+    #[error(
+        "UR type not supported, we support crypto-psbt, crypto-account, crypto-output, crypto-hdkey and bytes"
+    )]
     UrFormatNotSupported,
 
+    #[error("Invalid UR: {0}")]
+    InvalidUr(String),
+
     #[error("Invalid TapSigner {0}")]
     InvalidTapSigner(cove_tap_card::ffi::TapCardParseError),
 }
@@ -56,6 +78,11 @@ impl MultiFormat {
     pub fn try_from_data(data: &[u8]) -> Result<Self> {
         debug!("MultiFormat::try_from_data");
 
+        // try parsing a binary psbt
+        if let Ok(psbt) = Psbt::try_from_data(data) {
+            return Ok(Self::Psbt(Arc::new(psbt)));
+        }
+
         // try parsing a signed transaction
         if let Ok(txn) = BitcoinTransaction::try_from_data(data) {
             return Ok(Self::Transaction(Arc::new(txn)));
@@ -95,9 +122,31 @@ impl MultiFormat {
                 return Err(MultiFormatError::UnsupportedNetworkAddress);
             }
 
+            // a valid address but with parameters we can't honor, ex: unknown `req-` params
+            Err(AddressError::InvalidBip21(error)) => {
+                return Err(MultiFormatError::InvalidBip21(error));
+            }
+
             _ => {}
         }
 
+        // try to parse a multisig export (coldcard setup file, wsh(sortedmulti) descriptor...)
+        if let Ok(export) = MultisigExport::try_from_str(string) {
+            let hardware_export = HardwareExport::from(export);
+            return Ok(Self::HardwareExport(hardware_export.into()));
+        }
+
+        // try to parse a miniscript policy descriptor (timelocked recovery, liana...)
+        if let Ok(export) = MiniscriptExport::try_from_str(string) {
+            let hardware_export = HardwareExport::from(export);
+            return Ok(Self::HardwareExport(hardware_export.into()));
+        }
+
+        // BIP129 key record from a signer, descriptor records are parsed as a multisig export above
+        if let Ok(record) = BsmsRecord::try_from_plaintext(string) {
+            return Ok(Self::BsmsRecord(Arc::new(record)));
+        }
+
         // try to parse hardware export (xpub, json, descriptors...)
         if let Ok(format) = pubport::Format::try_new_from_str(string) {
             let hardware_export = HardwareExport::new(format);
@@ -115,18 +164,31 @@ impl MultiFormat {
             return Ok(Self::Mnemonic(Arc::new(mnemonic.into())));
         }
 
+        // try to parse a base64 or hex psbt
+        if let Ok(psbt) = Psbt::try_from_str(string) {
+            return Ok(Self::Psbt(Arc::new(psbt)));
+        }
+
         // try to parse a transaction
         if let Ok(txn) = BitcoinTransaction::try_from_str(string) {
             return Ok(Self::Transaction(Arc::new(txn)));
         }
 
-        if string.starts_with("UR:") || string.starts_with("ur:") {
-            return Err(MultiFormatError::UrFormatNotSupported);
+        // try to parse a WIF private key for sweeping, raw hex keys have to be entered on the
+        // sweep screen, any 32 byte hash would parse as one
+        if let Ok(key) = SweepKey::try_from_wif(string) {
+            return Ok(Self::PrivateKey(Arc::new(key)));
+        }
+
+        // single part UR, animated URs are joined by `MultiQr` first
+        if UrPart::is_ur(string) {
+            let payload = crate::ur::decode(string)?;
+            return Self::try_from_ur_payload(payload);
         }
 
         // try and parse bip329 labels
-        if let Ok(labels) = bip329::Labels::try_from_str(string) {
-            return Ok(Self::Bip329Labels(Arc::new(labels.into())));
+        if let Ok(labels) = Bip329Labels::try_from_str(string) {
+            return Ok(Self::Bip329Labels(Arc::new(labels)));
         }
 
         if string.contains("tapsigner.com/start") {
@@ -147,6 +209,27 @@ impl MultiFormat {
         warn!("could not parse string as MultiFormat: {string}");
         Err(MultiFormatError::UnrecognizedFormat)
     }
+
+    pub fn try_from_ur_payload(payload: UrPayload) -> Result<Self> {
+        match payload {
+            // fully signed, ready to broadcast, otherwise it still needs signatures
+            UrPayload::Psbt(psbt) => match crate::ur::finalized_transaction(&psbt) {
+                Ok(txn) => Ok(Self::Transaction(Arc::new(txn.into()))),
+                Err(_) => Ok(Self::Psbt(Arc::new(psbt.into()))),
+            },
+            UrPayload::Descriptor(descriptor) => Self::try_from_string(&descriptor),
+            UrPayload::Bytes(bytes) => StringOrData::new(bytes).try_into(),
+        }
+    }
+}
+
+impl From<UrError> for MultiFormatError {
+    fn from(error: UrError) -> Self {
+        match error {
+            UrError::UnsupportedType(_) => Self::UrFormatNotSupported,
+            error => Self::InvalidUr(error.to_string()),
+        }
+    }
 }
 
 impl StringOrData {
@@ -190,19 +273,35 @@ fn describe_multi_format_error(error: MultiFormatError) -> String {
     error.to_string()
 }
 
-#[derive(
-    Debug,
-    Clone,
-    PartialEq,
-    Eq,
-    uniffi::Object,
-    derive_more::Into,
-    derive_more::From,
-    derive_more::Deref,
-    derive_more::AsRef,
-)]
-
-pub struct Bip329Labels(pub bip329::Labels);
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Object)]
+pub struct Bip329Labels {
+    pub labels: bip329::Labels,
+
+    /// Outputs that set the `spendable` flag, `bip329` defaults a missing flag to spendable so
+    /// this is how we tell an output that was unfrozen from one that didn't say
+    pub spendable: BTreeMap<OutPoint, bool>,
+}
+
+impl Bip329Labels {
+    pub fn try_from_str(jsonl: &str) -> eyre::Result<Self> {
+        let labels = bip329::Labels::try_from_str(jsonl)?;
+        let spendable = jsonl.lines().filter_map(explicit_spendable).collect();
+
+        Ok(Self { labels, spendable })
+    }
+}
+
+fn explicit_spendable(line: &str) -> Option<(OutPoint, bool)> {
+    let value = serde_json::from_str::<serde_json::Value>(line.trim()).ok()?;
+    if value.get("type")?.as_str()? != "output" {
+        return None;
+    }
+
+    let outpoint = value.get("ref")?.as_str()?.parse().ok()?;
+    let spendable = value.get("spendable")?.as_bool()?;
+
+    Some((outpoint, spendable))
+}
 
 impl From<cove_tap_card::TapSigner> for MultiFormat {
     fn from(tap_signer: cove_tap_card::TapSigner) -> Self {
@@ -3,6 +3,7 @@ use std::sync::Arc;
 
 use bbqr::{
     continuous_join::{ContinuousJoinResult, ContinuousJoiner},
+    file_type::FileType,
     header::Header,
     join::Joined,
 };
@@ -11,8 +12,9 @@ use parking_lot::Mutex;
 
 use crate::{
     mnemonic::{ParseMnemonic as _, WordAccess as _},
-    multi_format::StringOrData,
+    multi_format::{MultiFormat, MultiFormatError, StringOrData},
     seed_qr::{SeedQr, SeedQrError},
+    ur::{UrDecoder, UrPart, UrPayload},
 };
 
 #[derive(uniffi::Object)]
@@ -21,6 +23,7 @@ pub enum MultiQr {
     SeedQr(SeedQr),
     Single(String),
     Bbqr(Header, Arc<Mutex<ContinuousJoiner>>),
+    Ur(Arc<Mutex<UrDecoder>>),
 }
 
 #[derive(Debug, uniffi::Object)]
@@ -29,6 +32,12 @@ pub struct BbqrJoinResult(ContinuousJoinResult);
 #[derive(Debug, uniffi::Object)]
 pub struct BbqrJoined(Joined);
 
+#[derive(Debug, uniffi::Object)]
+pub struct UrJoined {
+    ur_type: String,
+    payload: UrPayload,
+}
+
 type Error = MultiQrError;
 
 #[derive(Debug, thiserror::Error, uniffi::Error)]
@@ -59,6 +68,15 @@ pub enum MultiQrError {
 
     #[error("Invalid plain text seed QR")]
     InvalidPlainTextQr(String),
+
+    #[error("Cannot add binary data to UR")]
+    CannotAddBinaryDataToUr,
+
+    #[error("UR parts are added through handle_scan_result")]
+    CannotAddPartToUr,
+
+    #[error("UR error: {0}")]
+    InvalidUr(String),
 }
 
 #[derive(Debug, Clone, uniffi::Enum)]
@@ -67,6 +85,9 @@ pub enum MultiQrScanResult {
     Single(String),
     CompletedBBqr(Arc<BbqrJoined>),
     InProgressBBqr(u32),
+    CompletedUr(Arc<UrJoined>),
+    /// Parts left, an estimate since fountain coded parts can fill in more than one
+    InProgressUr(u32),
 }
 
 #[uniffi::export]
@@ -91,6 +112,14 @@ impl MultiQr {
             return Self::Bbqr(header, continuous_joiner);
         }
 
+        // try to parse a UR, single part URs are complete already
+        if UrPart::is_ur(&qr) {
+            let mut decoder = UrDecoder::new();
+            if decoder.receive(&qr).is_ok() {
+                return Self::Ur(Arc::new(Mutex::new(decoder)));
+            }
+        }
+
         // try to parse standard seed qr
         if let Ok(seed_qr) = SeedQr::try_from_str(&qr) {
             return Self::SeedQr(seed_qr);
@@ -132,11 +161,31 @@ impl MultiQr {
                 }
             }
 
+            (Self::Ur(decoder), R::String(qr)) => {
+                let mut decoder = decoder.lock();
+                decoder.receive(&qr).map_err(|e| MultiQrError::InvalidUr(e.to_string()))?;
+
+                match decoder.ur_type() {
+                    Some(ur_type) if decoder.is_complete() => {
+                        let payload = decoder
+                            .payload()
+                            .map_err(|e| MultiQrError::InvalidUr(e.to_string()))?;
+
+                        let joined = UrJoined { ur_type: ur_type.to_string(), payload };
+                        MultiQrScanResult::CompletedUr(Arc::new(joined))
+                    }
+
+                    _ => MultiQrScanResult::InProgressUr(decoder.parts_left() as u32),
+                }
+            }
+
             // errors
             (Self::Bbqr(_, _), StringOrData::Data(_vec)) => {
                 return Err(MultiQrError::CannotAddBinaryDataToBbqr);
             }
 
+            (Self::Ur(_), R::Data(_)) => return Err(MultiQrError::CannotAddBinaryDataToUr),
+
             (Self::Single(_), R::Data(_)) => return Err(MultiQrError::CannotAddPartToSingleQr),
         };
 
@@ -173,7 +222,9 @@ impl MultiQr {
                 Some(words)
             }
 
-            MultiQrScanResult::InProgressBBqr(_) => None,
+            MultiQrScanResult::CompletedUr(joined) => Some(joined.get_grouped_words(groups_of)?),
+
+            MultiQrScanResult::InProgressBBqr(_) | MultiQrScanResult::InProgressUr(_) => None,
         };
 
         Ok(words)
@@ -189,6 +240,11 @@ impl MultiQr {
         matches!(self, MultiQr::Bbqr(_, _))
     }
 
+    #[uniffi::method]
+    pub fn is_ur(&self) -> bool {
+        matches!(self, MultiQr::Ur(_))
+    }
+
     #[uniffi::method]
     pub fn add_part(&self, qr: String) -> Result<BbqrJoinResult, MultiQrError> {
         match self {
@@ -204,6 +260,7 @@ impl MultiQr {
             // error
             MultiQr::SeedQr(_) => Err(MultiQrError::CannotAddPartToSeedQr),
             MultiQr::Single(_) => Err(MultiQrError::CannotAddPartToSingleQr),
+            MultiQr::Ur(_) => Err(MultiQrError::CannotAddPartToUr),
         }
     }
 
@@ -213,6 +270,7 @@ impl MultiQr {
             MultiQr::Bbqr(header, _) => header.num_parts as u32,
             MultiQr::SeedQr(_) => 1,
             MultiQr::Single(_) => 1,
+            MultiQr::Ur(decoder) => decoder.lock().seq_len() as u32,
         }
     }
 }
@@ -225,6 +283,13 @@ impl BbqrJoinResult {
 
     pub fn final_result(&self) -> Result<String, MultiQrError> {
         match &self.0 {
+            // binary PSBTs as base64, so they parse as a `MultiFormat` string
+            ContinuousJoinResult::Complete(result) if result.file_type == FileType::Psbt => {
+                let psbt = crate::psbt::Psbt::try_from_data(&result.data)
+                    .map_err(|e| MultiQrError::ParseError(e.to_string()))?;
+
+                Ok(psbt.0.to_string())
+            }
             ContinuousJoinResult::Complete(result) => {
                 let data = result.data.clone();
                 let string = String::from_utf8(data).map_err(|_| MultiQrError::InvalidUtf8)?;
@@ -261,3 +326,50 @@ impl BbqrJoined {
         Ok(grouped)
     }
 }
+
+#[uniffi::export]
+impl UrJoined {
+    /// ex. "crypto-psbt", "crypto-account"
+    pub fn ur_type(&self) -> String {
+        self.ur_type.clone()
+    }
+
+    /// The payload decoded into the matching format
+    pub fn multi_format(&self) -> Result<MultiFormat, MultiFormatError> {
+        MultiFormat::try_from_ur_payload(self.payload.clone())
+    }
+
+    /// The payload as a string we can parse again, the descriptor for wallet exports and the
+    /// finalized transaction hex for signed PSBTs
+    pub fn final_result(&self) -> Result<String, MultiQrError> {
+        let string = match &self.payload {
+            UrPayload::Descriptor(descriptor) => descriptor.clone(),
+
+            // base64 if it still needs signatures
+            UrPayload::Psbt(psbt) => match crate::ur::finalized_transaction(psbt) {
+                Ok(txn) => bitcoin::consensus::encode::serialize_hex(&txn),
+                Err(_) => psbt.to_string(),
+            },
+
+            UrPayload::Bytes(bytes) => match str::from_utf8(bytes) {
+                Ok(string) => string.to_string(),
+                Err(_) => hex::encode(bytes),
+            },
+        };
+
+        Ok(string)
+    }
+
+    pub fn get_grouped_words(&self, chunks: u8) -> Result<Vec<Vec<String>>, Error> {
+        let UrPayload::Bytes(bytes) = &self.payload else {
+            return Err(MultiQrError::InvalidUr(format!("{} has no seed words", self.ur_type)));
+        };
+
+        let words_str = str::from_utf8(bytes).map_err(|_| MultiQrError::InvalidUtf8)?;
+        let mnemonic =
+            words_str.parse_mnemonic().map_err(|e| MultiQrError::ParseError(e.to_string()))?;
+
+        let words = mnemonic.words().map(ToString::to_string).collect::<Vec<_>>();
+        Ok(words.chunks(chunks as usize).map(|chunk| chunk.to_vec()).collect())
+    }
+}
@@ -0,0 +1,894 @@
+//! Multisig (`wsh(sortedmulti(...))`) watch-only wallets, imported from coordinator exports
+//! (Coldcard multisig setup files, Sparrow, Specter or plain descriptors)
+
+use std::{collections::BTreeSet, str::FromStr as _, sync::Arc};
+
+use bdk_wallet::{
+    bitcoin::{
+        NetworkKind, Psbt as BdkPsbt, base58,
+        bip32::{DerivationPath, Fingerprint as BdkFingerprint, Xpub},
+    },
+    descriptor::ExtendedDescriptor,
+    keys::DescriptorPublicKey,
+    miniscript::{
+        Descriptor,
+        descriptor::{DerivPaths, DescriptorMultiXKey, Wildcard, WshInner},
+    },
+};
+use tracing::debug;
+
+use crate::{keys::Descriptors, wallet::fingerprint::Fingerprint};
+
+/// Version bytes for the extended public keys we accept, SLIP-132 variants get converted to x/tpub
+const XPUB_VERSION: [u8; 4] = [0x04, 0x88, 0xb2, 0x1e];
+const TPUB_VERSION: [u8; 4] = [0x04, 0x35, 0x87, 0xcf];
+
+const MAINNET_VERSIONS: [[u8; 4]; 5] = [
+    XPUB_VERSION,
+    [0x04, 0x9d, 0x7c, 0xb2], // ypub
+    [0x04, 0xb2, 0x47, 0x46], // zpub
+    [0x02, 0x95, 0xb4, 0x3f], // Ypub
+    [0x02, 0xaa, 0x7e, 0xd3], // Zpub
+];
+
+const TESTNET_VERSIONS: [[u8; 4]; 5] = [
+    TPUB_VERSION,
+    [0x04, 0x4a, 0x52, 0x62], // upub
+    [0x04, 0x5f, 0x1c, 0xf6], // vpub
+    [0x02, 0x42, 0x89, 0xef], // Upub
+    [0x02, 0x57, 0x54, 0x83], // Vpub
+];
+
+/// Max number of cosigners allowed in a `sortedmulti`
+const MAX_COSIGNERS: usize = 20;
+
+/// Receive and change paths after the xpub when the export doesn't say, what coordinators use
+const DEFAULT_CHILD_PATH: &str = "<0;1>/*";
+
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Error, thiserror::Error)]
+pub enum MultisigError {
+    #[error("not a multisig export")]
+    NotMultisig,
+
+    #[error("unsupported multisig: {0}")]
+    Unsupported(String),
+
+    #[error("invalid descriptor: {0}")]
+    InvalidDescriptor(String),
+
+    #[error("invalid xpub: {0}")]
+    InvalidXpub(String),
+
+    #[error("invalid derivation path: {0}")]
+    InvalidDerivationPath(String),
+
+    #[error("invalid policy: {0}")]
+    InvalidPolicy(String),
+
+    #[error("cosigner {0} is missing its key origin (fingerprint and derivation path)")]
+    MissingKeyOrigin(String),
+
+    #[error("expected {expected} cosigners, found {found}")]
+    CosignerCountMismatch { expected: u32, found: u32 },
+
+    #[error("cosigner keys are from different networks")]
+    MixedNetworks,
+
+    #[error("the same key is used by more than one cosigner")]
+    DuplicateCosigner,
+
+    #[error("cosigner {0} doesn't have separate receive and change paths, like <0;1>/*")]
+    MissingChangePath(String),
+}
+
+type Error = MultisigError;
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// A single signer in a multisig wallet
+#[derive(
+    Debug, Clone, Hash, Eq, PartialEq, serde::Serialize, serde::Deserialize, uniffi::Record,
+)]
+pub struct Cosigner {
+    pub fingerprint: Arc<Fingerprint>,
+    pub xpub: String,
+    pub derivation_path: String,
+    /// Receive and change paths after the xpub, ex. `<0;1>/*`
+    #[serde(default = "default_child_path")]
+    pub child_path: String,
+}
+
+/// The `M of N` setup for a multisig wallet, saved in the wallet metadata
+#[derive(
+    Debug, Clone, Hash, Eq, PartialEq, serde::Serialize, serde::Deserialize, uniffi::Record,
+)]
+pub struct MultisigConfig {
+    pub threshold: u32,
+    pub cosigners: Vec<Cosigner>,
+}
+
+/// Parsed multisig export, ready to be imported as a wallet
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Object)]
+pub struct MultisigExport {
+    pub name: Option<String>,
+    pub config: MultisigConfig,
+    pub network: NetworkKind,
+    pub external: ExtendedDescriptor,
+    pub internal: ExtendedDescriptor,
+}
+
+/// How many of the required signatures a PSBT currently has
+#[derive(Debug, Clone, Hash, Eq, PartialEq, uniffi::Record)]
+pub struct MultisigSignatureStatus {
+    pub required: u32,
+    pub collected: u32,
+    /// Cosigners that have signed every input
+    pub signed_by: Vec<Arc<Fingerprint>>,
+    pub is_complete: bool,
+}
+
+// MARK: parsing
+
+impl MultisigExport {
+    pub fn try_from_str(string: &str) -> Result<Self> {
+        let string = string.trim();
+
+        // BIP129 descriptor record, the descriptor uses `/**` for the receive and change paths
+        if let Some(record) = string.strip_prefix("BSMS 1.0") {
+            let descriptor = record.trim().lines().next().ok_or(Error::NotMultisig)?;
+            return Self::try_from_descriptor(&descriptor.replace("/**", "/<0;1>/*"), None);
+        }
+
+        // specter and some coordinators export a json file with the descriptor in it
+        if string.starts_with('{') {
+            return Self::try_from_json(string);
+        }
+
+        // plain descriptor, possibly with the change descriptor on the next line
+        let mut lines = string.lines().map(str::trim).filter(|line| line.starts_with("wsh("));
+        if let Some(receive) = lines.next() {
+            return match lines.next() {
+                Some(change) => Self::try_from_receive_and_change(receive, change, None),
+                None => Self::try_from_descriptor(receive, None),
+            };
+        }
+
+        if string.lines().any(|line| line.trim_start().starts_with("sh(")) {
+            return Err(Error::Unsupported(
+                "only native segwit (wsh) multisig is supported".into(),
+            ));
+        }
+
+        Self::try_from_coldcard(string)
+    }
+
+    fn try_from_json(string: &str) -> Result<Self> {
+        let json: serde_json::Value =
+            serde_json::from_str(string).map_err(|_| Error::NotMultisig)?;
+
+        let descriptor =
+            json.get("descriptor").and_then(|d| d.as_str()).ok_or(Error::NotMultisig)?;
+        let name = ["label", "name"]
+            .iter()
+            .find_map(|key| json.get(key).and_then(|name| name.as_str()))
+            .map(ToString::to_string);
+
+        Self::try_from_descriptor(descriptor, name)
+    }
+
+    /// Descriptor with multipath keys, each key keeps its own receive and change paths
+    fn try_from_descriptor(descriptor: &str, name: Option<String>) -> Result<Self> {
+        let (threshold, keys) = sorted_multi_keys(descriptor)?;
+        let cosigners =
+            keys.iter().map(PartialCosigner::try_from_key).collect::<Result<Vec<_>>>()?;
+
+        Self::try_new(name, threshold, cosigners)
+    }
+
+    /// Separate receive and change descriptors, the keys are joined into multipath keys
+    fn try_from_receive_and_change(
+        receive: &str,
+        change: &str,
+        name: Option<String>,
+    ) -> Result<Self> {
+        let (threshold, receive_keys) = sorted_multi_keys(receive)?;
+        let (change_threshold, change_keys) = sorted_multi_keys(change)?;
+
+        if threshold != change_threshold || receive_keys.len() != change_keys.len() {
+            return Err(Error::InvalidDescriptor(
+                "change descriptor doesn't match the receive descriptor".into(),
+            ));
+        }
+
+        let cosigners = receive_keys
+            .into_iter()
+            .zip(change_keys)
+            .map(|(receive, change)| join_receive_and_change(receive, change))
+            .map(|key| PartialCosigner::try_from_key(&key?))
+            .collect::<Result<Vec<_>>>()?;
+
+        Self::try_new(name, threshold, cosigners)
+    }
+
+    /// Coldcard multisig setup file, also exported by Sparrow and Specter
+    ///
+    /// ```text
+    /// Name: My Multisig
+    /// Policy: 2 of 3
+    /// Derivation: m/48'/0'/0'/2'
+    /// Format: P2WSH
+    ///
+    /// 0F056943: xpub6E...
+    /// ```
+    fn try_from_coldcard(string: &str) -> Result<Self> {
+        let mut name = None;
+        let mut policy = None;
+        let mut derivation: Option<DerivationPath> = None;
+        let mut cosigners = Vec::new();
+
+        for line in string.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once(':') else { continue };
+            let (key, value) = (key.trim(), value.trim());
+
+            match key.to_ascii_lowercase().as_str() {
+                "name" => name = Some(value.to_string()),
+                "policy" => policy = Some(parse_policy(value)?),
+                "derivation" => derivation = Some(parse_derivation_path(value)?),
+                "format" => {
+                    if !value.eq_ignore_ascii_case("p2wsh") {
+                        return Err(Error::Unsupported(format!(
+                            "{value}, only P2WSH multisig is supported"
+                        )));
+                    }
+                }
+                _ if key.len() == 8 && key.chars().all(|c| c.is_ascii_hexdigit()) => {
+                    let fingerprint = BdkFingerprint::from_str(key)
+                        .map_err(|error| Error::InvalidXpub(error.to_string()))?;
+
+                    let path = derivation
+                        .clone()
+                        .ok_or_else(|| Error::MissingKeyOrigin(key.to_string()))?;
+
+                    let xpub = parse_xpub(value)?;
+                    let child_path = DEFAULT_CHILD_PATH.to_string();
+                    cosigners.push(PartialCosigner { fingerprint, path, xpub, child_path });
+                }
+                _ => {}
+            }
+        }
+
+        let Some((threshold, total)) = policy else {
+            return Err(Error::NotMultisig);
+        };
+
+        if total as usize != cosigners.len() {
+            return Err(Error::CosignerCountMismatch {
+                expected: total,
+                found: cosigners.len() as u32,
+            });
+        }
+
+        Self::try_new(name, threshold as usize, cosigners)
+    }
+
+    fn try_new(
+        name: Option<String>,
+        threshold: usize,
+        cosigners: Vec<PartialCosigner>,
+    ) -> Result<Self> {
+        debug!("multisig export {threshold} of {}", cosigners.len());
+
+        if cosigners.len() < 2 || cosigners.len() > MAX_COSIGNERS {
+            return Err(Error::InvalidPolicy(format!("{} cosigners", cosigners.len())));
+        }
+
+        if threshold == 0 || threshold > cosigners.len() {
+            return Err(Error::InvalidPolicy(format!("{threshold} of {}", cosigners.len())));
+        }
+
+        let network = cosigners[0].xpub.network;
+        if cosigners.iter().any(|cosigner| cosigner.xpub.network != network) {
+            return Err(Error::MixedNetworks);
+        }
+
+        let unique_keys = cosigners.iter().map(|c| c.xpub.public_key).collect::<BTreeSet<_>>();
+        if unique_keys.len() != cosigners.len() {
+            return Err(Error::DuplicateCosigner);
+        }
+
+        let config = MultisigConfig {
+            threshold: threshold as u32,
+            cosigners: cosigners.into_iter().map(Into::into).collect(),
+        };
+
+        let descriptors = config.descriptors()?;
+        let name = name.filter(|name| !name.is_empty());
+
+        Ok(Self {
+            name,
+            config,
+            network,
+            external: descriptors.external.extended_descriptor,
+            internal: descriptors.internal.extended_descriptor,
+        })
+    }
+
+    pub fn descriptors(&self) -> Descriptors {
+        Descriptors::new_from_public(self.external.clone(), self.internal.clone())
+    }
+}
+
+struct PartialCosigner {
+    fingerprint: BdkFingerprint,
+    path: DerivationPath,
+    xpub: Xpub,
+    child_path: String,
+}
+
+impl PartialCosigner {
+    fn try_from_key(key: &DescriptorPublicKey) -> Result<Self> {
+        let key = receive_and_change(key)?;
+        let (fingerprint, path) =
+            key.origin.clone().ok_or_else(|| Error::MissingKeyOrigin(key.xkey.to_string()))?;
+
+        // the key without its origin is `xpub/<0;1>/*`, keep everything after the xpub
+        let without_origin =
+            DescriptorPublicKey::MultiXPub(DescriptorMultiXKey { origin: None, ..key.clone() })
+                .to_string();
+
+        let child_path = without_origin
+            .strip_prefix(&format!("{}/", key.xkey))
+            .ok_or_else(|| Error::InvalidDescriptor(without_origin.clone()))?
+            .to_string();
+
+        Ok(Self { fingerprint, path, xpub: key.xkey, child_path })
+    }
+}
+
+impl From<PartialCosigner> for Cosigner {
+    fn from(cosigner: PartialCosigner) -> Self {
+        Self {
+            fingerprint: Arc::new(cosigner.fingerprint.into()),
+            xpub: cosigner.xpub.to_string(),
+            derivation_path: format!("m/{}", cosigner.path),
+            child_path: cosigner.child_path,
+        }
+    }
+}
+
+fn default_child_path() -> String {
+    DEFAULT_CHILD_PATH.to_string()
+}
+
+/// Threshold and keys of a `wsh(sortedmulti(...))` descriptor
+fn sorted_multi_keys(descriptor: &str) -> Result<(usize, Vec<DescriptorPublicKey>)> {
+    let descriptor = Descriptor::<DescriptorPublicKey>::from_str(descriptor)
+        .map_err(|error| Error::InvalidDescriptor(error.to_string()))?;
+
+    let Descriptor::Wsh(wsh) = &descriptor else {
+        return Err(Error::NotMultisig);
+    };
+
+    let WshInner::SortedMulti(sorted_multi) = wsh.as_inner() else {
+        return Err(Error::Unsupported("only sortedmulti descriptors are supported".into()));
+    };
+
+    Ok((sorted_multi.k(), sorted_multi.pks().to_vec()))
+}
+
+/// The key as long as it has exactly two different paths, the receive path then the change path
+fn receive_and_change(key: &DescriptorPublicKey) -> Result<&DescriptorMultiXKey<Xpub>> {
+    let key = match key {
+        DescriptorPublicKey::MultiXPub(key) => key,
+        DescriptorPublicKey::XPub(key) => {
+            return Err(Error::MissingChangePath(key.xkey.to_string()));
+        }
+        DescriptorPublicKey::Single(_) => {
+            return Err(Error::InvalidXpub("single keys are not supported".into()));
+        }
+    };
+
+    let paths = key.derivation_paths.paths();
+    if paths.len() != 2 || paths[0] == paths[1] || key.wildcard != Wildcard::Unhardened {
+        return Err(Error::MissingChangePath(key.xkey.to_string()));
+    }
+
+    Ok(key)
+}
+
+/// Same key from the receive and the change descriptor as one multipath key
+fn join_receive_and_change(
+    receive: DescriptorPublicKey,
+    change: DescriptorPublicKey,
+) -> Result<DescriptorPublicKey> {
+    let (DescriptorPublicKey::XPub(receive), DescriptorPublicKey::XPub(change)) = (receive, change)
+    else {
+        return Err(Error::InvalidDescriptor(
+            "separate receive and change descriptors need a single path per key".into(),
+        ));
+    };
+
+    if receive.origin != change.origin
+        || receive.xkey != change.xkey
+        || receive.wildcard != change.wildcard
+    {
+        return Err(Error::InvalidDescriptor(
+            "change descriptor doesn't match the receive descriptor".into(),
+        ));
+    }
+
+    let derivation_paths =
+        DerivPaths::new(vec![receive.derivation_path, change.derivation_path]).expect("two paths");
+
+    Ok(DescriptorPublicKey::MultiXPub(DescriptorMultiXKey {
+        origin: receive.origin,
+        xkey: receive.xkey,
+        derivation_paths,
+        wildcard: receive.wildcard,
+    }))
+}
+
+fn parse_policy(policy: &str) -> Result<(u32, u32)> {
+    let policy = policy.to_ascii_lowercase();
+    let (threshold, total) = policy
+        .split_once("of")
+        .or_else(|| policy.split_once('/'))
+        .ok_or_else(|| Error::InvalidPolicy(policy.clone()))?;
+
+    let parse = |n: &str| n.trim().parse::<u32>().map_err(|_| Error::InvalidPolicy(policy.clone()));
+    Ok((parse(threshold)?, parse(total)?))
+}
+
+fn parse_derivation_path(path: &str) -> Result<DerivationPath> {
+    DerivationPath::from_str(path).map_err(|error| Error::InvalidDerivationPath(error.to_string()))
+}
+
+/// Parse an xpub, converting any SLIP-132 (ypub, Zpub, Vpub...) variants into x/tpubs
+fn parse_xpub(xpub: &str) -> Result<Xpub> {
+    if let Ok(xpub) = Xpub::from_str(xpub) {
+        return Ok(xpub);
+    }
+
+    let mut data =
+        base58::decode_check(xpub).map_err(|error| Error::InvalidXpub(error.to_string()))?;
+
+    if data.len() != 78 {
+        return Err(Error::InvalidXpub(format!("invalid length {}", data.len())));
+    }
+
+    let version: [u8; 4] = data[..4].try_into().expect("checked length");
+    let new_version = if MAINNET_VERSIONS.contains(&version) {
+        XPUB_VERSION
+    } else if TESTNET_VERSIONS.contains(&version) {
+        TPUB_VERSION
+    } else {
+        return Err(Error::InvalidXpub("unknown version bytes".into()));
+    };
+
+    data[..4].copy_from_slice(&new_version);
+    Xpub::decode(&data).map_err(|error| Error::InvalidXpub(error.to_string()))
+}
+
+// MARK: config
+
+impl MultisigConfig {
+    /// Build the external and change `wsh(sortedmulti(...))` descriptors for this config, each key
+    /// uses its own receive and change paths
+    pub fn descriptors(&self) -> Result<Descriptors> {
+        let keys = self
+            .cosigners
+            .iter()
+            .map(|cosigner| {
+                let xkey = Xpub::from_str(&cosigner.xpub)
+                    .map_err(|error| Error::InvalidXpub(error.to_string()))?;
+
+                let path = parse_derivation_path(&cosigner.derivation_path)?;
+                let fingerprint: BdkFingerprint = **cosigner.fingerprint;
+
+                // parsing `xpub/<0;1>/*` checks the child path, the origin is added after
+                let key = format!("{xkey}/{}", cosigner.child_path);
+                let key = DescriptorPublicKey::from_str(&key)
+                    .map_err(|error| Error::InvalidDescriptor(error.to_string()))?;
+
+                let key = receive_and_change(&key)?;
+                Ok(DescriptorPublicKey::MultiXPub(DescriptorMultiXKey {
+                    origin: Some((fingerprint, path)),
+                    ..key.clone()
+                }))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let descriptor = Descriptor::new_wsh_sortedmulti(self.threshold as usize, keys)
+            .map_err(|error| Error::InvalidDescriptor(error.to_string()))?;
+
+        let mut descriptors = descriptor
+            .into_single_descriptors()
+            .map_err(|error| Error::InvalidDescriptor(error.to_string()))?
+            .into_iter();
+
+        let (Some(external), Some(internal)) = (descriptors.next(), descriptors.next()) else {
+            return Err(Error::InvalidDescriptor("missing change descriptor".into()));
+        };
+
+        Ok(Descriptors::new_from_public(external, internal))
+    }
+
+    pub fn fingerprints(&self) -> impl Iterator<Item = BdkFingerprint> + '_ {
+        self.cosigners.iter().map(|cosigner| **cosigner.fingerprint)
+    }
+
+    /// Signatures collected is the lowest number of cosigner signatures on any input, cosigners
+    /// don't have to sign every input as long as each input has enough signatures. Inputs that
+    /// are already finalized count as fully signed
+    pub fn signature_status(&self, psbt: &BdkPsbt) -> MultisigSignatureStatus {
+        let cosigners = self.fingerprints().collect::<BTreeSet<_>>();
+        let required = self.threshold;
+
+        let mut collected: Option<u32> = None;
+        let mut signed_by: Option<BTreeSet<BdkFingerprint>> = None;
+
+        for input in &psbt.inputs {
+            // segwit inputs finalize to a witness, anything a signer finalizes as legacy or
+            // wrapped segwit has a script sig too
+            if input.final_script_witness.is_some() || input.final_script_sig.is_some() {
+                continue;
+            }
+
+            let signers = input
+                .partial_sigs
+                .keys()
+                .filter_map(|public_key| input.bip32_derivation.get(&public_key.inner))
+                .map(|(fingerprint, _)| *fingerprint)
+                .filter(|fingerprint| cosigners.contains(fingerprint))
+                .collect::<BTreeSet<_>>();
+
+            let signatures = signers.len() as u32;
+            collected = Some(collected.map_or(signatures, |collected| collected.min(signatures)));
+
+            signed_by = match signed_by {
+                Some(signed_by) => Some(signed_by.intersection(&signers).copied().collect()),
+                None => Some(signers),
+            };
+        }
+
+        // every input is already finalized
+        let (Some(collected), Some(signed_by)) = (collected, signed_by) else {
+            return MultisigSignatureStatus {
+                required,
+                collected: required,
+                signed_by: vec![],
+                is_complete: true,
+            };
+        };
+
+        let signed_by = signed_by.into_iter().map(|f| Arc::new(Fingerprint::from(f))).collect();
+
+        MultisigSignatureStatus {
+            required,
+            collected,
+            signed_by,
+            is_complete: collected >= required,
+        }
+    }
+}
+
+// MARK: ffi
+
+#[uniffi::export]
+impl MultisigExport {
+    #[uniffi::method(name = "name")]
+    pub fn ffi_name(&self) -> Option<String> {
+        self.name.clone()
+    }
+
+    #[uniffi::method(name = "config")]
+    pub fn ffi_config(&self) -> MultisigConfig {
+        self.config.clone()
+    }
+
+    /// ex. "2 of 3"
+    pub fn policy(&self) -> String {
+        self.config.policy()
+    }
+}
+
+impl MultisigConfig {
+    pub fn policy(&self) -> String {
+        format!("{} of {}", self.threshold, self.cosigners.len())
+    }
+}
+
+#[uniffi::export]
+fn multisig_config_policy(config: MultisigConfig) -> String {
+    config.policy()
+}
+
+#[uniffi::export]
+fn describe_multisig_error(error: MultisigError) -> String {
+    error.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bdk_wallet::{KeychainKind, bitcoin::Network};
+
+    const FP1: &str = "73c5da0a";
+    const FP2: &str = "f245ae38";
+    const FP3: &str = "c5d87297";
+
+    const XPUB1: &str = "tpubDE2qaB2bR6mCJycd41cGvu4JFP2gXFTGyLb8nmhZcEqJY7JabfnPG6bXcuTekjpo6No3ZkjZYaPe7r7TBJJGR2mVRsC3hV2NHmEV2JwKFDs";
+    const XPUB2: &str = "tpubDE2qaB2bR6mCKJ2ztrMeDgejB6d7UZb6JKxKD5azHhvchQhYBmtuST8umbRZxm8kGhUmcLwaozs7NtXoU8udGoXoGpEJ2D1NpFcyozjELRe";
+    const XPUB3: &str = "tpubDE2qaB2bR6mCHNYhjcFvFRJKiCCc4Rp2MivEiunRhXgRb9ePVt5GKPKYmTmbtREWvCJQQTs6mX9LAgLKZhFSnLmWv6NW4AYJ8Fpb7JKgE71";
+
+    // same keys as above, in the SLIP-132 format coldcard uses for multisig
+    const VPUB1: &str = "Vpub5ktmZqbWyRH9mACusmWv8ssjJ7gPryeoukc4nh8Bt6A4JoaKnXzSxeUM9ARAnrncif6WKp2gyNEznG1UeGmx5c4fU4AJFYCA45nf54t8RFi";
+    const VPUB2: &str = "Vpub5ktmZqbWyRH9mUdHicGHRfUADqGppHndEjyFD11cZZFNU6yHNe6y911jHrP5zt6ZtynENQEiEniU3JRpw7PJwNpyK1CYaGBAaaB9rkYEPsw";
+    const VPUB3: &str = "Vpub5ktmZqbWyRH9jZ8zZNAZTQ7kkvrKQA1ZJ8wAiqD3yP1BMqv8gkHL1wCNHij7vYCLYUbsAXAECJzgq6EM2fj8Sv4gxHLkcDi5taNm9zfZPaY";
+
+    fn descriptor() -> String {
+        format!(
+            "wsh(sortedmulti(2,[{FP1}/48'/1'/0'/2']{XPUB1}/<0;1>/*,[{FP2}/48'/1'/0'/2']{XPUB2}/<0;1>/*,[{FP3}/48'/1'/0'/2']{XPUB3}/<0;1>/*))"
+        )
+    }
+
+    fn coldcard_file() -> String {
+        format!(
+            "# Coldcard Multisig setup file (created by Sparrow)\n#\nName: Test Multisig\nPolicy: 2 of 3\nDerivation: m/48'/1'/0'/2'\nFormat: P2WSH\n\n{}: {VPUB1}\n{}: {VPUB2}\n{}: {VPUB3}\n",
+            FP1.to_uppercase(),
+            FP2.to_uppercase(),
+            FP3.to_uppercase()
+        )
+    }
+
+    #[test]
+    fn test_parse_descriptor() {
+        let export = MultisigExport::try_from_str(&descriptor()).unwrap();
+
+        assert_eq!(export.config.threshold, 2);
+        assert_eq!(export.config.cosigners.len(), 3);
+        assert_eq!(export.config.cosigners[0].fingerprint.as_lowercase(), FP1);
+        assert_eq!(export.config.cosigners[0].derivation_path, "m/48'/1'/0'/2'");
+        assert_eq!(export.network, NetworkKind::Test);
+        assert_eq!(export.policy(), "2 of 3");
+
+        let external = export.external.to_string();
+        let internal = export.internal.to_string();
+        assert!(external.starts_with("wsh(sortedmulti(2,"));
+        assert!(external.contains(&format!("{XPUB1}/0/*")));
+        assert!(internal.contains(&format!("{XPUB1}/1/*")));
+    }
+
+    #[test]
+    fn test_coldcard_file_matches_descriptor() {
+        let from_descriptor = MultisigExport::try_from_str(&descriptor()).unwrap();
+        let from_file = MultisigExport::try_from_str(&coldcard_file()).unwrap();
+
+        assert_eq!(from_file.name.as_deref(), Some("Test Multisig"));
+        assert_eq!(from_file.config, from_descriptor.config);
+        assert_eq!(from_file.external, from_descriptor.external);
+        assert_eq!(from_file.internal, from_descriptor.internal);
+    }
+
+    #[test]
+    fn test_parse_specter_json() {
+        let json = serde_json::json!({
+            "label": "Specter Multisig",
+            "blockheight": 0,
+            "descriptor": descriptor(),
+        });
+
+        let export = MultisigExport::try_from_str(&json.to_string()).unwrap();
+        assert_eq!(export.name.as_deref(), Some("Specter Multisig"));
+        assert_eq!(export.config.cosigners.len(), 3);
+    }
+
+    #[test]
+    fn test_addresses_match_across_formats() {
+        let from_descriptor = MultisigExport::try_from_str(&descriptor()).unwrap();
+        let from_file = MultisigExport::try_from_str(&coldcard_file()).unwrap();
+
+        let address = |export: &MultisigExport| {
+            export
+                .external
+                .at_derivation_index(0)
+                .unwrap()
+                .address(Network::Testnet)
+                .unwrap()
+                .to_string()
+        };
+
+        assert!(address(&from_descriptor).starts_with("tb1q"));
+        assert_eq!(address(&from_descriptor), address(&from_file));
+    }
+
+    #[test]
+    fn test_rejects_bad_policy() {
+        let file = coldcard_file().replace("Policy: 2 of 3", "Policy: 4 of 3");
+        assert!(matches!(
+            MultisigExport::try_from_str(&file),
+            Err(MultisigError::CosignerCountMismatch { .. })
+        ));
+
+        let file = coldcard_file().replace("Policy: 2 of 3", "Policy: 0 of 3");
+        assert!(matches!(
+            MultisigExport::try_from_str(&file),
+            Err(MultisigError::InvalidPolicy(_))
+        ));
+
+        let file = coldcard_file().replace("Format: P2WSH", "Format: P2SH");
+        assert!(matches!(MultisigExport::try_from_str(&file), Err(MultisigError::Unsupported(_))));
+    }
+
+    #[test]
+    fn test_rejects_single_sig() {
+        let single = format!("wpkh([{FP1}/84'/1'/0']{XPUB1}/<0;1>/*)");
+        assert_eq!(MultisigExport::try_from_str(&single), Err(MultisigError::NotMultisig));
+    }
+
+    #[test]
+    fn test_signature_status() {
+        use bdk_wallet::bitcoin::{
+            PublicKey, ScriptBuf, Transaction, TxIn, Witness, absolute::LockTime,
+            bip32::ChildNumber, ecdsa, secp256k1::Secp256k1, transaction::Version,
+        };
+
+        let export = MultisigExport::try_from_str(&descriptor()).unwrap();
+        let secp = Secp256k1::new();
+
+        let tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn::default(), TxIn::default()],
+            output: vec![],
+        };
+
+        let mut psbt = BdkPsbt::from_unsigned_tx(tx).unwrap();
+
+        let status = export.config.signature_status(&psbt);
+        assert_eq!(status.required, 2);
+        assert_eq!(status.collected, 0);
+        assert!(!status.is_complete);
+
+        // derive each cosigners first receive key and fill in the derivations
+        let keys = export
+            .config
+            .cosigners
+            .iter()
+            .map(|cosigner| {
+                let xpub = Xpub::from_str(&cosigner.xpub).unwrap();
+                let child = xpub
+                    .derive_pub(&secp, &[ChildNumber::Normal { index: 0 }; 2])
+                    .unwrap()
+                    .public_key;
+
+                let path = parse_derivation_path(&cosigner.derivation_path).unwrap();
+                (child, (**cosigner.fingerprint, path))
+            })
+            .collect::<Vec<_>>();
+
+        for input in psbt.inputs.iter_mut() {
+            input.bip32_derivation = keys.iter().cloned().collect();
+        }
+
+        // dummy signature, the status only cares about who signed
+        let signature =
+            ecdsa::Signature::from_slice(&[0x30, 0x06, 0x02, 0x01, 0x01, 0x02, 0x01, 0x01, 0x01])
+                .unwrap();
+
+        // first cosigner signs both inputs, second only signs one
+        for input in psbt.inputs.iter_mut() {
+            input.partial_sigs.insert(PublicKey::new(keys[0].0), signature);
+        }
+        psbt.inputs[0].partial_sigs.insert(PublicKey::new(keys[1].0), signature);
+
+        let status = export.config.signature_status(&psbt);
+        assert_eq!(status.collected, 1);
+        assert_eq!(status.signed_by[0].as_lowercase(), FP1);
+        assert!(!status.is_complete);
+
+        psbt.inputs[1].partial_sigs.insert(PublicKey::new(keys[1].0), signature);
+
+        let status = export.config.signature_status(&psbt);
+        assert_eq!(status.collected, 2);
+        assert!(status.is_complete);
+
+        // different cosigners on each input, every input still has 2 of 3
+        psbt.inputs[1].partial_sigs.remove(&PublicKey::new(keys[0].0));
+        psbt.inputs[1].partial_sigs.insert(PublicKey::new(keys[2].0), signature);
+
+        let status = export.config.signature_status(&psbt);
+        assert_eq!(status.collected, 2);
+        assert_eq!(status.signed_by.len(), 1);
+        assert_eq!(status.signed_by[0].as_lowercase(), FP2);
+        assert!(status.is_complete);
+
+        // a finalized input has no partial signatures left, with either kind of final script
+        psbt.inputs[1].partial_sigs.clear();
+        psbt.inputs[1].final_script_sig = Some(ScriptBuf::from_bytes(vec![0x00]));
+
+        let status = export.config.signature_status(&psbt);
+        assert_eq!(status.collected, 2);
+        assert!(status.is_complete);
+
+        psbt.inputs[0].partial_sigs.clear();
+        psbt.inputs[0].final_script_witness = Some(Witness::from_slice(&[vec![0x00]]));
+
+        let status = export.config.signature_status(&psbt);
+        assert_eq!(status.collected, 2);
+        assert!(status.is_complete);
+    }
+
+    #[test]
+    fn test_keeps_child_paths() {
+        let descriptor = descriptor().replace("<0;1>", "<2;3>");
+        let export = MultisigExport::try_from_str(&descriptor).unwrap();
+
+        assert!(export.config.cosigners.iter().all(|cosigner| cosigner.child_path == "<2;3>/*"));
+        assert!(export.external.to_string().contains(&format!("{XPUB1}/2/*")));
+        assert!(export.internal.to_string().contains(&format!("{XPUB1}/3/*")));
+
+        // the saved config builds the same descriptors again
+        let descriptors = export.config.descriptors().unwrap();
+        assert_eq!(descriptors.external.extended_descriptor, export.external);
+        assert_eq!(descriptors.internal.extended_descriptor, export.internal);
+
+        // only one cosigner uses a different path
+        let descriptor = descriptor().replacen("<0;1>", "1/<0;1>", 1);
+        let export = MultisigExport::try_from_str(&descriptor).unwrap();
+        assert_eq!(export.config.cosigners[0].child_path, "1/<0;1>/*");
+        assert_eq!(export.config.cosigners[1].child_path, "<0;1>/*");
+        assert!(export.external.to_string().contains(&format!("{XPUB1}/1/0/*")));
+        assert!(export.internal.to_string().contains(&format!("{XPUB2}/1/*")));
+    }
+
+    #[test]
+    fn test_separate_change_descriptor() {
+        let receive = descriptor().replace("<0;1>", "0");
+        let change = descriptor().replace("<0;1>", "1");
+
+        let from_lines = MultisigExport::try_from_str(&format!("{receive}\n{change}\n")).unwrap();
+        let from_descriptor = MultisigExport::try_from_str(&descriptor()).unwrap();
+
+        assert_eq!(from_lines.config, from_descriptor.config);
+        assert_eq!(from_lines.external, from_descriptor.external);
+        assert_eq!(from_lines.internal, from_descriptor.internal);
+
+        // change keys have to be the receive keys
+        let change = change.replace(XPUB3, XPUB1);
+        assert!(MultisigExport::try_from_str(&format!("{receive}\n{change}")).is_err());
+    }
+
+    #[test]
+    fn test_rejects_descriptors_without_change() {
+        let receive_only = descriptor().replace("<0;1>", "0");
+        assert!(matches!(
+            MultisigExport::try_from_str(&receive_only),
+            Err(MultisigError::MissingChangePath(_))
+        ));
+
+        let three_paths = descriptor().replace("<0;1>", "<0;1;2>");
+        assert!(matches!(
+            MultisigExport::try_from_str(&three_paths),
+            Err(MultisigError::MissingChangePath(_))
+        ));
+
+        let hardened = descriptor().replace("/*", "/*'");
+        assert!(MultisigExport::try_from_str(&hardened).is_err());
+    }
+
+    #[test]
+    fn test_saved_config_without_child_path() {
+        let cosigner = serde_json::json!({
+            "fingerprint": FP1,
+            "xpub": XPUB1,
+            "derivation_path": "m/48'/1'/0'/2'",
+        });
+
+        let cosigner: Cosigner = serde_json::from_value(cosigner).unwrap();
+        assert_eq!(cosigner.child_path, "<0;1>/*");
+    }
+}
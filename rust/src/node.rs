@@ -2,7 +2,8 @@ pub mod client;
 pub mod client_builder;
 
 use crate::node_connect::{
-    BITCOIN_ELECTRUM, NodeSelection, SIGNET_ESPLORA, TESTNET_ESPLORA, TESTNET4_ESPLORA,
+    BITCOIN_ELECTRUM, NodeSelection, REGTEST_ELECTRUM, SIGNET_ESPLORA, TESTNET_ESPLORA,
+    TESTNET4_ESPLORA,
 };
 
 use client::NodeClient;
@@ -85,6 +86,16 @@ impl Node {
                     url: url.to_string(),
                 }
             }
+
+            Network::Regtest => {
+                let (name, url) = REGTEST_ELECTRUM[0];
+                Self {
+                    name: name.to_string(),
+                    network,
+                    api_type: ApiType::Electrum,
+                    url: url.to_string(),
+                }
+            }
         }
     }
 
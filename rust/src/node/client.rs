@@ -17,7 +17,7 @@ use bdk_wallet::{
         spk_client::{FullScanRequest, FullScanResponse},
     },
 };
-use bitcoin::{Transaction, Txid};
+use bitcoin::{OutPoint, ScriptBuf, Transaction, TxOut, Txid};
 use tracing::debug;
 
 use crate::node::Node;
@@ -79,6 +79,12 @@ pub enum Error {
 
     #[error("failed to get transaction: {0}")]
     ElectrumGetTransaction(electrum_client::Error),
+
+    #[error("failed to get utxos: {0}")]
+    EsploraUtxos(esplora_client::Error),
+
+    #[error("failed to get utxos: {0}")]
+    ElectrumUtxos(electrum_client::Error),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -222,6 +228,17 @@ impl NodeClient {
         }
     }
 
+    /// Unspent outputs for a script that is not part of any wallet, used when sweeping keys
+    pub async fn get_script_utxos(
+        &self,
+        script: ScriptBuf,
+    ) -> Result<Vec<(OutPoint, TxOut)>, Error> {
+        match self {
+            NodeClient::Esplora(client) => client.get_script_utxos(script).await,
+            NodeClient::Electrum(client) => client.get_script_utxos(script).await,
+        }
+    }
+
     pub async fn broadcast_transaction(&self, txn: Transaction) -> Result<Txid, Error> {
         match self {
             NodeClient::Esplora(client) => client.broadcast_transaction(txn).await,
@@ -13,7 +13,7 @@ use bdk_wallet::{
     KeychainKind,
     chain::spk_client::{FullScanRequest, FullScanResponse},
 };
-use bitcoin::{Transaction, Txid, consensus::Decodable};
+use bitcoin::{OutPoint, ScriptBuf, Transaction, TxOut, Txid, consensus::Decodable};
 use serde::Deserialize;
 use serde_json::Value;
 use tap::TapFallible as _;
@@ -269,6 +269,34 @@ impl ElectrumClient {
         Ok(tx_id)
     }
 
+    pub async fn get_script_utxos(
+        &self,
+        script: ScriptBuf,
+    ) -> Result<Vec<(OutPoint, TxOut)>, Error> {
+        let client = self.client.clone();
+        let unspent = crate::unblock::run_blocking({
+            let script = script.clone();
+            move || client.inner.script_list_unspent(&script)
+        })
+        .await
+        .map_err(Error::ElectrumUtxos)?;
+
+        let utxos = unspent
+            .into_iter()
+            .map(|utxo| {
+                let outpoint = OutPoint::new(utxo.tx_hash, utxo.tx_pos as u32);
+                let txout = TxOut {
+                    value: bitcoin::Amount::from_sat(utxo.value),
+                    script_pubkey: script.clone(),
+                };
+
+                (outpoint, txout)
+            })
+            .collect();
+
+        Ok(utxos)
+    }
+
     fn default_options() -> NodeClientOptions {
         NodeClientOptions { batch_size: ELECTRUM_BATCH_SIZE }
     }
@@ -1,5 +1,6 @@
 use std::sync::Arc;
 
+use ahash::{HashMap, HashSet};
 use bdk_esplora::{
     EsploraAsyncExt as _,
     esplora_client::{self, r#async::AsyncClient},
@@ -9,7 +10,7 @@ use bdk_wallet::chain::{
     spk_client::{FullScanRequest, FullScanResponse, SyncRequest, SyncResponse},
 };
 use bdk_wallet::{KeychainKind, chain::BlockId};
-use bitcoin::Txid;
+use bitcoin::{OutPoint, ScriptBuf, TxOut, Txid};
 use tap::TapFallible as _;
 use tracing::debug;
 
@@ -17,6 +18,8 @@ use crate::node::Node;
 
 use super::{ESPLORA_BATCH_SIZE, Error, NodeClientOptions};
 
+const ESPLORA_TXS_PER_PAGE: usize = 25;
+
 #[derive(Debug, Clone)]
 pub struct EsploraClient {
     client: Arc<AsyncClient>,
@@ -112,6 +115,59 @@ impl EsploraClient {
         Ok(txn.compute_txid())
     }
 
+    /// Unspent outputs paying to the script, built from the script's transaction history
+    pub async fn get_script_utxos(
+        &self,
+        script: ScriptBuf,
+    ) -> Result<Vec<(OutPoint, TxOut)>, Error> {
+        let mut txs = Vec::new();
+        let mut last_seen = None;
+
+        // esplora returns up to 25 confirmed transactions per page
+        loop {
+            let page = self
+                .client
+                .scripthash_txs(&script, last_seen)
+                .await
+                .map_err(Error::EsploraUtxos)?;
+
+            let confirmed_count = page.iter().filter(|tx| tx.status.confirmed).count();
+            last_seen = page.last().map(|tx| tx.txid);
+            txs.extend(page);
+
+            if confirmed_count < ESPLORA_TXS_PER_PAGE {
+                break;
+            }
+        }
+
+        let spent = txs
+            .iter()
+            .flat_map(|tx| tx.vin.iter())
+            .map(|vin| OutPoint::new(vin.txid, vin.vout))
+            .collect::<HashSet<_>>();
+
+        let utxos = txs
+            .iter()
+            .flat_map(|tx| {
+                tx.vout
+                    .iter()
+                    .enumerate()
+                    .map(|(vout, output)| (OutPoint::new(tx.txid, vout as u32), output))
+            })
+            .filter(|(outpoint, output)| output.scriptpubkey == script && !spent.contains(outpoint))
+            .map(|(outpoint, output)| {
+                let txout = TxOut {
+                    value: bitcoin::Amount::from_sat(output.value),
+                    script_pubkey: output.scriptpubkey.clone(),
+                };
+
+                (outpoint, txout)
+            })
+            .collect::<HashMap<_, _>>();
+
+        Ok(utxos.into_iter().collect())
+    }
+
     pub async fn check_address_for_txn(&self, address: Address) -> Result<bool, Error> {
         let stats = self.client.get_address_stats(&address).await.map_err(Error::EsploraAddress)?;
 
@@ -32,6 +32,11 @@ pub const TESTNET4_ELECTRUM: [(&str, &str); 1] =
 
 pub const SIGNET_ESPLORA: [(&str, &str); 1] = [("mutinynet", "https://mutinynet.com/api")];
 
+/// Local `bitcoind -regtest` stack, default ports for electrs and esplora
+pub const REGTEST_ELECTRUM: [(&str, &str); 1] = [("local electrs", "tcp://127.0.0.1:60401")];
+
+pub const REGTEST_ESPLORA: [(&str, &str); 1] = [("local esplora", "http://127.0.0.1:3002/")];
+
 #[derive(Debug, Clone, uniffi::Object)]
 pub struct NodeSelector {
     network: Network,
@@ -234,6 +239,21 @@ fn node_list(network: Network) -> Vec<Node> {
 
             nodes
         }
+
+        Network::Regtest => {
+            let mut nodes = REGTEST_ELECTRUM
+                .iter()
+                .map(|(name, url)| Node::new_electrum(name.to_string(), url.to_string(), network))
+                .collect::<Vec<Node>>();
+
+            nodes.extend(
+                REGTEST_ESPLORA.iter().map(|(name, url)| {
+                    Node::new_esplora(name.to_string(), url.to_string(), network)
+                }),
+            );
+
+            nodes
+        }
     }
 }
 
@@ -293,6 +313,7 @@ fn default_node_selection() -> NodeSelection {
         Network::Testnet => TESTNET_ESPLORA[0],
         Network::Signet => SIGNET_ESPLORA[0],
         Network::Testnet4 => TESTNET4_ESPLORA[0],
+        Network::Regtest => REGTEST_ESPLORA[0],
     };
 
     NodeSelection::Preset(Node::new_esplora(name.to_string(), url.to_string(), network))
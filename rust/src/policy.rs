@@ -0,0 +1,593 @@
+//! Miniscript policy wallets, ex. "key A, or key B after 6 months"
+//!
+//! Watch-only wallets for any `wsh(..)` or `tr(..)` descriptor with script paths, the spending
+//! paths come from BDK's policy tree and one is picked with `policy_path` when building a tx
+
+use std::{collections::BTreeMap, str::FromStr as _, sync::Arc};
+
+use bdk_wallet::{
+    bitcoin::{Amount as BdkAmount, NetworkKind, absolute, relative},
+    descriptor::{
+        ExtendedDescriptor,
+        policy::{PkOrF, Policy, SatisfiableItem},
+    },
+    keys::DescriptorPublicKey,
+};
+
+use cove_types::amount::Amount;
+
+use crate::{keys::Descriptors, wallet::fingerprint::Fingerprint};
+
+/// ~6 months of blocks, default wait before the recovery key can spend
+pub const SIX_MONTHS_IN_BLOCKS: u16 = 26_280;
+
+/// Average block interval, used to turn time based timelocks into a rough number of blocks
+const SECONDS_PER_BLOCK: u64 = 600;
+
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Error, thiserror::Error)]
+pub enum PolicyError {
+    #[error("not a miniscript descriptor")]
+    NotMiniscript,
+
+    #[error("invalid descriptor: {0}")]
+    InvalidDescriptor(String),
+
+    #[error("invalid key: {0}")]
+    InvalidKey(String),
+
+    #[error("descriptor keys are from different networks")]
+    MixedNetworks,
+
+    #[error("timelock must be between 1 and 65535 blocks")]
+    InvalidTimelock,
+
+    #[error("unable to get the wallet policy: {0}")]
+    Policy(String),
+
+    #[error("no spending path {0}, the policy has {1} paths")]
+    InvalidSpendingPath(u32, u32),
+}
+
+type Error = PolicyError;
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug, Clone, uniffi::Object)]
+pub struct MiniscriptExport {
+    pub name: Option<String>,
+    pub descriptors: Descriptors,
+    /// `None` when the descriptor only has single keys, no xpubs to tell the network from
+    pub network: Option<NetworkKind>,
+}
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, uniffi::Enum)]
+pub enum Timelock {
+    /// `older(n)`, the coins need `n` confirmations
+    RelativeBlocks(u16),
+    /// `older(n)` in 512 second units, stored as seconds
+    RelativeSeconds(u32),
+    /// `after(n)` with a block height
+    AbsoluteHeight(u32),
+    /// `after(n)` with a unix timestamp
+    AbsoluteTime(u32),
+}
+
+/// One way to spend from the wallet, a branch of the top level `or`
+#[derive(Debug, Clone, Hash, Eq, PartialEq, uniffi::Record)]
+pub struct SpendingPath {
+    /// Position in the top level policy, what gets passed to `policy_path`
+    pub index: u32,
+    /// ex. "key F245AE38 and 26280 blocks after confirmation"
+    pub description: String,
+    pub fingerprints: Vec<Arc<Fingerprint>>,
+    pub timelocks: Vec<Timelock>,
+    /// Relative timelocks are checked against the oldest coin in the wallet, so this is an upper
+    /// bound, newer coins might still be locked, see `spendable`
+    pub is_available: bool,
+    /// Estimated blocks until every timelock on the path is met, 0 when available
+    pub blocks_remaining: u32,
+    /// Total of the coins that can be spent on this path right now, relative timelocks are
+    /// checked against each coin's own confirmation
+    pub spendable: Arc<Amount>,
+}
+
+/// What the timelocks are checked against
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChainState {
+    pub height: u32,
+    /// Unix timestamp
+    pub now: u64,
+    /// Confirmation height and time of the oldest confirmed UTXO, relative timelocks start there
+    pub oldest_utxo: Option<(u32, u64)>,
+}
+
+// MARK: parsing
+
+impl MiniscriptExport {
+    pub fn try_from_str(string: &str) -> Result<Self> {
+        let string = string.trim();
+
+        // liana, sparrow and others export a json file with the descriptor in it
+        if string.starts_with('{') {
+            let json: serde_json::Value =
+                serde_json::from_str(string).map_err(|_| Error::NotMiniscript)?;
+
+            let descriptor =
+                json.get("descriptor").and_then(|d| d.as_str()).ok_or(Error::NotMiniscript)?;
+            let name = ["label", "name"]
+                .iter()
+                .find_map(|key| json.get(key).and_then(|name| name.as_str()))
+                .map(ToString::to_string);
+
+            return Self::try_from_descriptor(descriptor, name);
+        }
+
+        if !string.starts_with("wsh(") && !string.starts_with("tr(") {
+            return Err(Error::NotMiniscript);
+        }
+
+        Self::try_from_descriptor(string, None)
+    }
+
+    fn try_from_descriptor(descriptor: &str, name: Option<String>) -> Result<Self> {
+        let descriptors = Descriptors::try_from_miniscript(descriptor)
+            .map_err(|error| Error::InvalidDescriptor(error.to_string()))?;
+
+        let network = network_kind(&descriptors.external.extended_descriptor)?;
+        Ok(Self { name, descriptors, network })
+    }
+
+    pub fn descriptors(&self) -> Descriptors {
+        self.descriptors.clone()
+    }
+
+    /// Short name for the wallet when the export doesn't have one
+    pub fn default_name(&self) -> String {
+        match self.descriptors.external.extended_descriptor {
+            ExtendedDescriptor::Tr(_) => "Imported Taproot Policy".to_string(),
+            _ => "Imported Policy".to_string(),
+        }
+    }
+}
+
+fn network_kind(descriptor: &ExtendedDescriptor) -> Result<Option<NetworkKind>> {
+    let mut networks = Vec::new();
+    descriptor.for_each_key(|key| {
+        let network = match key {
+            DescriptorPublicKey::XPub(key) => key.xkey.network,
+            DescriptorPublicKey::MultiXPub(key) => key.xkey.network,
+            DescriptorPublicKey::Single(_) => return true,
+        };
+
+        if !networks.contains(&network) {
+            networks.push(network);
+        }
+
+        true
+    });
+
+    match networks.as_slice() {
+        [] => Ok(None),
+        [network] => Ok(Some(*network)),
+        _ => Err(Error::MixedNetworks),
+    }
+}
+
+/// `wsh(or_d(pk(primary),and_v(v:pk(recovery),older(blocks))))`
+///
+/// The primary key can always spend, the recovery key only once the coins are `blocks` old
+#[uniffi::export]
+pub fn timelocked_recovery_descriptor(
+    primary_key: String,
+    recovery_key: String,
+    blocks: u16,
+) -> Result<String> {
+    if blocks == 0 {
+        return Err(Error::InvalidTimelock);
+    }
+
+    let primary = wildcard_key(&primary_key)?;
+    let recovery = wildcard_key(&recovery_key)?;
+
+    let descriptor = format!("wsh(or_d(pk({primary}),and_v(v:pk({recovery}),older({blocks}))))");
+
+    let descriptor = ExtendedDescriptor::from_str(&descriptor)
+        .map_err(|error| Error::InvalidDescriptor(error.to_string()))?;
+
+    Ok(descriptor.to_string())
+}
+
+/// Add the receive and change branches to a bare `[fingerprint/path]xpub`
+fn wildcard_key(key: &str) -> Result<String> {
+    let key = key.trim();
+    let key = if key.ends_with("/*") { key.to_string() } else { format!("{key}/<0;1>/*") };
+
+    let has_origin = match DescriptorPublicKey::from_str(&key) {
+        Ok(DescriptorPublicKey::XPub(xpub)) => xpub.origin.is_some(),
+        Ok(DescriptorPublicKey::MultiXPub(xpub)) => xpub.origin.is_some(),
+        Ok(DescriptorPublicKey::Single(_)) => false,
+        Err(error) => return Err(Error::InvalidKey(error.to_string())),
+    };
+
+    if !has_origin {
+        return Err(Error::InvalidKey("expected an xpub with its key origin".to_string()));
+    }
+
+    Ok(key)
+}
+
+// MARK: spending paths
+
+/// The top level branches of the policy, a policy without an `or` at the top has a single path
+///
+/// `utxos` are the value and confirmation (height, time) of each unspent coin in the wallet
+pub fn spending_paths(
+    policy: &Policy,
+    chain: ChainState,
+    utxos: &[(BdkAmount, Option<(u32, u64)>)],
+) -> Vec<SpendingPath> {
+    match &policy.item {
+        SatisfiableItem::Thresh { items, threshold: 1 } if items.len() > 1 => items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| SpendingPath::new(index as u32, item, chain, utxos))
+            .collect(),
+
+        _ => vec![SpendingPath::new(0, policy, chain, utxos)],
+    }
+}
+
+/// The `policy_path` argument for BDK's tx builder, `None` when BDK can pick on its own
+pub fn policy_path(policy: &Policy, index: u32) -> Result<Option<BTreeMap<String, Vec<usize>>>> {
+    if !policy.requires_path() {
+        return Ok(None);
+    }
+
+    let SatisfiableItem::Thresh { items, .. } = &policy.item else {
+        return Err(Error::Policy("policy needs a path but has no branches".to_string()));
+    };
+
+    if index as usize >= items.len() {
+        return Err(Error::InvalidSpendingPath(index, items.len() as u32));
+    }
+
+    Ok(Some(BTreeMap::from([(policy.id.clone(), vec![index as usize])])))
+}
+
+impl SpendingPath {
+    fn new(
+        index: u32,
+        policy: &Policy,
+        chain: ChainState,
+        utxos: &[(BdkAmount, Option<(u32, u64)>)],
+    ) -> Self {
+        let mut fingerprints = Vec::new();
+        let mut timelocks = Vec::new();
+        collect(policy, &mut fingerprints, &mut timelocks);
+
+        // note: timelocks are treated as all being required, which is the case for the
+        // usual "key and timelock" branches
+        let blocks_remaining =
+            timelocks.iter().map(|timelock| timelock.blocks_remaining(chain)).max().unwrap_or(0);
+        let is_available = timelocks.iter().all(|timelock| timelock.is_met(chain));
+
+        let spendable = utxos
+            .iter()
+            .filter(|(_, confirmed)| {
+                let chain = ChainState { oldest_utxo: *confirmed, ..chain };
+                timelocks.iter().all(|timelock| timelock.is_met(chain))
+            })
+            .map(|(value, _)| *value)
+            .sum::<BdkAmount>();
+
+        Self {
+            index,
+            description: describe(policy),
+            fingerprints: fingerprints.into_iter().map(Arc::new).collect(),
+            timelocks,
+            is_available,
+            blocks_remaining,
+            spendable: Arc::new(spendable.into()),
+        }
+    }
+}
+
+fn collect(policy: &Policy, fingerprints: &mut Vec<Fingerprint>, timelocks: &mut Vec<Timelock>) {
+    let mut add_key = |key: &PkOrF| {
+        let PkOrF::Fingerprint(fingerprint) = key else { return };
+        let fingerprint = Fingerprint::from(*fingerprint);
+
+        if !fingerprints.contains(&fingerprint) {
+            fingerprints.push(fingerprint);
+        }
+    };
+
+    match &policy.item {
+        SatisfiableItem::EcdsaSignature(key) | SatisfiableItem::SchnorrSignature(key) => {
+            add_key(key)
+        }
+        SatisfiableItem::Multisig { keys, .. } => keys.iter().for_each(add_key),
+        SatisfiableItem::Thresh { items, .. } => {
+            items.iter().for_each(|item| collect(item, fingerprints, timelocks))
+        }
+        SatisfiableItem::RelativeTimelock { value } => timelocks.push((*value).into()),
+        SatisfiableItem::AbsoluteTimelock { value } => timelocks.push((*value).into()),
+        _ => {}
+    }
+}
+
+fn describe(policy: &Policy) -> String {
+    match &policy.item {
+        SatisfiableItem::EcdsaSignature(key) | SatisfiableItem::SchnorrSignature(key) => {
+            format!("key {}", describe_key(key))
+        }
+        SatisfiableItem::Multisig { keys, threshold } => {
+            let keys = keys.iter().map(describe_key).collect::<Vec<_>>();
+            format!("{threshold} of {} keys ({})", keys.len(), keys.join(", "))
+        }
+        SatisfiableItem::Thresh { items, threshold } => {
+            let items = items.iter().map(describe).collect::<Vec<_>>();
+            match *threshold {
+                threshold if threshold == items.len() => items.join(" and "),
+                1 => format!("({})", items.join(" or ")),
+                threshold => format!("{threshold} of ({})", items.join(", ")),
+            }
+        }
+        SatisfiableItem::RelativeTimelock { value } => Timelock::from(*value).to_string(),
+        SatisfiableItem::AbsoluteTimelock { value } => Timelock::from(*value).to_string(),
+        SatisfiableItem::Sha256Preimage { .. }
+        | SatisfiableItem::Hash256Preimage { .. }
+        | SatisfiableItem::Ripemd160Preimage { .. }
+        | SatisfiableItem::Hash160Preimage { .. } => "hash preimage".to_string(),
+    }
+}
+
+fn describe_key(key: &PkOrF) -> String {
+    match key {
+        PkOrF::Fingerprint(fingerprint) => Fingerprint::from(*fingerprint).as_uppercase(),
+        PkOrF::Pubkey(key) => key.to_string()[..8].to_string(),
+        PkOrF::XOnlyPubkey(key) => key.to_string()[..8].to_string(),
+    }
+}
+
+// MARK: timelocks
+
+impl Timelock {
+    fn is_met(&self, chain: ChainState) -> bool {
+        match self {
+            // relative timelocks need coins to have been confirmed first
+            Self::RelativeBlocks(_) | Self::RelativeSeconds(_) => {
+                chain.oldest_utxo.is_some() && self.blocks_remaining(chain) == 0
+            }
+            Self::AbsoluteHeight(_) | Self::AbsoluteTime(_) => self.blocks_remaining(chain) == 0,
+        }
+    }
+
+    /// Whether a coin confirmed at `confirmed` (height, time) can be spent with this timelock,
+    /// relative timelocks count from each coin's own confirmation
+    pub fn is_met_by_utxo(&self, chain: ChainState, confirmed: Option<(u32, u64)>) -> bool {
+        match self {
+            Self::RelativeBlocks(_) | Self::RelativeSeconds(_) => {
+                self.is_met(ChainState { oldest_utxo: confirmed, ..chain })
+            }
+            Self::AbsoluteHeight(_) | Self::AbsoluteTime(_) => true,
+        }
+    }
+
+    fn blocks_remaining(&self, chain: ChainState) -> u32 {
+        let blocks_for = |seconds: u64| seconds.div_ceil(SECONDS_PER_BLOCK) as u32;
+
+        match *self {
+            Self::RelativeBlocks(blocks) => {
+                let confirmations = chain
+                    .oldest_utxo
+                    .map(|(height, _)| chain.height.saturating_sub(height) + 1)
+                    .unwrap_or(0);
+
+                u32::from(blocks).saturating_sub(confirmations)
+            }
+            Self::RelativeSeconds(seconds) => {
+                let elapsed =
+                    chain.oldest_utxo.map(|(_, time)| chain.now.saturating_sub(time)).unwrap_or(0);
+
+                blocks_for(u64::from(seconds).saturating_sub(elapsed))
+            }
+            Self::AbsoluteHeight(height) => height.saturating_sub(chain.height),
+            Self::AbsoluteTime(time) => blocks_for(u64::from(time).saturating_sub(chain.now)),
+        }
+    }
+}
+
+impl std::fmt::Display for Timelock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RelativeBlocks(blocks) => write!(f, "{blocks} blocks after confirmation"),
+            Self::RelativeSeconds(seconds) if *seconds >= 86_400 => {
+                write!(f, "{} days after confirmation", seconds / 86_400)
+            }
+            Self::RelativeSeconds(seconds) => write!(f, "{seconds} seconds after confirmation"),
+            Self::AbsoluteHeight(height) => write!(f, "after block {height}"),
+            Self::AbsoluteTime(time) => match jiff::Timestamp::from_second(i64::from(*time)) {
+                Ok(timestamp) => write!(f, "after {}", timestamp.strftime("%Y-%m-%d")),
+                Err(_) => write!(f, "after {time}"),
+            },
+        }
+    }
+}
+
+impl From<relative::LockTime> for Timelock {
+    fn from(lock_time: relative::LockTime) -> Self {
+        match lock_time {
+            relative::LockTime::Blocks(height) => Self::RelativeBlocks(height.value()),
+            relative::LockTime::Time(time) => Self::RelativeSeconds(u32::from(time.value()) * 512),
+        }
+    }
+}
+
+impl From<absolute::LockTime> for Timelock {
+    fn from(lock_time: absolute::LockTime) -> Self {
+        match lock_time {
+            absolute::LockTime::Blocks(height) => Self::AbsoluteHeight(height.to_consensus_u32()),
+            absolute::LockTime::Seconds(time) => Self::AbsoluteTime(time.to_consensus_u32()),
+        }
+    }
+}
+
+// MARK: ffi
+
+#[uniffi::export]
+impl MiniscriptExport {
+    #[uniffi::method(name = "name")]
+    pub fn ffi_name(&self) -> Option<String> {
+        self.name.clone()
+    }
+
+    /// The receive descriptor, shown to the user before importing
+    pub fn descriptor(&self) -> String {
+        self.descriptors.external.extended_descriptor.to_string()
+    }
+}
+
+#[uniffi::export]
+fn timelock_to_string(timelock: Timelock) -> String {
+    timelock.to_string()
+}
+
+#[uniffi::export]
+fn six_months_in_blocks() -> u16 {
+    SIX_MONTHS_IN_BLOCKS
+}
+
+#[uniffi::export]
+fn describe_policy_error(error: PolicyError) -> String {
+    error.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bdk_wallet::KeychainKind;
+
+    const PRIMARY: &str = "[73c5da0a/48h/1h/0h/2h]tpubDE2qaB2bR6mCJycd41cGvu4JFP2gXFTGyLb8nmhZcEqJY7JabfnPG6bXcuTekjpo6No3ZkjZYaPe7r7TBJJGR2mVRsC3hV2NHmEV2JwKFDs";
+    const RECOVERY: &str = "[f245ae38/48h/1h/0h/2h]tpubDE2qaB2bR6mCKJ2ztrMeDgejB6d7UZb6JKxKD5azHhvchQhYBmtuST8umbRZxm8kGhUmcLwaozs7NtXoU8udGoXoGpEJ2D1NpFcyozjELRe";
+
+    fn policy() -> Policy {
+        let descriptor =
+            timelocked_recovery_descriptor(PRIMARY.into(), RECOVERY.into(), 144).unwrap();
+        let export = MiniscriptExport::try_from_str(&descriptor).unwrap();
+
+        let wallet = export
+            .descriptors()
+            .into_create_params()
+            .network(bdk_wallet::bitcoin::Network::Testnet)
+            .create_wallet_no_persist()
+            .unwrap();
+
+        wallet.policies(KeychainKind::External).unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_timelocked_recovery_descriptor() {
+        let descriptor =
+            timelocked_recovery_descriptor(PRIMARY.into(), RECOVERY.into(), 144).unwrap();
+        assert!(descriptor.starts_with("wsh(or_d(pk([73c5da0a/48'/1'/0'/2']"));
+        assert!(descriptor.contains("older(144)"));
+
+        let export = MiniscriptExport::try_from_str(&descriptor).unwrap();
+        assert_eq!(export.network, Some(NetworkKind::Test));
+        assert!(export.descriptors.is_miniscript());
+
+        assert_eq!(
+            timelocked_recovery_descriptor(PRIMARY.into(), RECOVERY.into(), 0),
+            Err(Error::InvalidTimelock)
+        );
+        assert!(timelocked_recovery_descriptor("not a key".into(), RECOVERY.into(), 144).is_err());
+    }
+
+    #[test]
+    fn test_parse_json_export() {
+        let descriptor =
+            timelocked_recovery_descriptor(PRIMARY.into(), RECOVERY.into(), 144).unwrap();
+        let json = serde_json::json!({ "name": "Inheritance", "descriptor": descriptor });
+
+        let export = MiniscriptExport::try_from_str(&json.to_string()).unwrap();
+        assert_eq!(export.name.as_deref(), Some("Inheritance"));
+
+        let wpkh = "wpkh([73c5da0a/84h/1h/0h]tpubDE2qaB2bR6mCJycd41cGvu4JFP2gXFTGyLb8nmhZcEqJY7JabfnPG6bXcuTekjpo6No3ZkjZYaPe7r7TBJJGR2mVRsC3hV2NHmEV2JwKFDs/<0;1>/*)";
+        assert_eq!(MiniscriptExport::try_from_str(wpkh).unwrap_err(), Error::NotMiniscript);
+    }
+
+    #[test]
+    fn test_spending_paths_availability() {
+        let policy = policy();
+        assert!(policy.requires_path());
+
+        // no coins yet, only the primary key can spend
+        let chain = ChainState { height: 1_000, now: 0, oldest_utxo: None };
+        let paths = spending_paths(&policy, chain, &[]);
+        assert_eq!(paths.len(), 2);
+
+        assert!(paths[0].is_available);
+        assert!(paths[0].timelocks.is_empty());
+        assert_eq!(paths[0].fingerprints[0].as_lowercase(), "73c5da0a");
+
+        assert!(!paths[1].is_available);
+        assert_eq!(paths[1].timelocks, vec![Timelock::RelativeBlocks(144)]);
+        assert_eq!(paths[1].blocks_remaining, 144);
+        assert_eq!(paths[1].description, "key F245AE38 and 144 blocks after confirmation");
+
+        // coins with 100 confirmations
+        let utxos = [(BdkAmount::from_sat(50_000), Some((1_000, 0)))];
+        let chain = ChainState { height: 1_099, now: 0, oldest_utxo: Some((1_000, 0)) };
+        let paths = spending_paths(&policy, chain, &utxos);
+        assert_eq!(paths[1].blocks_remaining, 44);
+        assert!(!paths[1].is_available);
+        assert_eq!(paths[1].spendable.as_sats(), 0);
+
+        let chain = ChainState { height: 1_143, now: 0, oldest_utxo: Some((1_000, 0)) };
+        assert!(spending_paths(&policy, chain, &utxos)[1].is_available);
+    }
+
+    #[test]
+    fn test_spendable_per_path() {
+        let policy = policy();
+
+        // one coin old enough for the recovery path, one newer coin and one unconfirmed coin
+        let utxos = [
+            (BdkAmount::from_sat(50_000), Some((1_000, 0))),
+            (BdkAmount::from_sat(20_000), Some((1_100, 0))),
+            (BdkAmount::from_sat(5_000), None),
+        ];
+
+        let chain = ChainState { height: 1_143, now: 0, oldest_utxo: Some((1_000, 0)) };
+        let paths = spending_paths(&policy, chain, &utxos);
+
+        assert_eq!(paths[0].spendable.as_sats(), 75_000);
+
+        // available going by the oldest coin, but only that coin can be spent
+        assert!(paths[1].is_available);
+        assert_eq!(paths[1].spendable.as_sats(), 50_000);
+    }
+
+    #[test]
+    fn test_relative_timelock_per_utxo() {
+        let timelock = Timelock::RelativeBlocks(144);
+        let chain = ChainState { height: 1_143, now: 0, oldest_utxo: None };
+
+        assert!(timelock.is_met_by_utxo(chain, Some((1_000, 0))));
+        assert!(!timelock.is_met_by_utxo(chain, Some((1_001, 0))));
+        assert!(!timelock.is_met_by_utxo(chain, None));
+
+        // absolute timelocks don't depend on the coin
+        assert!(Timelock::AbsoluteHeight(1_000).is_met_by_utxo(chain, None));
+    }
+
+    #[test]
+    fn test_policy_path() {
+        let policy = policy();
+
+        let path = policy_path(&policy, 1).unwrap().unwrap();
+        assert_eq!(path.get(&policy.id), Some(&vec![1]));
+
+        assert_eq!(policy_path(&policy, 2), Err(Error::InvalidSpendingPath(2, 2)));
+    }
+}
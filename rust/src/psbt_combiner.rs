@@ -0,0 +1,361 @@
+//! BIP174 combiner, merges the PSBTs signers send back for the same unsigned transaction
+//!
+//! Multisig cosigners and some hardware wallets only return their own partial signatures, so we
+//! collect them here, report how far along each input is, and finalize once every input is ready
+
+use std::sync::Arc;
+
+use bdk_wallet::{
+    bitcoin::{Psbt as BdkPsbt, PublicKey, Transaction, Txid, psbt::Input, secp256k1::Secp256k1},
+    miniscript::{Miniscript, Segwitv0, Terminal, psbt::PsbtExt as _},
+};
+use parking_lot::Mutex;
+use tracing::debug;
+
+use crate::{psbt::Psbt, transaction::ffi::BitcoinTransaction};
+
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Error, thiserror::Error)]
+pub enum PsbtCombineError {
+    #[error("no PSBTs to combine")]
+    Empty,
+
+    #[error("PSBT {index} is for transaction {actual}, expected {expected}")]
+    UnrelatedPsbt { index: u32, expected: String, actual: String },
+
+    #[error("unable to combine PSBT {index}: {error}")]
+    Combine { index: u32, error: String },
+
+    #[error("{ready} of {total} inputs are ready to finalize, signatures are still missing")]
+    Incomplete { ready: u32, total: u32 },
+
+    #[error("unable to finalize PSBT: {0}")]
+    Finalize(String),
+
+    #[error("unable to extract transaction: {0}")]
+    Extract(String),
+}
+
+type Error = PsbtCombineError;
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, uniffi::Record)]
+pub struct InputSignatureProgress {
+    pub index: u32,
+    pub signatures: u32,
+    /// Signatures needed, from the witness script for multisig, otherwise 1
+    pub required: u32,
+    pub is_finalized: bool,
+    /// Already finalized, or has enough valid signatures to finalize
+    pub is_ready: bool,
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, uniffi::Record)]
+pub struct PsbtCombineProgress {
+    pub inputs: Vec<InputSignatureProgress>,
+    pub ready_inputs: u32,
+    pub is_complete: bool,
+}
+
+/// Combine PSBTs for the same unsigned transaction, the first one sets the transaction
+pub fn combine(psbts: impl IntoIterator<Item = BdkPsbt>) -> Result<BdkPsbt> {
+    let mut psbts = psbts.into_iter();
+    let mut combined = psbts.next().ok_or(Error::Empty)?;
+
+    for (index, psbt) in psbts.enumerate() {
+        merge(&mut combined, psbt, index as u32 + 1)?;
+    }
+
+    Ok(combined)
+}
+
+/// The unsigned transactions must be identical, checking the txid first gives a useful error
+/// instead of the generic one from the combiner
+fn merge(combined: &mut BdkPsbt, psbt: BdkPsbt, index: u32) -> Result<()> {
+    let expected = combined.unsigned_tx.compute_txid();
+    let actual = psbt.unsigned_tx.compute_txid();
+
+    if expected != actual {
+        return Err(unrelated(index, expected, actual));
+    }
+
+    combined.combine(psbt).map_err(|error| Error::Combine { index, error: error.to_string() })
+}
+
+fn unrelated(index: u32, expected: Txid, actual: Txid) -> Error {
+    Error::UnrelatedPsbt { index, expected: expected.to_string(), actual: actual.to_string() }
+}
+
+pub fn progress(psbt: &BdkPsbt) -> PsbtCombineProgress {
+    let secp = Secp256k1::verification_only();
+
+    let inputs = psbt
+        .inputs
+        .iter()
+        .enumerate()
+        .map(|(index, input)| {
+            let is_finalized = is_finalized(input);
+
+            // finalizing a copy checks the signatures against the scripts
+            let is_ready = is_finalized || psbt.clone().finalize_inp_mut(&secp, index).is_ok();
+
+            InputSignatureProgress {
+                index: index as u32,
+                signatures: signatures(input),
+                required: required_signatures(input),
+                is_finalized,
+                is_ready,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let ready_inputs = inputs.iter().filter(|input| input.is_ready).count() as u32;
+    let is_complete = ready_inputs as usize == inputs.len();
+
+    PsbtCombineProgress { inputs, ready_inputs, is_complete }
+}
+
+/// Finalize and extract the transaction, fails early if any input is still missing signatures
+pub fn finalize(psbt: BdkPsbt) -> Result<Transaction> {
+    let progress = progress(&psbt);
+    if !progress.is_complete {
+        let total = progress.inputs.len() as u32;
+        return Err(Error::Incomplete { ready: progress.ready_inputs, total });
+    }
+
+    let finalized = psbt.finalize(&Secp256k1::verification_only()).map_err(|(_, errors)| {
+        let errors = errors.iter().map(ToString::to_string).collect::<Vec<_>>();
+        Error::Finalize(errors.join(", "))
+    })?;
+
+    finalized.extract_tx().map_err(|error| Error::Extract(error.to_string()))
+}
+
+fn is_finalized(input: &Input) -> bool {
+    input.final_script_sig.is_some() || input.final_script_witness.is_some()
+}
+
+fn signatures(input: &Input) -> u32 {
+    let taproot = input.tap_script_sigs.len() + usize::from(input.tap_key_sig.is_some());
+    (input.partial_sigs.len() + taproot) as u32
+}
+
+fn required_signatures(input: &Input) -> u32 {
+    let Some(script) = &input.witness_script else { return 1 };
+    let Ok(miniscript) = Miniscript::<PublicKey, Segwitv0>::parse_insane(script) else {
+        return 1;
+    };
+
+    match &miniscript.node {
+        Terminal::Multi(threshold) => threshold.k() as u32,
+        _ => 1,
+    }
+}
+
+// MARK: ffi
+
+/// Collects PSBTs one at a time as they're scanned or imported
+#[derive(Debug, uniffi::Object)]
+pub struct PsbtCombiner(Mutex<Combined>);
+
+#[derive(Debug)]
+struct Combined {
+    psbt: BdkPsbt,
+    /// PSBTs merged so far, including the first, so errors point at the right one
+    count: u32,
+}
+
+#[uniffi::export]
+impl PsbtCombiner {
+    #[uniffi::constructor]
+    pub fn new(psbt: Arc<Psbt>) -> Self {
+        Self(Mutex::new(Combined { psbt: Arc::unwrap_or_clone(psbt).0, count: 1 }))
+    }
+
+    /// Merge in another PSBT, unrelated PSBTs are rejected and leave the combined PSBT as is
+    pub fn add(&self, psbt: Arc<Psbt>) -> Result<PsbtCombineProgress> {
+        let mut combined = self.0.lock();
+        let mut merged = combined.psbt.clone();
+        merge(&mut merged, Arc::unwrap_or_clone(psbt).0, combined.count)?;
+
+        combined.psbt = merged;
+        combined.count += 1;
+
+        let progress = progress(&combined.psbt);
+        debug!("combined {} PSBTs, {} inputs ready", combined.count, progress.ready_inputs);
+
+        Ok(progress)
+    }
+
+    pub fn progress(&self) -> PsbtCombineProgress {
+        progress(&self.0.lock().psbt)
+    }
+
+    pub fn psbt(&self) -> Arc<Psbt> {
+        Arc::new(self.0.lock().psbt.clone().into())
+    }
+
+    pub fn finalize(&self) -> Result<BitcoinTransaction> {
+        let transaction = finalize(self.0.lock().psbt.clone())?;
+        Ok(transaction.into())
+    }
+}
+
+#[uniffi::export]
+fn combine_psbts(psbts: Vec<Arc<Psbt>>) -> Result<Arc<Psbt>> {
+    let combined = combine(psbts.into_iter().map(|psbt| Arc::unwrap_or_clone(psbt).0))?;
+    Ok(Arc::new(combined.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr as _;
+
+    use bdk_wallet::{
+        bitcoin::{
+            Amount, EcdsaSighashType, OutPoint, TxIn, TxOut,
+            absolute::LockTime,
+            ecdsa,
+            hashes::Hash as _,
+            secp256k1::{Message, SecretKey},
+            sighash::SighashCache,
+            transaction::Version,
+        },
+        miniscript::Descriptor,
+    };
+
+    use super::*;
+
+    const KEYS: [&str; 2] = [
+        "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+        "02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5",
+    ];
+
+    fn psbt(vout: u32) -> BdkPsbt {
+        let descriptor = format!("wsh(multi(2,{},{}))", KEYS[0], KEYS[1]);
+        let descriptor = Descriptor::<PublicKey>::from_str(&descriptor).unwrap();
+
+        let transaction = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint { txid: Txid::from_str(&"ab".repeat(32)).unwrap(), vout },
+                ..Default::default()
+            }],
+            output: vec![],
+        };
+
+        let mut psbt = BdkPsbt::from_unsigned_tx(transaction).unwrap();
+        psbt.inputs[0].witness_script = Some(descriptor.explicit_script().unwrap());
+        psbt.inputs[0].witness_utxo = Some(TxOut {
+            value: Amount::from_sat(10_000),
+            script_pubkey: descriptor.script_pubkey(),
+        });
+
+        psbt
+    }
+
+    fn signed(key: &str) -> BdkPsbt {
+        // dummy signature, counting doesn't check it
+        let signature =
+            ecdsa::Signature::from_slice(&[0x30, 0x06, 0x02, 0x01, 0x01, 0x02, 0x01, 0x01, 0x01])
+                .unwrap();
+
+        let mut psbt = psbt(0);
+        psbt.inputs[0].partial_sigs.insert(PublicKey::from_str(key).unwrap(), signature);
+        psbt
+    }
+
+    /// Actually sign input 0, the secret keys 1 and 2 are the two `KEYS`
+    fn signed_with(secret: u8) -> BdkPsbt {
+        let secp = Secp256k1::new();
+        let mut secret_key = [0; 32];
+        secret_key[31] = secret;
+        let secret_key = SecretKey::from_slice(&secret_key).unwrap();
+
+        let mut psbt = psbt(0);
+        let input = &psbt.inputs[0];
+        let script = input.witness_script.clone().unwrap();
+        let value = input.witness_utxo.as_ref().unwrap().value;
+
+        let sighash = SighashCache::new(&psbt.unsigned_tx)
+            .p2wsh_signature_hash(0, &script, value, EcdsaSighashType::All)
+            .unwrap();
+
+        let message = Message::from_digest(sighash.to_byte_array());
+        let signature = ecdsa::Signature::sighash_all(secp.sign_ecdsa(&message, &secret_key));
+        let key = PublicKey::new(secret_key.public_key(&secp));
+
+        psbt.inputs[0].partial_sigs.insert(key, signature);
+        psbt
+    }
+
+    #[test]
+    fn test_combine_progress() {
+        let combined = combine([psbt(0), signed(KEYS[0]), signed(KEYS[1])]).unwrap();
+
+        let progress = progress(&combined);
+        assert_eq!(progress.inputs.len(), 1);
+        assert_eq!(progress.inputs[0].signatures, 2);
+        assert_eq!(progress.inputs[0].required, 2);
+        assert!(!progress.inputs[0].is_finalized);
+
+        // the signatures are not valid so it can't be finalized
+        assert!(!progress.is_complete);
+        assert_eq!(finalize(combined), Err(Error::Incomplete { ready: 0, total: 1 }));
+    }
+
+    #[test]
+    fn test_combine_unrelated() {
+        assert_eq!(combine([]), Err(Error::Empty));
+
+        let expected = psbt(0).unsigned_tx.compute_txid();
+        let actual = psbt(1).unsigned_tx.compute_txid();
+
+        let error = combine([psbt(0), signed(KEYS[0]), psbt(1)]).unwrap_err();
+        assert_eq!(error, unrelated(2, expected, actual));
+
+        let combiner = PsbtCombiner::new(Arc::new(psbt(0).into()));
+        assert!(combiner.add(Arc::new(psbt(1).into())).is_err());
+        assert_eq!(combiner.add(Arc::new(signed(KEYS[1]).into())).unwrap().inputs[0].signatures, 1);
+    }
+
+    #[test]
+    fn test_combine_signed_finalize() {
+        let combiner = PsbtCombiner::new(Arc::new(psbt(0).into()));
+
+        let progress = combiner.add(Arc::new(signed_with(1).into())).unwrap();
+        assert_eq!(progress.inputs[0].signatures, 1);
+        assert!(!progress.is_complete);
+        assert_eq!(combiner.finalize().map(|_| ()), Err(Error::Incomplete { ready: 0, total: 1 }));
+
+        let progress = combiner.add(Arc::new(signed_with(2).into())).unwrap();
+        assert_eq!(progress.inputs[0].signatures, 2);
+        assert!(progress.inputs[0].is_ready);
+        assert!(progress.is_complete);
+
+        let transaction = finalize(combiner.psbt().0.clone()).unwrap();
+        assert_eq!(transaction.compute_txid(), psbt(0).unsigned_tx.compute_txid());
+
+        // empty dummy item for CHECKMULTISIG, both signatures, then the witness script
+        let witness = &transaction.input[0].witness;
+        assert_eq!(witness.len(), 4);
+        assert!(witness.nth(0).unwrap().is_empty());
+        assert_eq!(
+            witness.last(),
+            psbt(0).inputs[0].witness_script.as_ref().map(|script| script.as_bytes())
+        );
+    }
+
+    #[test]
+    fn test_combiner_error_index() {
+        let combiner = PsbtCombiner::new(Arc::new(psbt(0).into()));
+        combiner.add(Arc::new(signed(KEYS[0]).into())).unwrap();
+
+        let expected = psbt(0).unsigned_tx.compute_txid();
+        let actual = psbt(1).unsigned_tx.compute_txid();
+        assert_eq!(combiner.add(Arc::new(psbt(1).into())), Err(unrelated(2, expected, actual)));
+
+        // a rejected PSBT doesn't count
+        assert_eq!(combiner.add(Arc::new(psbt(1).into())), Err(unrelated(2, expected, actual)));
+    }
+}
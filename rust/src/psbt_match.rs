@@ -0,0 +1,266 @@
+//! Work out which of our wallets a PSBT from somewhere else (a coordinator, another device) is
+//! spending from
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use bdk_wallet::{
+    bitcoin::{
+        Psbt as BdkPsbt, Script, TapLeafHash,
+        bip32::{ChildNumber, KeySource},
+        secp256k1::{PublicKey, XOnlyPublicKey},
+    },
+    descriptor::ExtendedDescriptor,
+    miniscript::ForEachKey as _,
+    psbt::PsbtUtils as _,
+};
+use cove_types::WalletId;
+use tracing::{debug, warn};
+
+use crate::{
+    bdk_store::BdkStore,
+    database::{Database, unsigned_transactions::UnsignedTransactionRecord},
+    psbt::Psbt,
+};
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, uniffi::Record)]
+pub struct PsbtWalletMatch {
+    pub wallet_id: WalletId,
+    pub wallet_name: String,
+    /// How many of the PSBT's inputs this wallet can sign for
+    pub matched_inputs: u32,
+    /// Our saved unsigned transaction with the same txid, if there is one
+    pub unsigned_transaction: Option<Arc<UnsignedTransactionRecord>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Error, thiserror::Error)]
+pub enum PsbtMatchError {
+    #[error("unable to load wallets: {0}")]
+    LoadWallets(String),
+
+    #[error("this PSBT doesn't spend from any of your wallets")]
+    NoMatchingWallet,
+}
+
+type Error = PsbtMatchError;
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// A wallet's public descriptors, external then internal
+#[derive(Debug, Clone)]
+pub struct WalletDescriptors {
+    pub id: WalletId,
+    pub name: String,
+    pub descriptors: [ExtendedDescriptor; 2],
+}
+
+/// The saved public descriptors of every wallet on the selected network, read straight from each
+/// wallet's store so nothing is loaded or synced
+pub fn saved_wallet_descriptors() -> Result<Vec<WalletDescriptors>> {
+    let db = Database::global();
+    let network = db.global_config.selected_network();
+    let wallets = db.wallets.all().map_err(|error| Error::LoadWallets(error.to_string()))?;
+
+    let descriptors = wallets
+        .into_iter()
+        .filter(|metadata| metadata.network == network)
+        .filter_map(|metadata| {
+            let descriptors = BdkStore::try_new(&metadata.id, network)
+                .and_then(|mut store| store.public_descriptors())
+                .inspect_err(|error| {
+                    warn!("unable to read descriptors for {}: {error}", metadata.id)
+                })
+                .ok()?;
+
+            Some(WalletDescriptors { id: metadata.id, name: metadata.name, descriptors })
+        })
+        .collect();
+
+    Ok(descriptors)
+}
+
+/// Matching wallets, the one with the most matched inputs first
+pub fn wallet_matches(psbt: &BdkPsbt) -> Result<Vec<PsbtWalletMatch>> {
+    let wallets = saved_wallet_descriptors()?;
+
+    let tx_id = psbt.unsigned_tx.compute_txid().into();
+    let record =
+        Database::global().unsigned_transactions().get_tx(&tx_id).unwrap_or_else(|error| {
+            warn!("unable to check unsigned transactions: {error}");
+            None
+        });
+
+    let mut matches = wallets
+        .into_iter()
+        .filter_map(|wallet| {
+            let unsigned_transaction = record
+                .as_ref()
+                .filter(|record| record.wallet_id == wallet.id)
+                .map(|record| Arc::new(record.clone()));
+
+            let matched_inputs = matched_inputs(psbt, &wallet.descriptors);
+            if matched_inputs == 0 && unsigned_transaction.is_none() {
+                return None;
+            }
+
+            Some(PsbtWalletMatch {
+                wallet_id: wallet.id,
+                wallet_name: wallet.name,
+                matched_inputs: matched_inputs as u32,
+                unsigned_transaction,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if matches.is_empty() {
+        return Err(Error::NoMatchingWallet);
+    }
+
+    matches.sort_by_key(|wallet_match| std::cmp::Reverse(wallet_match.matched_inputs));
+    debug!("PSBT {tx_id:?} matched {} wallet(s)", matches.len());
+
+    Ok(matches)
+}
+
+/// Inputs with a key origin from one of the descriptors' fingerprints, when the PSBT has the UTXO
+/// the descriptor has to derive its script too, so a shared signer doesn't match every wallet
+fn matched_inputs(psbt: &BdkPsbt, descriptors: &[ExtendedDescriptor]) -> usize {
+    (0..psbt.inputs.len())
+        .filter(|index| {
+            let input = &psbt.inputs[*index];
+            let script = psbt.get_utxo_for(*index).map(|utxo| utxo.script_pubkey);
+
+            key_origins(&input.bip32_derivation, &input.tap_key_origins).any(|origin| {
+                matches!(
+                    match_origin(descriptors, origin, script.as_deref()),
+                    OriginMatch::Fingerprint | OriginMatch::Derived { .. }
+                )
+            })
+        })
+        .count()
+}
+
+// MARK: key origins
+
+/// What a key origin from a PSBT input or output says about a wallet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OriginMatch {
+    /// Not one of the descriptors' fingerprints
+    NotOurs,
+    /// Our fingerprint, but there's no script to check the path against
+    Fingerprint,
+    /// The descriptor at `position` derives the script at `index`
+    Derived { position: usize, index: u32 },
+    /// Our fingerprint, but the path doesn't derive the script
+    WrongPath,
+}
+
+/// ECDSA then taproot key origins, inputs and outputs keep them the same way
+pub fn key_origins<'a>(
+    bip32_derivation: &'a BTreeMap<PublicKey, KeySource>,
+    tap_key_origins: &'a BTreeMap<XOnlyPublicKey, (Vec<TapLeafHash>, KeySource)>,
+) -> impl Iterator<Item = &'a KeySource> {
+    let taproot_origins = tap_key_origins.values().map(|(_, origin)| origin);
+    bip32_derivation.values().chain(taproot_origins)
+}
+
+/// The last step of the path has to derive `script` from a descriptor with the origin's
+/// fingerprint, nothing else in the origin is trusted
+pub fn match_origin(
+    descriptors: &[ExtendedDescriptor],
+    (fingerprint, path): &KeySource,
+    script: Option<&Script>,
+) -> OriginMatch {
+    let mut ours = descriptors
+        .iter()
+        .enumerate()
+        .filter(|(_, descriptor)| {
+            descriptor.for_any_key(|key| key.master_fingerprint() == *fingerprint)
+        })
+        .peekable();
+
+    if ours.peek().is_none() {
+        return OriginMatch::NotOurs;
+    }
+
+    let Some(script) = script else { return OriginMatch::Fingerprint };
+    let Some(ChildNumber::Normal { index }) = path.into_iter().last().copied() else {
+        return OriginMatch::WrongPath;
+    };
+
+    ours.find(|(_, descriptor)| {
+        descriptor
+            .at_derivation_index(index)
+            .is_ok_and(|derived| derived.script_pubkey() == *script)
+    })
+    .map_or(OriginMatch::WrongPath, |(position, _)| OriginMatch::Derived { position, index })
+}
+
+// MARK: ffi
+
+#[uniffi::export]
+fn psbt_wallet_matches(psbt: Arc<Psbt>) -> Result<Vec<PsbtWalletMatch>> {
+    wallet_matches(&psbt.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr as _;
+
+    use bdk_wallet::bitcoin::{
+        Amount, ScriptBuf, Transaction, TxIn, TxOut,
+        absolute::LockTime,
+        bip32::{DerivationPath, Fingerprint, Xpub},
+        transaction::Version,
+    };
+
+    use super::*;
+
+    // abandon * 11 + about, account 0
+    const XPUB: &str = "xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V";
+
+    fn descriptors() -> [ExtendedDescriptor; 2] {
+        [0, 1].map(|change| {
+            let descriptor = format!("wpkh([73c5da0a/84'/0'/0']{XPUB}/{change}/*)");
+            ExtendedDescriptor::from_str(&descriptor).unwrap()
+        })
+    }
+
+    fn psbt(fingerprint: &str, path: &str, script: Option<ScriptBuf>) -> BdkPsbt {
+        let transaction = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn::default()],
+            output: vec![],
+        };
+
+        let mut psbt = BdkPsbt::from_unsigned_tx(transaction).unwrap();
+        let key = Xpub::from_str(XPUB).unwrap().public_key;
+        let origin =
+            (Fingerprint::from_str(fingerprint).unwrap(), DerivationPath::from_str(path).unwrap());
+
+        psbt.inputs[0].bip32_derivation.insert(key, origin);
+        psbt.inputs[0].witness_utxo =
+            script.map(|script_pubkey| TxOut { value: Amount::from_sat(10_000), script_pubkey });
+
+        psbt
+    }
+
+    #[test]
+    fn test_matched_inputs() {
+        let descriptors = descriptors();
+        let change_script = descriptors[1].at_derivation_index(5).unwrap().script_pubkey();
+
+        let matched = psbt("73c5da0a", "m/84'/0'/0'/1/5", Some(change_script.clone()));
+        assert_eq!(matched_inputs(&matched, &descriptors), 1);
+
+        // no utxo, the fingerprint is all we have
+        let no_utxo = psbt("73c5da0a", "m/84'/0'/0'/0/0", None);
+        assert_eq!(matched_inputs(&no_utxo, &descriptors), 1);
+
+        // same signer, different wallet
+        let other_wallet = psbt("73c5da0a", "m/84'/0'/0'/1/6", Some(change_script.clone()));
+        assert_eq!(matched_inputs(&other_wallet, &descriptors), 0);
+
+        let other_signer = psbt("f245ae38", "m/84'/0'/0'/1/5", Some(change_script));
+        assert_eq!(matched_inputs(&other_signer, &descriptors), 0);
+    }
+}
@@ -0,0 +1,479 @@
+//! Check a signed PSBT from a hardware wallet against what we exported before approving it
+//!
+//! Everything is worked out from our own descriptors, nothing the coordinator or signer put in
+//! the PSBT is trusted, so a swapped output or a bogus change path shows up as a warning
+
+use std::{collections::HashSet, sync::Arc};
+
+use bdk_wallet::{
+    KeychainKind, Wallet as BdkWallet,
+    bitcoin::{
+        Address as BdkAddress, Amount as BdkAmount, EcdsaSighashType, Psbt as BdkPsbt, ScriptBuf,
+        TapSighashType, params::Params, psbt::Output,
+    },
+    descriptor::ExtendedDescriptor,
+};
+use cove_common::consts::GAP_LIMIT;
+use cove_types::{WalletId, address::Address, amount::Amount};
+use tracing::debug;
+
+use crate::psbt_match::{OriginMatch, WalletDescriptors, key_origins, match_origin};
+
+/// How much the signed fee can go over the confirmed fee before it's flagged
+const FEE_TOLERANCE_PERCENT: u64 = 10;
+
+/// Outputs to another of our wallets without key origins are only recognized this far into each
+/// of its keychains
+const OTHER_WALLET_ADDRESSES: u32 = 500;
+
+const KEYCHAINS: [KeychainKind; 2] = [KeychainKind::External, KeychainKind::Internal];
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, uniffi::Record)]
+pub struct PsbtVerification {
+    pub inputs: Vec<VerifiedInput>,
+    pub outputs: Vec<VerifiedOutput>,
+    pub fee: Option<Arc<Amount>>,
+    pub warnings: Vec<PsbtWarning>,
+    /// No warnings, safe to approve
+    pub is_safe: bool,
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, uniffi::Record)]
+pub struct VerifiedInput {
+    pub index: u32,
+    pub previous_output: String,
+    pub amount: Option<Arc<Amount>>,
+    pub is_ours: bool,
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, uniffi::Record)]
+pub struct VerifiedOutput {
+    pub index: u32,
+    pub address: Option<Arc<Address>>,
+    pub amount: Arc<Amount>,
+    pub kind: OutputKind,
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, uniffi::Enum)]
+pub enum OutputKind {
+    External,
+    Change,
+    /// One of this wallet's receive addresses, a consolidation
+    Receive,
+    OtherWallet {
+        id: WalletId,
+        name: String,
+    },
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, uniffi::Enum)]
+pub enum PsbtWarning {
+    /// Has one of our key origins but the path doesn't derive the output's script
+    ChangePathNotInWallet {
+        index: u32,
+        path: String,
+    },
+    /// Ours, but so far past the last used address the wallet would never find it
+    ChangeIndexTooHigh {
+        index: u32,
+        derivation_index: u32,
+    },
+    FeeAboveConfirmed {
+        confirmed: Arc<Amount>,
+        actual: Arc<Amount>,
+    },
+    UnknownFee,
+    OutputChanged {
+        index: u32,
+    },
+    InputsChanged,
+    NonDefaultSighash {
+        index: u32,
+        sighash: String,
+    },
+}
+
+/// Compare the signed PSBT to the one we exported and confirmed
+pub fn verify(
+    psbt: &BdkPsbt,
+    exported: &BdkPsbt,
+    confirmed_fee: BdkAmount,
+    wallet: &BdkWallet,
+    others: &[WalletDescriptors],
+) -> PsbtVerification {
+    let mut warnings = Vec::new();
+    let descriptors = KEYCHAINS.map(|keychain| wallet.public_descriptor(keychain).clone());
+    let others = others.iter().map(OtherWallet::new).collect::<Vec<_>>();
+
+    let inputs = (0..psbt.inputs.len())
+        .map(|index| {
+            let utxo = psbt.get_utxo_for(index);
+            let is_ours =
+                utxo.as_ref().is_some_and(|utxo| wallet.is_mine(utxo.script_pubkey.clone()));
+
+            VerifiedInput {
+                index: index as u32,
+                previous_output: psbt.unsigned_tx.input[index].previous_output.to_string(),
+                amount: utxo.map(|utxo| Arc::new(utxo.value.into())),
+                is_ours,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let outputs = psbt
+        .unsigned_tx
+        .output
+        .iter()
+        .zip(&psbt.outputs)
+        .enumerate()
+        .map(|(index, (output, psbt_output))| {
+            let index = index as u32;
+            let script = &output.script_pubkey;
+            let kind = output_kind(
+                script,
+                psbt_output,
+                index,
+                wallet,
+                &descriptors,
+                &others,
+                &mut warnings,
+            );
+            let address = BdkAddress::from_script(script, Params::from(wallet.network()))
+                .ok()
+                .map(|address| Arc::new(address.into()));
+
+            VerifiedOutput { index, address, amount: Arc::new(output.value.into()), kind }
+        })
+        .collect::<Vec<_>>();
+
+    // MARK: compare to what we exported
+
+    let previous_outputs = |psbt: &BdkPsbt| {
+        psbt.unsigned_tx.input.iter().map(|input| input.previous_output).collect::<Vec<_>>()
+    };
+
+    if previous_outputs(psbt) != previous_outputs(exported) {
+        warnings.push(PsbtWarning::InputsChanged);
+    }
+
+    let signed_outputs = &psbt.unsigned_tx.output;
+    let exported_outputs = &exported.unsigned_tx.output;
+    for index in 0..signed_outputs.len().max(exported_outputs.len()) {
+        if signed_outputs.get(index) != exported_outputs.get(index) {
+            warnings.push(PsbtWarning::OutputChanged { index: index as u32 });
+        }
+    }
+
+    let fee = psbt.fee().ok();
+    match fee {
+        Some(fee) if fee > confirmed_fee + confirmed_fee * FEE_TOLERANCE_PERCENT / 100 => {
+            warnings.push(PsbtWarning::FeeAboveConfirmed {
+                confirmed: Arc::new(confirmed_fee.into()),
+                actual: Arc::new(fee.into()),
+            });
+        }
+        Some(_) => {}
+        None => warnings.push(PsbtWarning::UnknownFee),
+    }
+
+    // MARK: sighash types
+
+    for (index, input) in psbt.inputs.iter().enumerate() {
+        let index = index as u32;
+
+        let requested = input.sighash_type.map(|sighash| sighash.to_u32());
+        let requested = requested.filter(|sighash| *sighash > EcdsaSighashType::All.to_u32());
+
+        let ecdsa = input
+            .partial_sigs
+            .values()
+            .map(|signature| signature.sighash_type)
+            .find(|sighash| *sighash != EcdsaSighashType::All)
+            .map(|sighash| sighash.to_string());
+
+        let taproot = input
+            .tap_key_sig
+            .iter()
+            .chain(input.tap_script_sigs.values())
+            .map(|signature| signature.sighash_type)
+            .find(|sighash| !matches!(sighash, TapSighashType::Default | TapSighashType::All))
+            .map(|sighash| sighash.to_string());
+
+        let sighash = ecdsa.or(taproot).or_else(|| requested.map(|sighash| sighash.to_string()));
+        if let Some(sighash) = sighash {
+            warnings.push(PsbtWarning::NonDefaultSighash { index, sighash });
+        }
+    }
+
+    debug!("verified signed PSBT, {} warning(s)", warnings.len());
+    let is_safe = warnings.is_empty();
+
+    PsbtVerification {
+        inputs,
+        outputs,
+        fee: fee.map(|fee| Arc::new(fee.into())),
+        warnings,
+        is_safe,
+    }
+}
+
+/// Another of our wallets, outputs to it are ours but not this wallet's
+struct OtherWallet<'a> {
+    wallet: &'a WalletDescriptors,
+    scripts: HashSet<ScriptBuf>,
+}
+
+impl<'a> OtherWallet<'a> {
+    fn new(wallet: &'a WalletDescriptors) -> Self {
+        let scripts = wallet
+            .descriptors
+            .iter()
+            .flat_map(|descriptor| {
+                (0..OTHER_WALLET_ADDRESSES).filter_map(|index| {
+                    Some(descriptor.at_derivation_index(index).ok()?.script_pubkey())
+                })
+            })
+            .collect();
+
+        Self { wallet, scripts }
+    }
+
+    fn is_mine(&self, script: &ScriptBuf, psbt_output: &Output) -> bool {
+        if self.scripts.contains(script) {
+            return true;
+        }
+
+        key_origins(&psbt_output.bip32_derivation, &psbt_output.tap_key_origins).any(|origin| {
+            let matched = match_origin(&self.wallet.descriptors, origin, Some(script));
+            matches!(matched, OriginMatch::Derived { .. })
+        })
+    }
+}
+
+fn output_kind(
+    script: &ScriptBuf,
+    psbt_output: &Output,
+    index: u32,
+    wallet: &BdkWallet,
+    descriptors: &[ExtendedDescriptor; 2],
+    others: &[OtherWallet<'_>],
+    warnings: &mut Vec<PsbtWarning>,
+) -> OutputKind {
+    let keychain_kind = |keychain| match keychain {
+        KeychainKind::External => OutputKind::Receive,
+        KeychainKind::Internal => OutputKind::Change,
+    };
+
+    // key origins claiming the output is ours have to derive its script from our descriptors
+    let claimed = claimed_derivation(script, psbt_output, index, descriptors, warnings);
+
+    if let Some((keychain, _)) = wallet.derivation_of_spk(script.clone()) {
+        return keychain_kind(keychain);
+    }
+
+    if let Some((keychain, derivation_index)) = claimed {
+        let next_index = wallet.next_derivation_index(keychain);
+        if derivation_index > next_index.saturating_add(GAP_LIMIT as u32) {
+            warnings.push(PsbtWarning::ChangeIndexTooHigh { index, derivation_index });
+        }
+
+        return keychain_kind(keychain);
+    }
+
+    for other in others {
+        if other.is_mine(script, psbt_output) {
+            let WalletDescriptors { id, name, .. } = other.wallet;
+            return OutputKind::OtherWallet { id: id.clone(), name: name.clone() };
+        }
+    }
+
+    OutputKind::External
+}
+
+/// The keychain and index from the output's key origins, when one of them is ours
+fn claimed_derivation(
+    script: &ScriptBuf,
+    psbt_output: &Output,
+    index: u32,
+    descriptors: &[ExtendedDescriptor; 2],
+    warnings: &mut Vec<PsbtWarning>,
+) -> Option<(KeychainKind, u32)> {
+    let mut claimed = None;
+
+    for origin in key_origins(&psbt_output.bip32_derivation, &psbt_output.tap_key_origins) {
+        match match_origin(descriptors, origin, Some(script)) {
+            OriginMatch::Derived { position, index: child } => {
+                claimed = Some((KEYCHAINS[position], child));
+            }
+            OriginMatch::WrongPath => {
+                let path = origin.1.to_string();
+                warnings.push(PsbtWarning::ChangePathNotInWallet { index, path });
+            }
+            OriginMatch::NotOurs | OriginMatch::Fingerprint => {}
+        }
+    }
+
+    claimed
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr as _;
+
+    use bdk_wallet::bitcoin::{
+        Network, OutPoint, Transaction, TxIn, TxOut, Txid,
+        absolute::LockTime,
+        bip32::{DerivationPath, Fingerprint},
+        psbt::PsbtSighashType,
+        transaction::Version,
+    };
+
+    use super::*;
+
+    // abandon * 11 + about, account 0
+    const XPUB: &str = "xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V";
+
+    fn wallet() -> BdkWallet {
+        let descriptor = |change| format!("wpkh([73c5da0a/84'/0'/0']{XPUB}/{change}/*)");
+
+        BdkWallet::create(descriptor(0), descriptor(1))
+            .network(Network::Bitcoin)
+            .create_wallet_no_persist()
+            .unwrap()
+    }
+
+    fn exported(wallet: &BdkWallet) -> BdkPsbt {
+        let external = BdkAddress::from_str("bc1qxy2kgdygjrsqtzq2n0yrf2493p83kkfjhx0wlh")
+            .unwrap()
+            .assume_checked()
+            .script_pubkey();
+
+        let change = wallet.peek_address(KeychainKind::Internal, 0).script_pubkey();
+        let spent = wallet.peek_address(KeychainKind::External, 0).script_pubkey();
+
+        let transaction = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint {
+                    txid: Txid::from_str(&"ab".repeat(32)).unwrap(),
+                    vout: 0,
+                },
+                ..Default::default()
+            }],
+            output: vec![
+                TxOut { value: BdkAmount::from_sat(50_000), script_pubkey: external },
+                TxOut { value: BdkAmount::from_sat(40_000), script_pubkey: change },
+            ],
+        };
+
+        let mut psbt = BdkPsbt::from_unsigned_tx(transaction).unwrap();
+        psbt.inputs[0].witness_utxo =
+            Some(TxOut { value: BdkAmount::from_sat(100_000), script_pubkey: spent });
+
+        psbt
+    }
+
+    fn origin(path: &str) -> (Fingerprint, DerivationPath) {
+        (Fingerprint::from_str("73c5da0a").unwrap(), DerivationPath::from_str(path).unwrap())
+    }
+
+    #[test]
+    fn test_verify_unchanged() {
+        let wallet = wallet();
+        let exported = exported(&wallet);
+
+        let mut signed = exported.clone();
+        let key = bdk_wallet::bitcoin::secp256k1::PublicKey::from_str(
+            "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+        )
+        .unwrap();
+        signed.outputs[1].bip32_derivation.insert(key, origin("m/84'/0'/0'/1/0"));
+
+        let report = verify(&signed, &exported, BdkAmount::from_sat(10_000), &wallet, &[]);
+        assert!(report.is_safe, "{:?}", report.warnings);
+        assert_eq!(report.fee, Some(Arc::new(BdkAmount::from_sat(10_000).into())));
+        assert!(report.inputs[0].is_ours);
+        assert_eq!(report.outputs[0].kind, OutputKind::External);
+        assert_eq!(report.outputs[1].kind, OutputKind::Change);
+    }
+
+    #[test]
+    fn test_verify_tampered() {
+        let wallet = wallet();
+        let exported = exported(&wallet);
+        let key = bdk_wallet::bitcoin::secp256k1::PublicKey::from_str(
+            "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+        )
+        .unwrap();
+
+        // change swapped for an output we can't spend, claiming the original change path
+        let mut signed = exported.clone();
+        signed.unsigned_tx.output[1].script_pubkey = ScriptBuf::new_op_return([1, 2, 3]);
+        signed.unsigned_tx.output[1].value = BdkAmount::from_sat(20_000);
+        signed.outputs[1].bip32_derivation.insert(key, origin("m/84'/0'/0'/1/0"));
+        signed.inputs[0].sighash_type = Some(PsbtSighashType::from(EcdsaSighashType::None));
+
+        let report = verify(&signed, &exported, BdkAmount::from_sat(10_000), &wallet, &[]);
+        assert!(!report.is_safe);
+        assert_eq!(report.outputs[1].kind, OutputKind::External);
+
+        let warnings = report.warnings;
+        assert!(warnings.iter().any(|warning| {
+            matches!(warning, PsbtWarning::ChangePathNotInWallet { index: 1, path } if path.ends_with("1/0"))
+        }));
+        assert!(warnings.contains(&PsbtWarning::OutputChanged { index: 1 }));
+        assert!(
+            warnings.iter().any(|warning| matches!(warning, PsbtWarning::FeeAboveConfirmed { .. }))
+        );
+        assert!(
+            warnings
+                .iter()
+                .any(|warning| matches!(warning, PsbtWarning::NonDefaultSighash { index: 0, .. }))
+        );
+        assert!(!warnings.contains(&PsbtWarning::InputsChanged));
+
+        // ours but far past anything the wallet would scan
+        let far = wallet.peek_address(KeychainKind::Internal, 500).script_pubkey();
+        let mut signed = exported.clone();
+        signed.unsigned_tx.output[1].script_pubkey = far;
+        signed.outputs[1].bip32_derivation.insert(key, origin("m/84'/0'/0'/1/500"));
+
+        let report = verify(&signed, &exported, BdkAmount::from_sat(10_000), &wallet, &[]);
+        assert_eq!(report.outputs[1].kind, OutputKind::Change);
+        assert!(
+            report
+                .warnings
+                .contains(&PsbtWarning::ChangeIndexTooHigh { index: 1, derivation_index: 500 })
+        );
+    }
+
+    #[test]
+    fn test_verify_other_wallet() {
+        let wallet = wallet();
+        let exported = exported(&wallet);
+
+        let descriptor = ExtendedDescriptor::from_str(
+            "wpkh(02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5)",
+        )
+        .unwrap();
+
+        let other = WalletDescriptors {
+            id: WalletId::preview_new(),
+            name: "Savings".to_string(),
+            descriptors: [descriptor.clone(), descriptor.clone()],
+        };
+
+        let mut signed = exported.clone();
+        signed.unsigned_tx.output[0].script_pubkey =
+            descriptor.at_derivation_index(0).unwrap().script_pubkey();
+
+        let report = verify(&signed, &exported, BdkAmount::from_sat(10_000), &wallet, &[other]);
+        let OutputKind::OtherWallet { name, .. } = &report.outputs[0].kind else {
+            panic!("expected another wallet, got {:?}", report.outputs[0].kind);
+        };
+
+        assert_eq!(name, "Savings");
+        assert!(report.warnings.contains(&PsbtWarning::OutputChanged { index: 0 }));
+    }
+}
@@ -0,0 +1,257 @@
+//! End to end tests against a local `bitcoind -regtest` and electrs
+//!
+//! These are ignored by default, point `BITCOIND_EXE` and `ELECTRS_EXE` at the binaries and run
+//! `cargo test regtest -- --ignored`
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    net::TcpListener,
+    process::{Child, Command, Stdio},
+    str::FromStr as _,
+    sync::Arc,
+    time::Duration,
+};
+
+use act_zero::call;
+use bitcoin::FeeRate as BdkFeeRate;
+use cove_types::{amount::Amount, network::Network};
+use parking_lot::Mutex;
+use serde_json::{Value, json};
+use tempfile::TempDir;
+
+use crate::{
+    database::Database,
+    keychain::{Keychain, KeychainAccess, KeychainError},
+    manager::{send_flow_manager::recipient::Recipient, wallet_manager::actor::WalletActor},
+    node::Node,
+    task,
+    wallet::{Address, Wallet, fingerprint::Fingerprint, metadata::WalletMetadata},
+};
+
+const RPC_USER: &str = "cove";
+const RPC_PASSWORD: &str = "cove";
+
+const WORDS: &str =
+    "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+/// bitcoind and electrs running in temp dirs, killed on drop
+struct RegtestStack {
+    bitcoind: Child,
+    electrs: Option<Child>,
+    rpc_url: String,
+    electrum_url: String,
+    http: reqwest::Client,
+    bitcoind_dir: TempDir,
+    electrs_dir: TempDir,
+}
+
+impl RegtestStack {
+    async fn start() -> Self {
+        let bitcoind_exe = std::env::var("BITCOIND_EXE").expect("BITCOIND_EXE not set");
+        let electrs_exe = std::env::var("ELECTRS_EXE").expect("ELECTRS_EXE not set");
+
+        let bitcoind_dir = TempDir::new().unwrap();
+        let electrs_dir = TempDir::new().unwrap();
+
+        let (rpc_port, p2p_port, electrum_port) = (free_port(), free_port(), free_port());
+
+        let bitcoind = Command::new(bitcoind_exe)
+            .arg("-regtest")
+            .arg(format!("-datadir={}", bitcoind_dir.path().display()))
+            .arg(format!("-rpcport={rpc_port}"))
+            .arg(format!("-port={p2p_port}"))
+            .arg(format!("-rpcuser={RPC_USER}"))
+            .arg(format!("-rpcpassword={RPC_PASSWORD}"))
+            .args(["-server=1", "-listen=1", "-fallbackfee=0.0001", "-printtoconsole=0"])
+            .stdout(Stdio::null())
+            .spawn()
+            .expect("failed to start bitcoind");
+
+        let mut stack = Self {
+            bitcoind,
+            electrs: None,
+            rpc_url: format!("http://127.0.0.1:{rpc_port}"),
+            electrum_url: format!("tcp://127.0.0.1:{electrum_port}"),
+            http: reqwest::Client::new(),
+            bitcoind_dir,
+            electrs_dir,
+        };
+
+        {
+            let stack = &stack;
+            wait_for("bitcoind", || async move {
+                stack.try_rpc("getblockchaininfo", json!([])).await.is_ok()
+            })
+            .await;
+        }
+
+        // electrs waits for bitcoind to leave initial block download, so mine the coinbase first
+        stack.rpc("createwallet", json!(["miner"])).await;
+        stack.mine(101).await;
+
+        let electrs = Command::new(electrs_exe)
+            .args(["--network", "regtest", "--skip-default-conf-files", "--log-filters", "warn"])
+            .arg("--db-dir")
+            .arg(stack.electrs_dir.path())
+            .arg("--daemon-dir")
+            .arg(stack.bitcoind_dir.path())
+            .args(["--daemon-rpc-addr", &format!("127.0.0.1:{rpc_port}")])
+            .args(["--daemon-p2p-addr", &format!("127.0.0.1:{p2p_port}")])
+            .args(["--electrum-rpc-addr", &format!("127.0.0.1:{electrum_port}")])
+            .args(["--auth", &format!("{RPC_USER}:{RPC_PASSWORD}")])
+            .stdout(Stdio::null())
+            .spawn()
+            .expect("failed to start electrs");
+
+        stack.electrs = Some(electrs);
+        stack
+    }
+
+    fn node(&self) -> Node {
+        Node::new_electrum("local electrs".to_string(), self.electrum_url.clone(), Network::Regtest)
+    }
+
+    async fn try_rpc(&self, method: &str, params: Value) -> eyre::Result<Value> {
+        let body = json!({ "jsonrpc": "1.0", "id": "cove", "method": method, "params": params });
+
+        let response: Value = self
+            .http
+            .post(&self.rpc_url)
+            .basic_auth(RPC_USER, Some(RPC_PASSWORD))
+            .json(&body)
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        match &response["error"] {
+            Value::Null => Ok(response["result"].clone()),
+            error => Err(eyre::eyre!("{method} failed: {error}")),
+        }
+    }
+
+    async fn rpc(&self, method: &str, params: Value) -> Value {
+        self.try_rpc(method, params).await.unwrap()
+    }
+
+    async fn mine(&self, blocks: u32) {
+        let address = self.rpc("getnewaddress", json!([])).await;
+        self.rpc("generatetoaddress", json!([blocks, address])).await;
+    }
+
+    async fn in_mempool(&self, txid: &str) -> bool {
+        let mempool = self.rpc("getrawmempool", json!([])).await;
+        mempool.as_array().unwrap().iter().any(|id| id.as_str() == Some(txid))
+    }
+}
+
+impl Drop for RegtestStack {
+    fn drop(&mut self) {
+        if let Some(electrs) = self.electrs.as_mut() {
+            let _ = electrs.kill();
+        }
+
+        let _ = self.bitcoind.kill();
+    }
+}
+
+#[derive(Debug, Default)]
+struct MemoryKeychain(Mutex<HashMap<String, String>>);
+
+impl KeychainAccess for MemoryKeychain {
+    fn save(&self, key: String, value: String) -> Result<(), KeychainError> {
+        self.0.lock().insert(key, value);
+        Ok(())
+    }
+
+    fn get(&self, key: String) -> Option<String> {
+        self.0.lock().get(&key).cloned()
+    }
+
+    fn delete(&self, key: String) -> bool {
+        self.0.lock().remove(&key).is_some()
+    }
+}
+
+fn free_port() -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.local_addr().unwrap().port()
+}
+
+/// Poll every half second for up to 30 seconds
+async fn wait_for<F, Fut>(what: &str, mut check: F)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = bool>,
+{
+    for _ in 0..60 {
+        if check().await {
+            return;
+        }
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+
+    panic!("timed out waiting for {what}");
+}
+
+#[tokio::test]
+#[ignore = "needs bitcoind and electrs, set BITCOIND_EXE and ELECTRS_EXE"]
+async fn test_regtest_scan_and_broadcast() {
+    let stack = RegtestStack::start().await;
+
+    task::init_tokio();
+    Keychain::new(Box::new(MemoryKeychain::default()));
+
+    let database = Database::global();
+    database.global_config.set_selected_network(Network::Regtest).unwrap();
+    database.global_config.set_selected_node(&stack.node()).unwrap();
+
+    let mnemonic = bip39::Mnemonic::from_str(WORDS).unwrap();
+    let metadata = WalletMetadata::new("Regtest", None::<Fingerprint>);
+    let wallet = Wallet::try_new_persisted_and_selected(metadata, mnemonic, None).unwrap();
+
+    let (sender, _receiver) = flume::unbounded();
+    let actor = &task::spawn_actor(WalletActor::new(wallet, sender));
+
+    // fund the wallet
+    let address = call!(actor.next_address()).await.unwrap().info.address.to_string();
+    stack.rpc("sendtoaddress", json!([address, 1.0])).await;
+    stack.mine(1).await;
+
+    wait_for("funding to be scanned", || async move {
+        call!(actor.wallet_scan_and_notify(true)).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(500)).await;
+
+        let balance = call!(actor.balance()).await.unwrap();
+        balance.0.confirmed == bitcoin::Amount::ONE_BTC
+    })
+    .await;
+
+    // send some back to the miner
+    let miner_address = stack.rpc("getnewaddress", json!([])).await;
+    let miner_address =
+        Address::from_string(miner_address.as_str().unwrap(), Network::Regtest).unwrap();
+
+    let recipient = Recipient::new(Arc::new(miner_address), Amount::from_sat(10_000_000));
+    let fee_rate = BdkFeeRate::from_sat_per_vb_unchecked(2);
+
+    let psbt = call!(actor.build_tx(vec![recipient], fee_rate)).await.unwrap().unwrap();
+    let txid = psbt.unsigned_tx.compute_txid().to_string();
+
+    call!(actor.sign_and_broadcast_transaction(psbt)).await.unwrap().unwrap();
+    assert!(stack.in_mempool(&txid).await);
+
+    stack.mine(1).await;
+
+    wait_for("spend to be scanned", || async move {
+        call!(actor.wallet_scan_and_notify(true)).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(500)).await;
+
+        let balance = call!(actor.balance()).await.unwrap();
+        balance.0.confirmed > bitcoin::Amount::ZERO
+            && balance.0.confirmed < bitcoin::Amount::from_sat(90_000_000)
+    })
+    .await;
+}
@@ -47,6 +47,8 @@ pub enum HotWalletRoute {
 #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, From, uniffi::Enum)]
 pub enum ColdWalletRoute {
     QrCode,
+    /// BIP129 multisig setup, Cove is the coordinator
+    BsmsCoordinator,
 }
 
 #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, From, uniffi::Enum)]
@@ -88,6 +90,7 @@ pub enum SendRoute {
     CoinControlSetAmount { id: WalletId, utxos: Vec<Utxo> },
     HardwareExport { id: WalletId, details: Arc<ConfirmDetails> },
     Confirm(SendRouteConfirmArgs),
+    SignPsbt { id: WalletId, psbt: Arc<Psbt> },
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, uniffi::Enum)]
@@ -295,6 +298,10 @@ impl RouteFactory {
         ColdWalletRoute::QrCode.into()
     }
 
+    pub fn bsms_coordinator(&self) -> Route {
+        ColdWalletRoute::BsmsCoordinator.into()
+    }
+
     pub fn load_and_reset_nested_to(
         &self,
         default_route: Route,
@@ -348,6 +355,11 @@ impl RouteFactory {
         Route::Send(send)
     }
 
+    pub fn send_sign_psbt(&self, id: WalletId, psbt: Arc<Psbt>) -> Route {
+        let send = SendRoute::SignPsbt { id, psbt };
+        Route::Send(send)
+    }
+
     pub fn coin_control_send(&self, id: WalletId, utxos: Vec<Utxo>) -> Route {
         let send = SendRoute::CoinControlSetAmount { id, utxos };
         Route::Send(send)
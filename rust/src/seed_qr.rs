@@ -1,4 +1,7 @@
+use std::sync::Arc;
+
 use bip39::{Language, Mnemonic};
+use qrcode::{Color, EcLevel, QrCode, Version, bits::Bits};
 
 use crate::mnemonic::WordAccess as _;
 
@@ -21,6 +24,21 @@ pub enum SeedQrError {
 
     #[error("unable to parse mnemonic: {0}")]
     InvalidMnemonic(#[from] Bip39Error),
+
+    #[error("SeedQR backups are only supported for 12 or 24 words, got: {0}")]
+    UnsupportedWordCount(u16),
+
+    #[error("unable to create QR code: {0}")]
+    QrCodeCreation(String),
+}
+
+/// QR modules for copying a SeedQR onto a paper template by hand
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct SeedQrGrid {
+    /// Modules per side, 21 (compact 12 words) up to 29 (standard 24 words)
+    pub size: u16,
+    /// Row by row, `true` is a filled in (dark) module
+    pub rows: Vec<Vec<bool>>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Copy, thiserror::Error, uniffi::Error)]
@@ -80,6 +98,63 @@ impl SeedQr {
         let mnemonic = self.mnemonic();
         mnemonic.words()
     }
+
+    // MARK: encoding
+
+    /// Every word's index in the BIP39 word list, as 4 digits
+    pub fn to_standard_string(&self) -> String {
+        self.mnemonic().word_indices().map(|index| format!("{index:04}")).collect()
+    }
+
+    /// CompactSeedQR is the raw entropy, without the checksum
+    pub fn to_compact_data(&self) -> Vec<u8> {
+        self.mnemonic().to_entropy()
+    }
+
+    /// The QR code SeedSigner would show, same version and error correction, so it lines up
+    /// with the printed templates
+    pub fn to_grid(&self) -> Result<SeedQrGrid, Error> {
+        let word_count = self.mnemonic().word_count();
+
+        // 12 words / 24 words
+        let version = match (self, word_count) {
+            (SeedQr::Standard(_), 12) => Version::Normal(2),
+            (SeedQr::Standard(_), 24) => Version::Normal(3),
+            (SeedQr::Compact(_), 12) => Version::Normal(1),
+            (SeedQr::Compact(_), 24) => Version::Normal(2),
+            (_, other) => return Err(Error::UnsupportedWordCount(other as u16)),
+        };
+
+        let qr_error = |error: qrcode::types::QrError| Error::QrCodeCreation(error.to_string());
+
+        // no mixed mode segments, standard is all numeric and compact is all bytes
+        let mut bits = Bits::new(version);
+        let pushed = match self {
+            SeedQr::Standard(_) => bits.push_numeric_data(self.to_standard_string().as_bytes()),
+            SeedQr::Compact(_) => bits.push_byte_data(&self.to_compact_data()),
+        };
+
+        pushed.map_err(qr_error)?;
+
+        bits.push_terminator(EcLevel::L).map_err(qr_error)?;
+        let qr = QrCode::with_bits(bits, EcLevel::L).map_err(qr_error)?;
+
+        let size = qr.width();
+        let rows = qr
+            .to_colors()
+            .chunks(size)
+            .map(|row| row.iter().map(|color| *color == Color::Dark).collect())
+            .collect();
+
+        Ok(SeedQrGrid { size: size as u16, rows })
+    }
+
+    fn check_word_count(mnemonic: &Mnemonic) -> Result<(), Error> {
+        match mnemonic.word_count() {
+            12 | 24 => Ok(()),
+            other => Err(Error::UnsupportedWordCount(other as u16)),
+        }
+    }
 }
 
 #[uniffi::export]
@@ -94,6 +169,41 @@ impl SeedQr {
         Self::try_from_str(qr)
     }
 
+    /// For backing up a wallet, the mnemonic only loads after the secret words auth check
+    #[uniffi::constructor]
+    pub fn new_standard(mnemonic: Arc<crate::mnemonic::Mnemonic>) -> Result<Self, Error> {
+        let mnemonic: &Mnemonic = &mnemonic;
+        Self::check_word_count(mnemonic)?;
+        Ok(Self::Standard(mnemonic.clone()))
+    }
+
+    #[uniffi::constructor]
+    pub fn new_compact(mnemonic: Arc<crate::mnemonic::Mnemonic>) -> Result<Self, Error> {
+        let mnemonic: &Mnemonic = &mnemonic;
+        Self::check_word_count(mnemonic)?;
+        Ok(Self::Compact(mnemonic.clone()))
+    }
+
+    #[uniffi::method(name = "standardString")]
+    pub fn ffi_standard_string(&self) -> String {
+        self.to_standard_string()
+    }
+
+    #[uniffi::method(name = "compactData")]
+    pub fn ffi_compact_data(&self) -> Vec<u8> {
+        self.to_compact_data()
+    }
+
+    #[uniffi::method(name = "grid")]
+    pub fn ffi_grid(&self) -> Result<SeedQrGrid, Error> {
+        self.to_grid()
+    }
+
+    #[uniffi::method]
+    pub fn is_compact(&self) -> bool {
+        matches!(self, SeedQr::Compact(_))
+    }
+
     #[uniffi::method]
     pub fn get_words(&self) -> Vec<String> {
         self.words().map(|word| word.to_string()).collect()
@@ -291,7 +401,7 @@ pub mod tests {
             },
         ];
 
-        for vector in test_vectors {
+        for vector in &test_vectors {
             let vector_words = vector.words.split_whitespace().collect::<Vec<&str>>();
 
             let seed_qr = SeedQr::try_from_str(vector.standard);
@@ -304,6 +414,53 @@ pub mod tests {
             let seed_qr = seed_qr.unwrap();
             assert_eq!(seed_qr.get_words(), vector_words);
         }
+
+        // and back again
+        for vector in test_vectors {
+            let mnemonic = Mnemonic::parse_in(Language::English, vector.words).unwrap();
+
+            let standard = SeedQr::Standard(mnemonic.clone());
+            assert_eq!(standard.to_standard_string(), vector.standard);
+
+            let compact = SeedQr::Compact(mnemonic);
+            assert_eq!(compact.to_compact_data(), vector.bytes);
+
+            let parsed = SeedQr::try_from_str(&standard.to_standard_string()).unwrap();
+            assert_eq!(parsed.get_words(), standard.get_words());
+
+            let parsed = SeedQr::try_from_data(&compact.to_compact_data()).unwrap();
+            assert_eq!(parsed.get_words(), compact.get_words());
+        }
+    }
+
+    #[test]
+    fn test_grid_sizes() {
+        let twelve = "forum undo fragile fade shy sign arrest garment culture tube off merit";
+        let twenty_four = "attack pizza motion avocado network gather crop fresh patrol unusual wild holiday candy pony ranch winter theme error hybrid van cereal salon goddess expire";
+
+        let sizes = [(twelve, 25, 21), (twenty_four, 29, 25)];
+        for (words, standard_size, compact_size) in sizes {
+            let mnemonic = Mnemonic::parse_in(Language::English, words).unwrap();
+
+            let standard = SeedQr::Standard(mnemonic.clone()).to_grid().unwrap();
+            assert_eq!(standard.size, standard_size);
+
+            let compact = SeedQr::Compact(mnemonic).to_grid().unwrap();
+            assert_eq!(compact.size, compact_size);
+
+            for grid in [standard, compact] {
+                assert_eq!(grid.rows.len(), grid.size as usize);
+                assert!(grid.rows.iter().all(|row| row.len() == grid.size as usize));
+
+                // finder pattern corner
+                assert!(grid.rows[0][..7].iter().all(|dark| *dark));
+                assert!(!grid.rows[1][1]);
+            }
+        }
+
+        let fifteen = "play element inch believe wrestle because feed sign pool soldier roof loop monitor burst grace";
+        let mnemonic = Mnemonic::parse_in(Language::English, fifteen).unwrap();
+        assert_eq!(SeedQr::Compact(mnemonic).to_grid(), Err(Error::UnsupportedWordCount(15)));
     }
 
     #[test]
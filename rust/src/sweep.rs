@@ -0,0 +1,538 @@
+//! Sweeping funds from a single private key (WIF or raw hex) into a wallet
+//!
+//! A bare key doesn't say which script it was used with, so we check all the standard
+//! single key scripts: legacy, nested segwit, native segwit and taproot
+
+use bitcoin::{
+    Amount, CompressedPublicKey, EcdsaSighashType, FeeRate, NetworkKind, OutPoint, PrivateKey,
+    Psbt, PublicKey, ScriptBuf, Sequence, TapSighashType, Transaction, TxIn, TxOut, Weight,
+    Witness,
+    absolute::LockTime,
+    key::{Keypair, Secp256k1, TapTweak as _},
+    script::PushBytesBuf,
+    secp256k1::{All, Message, SecretKey},
+    sighash::{Prevouts, SighashCache},
+    transaction::Version,
+};
+use zeroize::Zeroize as _;
+
+/// Single key script types that we look for funds in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, uniffi::Enum)]
+pub enum SweepScriptType {
+    /// P2PKH
+    Legacy,
+    /// P2SH-P2WPKH
+    WrappedSegwit,
+    /// P2WPKH
+    NativeSegwit,
+    /// P2TR key path spend, no script tree
+    Taproot,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error, uniffi::Error)]
+pub enum SweepError {
+    #[error("Not a valid private key: {0}")]
+    InvalidKey(String),
+
+    #[error("Private key is for a different network")]
+    NetworkMismatch,
+
+    #[error("No funds found for this private key")]
+    NoFunds,
+
+    #[error("Amount {0} is too small to cover the fee")]
+    AmountTooSmall(u64),
+
+    #[error("Unable to sign sweep transaction: {0}")]
+    Sign(String),
+}
+
+type Result<T, E = SweepError> = std::result::Result<T, E>;
+
+/// A private key to sweep funds from
+#[derive(Clone, PartialEq, Eq, uniffi::Object)]
+pub struct SweepKey {
+    secret_key: SecretKey,
+    compressed: bool,
+    /// `None` for raw hex keys, which don't encode a network
+    network: Option<NetworkKind>,
+}
+
+/// An unspent output controlled by a [`SweepKey`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SweepUtxo {
+    pub outpoint: OutPoint,
+    pub txout: TxOut,
+    pub script_type: SweepScriptType,
+}
+
+impl SweepKey {
+    /// Only WIF keys, used when we don't know the string is meant to be a private key
+    pub fn try_from_wif(string: &str) -> Result<Self> {
+        let private_key = PrivateKey::from_wif(string.trim())
+            .map_err(|e| SweepError::InvalidKey(e.to_string()))?;
+
+        Ok(Self {
+            secret_key: private_key.inner,
+            compressed: private_key.compressed,
+            network: Some(private_key.network),
+        })
+    }
+
+    /// WIF or raw hex keys, raw hex looks like any other 32 byte hash (a txid, a fingerprint)
+    /// so it's only accepted when the user entered it as a private key to sweep
+    pub fn try_from_wif_or_hex(string: &str) -> Result<Self> {
+        let string = string.trim();
+        if string.len() != 64 || !string.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Self::try_from_wif(string);
+        }
+
+        let mut bytes = hex::decode(string).map_err(|e| SweepError::InvalidKey(e.to_string()))?;
+        let secret_key = SecretKey::from_slice(&bytes);
+        bytes.zeroize();
+
+        let secret_key = secret_key.map_err(|e| SweepError::InvalidKey(e.to_string()))?;
+        Ok(Self { secret_key, compressed: true, network: None })
+    }
+
+    /// Errors if the key was encoded for a different network than the one given
+    pub fn check_network(&self, network: impl Into<NetworkKind>) -> Result<()> {
+        match self.network {
+            Some(key_network) if key_network != network.into() => Err(SweepError::NetworkMismatch),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        let secp = Secp256k1::signing_only();
+        let inner = self.secret_key.public_key(&secp);
+
+        PublicKey { compressed: self.compressed, inner }
+    }
+
+    /// All the scripts this key could have received funds on
+    ///
+    /// Uncompressed keys can only be used with legacy scripts
+    pub fn scripts(&self) -> Vec<(SweepScriptType, ScriptBuf)> {
+        let public_key = self.public_key();
+        let legacy = ScriptBuf::new_p2pkh(&public_key.pubkey_hash());
+
+        let Ok(compressed) = CompressedPublicKey::try_from(public_key) else {
+            return vec![(SweepScriptType::Legacy, legacy)];
+        };
+
+        let secp = Secp256k1::verification_only();
+        let wpkh = ScriptBuf::new_p2wpkh(&compressed.wpubkey_hash());
+        let (x_only, _parity) = public_key.inner.x_only_public_key();
+
+        vec![
+            (SweepScriptType::Legacy, legacy),
+            (SweepScriptType::WrappedSegwit, ScriptBuf::new_p2sh(&wpkh.script_hash())),
+            (SweepScriptType::NativeSegwit, wpkh),
+            (SweepScriptType::Taproot, ScriptBuf::new_p2tr(&secp, x_only, None)),
+        ]
+    }
+
+    /// Build and sign a transaction spending all the utxos to the destination script,
+    /// the fee is taken out of the swept amount
+    pub fn build_sweep_tx(
+        &self,
+        utxos: &[SweepUtxo],
+        destination: ScriptBuf,
+        fee_rate: FeeRate,
+    ) -> Result<Transaction> {
+        if utxos.is_empty() {
+            return Err(SweepError::NoFunds);
+        }
+
+        let total = utxos.iter().map(|utxo| utxo.txout.value).sum::<Amount>();
+        let fee = fee_rate * self.estimate_weight(utxos, &destination);
+
+        let amount = total
+            .checked_sub(fee)
+            .filter(|amount| *amount >= destination.minimal_non_dust())
+            .ok_or(SweepError::AmountTooSmall(total.to_sat()))?;
+
+        let input = utxos
+            .iter()
+            .map(|utxo| TxIn {
+                previous_output: utxo.outpoint,
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                ..Default::default()
+            })
+            .collect();
+
+        let mut tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input,
+            output: vec![TxOut { value: amount, script_pubkey: destination }],
+        };
+
+        self.sign(&mut tx, utxos)?;
+        Ok(tx)
+    }
+
+    /// The signed sweep transaction as a finalized PSBT, with the spent outputs filled in so it
+    /// can go through the same confirm screen as any other spend
+    pub fn build_sweep_psbt(
+        &self,
+        utxos: &[SweepUtxo],
+        destination: ScriptBuf,
+        fee_rate: FeeRate,
+    ) -> Result<Psbt> {
+        let signed = self.build_sweep_tx(utxos, destination, fee_rate)?;
+
+        let mut unsigned = signed.clone();
+        for input in unsigned.input.iter_mut() {
+            input.script_sig = ScriptBuf::new();
+            input.witness = Witness::new();
+        }
+
+        let mut psbt =
+            Psbt::from_unsigned_tx(unsigned).map_err(|e| SweepError::Sign(e.to_string()))?;
+
+        for ((input, signed), utxo) in psbt.inputs.iter_mut().zip(signed.input).zip(utxos) {
+            input.witness_utxo = Some(utxo.txout.clone());
+
+            if !signed.script_sig.is_empty() {
+                input.final_script_sig = Some(signed.script_sig);
+            }
+
+            if !signed.witness.is_empty() {
+                input.final_script_witness = Some(signed.witness);
+            }
+        }
+
+        Ok(psbt)
+    }
+
+    fn sign(&self, tx: &mut Transaction, utxos: &[SweepUtxo]) -> Result<()> {
+        let secp = Secp256k1::new();
+        let public_key = self.public_key();
+        let prevouts = utxos.iter().map(|utxo| utxo.txout.clone()).collect::<Vec<_>>();
+
+        let mut script_sigs = Vec::with_capacity(utxos.len());
+        let mut witnesses = Vec::with_capacity(utxos.len());
+
+        let mut cache = SighashCache::new(&*tx);
+        for (index, utxo) in utxos.iter().enumerate() {
+            let spk = &utxo.txout.script_pubkey;
+            let value = utxo.txout.value;
+
+            let (script_sig, witness) = match utxo.script_type {
+                SweepScriptType::Legacy => {
+                    let sighash = cache
+                        .legacy_signature_hash(index, spk, EcdsaSighashType::All.to_u32())
+                        .map_err(|e| SweepError::Sign(e.to_string()))?;
+
+                    let signature = self.sign_ecdsa(&secp, sighash.as_ref())?;
+                    let script_sig = bitcoin::script::Builder::new()
+                        .push_slice(signature.serialize())
+                        .push_key(&public_key)
+                        .into_script();
+
+                    (script_sig, Witness::new())
+                }
+
+                SweepScriptType::NativeSegwit | SweepScriptType::WrappedSegwit => {
+                    let compressed = self.compressed_public_key()?;
+                    let wpkh = ScriptBuf::new_p2wpkh(&compressed.wpubkey_hash());
+
+                    let sighash = cache
+                        .p2wpkh_signature_hash(index, &wpkh, value, EcdsaSighashType::All)
+                        .map_err(|e| SweepError::Sign(e.to_string()))?;
+
+                    let signature = self.sign_ecdsa(&secp, sighash.as_ref())?;
+                    let witness = Witness::p2wpkh(&signature, &compressed.0);
+
+                    let script_sig = if utxo.script_type == SweepScriptType::WrappedSegwit {
+                        let redeem_script = PushBytesBuf::try_from(wpkh.into_bytes())
+                            .map_err(|e| SweepError::Sign(e.to_string()))?;
+
+                        bitcoin::script::Builder::new().push_slice(redeem_script).into_script()
+                    } else {
+                        ScriptBuf::new()
+                    };
+
+                    (script_sig, witness)
+                }
+
+                SweepScriptType::Taproot => {
+                    let sighash = cache
+                        .taproot_key_spend_signature_hash(
+                            index,
+                            &Prevouts::All(&prevouts),
+                            TapSighashType::Default,
+                        )
+                        .map_err(|e| SweepError::Sign(e.to_string()))?;
+
+                    let message = Message::from_digest_slice(sighash.as_ref())
+                        .map_err(|e| SweepError::Sign(e.to_string()))?;
+
+                    let keypair = Keypair::from_secret_key(&secp, &self.secret_key)
+                        .tap_tweak(&secp, None)
+                        .to_inner();
+
+                    let signature = bitcoin::taproot::Signature {
+                        signature: secp.sign_schnorr_no_aux_rand(&message, &keypair),
+                        sighash_type: TapSighashType::Default,
+                    };
+
+                    (ScriptBuf::new(), Witness::p2tr_key_spend(&signature))
+                }
+            };
+
+            script_sigs.push(script_sig);
+            witnesses.push(witness);
+        }
+
+        for ((input, script_sig), witness) in tx.input.iter_mut().zip(script_sigs).zip(witnesses) {
+            input.script_sig = script_sig;
+            input.witness = witness;
+        }
+
+        Ok(())
+    }
+
+    fn sign_ecdsa(
+        &self,
+        secp: &Secp256k1<All>,
+        sighash: &[u8],
+    ) -> Result<bitcoin::ecdsa::Signature> {
+        let message =
+            Message::from_digest_slice(sighash).map_err(|e| SweepError::Sign(e.to_string()))?;
+
+        let signature = secp.sign_ecdsa(&message, &self.secret_key);
+        Ok(bitcoin::ecdsa::Signature::sighash_all(signature))
+    }
+
+    fn compressed_public_key(&self) -> Result<CompressedPublicKey> {
+        CompressedPublicKey::try_from(self.public_key())
+            .map_err(|_| SweepError::Sign("segwit requires a compressed key".to_string()))
+    }
+
+    /// Estimated weight of the signed sweep transaction, using worst case signature sizes
+    fn estimate_weight(&self, utxos: &[SweepUtxo], destination: &ScriptBuf) -> Weight {
+        let input_vbytes = utxos
+            .iter()
+            .map(|utxo| match utxo.script_type {
+                SweepScriptType::Legacy if self.compressed => 148,
+                SweepScriptType::Legacy => 180,
+                SweepScriptType::WrappedSegwit => 91,
+                SweepScriptType::NativeSegwit => 68,
+                SweepScriptType::Taproot => 58,
+            })
+            .sum::<u64>();
+
+        let has_witness = utxos.iter().any(|utxo| utxo.script_type != SweepScriptType::Legacy);
+
+        // version, locktime, input and output counts, plus the segwit marker and flag
+        let overhead_vbytes = if has_witness { 11 } else { 10 };
+        let output_vbytes = 8 + 1 + destination.len() as u64;
+
+        Weight::from_vb_unchecked(overhead_vbytes + input_vbytes + output_vbytes)
+    }
+}
+
+/// Don't leave the key in memory once the sweep is done
+impl Drop for SweepKey {
+    fn drop(&mut self) {
+        self.secret_key.non_secure_erase();
+    }
+}
+
+impl std::fmt::Debug for SweepKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SweepKey")
+            .field("public_key", &self.public_key())
+            .field("network", &self.network)
+            .finish_non_exhaustive()
+    }
+}
+
+mod ffi {
+    use super::*;
+
+    #[uniffi::export]
+    impl SweepKey {
+        /// From the sweep key entry screen, accepts raw hex keys as well as WIF
+        #[uniffi::constructor(name = "new")]
+        pub fn ffi_new(key: String) -> Result<Self> {
+            Self::try_from_wif_or_hex(&key)
+        }
+
+        /// Hex encoded public key, safe to show to the user
+        #[uniffi::method(name = "publicKey")]
+        pub fn ffi_public_key(&self) -> String {
+            self.public_key().to_string()
+        }
+
+        pub fn is_compressed(&self) -> bool {
+            self.compressed
+        }
+
+        pub fn script_types(&self) -> Vec<SweepScriptType> {
+            self.scripts().into_iter().map(|(script_type, _)| script_type).collect()
+        }
+
+        pub fn is_equal(&self, rhs: &Self) -> bool {
+            self == rhs
+        }
+    }
+
+    #[uniffi::export]
+    fn describe_sweep_error(error: SweepError) -> String {
+        error.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::{Address, Network, Txid, hashes::Hash as _};
+
+    use super::*;
+
+    const COMPRESSED_WIF: &str = "KwdMAjGmerYanjeui5SHS7JkmpZvVipYvB2LJGU1ZxJwYvP98617";
+    const UNCOMPRESSED_WIF: &str = "5HueCGU8rMjxEXxiPuD5BDku4MkFqeZyd4dZ1jvhTVqvbTLvyTJ";
+
+    /// The private key `1`
+    const ONE_KEY_HEX: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+
+    fn address(script: &ScriptBuf) -> String {
+        Address::from_script(script, Network::Bitcoin).unwrap().to_string()
+    }
+
+    fn utxo(key: &SweepKey, script_type: SweepScriptType, sats: u64, vout: u32) -> SweepUtxo {
+        let script_pubkey = key
+            .scripts()
+            .into_iter()
+            .find(|(kind, _)| *kind == script_type)
+            .map(|(_, script)| script)
+            .unwrap();
+
+        SweepUtxo {
+            outpoint: OutPoint::new(Txid::all_zeros(), vout),
+            txout: TxOut { value: Amount::from_sat(sats), script_pubkey },
+            script_type,
+        }
+    }
+
+    #[test]
+    fn test_parse_wif() {
+        let key = SweepKey::try_from_wif(COMPRESSED_WIF).unwrap();
+        assert!(key.compressed);
+        assert_eq!(key.network, Some(NetworkKind::Main));
+        assert_eq!(key.scripts().len(), 4);
+
+        let key = SweepKey::try_from_wif(UNCOMPRESSED_WIF).unwrap();
+        assert!(!key.compressed);
+        assert_eq!(key.scripts().len(), 1);
+        assert_eq!(key.scripts()[0].0, SweepScriptType::Legacy);
+
+        assert!(SweepKey::try_from_wif("not a key").is_err());
+    }
+
+    #[test]
+    fn test_parse_raw_hex_key() {
+        let key = SweepKey::try_from_wif_or_hex(ONE_KEY_HEX).unwrap();
+        assert!(key.compressed);
+        assert_eq!(key.network, None);
+        assert!(key.check_network(NetworkKind::Test).is_ok());
+
+        let zero_key = "0".repeat(64);
+        assert!(SweepKey::try_from_wif_or_hex(&zero_key).is_err());
+
+        // scanned or pasted strings are only treated as keys when they are WIF
+        assert!(SweepKey::try_from_wif(ONE_KEY_HEX).is_err());
+        assert!(SweepKey::try_from_wif_or_hex(COMPRESSED_WIF).is_ok());
+    }
+
+    #[test]
+    fn test_network_mismatch() {
+        let key = SweepKey::try_from_wif(COMPRESSED_WIF).unwrap();
+        assert!(key.check_network(NetworkKind::Main).is_ok());
+        assert_eq!(key.check_network(NetworkKind::Test), Err(SweepError::NetworkMismatch));
+    }
+
+    #[test]
+    fn test_scripts_for_key_one() {
+        let key = SweepKey::try_from_wif_or_hex(ONE_KEY_HEX).unwrap();
+        let scripts = key.scripts();
+
+        assert_eq!(address(&scripts[0].1), "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH");
+        assert_eq!(address(&scripts[1].1), "3JvL6Ymt8MVWiCNHC7oWU6nLeHNJKLZGLN");
+        assert_eq!(address(&scripts[2].1), "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4");
+        assert!(scripts[3].1.is_p2tr());
+    }
+
+    #[test]
+    fn test_build_sweep_tx() {
+        let key = SweepKey::try_from_wif_or_hex(ONE_KEY_HEX).unwrap();
+        let utxos = vec![
+            utxo(&key, SweepScriptType::Legacy, 10_000, 0),
+            utxo(&key, SweepScriptType::WrappedSegwit, 10_000, 1),
+            utxo(&key, SweepScriptType::NativeSegwit, 10_000, 2),
+            utxo(&key, SweepScriptType::Taproot, 10_000, 3),
+        ];
+
+        let destination = utxos[2].txout.script_pubkey.clone();
+        let fee_rate = FeeRate::from_sat_per_vb_unchecked(2);
+        let tx = key.build_sweep_tx(&utxos, destination.clone(), fee_rate).unwrap();
+
+        assert_eq!(tx.input.len(), 4);
+        assert_eq!(tx.output.len(), 1);
+        assert_eq!(tx.output[0].script_pubkey, destination);
+
+        // legacy input signs in the script sig only
+        assert!(!tx.input[0].script_sig.is_empty());
+        assert!(tx.input[0].witness.is_empty());
+
+        // wrapped segwit has both
+        assert!(!tx.input[1].script_sig.is_empty());
+        assert_eq!(tx.input[1].witness.len(), 2);
+
+        // native segwit and taproot only have a witness
+        assert!(tx.input[2].script_sig.is_empty());
+        assert_eq!(tx.input[2].witness.len(), 2);
+        assert!(tx.input[3].script_sig.is_empty());
+        assert_eq!(tx.input[3].witness.len(), 1);
+
+        // estimate should never be lower than the actual size
+        let fee = Amount::from_sat(40_000) - tx.output[0].value;
+        assert!(fee >= fee_rate * tx.weight());
+    }
+
+    #[test]
+    fn test_build_sweep_psbt() {
+        let key = SweepKey::try_from_wif_or_hex(ONE_KEY_HEX).unwrap();
+        let utxos = vec![
+            utxo(&key, SweepScriptType::Legacy, 10_000, 0),
+            utxo(&key, SweepScriptType::WrappedSegwit, 10_000, 1),
+            utxo(&key, SweepScriptType::Taproot, 10_000, 2),
+        ];
+
+        let destination = utxos[0].txout.script_pubkey.clone();
+        let fee_rate = FeeRate::from_sat_per_vb_unchecked(2);
+
+        let tx = key.build_sweep_tx(&utxos, destination.clone(), fee_rate).unwrap();
+        let psbt = key.build_sweep_psbt(&utxos, destination, fee_rate).unwrap();
+
+        assert_eq!(psbt.fee().unwrap(), Amount::from_sat(30_000) - tx.output[0].value);
+        assert_eq!(psbt.extract_tx_unchecked_fee_rate(), tx);
+    }
+
+    #[test]
+    fn test_sweep_too_small() {
+        let key = SweepKey::try_from_wif(COMPRESSED_WIF).unwrap();
+        let utxos = vec![utxo(&key, SweepScriptType::NativeSegwit, 500, 0)];
+        let destination = utxos[0].txout.script_pubkey.clone();
+
+        let result =
+            key.build_sweep_tx(&utxos, destination.clone(), FeeRate::from_sat_per_vb_unchecked(10));
+        assert_eq!(result, Err(SweepError::AmountTooSmall(500)));
+
+        let result = key.build_sweep_tx(&[], destination, FeeRate::from_sat_per_vb_unchecked(1));
+        assert_eq!(result, Err(SweepError::NoFunds));
+    }
+}
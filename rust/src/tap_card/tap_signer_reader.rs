@@ -72,7 +72,7 @@ pub struct TapSignerReader {
 pub enum TapSignerCmd {
     Setup(Arc<SetupCmd>),
     Backup { pin: String },
-    Derive { pin: String },
+    Derive { pin: String, account: u32 },
     Change { current_pin: String, new_pin: String },
     Sign { psbt: Arc<Psbt>, pin: String },
 }
@@ -192,8 +192,8 @@ impl TapSignerReader {
                 Ok(TapSignerResponse::Backup(response))
             }
 
-            TapSignerCmd::Derive { pin } => {
-                let response = self.derive(&pin).await?;
+            TapSignerCmd::Derive { pin, account } => {
+                let response = self.derive(&pin, account).await?;
                 Ok(TapSignerResponse::Import(response))
             }
 
@@ -318,7 +318,7 @@ impl TapSignerReader {
     }
 
     async fn derive_and_change(&self, cmd: Arc<SetupCmd>, backup: Vec<u8>) -> SetupCmdResponse {
-        let derive_info = match self.derive(&cmd.factory_pin).await {
+        let derive_info = match self.derive(&cmd.factory_pin, 0).await {
             Ok(derive) => derive,
             Err(error) => {
                 let response = SetupCmdResponse::ContinueFromBackup(ContinueFromBackup {
@@ -382,12 +382,12 @@ impl TapSignerReader {
         Ok(())
     }
 
-    async fn derive(&self, pin: &str) -> Result<DeriveInfo, Error> {
-        debug!("starting derive");
+    async fn derive(&self, pin: &str, account: u32) -> Result<DeriveInfo, Error> {
+        debug!("starting derive for account {account}");
 
         let path: [u32; 3] = match self.network {
-            Network::Bitcoin => [84, 0, 0],
-            _ => [84, 1, 0],
+            Network::Bitcoin => [84, 0, account],
+            _ => [84, 1, account],
         };
 
         let derive_response = self.reader.lock().await.derive(&path, pin).await?;
@@ -59,6 +59,8 @@ pub struct TransactionDetails {
     pub network: Network,
     // for outgoing transactions we might have a change address
     pub change_address: Option<Address>,
+    // signals BIP125 replaceability, so its fee can be bumped while unconfirmed
+    pub is_rbf: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, uniffi::Enum)]
@@ -84,6 +86,7 @@ impl TransactionDetails {
 
         let address = Address::try_new(&tx, wallet)?;
         let pending_or_confirmed = PendingOrConfirmed::new(chain_postition);
+        let is_rbf = tx_details.is_explicitly_rbf();
 
         let change_address = match sent_and_received.direction {
             TransactionDirection::Incoming => None,
@@ -142,6 +145,7 @@ impl TransactionDetails {
             input_indexes,
             output_indexes,
             change_address,
+            is_rbf,
         };
 
         Ok(me)
@@ -292,6 +296,12 @@ impl TransactionDetails {
         self.pending_or_confirmed.is_confirmed()
     }
 
+    /// Only unconfirmed outgoing transactions that signal RBF can have their fee bumped
+    #[uniffi::method]
+    pub fn can_bump_fee(&self) -> bool {
+        self.is_sent() && !self.is_confirmed() && self.is_rbf
+    }
+
     #[uniffi::method]
     pub fn confirmation_date_time(&self) -> Option<String> {
         let confirm_time = match &self.pending_or_confirmed {
@@ -325,6 +335,7 @@ impl TransactionDetails {
             Network::Testnet => format!("https://mempool.space/testnet/tx/{}", self.tx_id.0),
             Network::Testnet4 => format!("https://mempool.space/testnet4/tx/{}", self.tx_id.0),
             Network::Signet => format!("https://mutinynet.com/tx/{}", self.tx_id.0),
+            Network::Regtest => format!("http://127.0.0.1:3002/tx/{}", self.tx_id.0),
         }
     }
 
@@ -377,6 +388,7 @@ impl TransactionDetails {
             input_indexes: vec![],
             output_indexes: vec![],
             change_address: None,
+            is_rbf: true,
         }
     }
 
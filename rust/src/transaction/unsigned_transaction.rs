@@ -1,4 +1,4 @@
-use cove_types::{ConfirmDetails, WalletId};
+use cove_types::{ConfirmDetails, WalletId, confirm::AddressAndAmount};
 
 use crate::database::unsigned_transactions::UnsignedTransactionRecord;
 
@@ -35,6 +35,10 @@ impl UnsignedTransaction {
     pub fn sending_amount(&self) -> Amount {
         self.confirm_details.sending_amount()
     }
+
+    pub fn recipients(&self) -> Vec<AddressAndAmount> {
+        self.confirm_details.recipients()
+    }
 }
 
 impl From<UnsignedTransactionRecord> for UnsignedTransaction {
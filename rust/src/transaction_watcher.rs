@@ -70,6 +70,7 @@ impl TransactionWatcher {
             Network::Bitcoin => Duration::from_secs(20),
             Network::Testnet | Network::Testnet4 => Duration::from_secs(20),
             Network::Signet => Duration::from_secs(10),
+            Network::Regtest => Duration::from_secs(5),
         };
 
         self.addr.send_fut(async move {
@@ -0,0 +1,432 @@
+//! Uniform Resources (BCR-2020-005), the QR format Keystone, Passport, Jade, SeedSigner and
+//! Foundation devices use, ex. `ur:crypto-psbt/12-3/lpbn...`
+//!
+//! Animated URs are fountain coded, so parts can be scanned in any order and missed parts get
+//! covered by later ones
+
+mod bytewords;
+mod cbor;
+mod fountain;
+mod registry;
+
+use std::sync::Arc;
+
+use bdk_wallet::{bitcoin::Psbt, descriptor::ExtendedDescriptor};
+use bytewords::BytewordsError;
+use cbor::{Cbor, CborError};
+use cove_types::ConfirmDetails;
+use fountain::{FountainDecoder, FountainEncoder, FountainError, FountainPart};
+
+pub use registry::{UrPayload, finalized_transaction};
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum UrError {
+    #[error("not a UR")]
+    NotUr,
+
+    #[error("invalid UR type: {0}")]
+    InvalidType(String),
+
+    #[error("invalid sequence: {0}")]
+    InvalidSequence(String),
+
+    #[error("part is for a {0}, not a {1}")]
+    MismatchedType(String, String),
+
+    #[error("invalid bytewords: {0}")]
+    InvalidBytewords(#[from] BytewordsError),
+
+    #[error("invalid CBOR: {0}")]
+    InvalidCbor(#[from] CborError),
+
+    #[error(transparent)]
+    Fountain(#[from] FountainError),
+
+    #[error("animated UR, scan all the parts")]
+    Incomplete,
+
+    #[error("unsupported UR type: {0}")]
+    UnsupportedType(String),
+
+    #[error("invalid payload: {0}")]
+    InvalidPayload(String),
+
+    #[error("PSBT is not fully signed: {0}")]
+    UnsignedPsbt(String),
+}
+
+type Error = UrError;
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// A single scanned UR
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UrPart {
+    pub ur_type: String,
+    /// `(seq_num, seq_len)`, `None` for single part URs
+    pub sequence: Option<(u32, u32)>,
+    pub body: Vec<u8>,
+}
+
+impl UrPart {
+    pub fn is_ur(string: &str) -> bool {
+        string.trim().get(..3).is_some_and(|prefix| prefix.eq_ignore_ascii_case("ur:"))
+    }
+
+    pub fn try_from_str(string: &str) -> Result<Self> {
+        // QR codes use uppercase for the smaller alphanumeric mode
+        let string = string.trim().to_ascii_lowercase();
+        let rest = string.strip_prefix("ur:").ok_or(Error::NotUr)?;
+
+        let components = rest.split('/').collect::<Vec<_>>();
+        let (ur_type, sequence, body) = match components.as_slice() {
+            [ur_type, body] => (*ur_type, None, *body),
+            [ur_type, sequence, body] => (*ur_type, Some(parse_sequence(sequence)?), *body),
+            _ => return Err(Error::InvalidSequence(rest.to_string())),
+        };
+
+        let valid_type = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-';
+        if ur_type.is_empty() || !ur_type.chars().all(valid_type) {
+            return Err(Error::InvalidType(ur_type.to_string()));
+        }
+
+        let body = bytewords::decode(body)?;
+        Ok(Self { ur_type: ur_type.to_string(), sequence, body })
+    }
+}
+
+fn parse_sequence(sequence: &str) -> Result<(u32, u32)> {
+    let invalid = || Error::InvalidSequence(sequence.to_string());
+
+    let (seq_num, seq_len) = sequence.split_once('-').ok_or_else(invalid)?;
+    let seq_num = seq_num.parse::<u32>().map_err(|_| invalid())?;
+    let seq_len = seq_len.parse::<u32>().map_err(|_| invalid())?;
+
+    if seq_num == 0 || seq_len == 0 {
+        return Err(invalid());
+    }
+
+    Ok((seq_num, seq_len))
+}
+
+/// Joins the parts of a UR, single part URs are complete after the first part
+#[derive(Debug, Default)]
+pub struct UrDecoder {
+    ur_type: Option<String>,
+    fountain: FountainDecoder,
+    single: Option<Vec<u8>>,
+}
+
+impl UrDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn receive(&mut self, ur: &str) -> Result<()> {
+        let part = UrPart::try_from_str(ur)?;
+
+        if let Some(ur_type) = self.ur_type.as_ref().filter(|ur_type| **ur_type != part.ur_type) {
+            return Err(Error::MismatchedType(part.ur_type, ur_type.clone()));
+        }
+
+        match part.sequence {
+            None => self.single = Some(part.body),
+            Some((seq_num, seq_len)) => {
+                let fountain_part = FountainPart::try_from_cbor(&part.body)?;
+                if fountain_part.seq_num != seq_num || fountain_part.seq_len != seq_len as usize {
+                    return Err(Error::InvalidSequence(format!("{seq_num}-{seq_len}")));
+                }
+
+                // the fountain decoder starts over on a bad checksum, so does the type
+                if let Err(error) = self.fountain.receive(fountain_part) {
+                    if error == FountainError::InvalidChecksum {
+                        *self = Self::default();
+                    }
+
+                    return Err(error.into());
+                }
+            }
+        }
+
+        self.ur_type.get_or_insert(part.ur_type);
+        Ok(())
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.message().is_some()
+    }
+
+    pub fn ur_type(&self) -> Option<&str> {
+        self.ur_type.as_deref()
+    }
+
+    /// Number of parts in the sequence, 1 for single part URs
+    pub fn seq_len(&self) -> usize {
+        self.fountain.seq_len().unwrap_or(1)
+    }
+
+    /// Estimate, a mixed part can fill in more than one missing fragment
+    pub fn parts_left(&self) -> usize {
+        match self.is_complete() {
+            true => 0,
+            false => self.fountain.parts_left(),
+        }
+    }
+
+    /// The CBOR message once all the parts are in
+    pub fn message(&self) -> Option<&[u8]> {
+        self.single.as_deref().or_else(|| self.fountain.message())
+    }
+
+    pub fn payload(&self) -> Result<UrPayload> {
+        let (Some(ur_type), Some(message)) = (self.ur_type(), self.message()) else {
+            return Err(Error::Incomplete);
+        };
+
+        UrPayload::try_new(ur_type, message)
+    }
+}
+
+/// Decode a single part UR
+pub fn decode(ur: &str) -> Result<UrPayload> {
+    let mut decoder = UrDecoder::new();
+    decoder.receive(ur)?;
+    decoder.payload()
+}
+
+// MARK: encoding
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, uniffi::Enum)]
+pub enum PsbtUrType {
+    /// `ur:crypto-psbt`, what most signers expect
+    CryptoPsbt,
+    /// `ur:psbt`, the newer registry name
+    Psbt,
+}
+
+impl PsbtUrType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::CryptoPsbt => "crypto-psbt",
+            Self::Psbt => "psbt",
+        }
+    }
+}
+
+/// Split a CBOR message into UR frames to show in a loop, single part if it fits in one fragment
+///
+/// Fountain coded URs get twice as many frames as fragments, the mixed frames fill in any the
+/// scanner missed without waiting for the whole loop again
+pub fn encode(ur_type: &str, message: &[u8], max_fragment_len: usize) -> Vec<String> {
+    let mut encoder = FountainEncoder::new(message, max_fragment_len);
+    let seq_len = encoder.seq_len();
+
+    if seq_len <= 1 {
+        return vec![format!("ur:{ur_type}/{}", bytewords::encode(message))];
+    }
+
+    (0..seq_len * 2)
+        .map(|_| {
+            let part = encoder.next_part();
+            let body = bytewords::encode(&part.to_cbor());
+            format!("ur:{ur_type}/{}-{seq_len}/{body}", part.seq_num)
+        })
+        .collect()
+}
+
+pub fn psbt_to_ur(psbt: &Psbt, ur_type: PsbtUrType, max_fragment_len: usize) -> Vec<String> {
+    let message = Cbor::Bytes(psbt.serialize()).encode();
+    encode(ur_type.as_str(), &message, max_fragment_len)
+}
+
+/// `ur:crypto-output` for the descriptor, use the receive descriptor, signers derive the change
+pub fn descriptor_to_output_ur(descriptor: &ExtendedDescriptor) -> Result<String> {
+    let message = registry::output_cbor(descriptor)?.encode();
+    Ok(format!("ur:crypto-output/{}", bytewords::encode(&message)))
+}
+
+/// `ur:crypto-account` for a single sig descriptor
+pub fn descriptor_to_account_ur(descriptor: &ExtendedDescriptor) -> Result<String> {
+    let message = registry::account_cbor(descriptor)?.encode();
+    Ok(format!("ur:crypto-account/{}", bytewords::encode(&message)))
+}
+
+/// Re-split a single part UR into animated frames
+pub fn split(ur: &str, max_fragment_len: usize) -> Result<Vec<String>> {
+    let part = UrPart::try_from_str(ur)?;
+    if part.sequence.is_some() {
+        return Err(Error::InvalidSequence("already split".to_string()));
+    }
+
+    Ok(encode(&part.ur_type, &part.body, max_fragment_len))
+}
+
+// MARK: ffi
+
+#[uniffi::export]
+fn confirm_details_psbt_to_ur(
+    details: Arc<ConfirmDetails>,
+    ur_type: PsbtUrType,
+    max_fragment_len: u32,
+) -> Vec<String> {
+    psbt_to_ur(&details.psbt.0, ur_type, max_fragment_len as usize)
+}
+
+/// UR parts for any PSBT, ex. the BIP322 message signing PSBT
+#[uniffi::export]
+fn psbt_ur_parts(
+    psbt: Arc<crate::psbt::Psbt>,
+    ur_type: PsbtUrType,
+    max_fragment_len: u32,
+) -> Vec<String> {
+    psbt_to_ur(&psbt.0, ur_type, max_fragment_len as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr as _;
+
+    use super::*;
+    use fountain::Xoshiro256;
+
+    // reference test vector, 256 random bytes seeded with "Wolf" split into 30 byte fragments
+    const PARTS: [&str; 20] = [
+        "ur:bytes/1-9/lpadascfadaxcywenbpljkhdcahkadaemejtswhhylkepmykhhtsytsnoyoyaxaedsuttydmmhhpktpmsrjtdkgslpgh",
+        "ur:bytes/2-9/lpaoascfadaxcywenbpljkhdcagwdpfnsboxgwlbaawzuefywkdplrsrjynbvygabwjldapfcsgmghhkhstlrdcxaefz",
+        "ur:bytes/3-9/lpaxascfadaxcywenbpljkhdcahelbknlkuejnbadmssfhfrdpsbiegecpasvssovlgeykssjykklronvsjksopdzmol",
+        "ur:bytes/4-9/lpaaascfadaxcywenbpljkhdcasotkhemthydawydtaxneurlkosgwcekonertkbrlwmplssjtammdplolsbrdzcrtas",
+        "ur:bytes/5-9/lpahascfadaxcywenbpljkhdcatbbdfmssrkzmcwnezelennjpfzbgmuktrhtejscktelgfpdlrkfyfwdajldejokbwf",
+        "ur:bytes/6-9/lpamascfadaxcywenbpljkhdcackjlhkhybssklbwefectpfnbbectrljectpavyrolkzczcpkmwidmwoxkilghdsowp",
+        "ur:bytes/7-9/lpatascfadaxcywenbpljkhdcavszmwnjkwtclrtvaynhpahrtoxmwvwatmedibkaegdosftvandiodagdhthtrlnnhy",
+        "ur:bytes/8-9/lpayascfadaxcywenbpljkhdcadmsponkkbbhgsoltjntegepmttmoonftnbuoiyrehfrtsabzsttorodklubbuyaetk",
+        "ur:bytes/9-9/lpasascfadaxcywenbpljkhdcajskecpmdckihdyhphfotjojtfmlnwmadspaxrkytbztpbauotbgtgtaeaevtgavtny",
+        "ur:bytes/10-9/lpbkascfadaxcywenbpljkhdcahkadaemejtswhhylkepmykhhtsytsnoyoyaxaedsuttydmmhhpktpmsrjtwdkiplzs",
+        "ur:bytes/11-9/lpbdascfadaxcywenbpljkhdcahelbknlkuejnbadmssfhfrdpsbiegecpasvssovlgeykssjykklronvsjkvetiiapk",
+        "ur:bytes/12-9/lpbnascfadaxcywenbpljkhdcarllaluzmdmgstospeyiefmwejlwtpedamktksrvlcygmzemovovllarodtmtbnptrs",
+        "ur:bytes/13-9/lpbtascfadaxcywenbpljkhdcamtkgtpknghchchyketwsvwgwfdhpgmgtylctotzopdrpayoschcmhplffziachrfgd",
+        "ur:bytes/14-9/lpbaascfadaxcywenbpljkhdcapazewnvonnvdnsbyleynwtnsjkjndeoldydkbkdslgjkbbkortbelomueekgvstegt",
+        "ur:bytes/15-9/lpbsascfadaxcywenbpljkhdcaynmhpddpzmversbdqdfyrehnqzlugmjzmnmtwmrouohtstgsbsahpawkditkckynwt",
+        "ur:bytes/16-9/lpbeascfadaxcywenbpljkhdcawygekobamwtlihsnpalnsghenskkiynthdzotsimtojetprsttmukirlrsbtamjtpd",
+        "ur:bytes/17-9/lpbyascfadaxcywenbpljkhdcamklgftaxykpewyrtqzhydntpnytyisincxmhtbceaykolduortotiaiaiafhiaoyce",
+        "ur:bytes/18-9/lpbgascfadaxcywenbpljkhdcahkadaemejtswhhylkepmykhhtsytsnoyoyaxaedsuttydmmhhpktpmsrjtntwkbkwy",
+        "ur:bytes/19-9/lpbwascfadaxcywenbpljkhdcadekicpaajootjzpsdrbalpeywllbdsnbinaerkurspbncxgslgftvtsrjtksplcpeo",
+        "ur:bytes/20-9/lpbbascfadaxcywenbpljkhdcayapmrleeleaxpasfrtrdkncffwjyjzgyetdmlewtkpktgllepfrltataztksmhkbot",
+    ];
+
+    fn message() -> Vec<u8> {
+        let mut rng = Xoshiro256::new(b"Wolf");
+        (0..256).map(|_| rng.next_int(0, 255) as u8).collect()
+    }
+
+    #[test]
+    fn test_decode_in_order() {
+        let mut decoder = UrDecoder::new();
+        for (index, part) in PARTS[..9].iter().enumerate() {
+            decoder.receive(part).unwrap();
+            assert_eq!(decoder.parts_left(), 8 - index);
+        }
+
+        assert!(decoder.is_complete());
+        assert_eq!(decoder.ur_type(), Some("bytes"));
+        assert_eq!(decoder.payload().unwrap(), UrPayload::Bytes(message()));
+    }
+
+    #[test]
+    fn test_decode_with_missing_parts() {
+        let mut decoder = UrDecoder::new();
+
+        // missing the 4th fragment, mixed part 13 fills it in
+        for part in PARTS.iter().skip(9).chain(&PARTS[..3]).chain(&PARTS[4..9]) {
+            decoder.receive(&part.to_uppercase()).unwrap();
+            if decoder.is_complete() {
+                break;
+            }
+        }
+
+        assert!(decoder.is_complete());
+        assert_eq!(decoder.payload().unwrap(), UrPayload::Bytes(message()));
+    }
+
+    #[test]
+    fn test_duplicate_and_mismatched_parts() {
+        let mut decoder = UrDecoder::new();
+        decoder.receive(PARTS[0]).unwrap();
+        decoder.receive(PARTS[0]).unwrap();
+
+        assert_eq!(decoder.seq_len(), 9);
+        assert_eq!(decoder.parts_left(), 8);
+        assert_eq!(decoder.payload(), Err(Error::Incomplete));
+
+        let other_type = PARTS[1].replace("ur:bytes", "ur:crypto-psbt");
+        assert!(matches!(decoder.receive(&other_type), Err(Error::MismatchedType(..))));
+
+        let wrong_sequence = PARTS[1].replace("/2-9/", "/3-9/");
+        assert!(matches!(decoder.receive(&wrong_sequence), Err(Error::InvalidSequence(_))));
+    }
+
+    // unsigned psbt from BIP174
+    const PSBT: &str = "cHNidP8BAHUCAAAAASaBcTce3/KF6Tet7qSze3gADAVmy7OtZGQXE8pCFxv2AAAAAAD+////AtPf9QUAAAAAGXapFNDFmQPFusKGh2DpD9UhpGZap2UgiKwA4fUFAAAAABepFDVF5uM7gyxHBQ8k0+65PJwDlIvHh7MuEwAAAQD9pQEBAAAAAAECiaPHHqtNIOA3G7ukzGmPopXJRjr6Ljl/hTPMti+VZ+UBAAAAFxYAFL4Y0VKpsBIDna89p95PUzSe7LmF/////4b4qkOnHf8USIk6UwpyN+9rRgi7st0tAXHmOuxqSJC0AQAAABcWABT+Pp7xp0XpdNkCxDVZQ6vLNL1TU/////8CAMLrCwAAAAAZdqkUhc/xCX/Z4Ai7NK9wnGIZeziXikiIrHL++E4sAAAAF6kUM5cluiHv1irHU6m80GfWx6ajnQWHAkcwRAIgJxK+IuAnDzlPVoMR3HyppolwuAJf3TskAinwf4pfOiQCIAGLONfc0xTnNMkna9b7QPZzMlvEuqFEyADS8vAtsnZcASED0uFWdJQbrUqZY3LLh+GFbTZSYG2YVi/jnF6efkE/IQUCSDBFAiEA0SuFLYXc2WHS9fSrZgZU327tzHlMDDPOXMMJ/7X85Y0CIGczio4OFyXBl/saiK9Z9R5E5CVbIBZ8hoQDHAXR8lkqASECI7cr7vCWXRC+B3jv7NYfysb3mk6haTkzgHNEZPhPKrMAAAAAAAAA";
+
+    #[test]
+    fn test_encode_matches_reference() {
+        let message = Cbor::Bytes(message()).encode();
+        let frames = encode("bytes", &message, 30);
+
+        assert_eq!(frames.len(), 18);
+        assert_eq!(frames, PARTS[..18]);
+    }
+
+    #[test]
+    fn test_psbt_round_trip() {
+        let psbt = Psbt::from_str(PSBT).unwrap();
+
+        let ur_types = [(PsbtUrType::CryptoPsbt, "crypto-psbt"), (PsbtUrType::Psbt, "psbt")];
+        for (ur_type, name) in ur_types {
+            let frames = psbt_to_ur(&psbt, ur_type, 100);
+            assert!(frames.len() > 2);
+            assert!(frames[0].starts_with(&format!("ur:{name}/1-")));
+
+            let mut decoder = UrDecoder::new();
+            for frame in frames.iter().skip(1) {
+                decoder.receive(frame).unwrap();
+            }
+
+            assert_eq!(decoder.payload().unwrap(), UrPayload::Psbt(psbt.clone()));
+        }
+    }
+
+    #[test]
+    fn test_descriptor_round_trip() {
+        let multipath = "wpkh([73c5da0a/84'/0'/0']xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V/<0;1>/*)";
+        let external = ExtendedDescriptor::from_str(&multipath.replace("<0;1>", "0")).unwrap();
+        let expected = UrPayload::Descriptor(multipath.replace('\'', "h"));
+
+        let output = descriptor_to_output_ur(&external).unwrap();
+        assert!(output.starts_with("ur:crypto-output/"));
+        assert_eq!(decode(&output).unwrap(), expected);
+
+        let account = descriptor_to_account_ur(&external).unwrap();
+        assert_eq!(decode(&account).unwrap(), expected);
+
+        // animated
+        let frames = split(&account, 20).unwrap();
+        let mut decoder = UrDecoder::new();
+        frames.iter().for_each(|frame| decoder.receive(frame).unwrap());
+        assert_eq!(decoder.payload().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_parse_part() {
+        let part = UrPart::try_from_str(" UR:BYTES/AEADAOLAZMJENDEOTI ").unwrap();
+        assert_eq!(part.ur_type, "bytes");
+        assert_eq!(part.sequence, None);
+        assert_eq!(part.body, [0, 1, 2, 128, 255]);
+
+        assert!(UrPart::is_ur("UR:BYTES/AEADAOLAZMJENDEOTI"));
+        assert!(!UrPart::is_ur("bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"));
+        assert_eq!(UrPart::try_from_str("bytes/aeadaolazmjendeoti"), Err(Error::NotUr));
+        assert!(matches!(
+            UrPart::try_from_str("ur:bytes/0-9/aeadaolazmjendeoti"),
+            Err(Error::InvalidSequence(_))
+        ));
+    }
+}
@@ -0,0 +1,126 @@
+//! Bytewords (BCR-2020-012), the bytes to letters encoding used in the body of a UR
+//!
+//! URs use the minimal style, the first and last letter of each word, with a CRC32 checksum
+//! appended before encoding
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BytewordsError {
+    #[error("invalid length")]
+    InvalidLength,
+
+    #[error("invalid word: {0}")]
+    InvalidWord(String),
+
+    #[error("checksum mismatch")]
+    InvalidChecksum,
+}
+
+type Error = BytewordsError;
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+const WORDS: [&str; 256] = [
+    "able", "acid", "also", "apex", "aqua", "arch", "atom", "aunt", "away", "axis", "back", "bald",
+    "barn", "belt", "beta", "bias", "blue", "body", "brag", "brew", "bulb", "buzz", "calm", "cash",
+    "cats", "chef", "city", "claw", "code", "cola", "cook", "cost", "crux", "curl", "cusp", "cyan",
+    "dark", "data", "days", "deli", "dice", "diet", "door", "down", "draw", "drop", "drum", "dull",
+    "duty", "each", "easy", "echo", "edge", "epic", "even", "exam", "exit", "eyes", "fact", "fair",
+    "fern", "figs", "film", "fish", "fizz", "flap", "flew", "flux", "foxy", "free", "frog", "fuel",
+    "fund", "gala", "game", "gear", "gems", "gift", "girl", "glow", "good", "gray", "grim", "guru",
+    "gush", "gyro", "half", "hang", "hard", "hawk", "heat", "help", "high", "hill", "holy", "hope",
+    "horn", "huts", "iced", "idea", "idle", "inch", "inky", "into", "iris", "iron", "item", "jade",
+    "jazz", "join", "jolt", "jowl", "judo", "jugs", "jump", "junk", "jury", "keep", "keno", "kept",
+    "keys", "kick", "kiln", "king", "kite", "kiwi", "knob", "lamb", "lava", "lazy", "leaf", "legs",
+    "liar", "limp", "lion", "list", "logo", "loud", "love", "luau", "luck", "lung", "main", "many",
+    "math", "maze", "memo", "menu", "meow", "mild", "mint", "miss", "monk", "nail", "navy", "need",
+    "news", "next", "noon", "note", "numb", "obey", "oboe", "omit", "onyx", "open", "oval", "owls",
+    "paid", "part", "peck", "play", "plus", "poem", "pool", "pose", "puff", "puma", "purr", "quad",
+    "quiz", "race", "ramp", "real", "redo", "rich", "road", "rock", "roof", "ruby", "ruin", "runs",
+    "rust", "safe", "saga", "scar", "sets", "silk", "skew", "slot", "soap", "solo", "song", "stub",
+    "surf", "swan", "taco", "task", "taxi", "tent", "tied", "time", "tiny", "toil", "tomb", "toys",
+    "trip", "tuna", "twin", "ugly", "undo", "unit", "urge", "user", "vast", "very", "veto", "vial",
+    "vibe", "view", "visa", "void", "vows", "wall", "wand", "warm", "wasp", "wave", "waxy", "webs",
+    "what", "when", "whiz", "wolf", "work", "yank", "yawn", "yell", "yoga", "yurt", "zaps", "zero",
+    "zest", "zinc", "zone", "zoom",
+];
+
+/// Encode bytes into minimal bytewords, ex. `"lpadas..."`
+pub fn encode(data: &[u8]) -> String {
+    let checksum = crc32(data).to_be_bytes();
+
+    data.iter()
+        .chain(checksum.iter())
+        .flat_map(|byte| {
+            let word = WORDS[*byte as usize].as_bytes();
+            [word[0] as char, word[3] as char]
+        })
+        .collect()
+}
+
+/// Decode minimal bytewords and verify the checksum
+pub fn decode(string: &str) -> Result<Vec<u8>> {
+    let string = string.as_bytes();
+    if string.len() % 2 != 0 || string.len() < 10 {
+        return Err(Error::InvalidLength);
+    }
+
+    let mut bytes = string
+        .chunks(2)
+        .map(|pair| {
+            let (first, last) = (pair[0].to_ascii_lowercase(), pair[1].to_ascii_lowercase());
+            WORDS
+                .iter()
+                .position(|word| word.as_bytes()[0] == first && word.as_bytes()[3] == last)
+                .map(|index| index as u8)
+                .ok_or_else(|| Error::InvalidWord(String::from_utf8_lossy(pair).to_string()))
+        })
+        .collect::<Result<Vec<u8>>>()?;
+
+    let checksum = bytes.split_off(bytes.len() - 4);
+    if crc32(&bytes).to_be_bytes() != checksum.as_slice() {
+        return Err(Error::InvalidChecksum);
+    }
+
+    Ok(bytes)
+}
+
+/// CRC32 (ISO-HDLC), same as zlib
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b"Hello, world!"), 0xebe6_c6e6);
+        assert_eq!(crc32(b"Wolf"), 0x598c_84dc);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let data = [0, 1, 2, 128, 255];
+        let encoded = encode(&data);
+
+        assert_eq!(encoded, "aeadaolazmjendeoti");
+        assert_eq!(decode(&encoded).unwrap(), data);
+        assert_eq!(decode(&encoded.to_uppercase()).unwrap(), data);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!(decode("adadaolazmjendeoti"), Err(Error::InvalidChecksum));
+        assert_eq!(decode("aeadaolazmjendeot"), Err(Error::InvalidLength));
+        assert_eq!(decode("xxadaolazmjendeoti"), Err(Error::InvalidWord("xx".into())));
+    }
+}
@@ -0,0 +1,280 @@
+//! Just enough CBOR (RFC 8949) for the UR registry types and fountain parts
+//!
+//! Only definite lengths are supported, floats are rejected
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cbor {
+    Unsigned(u64),
+    Negative(u64),
+    Bytes(Vec<u8>),
+    Text(String),
+    Array(Vec<Cbor>),
+    Map(Vec<(Cbor, Cbor)>),
+    Tag(u64, Box<Cbor>),
+    Bool(bool),
+    Null,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CborError {
+    #[error("unexpected end of data")]
+    UnexpectedEnd,
+
+    #[error("trailing data after the item")]
+    TrailingData,
+
+    #[error("unsupported item, major type {0}, info {1}")]
+    Unsupported(u8, u8),
+
+    #[error("invalid UTF-8 in text")]
+    InvalidUtf8,
+
+    #[error("too deeply nested")]
+    TooDeep,
+}
+
+type Error = CborError;
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+const MAX_DEPTH: usize = 32;
+
+impl Cbor {
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        let mut decoder = Decoder { data, position: 0 };
+        let item = decoder.item(0)?;
+
+        if decoder.position != data.len() {
+            return Err(Error::TrailingData);
+        }
+
+        Ok(item)
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        self.encode_into(&mut buffer);
+        buffer
+    }
+
+    fn encode_into(&self, buffer: &mut Vec<u8>) {
+        match self {
+            Self::Unsigned(value) => head(buffer, 0, *value),
+            Self::Negative(value) => head(buffer, 1, *value),
+            Self::Bytes(bytes) => {
+                head(buffer, 2, bytes.len() as u64);
+                buffer.extend_from_slice(bytes);
+            }
+            Self::Text(text) => {
+                head(buffer, 3, text.len() as u64);
+                buffer.extend_from_slice(text.as_bytes());
+            }
+            Self::Array(items) => {
+                head(buffer, 4, items.len() as u64);
+                items.iter().for_each(|item| item.encode_into(buffer));
+            }
+            Self::Map(entries) => {
+                head(buffer, 5, entries.len() as u64);
+                for (key, value) in entries {
+                    key.encode_into(buffer);
+                    value.encode_into(buffer);
+                }
+            }
+            Self::Tag(tag, item) => {
+                head(buffer, 6, *tag);
+                item.encode_into(buffer);
+            }
+            Self::Bool(false) => buffer.push(0xf4),
+            Self::Bool(true) => buffer.push(0xf5),
+            Self::Null => buffer.push(0xf6),
+        }
+    }
+
+    // MARK: accessors
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Self::Unsigned(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_u32(&self) -> Option<u32> {
+        self.as_u64().and_then(|value| u32::try_from(value).ok())
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Self::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Cbor]> {
+        match self {
+            Self::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// The tag and the tagged item
+    pub fn as_tagged(&self) -> Option<(u64, &Cbor)> {
+        match self {
+            Self::Tag(tag, item) => Some((*tag, item)),
+            _ => None,
+        }
+    }
+
+    /// Look up an integer key in a map, registry types all use integer keys
+    pub fn get(&self, key: u64) -> Option<&Cbor> {
+        let Self::Map(entries) = self else { return None };
+
+        entries.iter().find(|(k, _)| k.as_u64() == Some(key)).map(|(_, value)| value)
+    }
+}
+
+fn head(buffer: &mut Vec<u8>, major: u8, value: u64) {
+    let major = major << 5;
+
+    match value {
+        0..24 => buffer.push(major | value as u8),
+        24..0x100 => buffer.extend_from_slice(&[major | 24, value as u8]),
+        0x100..0x10000 => {
+            buffer.push(major | 25);
+            buffer.extend_from_slice(&(value as u16).to_be_bytes());
+        }
+        0x10000..0x1_0000_0000 => {
+            buffer.push(major | 26);
+            buffer.extend_from_slice(&(value as u32).to_be_bytes());
+        }
+        _ => {
+            buffer.push(major | 27);
+            buffer.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+}
+
+struct Decoder<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8]> {
+        let end = self.position.checked_add(length).ok_or(Error::UnexpectedEnd)?;
+        let bytes = self.data.get(self.position..end).ok_or(Error::UnexpectedEnd)?;
+        self.position = end;
+
+        Ok(bytes)
+    }
+
+    fn argument(&mut self, major: u8, info: u8) -> Result<u64> {
+        let value = match info {
+            0..24 => u64::from(info),
+            24 => u64::from(self.take(1)?[0]),
+            25 => u64::from(u16::from_be_bytes(self.take(2)?.try_into().expect("2 bytes"))),
+            26 => u64::from(u32::from_be_bytes(self.take(4)?.try_into().expect("4 bytes"))),
+            27 => u64::from_be_bytes(self.take(8)?.try_into().expect("8 bytes")),
+            _ => return Err(Error::Unsupported(major, info)),
+        };
+
+        Ok(value)
+    }
+
+    /// A length, checked against the remaining data so a bad length can't allocate
+    fn length(&mut self, major: u8, info: u8) -> Result<usize> {
+        let length = self.argument(major, info)?;
+        let remaining = (self.data.len() - self.position) as u64;
+        if length > remaining {
+            return Err(Error::UnexpectedEnd);
+        }
+
+        Ok(length as usize)
+    }
+
+    fn item(&mut self, depth: usize) -> Result<Cbor> {
+        if depth > MAX_DEPTH {
+            return Err(Error::TooDeep);
+        }
+
+        let initial = self.take(1)?[0];
+        let (major, info) = (initial >> 5, initial & 0x1f);
+
+        let item = match major {
+            0 => Cbor::Unsigned(self.argument(major, info)?),
+            1 => Cbor::Negative(self.argument(major, info)?),
+            2 => {
+                let length = self.length(major, info)?;
+                Cbor::Bytes(self.take(length)?.to_vec())
+            }
+            3 => {
+                let length = self.length(major, info)?;
+                let text =
+                    std::str::from_utf8(self.take(length)?).map_err(|_| Error::InvalidUtf8)?;
+                Cbor::Text(text.to_string())
+            }
+            4 => {
+                let length = self.length(major, info)?;
+                let items = (0..length).map(|_| self.item(depth + 1)).collect::<Result<_>>()?;
+                Cbor::Array(items)
+            }
+            5 => {
+                let length = self.length(major, info)?;
+                let entries = (0..length)
+                    .map(|_| Ok((self.item(depth + 1)?, self.item(depth + 1)?)))
+                    .collect::<Result<_>>()?;
+                Cbor::Map(entries)
+            }
+            6 => {
+                let tag = self.argument(major, info)?;
+                Cbor::Tag(tag, Box::new(self.item(depth + 1)?))
+            }
+            7 => match info {
+                20 => Cbor::Bool(false),
+                21 => Cbor::Bool(true),
+                22 => Cbor::Null,
+                _ => return Err(Error::Unsupported(major, info)),
+            },
+            _ => unreachable!("major type is 3 bits"),
+        };
+
+        Ok(item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let item = Cbor::Map(vec![
+            (Cbor::Unsigned(1), Cbor::Unsigned(0x7365_6564)),
+            (
+                Cbor::Unsigned(2),
+                Cbor::Array(vec![Cbor::Tag(308, Box::new(Cbor::Bytes(vec![0xab; 300])))]),
+            ),
+            (Cbor::Unsigned(3), Cbor::Text("cove".into())),
+            (Cbor::Unsigned(4), Cbor::Bool(true)),
+        ]);
+
+        let encoded = item.encode();
+        assert_eq!(&encoded[..6], &[0xa4, 0x01, 0x1a, 0x73, 0x65, 0x65]);
+        assert_eq!(Cbor::decode(&encoded).unwrap(), item);
+        assert_eq!(item.get(1).and_then(Cbor::as_u32), Some(0x7365_6564));
+    }
+
+    #[test]
+    fn test_invalid() {
+        // byte string claiming 16 bytes with only 1
+        assert_eq!(Cbor::decode(&[0x50, 0x00]), Err(Error::UnexpectedEnd));
+        assert_eq!(Cbor::decode(&[0x01, 0x02]), Err(Error::TrailingData));
+        assert_eq!(Cbor::decode(&[0xf9, 0x00, 0x00]), Err(Error::Unsupported(7, 25)));
+    }
+}
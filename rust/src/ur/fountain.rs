@@ -0,0 +1,586 @@
+//! Fountain codes for multi part URs
+//!
+//! The first `seq_len` parts are the plain fragments, after that each part is the XOR of a
+//! random set of fragments, seeded from the sequence number and checksum so both sides agree on
+//! which fragments were mixed. Any large enough set of parts is enough to rebuild the message
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use bitcoin::hashes::{Hash as _, sha256};
+
+use super::{bytewords::crc32, cbor::Cbor};
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum FountainError {
+    #[error("invalid part: {0}")]
+    InvalidPart(&'static str),
+
+    #[error("part is from a different message")]
+    MismatchedPart,
+
+    #[error("checksum mismatch, the joined message is corrupt")]
+    InvalidChecksum,
+}
+
+type Error = FountainError;
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Indexes of the fragments mixed into a part
+type Indexes = BTreeSet<usize>;
+
+/// Picking the fragments for a part allocates per fragment, so a part can't claim a huge sequence
+const MAX_SEQ_LEN: usize = 4096;
+
+/// Way bigger than any PSBT or wallet export we would scan
+const MAX_MESSAGE_LEN: usize = 8 * 1024 * 1024;
+
+// MARK: part
+
+/// The CBOR body of a multi part UR, `[seq_num, seq_len, message_len, checksum, fragment]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FountainPart {
+    pub seq_num: u32,
+    pub seq_len: usize,
+    pub message_len: usize,
+    pub checksum: u32,
+    pub data: Vec<u8>,
+}
+
+impl FountainPart {
+    pub fn try_from_cbor(data: &[u8]) -> Result<Self> {
+        let cbor = Cbor::decode(data).map_err(|_| Error::InvalidPart("not CBOR"))?;
+        let [seq_num, seq_len, message_len, checksum, fragment] = cbor.as_array().unwrap_or(&[])
+        else {
+            return Err(Error::InvalidPart("expected 5 items"));
+        };
+
+        let part = Self {
+            seq_num: seq_num.as_u32().ok_or(Error::InvalidPart("sequence number"))?,
+            seq_len: seq_len.as_u32().ok_or(Error::InvalidPart("sequence length"))? as usize,
+            message_len: message_len.as_u32().ok_or(Error::InvalidPart("message length"))? as usize,
+            checksum: checksum.as_u32().ok_or(Error::InvalidPart("checksum"))?,
+            data: fragment.as_bytes().ok_or(Error::InvalidPart("fragment"))?.to_vec(),
+        };
+
+        if part.seq_num == 0 || part.seq_len == 0 || part.data.is_empty() {
+            return Err(Error::InvalidPart("empty"));
+        }
+
+        if part.seq_len > MAX_SEQ_LEN {
+            return Err(Error::InvalidPart("too many parts"));
+        }
+
+        if part.message_len > MAX_MESSAGE_LEN {
+            return Err(Error::InvalidPart("message too large"));
+        }
+
+        // the fragments have to cover the message, without a whole extra fragment of padding
+        let fragments_len = part.seq_len.saturating_mul(part.data.len());
+        if fragments_len < part.message_len || fragments_len - part.message_len >= part.data.len() {
+            return Err(Error::InvalidPart("message length"));
+        }
+
+        Ok(part)
+    }
+
+    pub fn to_cbor(&self) -> Vec<u8> {
+        Cbor::Array(vec![
+            Cbor::Unsigned(self.seq_num.into()),
+            Cbor::Unsigned(self.seq_len as u64),
+            Cbor::Unsigned(self.message_len as u64),
+            Cbor::Unsigned(self.checksum.into()),
+            Cbor::Bytes(self.data.clone()),
+        ])
+        .encode()
+    }
+
+    fn indexes(&self) -> Indexes {
+        choose_fragments(self.seq_num, self.seq_len, self.checksum)
+    }
+}
+
+// MARK: encoder
+
+/// Fragments can't get smaller than this, so tiny messages aren't split into many parts
+const MIN_FRAGMENT_LEN: usize = 10;
+
+#[derive(Debug)]
+pub struct FountainEncoder {
+    message_len: usize,
+    checksum: u32,
+    fragments: Vec<Vec<u8>>,
+    seq_num: u32,
+}
+
+impl FountainEncoder {
+    pub fn new(message: &[u8], max_fragment_len: usize) -> Self {
+        let fragment_len = fragment_len(message.len(), MIN_FRAGMENT_LEN, max_fragment_len);
+
+        // zero pad the last fragment
+        let fragments = message
+            .chunks(fragment_len)
+            .map(|chunk| {
+                let mut fragment = chunk.to_vec();
+                fragment.resize(fragment_len, 0);
+                fragment
+            })
+            .collect();
+
+        Self { message_len: message.len(), checksum: crc32(message), fragments, seq_num: 0 }
+    }
+
+    pub fn seq_len(&self) -> usize {
+        self.fragments.len()
+    }
+
+    /// The plain fragments first, then mixed parts forever
+    pub fn next_part(&mut self) -> FountainPart {
+        self.seq_num += 1;
+
+        let mut data = vec![0; self.fragments[0].len()];
+        for index in choose_fragments(self.seq_num, self.seq_len(), self.checksum) {
+            xor_into(&mut data, &self.fragments[index]);
+        }
+
+        FountainPart {
+            seq_num: self.seq_num,
+            seq_len: self.seq_len(),
+            message_len: self.message_len,
+            checksum: self.checksum,
+            data,
+        }
+    }
+}
+
+/// Smallest number of equal fragments that fit in `max_len`, matches the reference encoder
+fn fragment_len(message_len: usize, min_len: usize, max_len: usize) -> usize {
+    let max_len = max_len.max(min_len);
+    let max_count = (message_len / min_len).max(1);
+
+    (1..=max_count)
+        .map(|count| message_len.div_ceil(count))
+        .find(|len| *len <= max_len)
+        .unwrap_or_else(|| message_len.div_ceil(max_count))
+        .max(1)
+}
+
+// MARK: decoder
+
+#[derive(Debug, Default)]
+pub struct FountainDecoder {
+    /// `(seq_len, message_len, checksum, fragment_len)` from the first part
+    params: Option<(usize, usize, u32, usize)>,
+    simple: HashMap<usize, Vec<u8>>,
+    mixed: HashMap<Indexes, Vec<u8>>,
+    received: HashSet<u32>,
+    message: Option<Vec<u8>>,
+}
+
+impl FountainDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a part, duplicate parts are ignored
+    ///
+    /// If the joined message doesn't match the checksum the decoder is reset, so scanning can
+    /// start over
+    pub fn receive(&mut self, part: FountainPart) -> Result<()> {
+        if self.message.is_some() {
+            return Ok(());
+        }
+
+        let params = (part.seq_len, part.message_len, part.checksum, part.data.len());
+        if *self.params.get_or_insert(params) != params {
+            return Err(Error::MismatchedPart);
+        }
+
+        if !self.received.insert(part.seq_num) {
+            return Ok(());
+        }
+
+        let mut queue = vec![(part.indexes(), part.data)];
+        while let Some((indexes, data)) = queue.pop() {
+            if indexes.len() == 1 {
+                self.add_simple(indexes, data, &mut queue);
+            } else {
+                self.add_mixed(indexes, data, &mut queue);
+            }
+
+            if self.simple.len() == part.seq_len {
+                return self.join();
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn message(&self) -> Option<&[u8]> {
+        self.message.as_deref()
+    }
+
+    pub fn seq_len(&self) -> Option<usize> {
+        self.params.map(|(seq_len, ..)| seq_len)
+    }
+
+    /// Fragments still missing, mixed parts can finish it off early so this is an estimate
+    pub fn parts_left(&self) -> usize {
+        match (self.message.is_some(), self.seq_len()) {
+            (true, _) => 0,
+            (false, Some(seq_len)) => seq_len - self.simple.len(),
+            (false, None) => 1,
+        }
+    }
+
+    fn add_simple(&mut self, indexes: Indexes, data: Vec<u8>, queue: &mut Vec<(Indexes, Vec<u8>)>) {
+        let index = *indexes.first().expect("one index");
+        if self.simple.contains_key(&index) {
+            return;
+        }
+
+        // take this fragment out of every mixed part that contains it
+        for (mixed_indexes, mut mixed_data) in std::mem::take(&mut self.mixed) {
+            if !mixed_indexes.contains(&index) {
+                self.mixed.insert(mixed_indexes, mixed_data);
+                continue;
+            }
+
+            xor_into(&mut mixed_data, &data);
+            let reduced = &mixed_indexes - &indexes;
+
+            if reduced.len() == 1 {
+                queue.push((reduced, mixed_data));
+            } else {
+                self.mixed.insert(reduced, mixed_data);
+            }
+        }
+
+        self.simple.insert(index, data);
+    }
+
+    fn add_mixed(
+        &mut self,
+        mut indexes: Indexes,
+        mut data: Vec<u8>,
+        queue: &mut Vec<(Indexes, Vec<u8>)>,
+    ) {
+        if self.mixed.contains_key(&indexes) {
+            return;
+        }
+
+        // reduce by the fragments we already have
+        for (index, fragment) in &self.simple {
+            if indexes.len() > 1 && indexes.remove(index) {
+                xor_into(&mut data, fragment);
+            }
+        }
+
+        // and by any mixed part that is a subset of this one
+        for (mixed_indexes, mixed_data) in &self.mixed {
+            if indexes.len() > mixed_indexes.len() && mixed_indexes.is_subset(&indexes) {
+                indexes = &indexes - mixed_indexes;
+                xor_into(&mut data, mixed_data);
+            }
+        }
+
+        if indexes.len() == 1 {
+            queue.push((indexes, data));
+            return;
+        }
+
+        // then use it to reduce the mixed parts that contain it
+        for (mixed_indexes, mut mixed_data) in std::mem::take(&mut self.mixed) {
+            if mixed_indexes.len() <= indexes.len() || !indexes.is_subset(&mixed_indexes) {
+                self.mixed.insert(mixed_indexes, mixed_data);
+                continue;
+            }
+
+            xor_into(&mut mixed_data, &data);
+            let reduced = &mixed_indexes - &indexes;
+
+            if reduced.len() == 1 {
+                queue.push((reduced, mixed_data));
+            } else {
+                self.mixed.insert(reduced, mixed_data);
+            }
+        }
+
+        self.mixed.insert(indexes, data);
+    }
+
+    fn join(&mut self) -> Result<()> {
+        let (seq_len, message_len, checksum, _) = self.params.expect("params set by first part");
+
+        let mut message =
+            (0..seq_len).flat_map(|index| self.simple[&index].iter().copied()).collect::<Vec<u8>>();
+        message.truncate(message_len);
+
+        if crc32(&message) != checksum {
+            *self = Self::default();
+            return Err(Error::InvalidChecksum);
+        }
+
+        self.message = Some(message);
+        Ok(())
+    }
+}
+
+fn xor_into(target: &mut [u8], other: &[u8]) {
+    target.iter_mut().zip(other).for_each(|(a, b)| *a ^= b);
+}
+
+// MARK: fragment selection
+
+/// Which fragments are mixed into part `seq_num`, the first `seq_len` parts are single fragments
+pub fn choose_fragments(seq_num: u32, seq_len: usize, checksum: u32) -> Indexes {
+    if seq_num as usize <= seq_len {
+        return BTreeSet::from([seq_num as usize - 1]);
+    }
+
+    let mut seed = [0u8; 8];
+    seed[..4].copy_from_slice(&seq_num.to_be_bytes());
+    seed[4..].copy_from_slice(&checksum.to_be_bytes());
+
+    let mut rng = Xoshiro256::new(&seed);
+    let degree = choose_degree(seq_len, &mut rng);
+
+    // partial fisher-yates, the order of the draws has to match the reference implementation
+    let mut remaining = (0..seq_len).collect::<Vec<_>>();
+    (0..degree)
+        .map(|_| {
+            let index = rng.next_int(0, remaining.len() as u64 - 1) as usize;
+            remaining.remove(index)
+        })
+        .collect()
+}
+
+/// Degrees are picked with probability proportional to 1/degree
+fn choose_degree(seq_len: usize, rng: &mut Xoshiro256) -> usize {
+    let probabilities = (1..=seq_len).map(|degree| 1.0 / degree as f64).collect::<Vec<_>>();
+    RandomSampler::new(&probabilities).next(rng) + 1
+}
+
+/// Walker's alias method, matches the reference implementation's float math exactly
+struct RandomSampler {
+    probabilities: Vec<f64>,
+    aliases: Vec<usize>,
+}
+
+impl RandomSampler {
+    fn new(weights: &[f64]) -> Self {
+        let count = weights.len();
+        let sum: f64 = weights.iter().sum();
+        let mut scaled =
+            weights.iter().map(|weight| weight * count as f64 / sum).collect::<Vec<_>>();
+
+        let (mut small, mut large) = (Vec::new(), Vec::new());
+        for index in (0..count).rev() {
+            if scaled[index] < 1.0 { small.push(index) } else { large.push(index) }
+        }
+
+        let mut probabilities = vec![0.0; count];
+        let mut aliases = vec![0; count];
+
+        while !small.is_empty() && !large.is_empty() {
+            let less = small.pop().expect("not empty");
+            let more = large.pop().expect("not empty");
+
+            probabilities[less] = scaled[less];
+            aliases[less] = more;
+            scaled[more] = scaled[more] + scaled[less] - 1.0;
+
+            if scaled[more] < 1.0 { small.push(more) } else { large.push(more) }
+        }
+
+        for index in large.into_iter().chain(small) {
+            probabilities[index] = 1.0;
+        }
+
+        Self { probabilities, aliases }
+    }
+
+    fn next(&self, rng: &mut Xoshiro256) -> usize {
+        let (first, second) = (rng.next_double(), rng.next_double());
+        let index = (self.probabilities.len() as f64 * first) as usize;
+
+        if second < self.probabilities[index] { index } else { self.aliases[index] }
+    }
+}
+
+/// xoshiro256**, seeded with the SHA256 of the seed
+pub struct Xoshiro256 {
+    state: [u64; 4],
+}
+
+impl Xoshiro256 {
+    pub fn new(seed: &[u8]) -> Self {
+        let digest = sha256::Hash::hash(seed).to_byte_array();
+
+        let mut state = [0u64; 4];
+        for (value, chunk) in state.iter_mut().zip(digest.chunks_exact(8)) {
+            *value = u64::from_be_bytes(chunk.try_into().expect("8 bytes"));
+        }
+
+        Self { state }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let s = &mut self.state;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+
+        result
+    }
+
+    fn next_double(&mut self) -> f64 {
+        self.next_u64() as f64 / (u64::MAX as f64 + 1.0)
+    }
+
+    /// Random int in the inclusive range
+    pub fn next_int(&mut self, low: u64, high: u64) -> u64 {
+        (self.next_double() * (high - low + 1) as f64) as u64 + low
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rng() {
+        let mut rng = Xoshiro256::new(b"Wolf");
+        let numbers = (0..20).map(|_| rng.next_u64() % 100).collect::<Vec<_>>();
+
+        assert_eq!(
+            numbers,
+            [42, 81, 85, 8, 82, 84, 76, 73, 70, 88, 2, 74, 40, 48, 77, 54, 88, 7, 5, 88]
+        );
+    }
+
+    #[test]
+    fn test_choose_fragments() {
+        // matches the parts in the reference `ur:bytes` test vector, see `ur::tests`
+        let checksum = 0xeda0_ae73;
+        let chosen =
+            (10..=20).map(|seq_num| choose_fragments(seq_num, 9, checksum)).collect::<Vec<_>>();
+
+        let expected: [&[usize]; 11] = [
+            &[0],
+            &[2],
+            &[2, 6],
+            &[3, 7, 8],
+            &[0, 6],
+            &[5, 6],
+            &[0, 6, 7, 8],
+            &[1, 3, 5],
+            &[0],
+            &[0, 8],
+            &[1, 2, 6],
+        ];
+
+        for (chosen, expected) in chosen.iter().zip(expected) {
+            assert_eq!(chosen.iter().copied().collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn test_fragment_len() {
+        assert_eq!(fragment_len(12345, 1005, 1955), 1764);
+        assert_eq!(fragment_len(12345, 1005, 30000), 12345);
+        assert_eq!(fragment_len(259, 10, 30), 29);
+        assert_eq!(fragment_len(5, 10, 30), 5);
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        let message = (0..=255).cycle().take(1000).collect::<Vec<u8>>();
+        let mut encoder = FountainEncoder::new(&message, 100);
+        assert_eq!(encoder.seq_len(), 10);
+
+        // skip every third part, the mixed parts make up for them
+        let mut decoder = FountainDecoder::new();
+        for seq_num in 1..100 {
+            let part = encoder.next_part();
+            if seq_num % 3 == 0 {
+                continue;
+            }
+
+            let part = FountainPart::try_from_cbor(&part.to_cbor()).unwrap();
+            decoder.receive(part).unwrap();
+            if decoder.message().is_some() {
+                break;
+            }
+        }
+
+        assert_eq!(decoder.message(), Some(message.as_slice()));
+    }
+
+    #[test]
+    fn test_mismatched_part() {
+        let part = |checksum| FountainPart {
+            seq_num: 1,
+            seq_len: 2,
+            message_len: 3,
+            checksum,
+            data: vec![1, 2],
+        };
+
+        let mut decoder = FountainDecoder::new();
+        decoder.receive(part(1)).unwrap();
+        assert_eq!(decoder.parts_left(), 1);
+        assert_eq!(decoder.receive(part(2)), Err(Error::MismatchedPart));
+    }
+
+    #[test]
+    fn test_part_limits() {
+        let part = |seq_len: usize, message_len: usize| FountainPart {
+            seq_num: 1,
+            seq_len,
+            message_len,
+            checksum: 1,
+            data: vec![1; 10],
+        };
+
+        let too_many = part(MAX_SEQ_LEN + 1, (MAX_SEQ_LEN + 1) * 10);
+        assert_eq!(
+            FountainPart::try_from_cbor(&too_many.to_cbor()),
+            Err(Error::InvalidPart("too many parts"))
+        );
+
+        let too_large = FountainPart { data: vec![1; 4096], ..part(4096, MAX_MESSAGE_LEN + 1) };
+        assert_eq!(
+            FountainPart::try_from_cbor(&too_large.to_cbor()),
+            Err(Error::InvalidPart("message too large"))
+        );
+
+        let at_limit = part(MAX_SEQ_LEN, MAX_SEQ_LEN * 10);
+        assert_eq!(FountainPart::try_from_cbor(&at_limit.to_cbor()), Ok(at_limit));
+    }
+
+    #[test]
+    fn test_invalid_checksum_resets() {
+        let message = (0..=255).cycle().take(100).collect::<Vec<u8>>();
+        let mut encoder = FountainEncoder::new(&message, 50);
+        let parts = (0..encoder.seq_len()).map(|_| encoder.next_part()).collect::<Vec<_>>();
+
+        let mut corrupt = parts.clone();
+        corrupt.iter_mut().for_each(|part| part.checksum ^= 1);
+
+        let mut decoder = FountainDecoder::new();
+        let last = corrupt.pop().unwrap();
+        corrupt.into_iter().for_each(|part| decoder.receive(part).unwrap());
+        assert_eq!(decoder.receive(last), Err(Error::InvalidChecksum));
+        assert_eq!(decoder.seq_len(), None);
+
+        // the real parts go in cleanly after the reset
+        parts.into_iter().for_each(|part| decoder.receive(part).unwrap());
+        assert_eq!(decoder.message(), Some(message.as_slice()));
+    }
+}
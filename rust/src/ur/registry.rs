@@ -0,0 +1,660 @@
+//! UR registry types (BCR-2020-006), the payloads hardware wallets send
+//!
+//! Wallet exports (crypto-account, crypto-output, crypto-hdkey) are turned into a descriptor
+//! string so they go through the same parsing as a pasted or BBQr descriptor
+
+use bdk_wallet::{
+    bitcoin::{
+        NetworkKind, Psbt, Transaction,
+        bip32::{ChainCode, ChildNumber, Fingerprint, Xpub},
+        secp256k1::{PublicKey, Secp256k1},
+    },
+    descriptor::ExtendedDescriptor,
+    keys::DescriptorPublicKey,
+    miniscript::{
+        Descriptor,
+        descriptor::{ShInner, SinglePubKey, SortedMultiVec, Wildcard, WshInner},
+        psbt::PsbtExt as _,
+    },
+};
+use cove_bdk::descriptor_ext::DescriptorExt as _;
+
+use super::{UrError, cbor::Cbor};
+
+type Error = UrError;
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+// MARK: tags
+
+const TAG_HDKEY: u64 = 303;
+const TAG_KEYPATH: u64 = 304;
+const TAG_COIN_INFO: u64 = 305;
+const TAG_ECKEY: u64 = 306;
+const TAG_OUTPUT: u64 = 308;
+
+// script expressions, BCR-2020-010
+const TAG_SH: u64 = 400;
+const TAG_WSH: u64 = 401;
+const TAG_PK: u64 = 402;
+const TAG_PKH: u64 = 403;
+const TAG_WPKH: u64 = 404;
+const TAG_COMBO: u64 = 405;
+const TAG_MULTI: u64 = 406;
+const TAG_SORTED_MULTI: u64 = 407;
+const TAG_TR: u64 = 409;
+
+/// Decoded UR message
+#[derive(Debug, Clone, PartialEq)]
+pub enum UrPayload {
+    Psbt(Psbt),
+    /// Wallet descriptor, single sig keys use `<0;1>/*` for the receive and change paths
+    Descriptor(String),
+    Bytes(Vec<u8>),
+}
+
+impl UrPayload {
+    pub fn try_new(ur_type: &str, message: &[u8]) -> Result<Self> {
+        let cbor = Cbor::decode(message)?;
+
+        let payload = match ur_type {
+            "crypto-psbt" | "psbt" => {
+                let bytes = cbor.as_bytes().ok_or_else(|| invalid("PSBT is not a byte string"))?;
+                let psbt = Psbt::deserialize(bytes).map_err(|error| invalid(error.to_string()))?;
+                Self::Psbt(psbt)
+            }
+
+            "bytes" => {
+                let bytes = cbor.as_bytes().ok_or_else(|| invalid("not a byte string"))?;
+                Self::Bytes(bytes.to_vec())
+            }
+
+            "crypto-output" => Self::Descriptor(output_descriptor(&cbor, None)?),
+            "crypto-account" => Self::Descriptor(account_descriptor(&cbor)?),
+            "crypto-hdkey" => Self::Descriptor(hdkey_descriptor(&cbor)?),
+
+            ur_type => return Err(Error::UnsupportedType(ur_type.to_string())),
+        };
+
+        Ok(payload)
+    }
+}
+
+/// Hardware wallets send back signed but not finalized PSBTs, finalize and extract the txn
+pub fn finalized_transaction(psbt: &Psbt) -> Result<Transaction> {
+    let is_finalized = psbt
+        .inputs
+        .iter()
+        .all(|input| input.final_script_sig.is_some() || input.final_script_witness.is_some());
+
+    let psbt = match is_finalized {
+        true => psbt.clone(),
+        false => {
+            psbt.clone().finalize(&Secp256k1::verification_only()).map_err(|(_, errors)| {
+                let errors = errors.iter().map(ToString::to_string).collect::<Vec<_>>();
+                Error::UnsignedPsbt(errors.join(", "))
+            })?
+        }
+    };
+
+    Ok(psbt.extract_tx_unchecked_fee_rate())
+}
+
+// MARK: wallet exports
+
+/// Pick one output from the account, cove wallets are native segwit by default
+fn account_descriptor(cbor: &Cbor) -> Result<String> {
+    let fingerprint = cbor.get(1).and_then(Cbor::as_u32);
+    let outputs = cbor
+        .get(2)
+        .and_then(Cbor::as_array)
+        .ok_or_else(|| invalid("account has no output descriptors"))?;
+
+    let preference = |output: &Cbor| {
+        let script = untag_output(output);
+        match script.as_tagged() {
+            Some((TAG_WPKH, _)) => 0,
+            Some((TAG_TR, _)) => 1,
+            Some((TAG_SH, inner)) if matches!(inner.as_tagged(), Some((TAG_WPKH, _))) => 2,
+            Some((TAG_PKH, _)) => 3,
+            _ => 4,
+        }
+    };
+
+    let output = outputs
+        .iter()
+        .min_by_key(|output| preference(output))
+        .ok_or_else(|| invalid("account has no output descriptors"))?;
+
+    output_descriptor(output, fingerprint)
+}
+
+/// A key without a script, pick the script type from the BIP44 purpose in its origin
+fn hdkey_descriptor(cbor: &Cbor) -> Result<String> {
+    let key = hdkey_expression(cbor, None)?;
+    let purpose = cbor
+        .get(6)
+        .map(untag_keypath)
+        .and_then(|origin| origin.get(1))
+        .and_then(Cbor::as_array)
+        .and_then(|components| components.first())
+        .and_then(Cbor::as_u64);
+
+    let descriptor = match purpose {
+        Some(44) => format!("pkh({key})"),
+        Some(49) => format!("sh(wpkh({key}))"),
+        Some(86) => format!("tr({key})"),
+        _ => format!("wpkh({key})"),
+    };
+
+    Ok(descriptor)
+}
+
+fn output_descriptor(cbor: &Cbor, fingerprint: Option<u32>) -> Result<String> {
+    script_expression(untag_output(cbor), fingerprint, 0)
+}
+
+fn script_expression(cbor: &Cbor, fingerprint: Option<u32>, depth: usize) -> Result<String> {
+    if depth > 4 {
+        return Err(invalid("script expression too deep"));
+    }
+
+    let (tag, inner) = cbor.as_tagged().ok_or_else(|| invalid("expected a script expression"))?;
+
+    let name = match tag {
+        TAG_SH => "sh",
+        TAG_WSH => "wsh",
+        TAG_PK => "pk",
+        TAG_PKH => "pkh",
+        TAG_WPKH => "wpkh",
+        TAG_COMBO => "combo",
+        TAG_MULTI => "multi",
+        TAG_SORTED_MULTI => "sortedmulti",
+        TAG_TR => "tr",
+        tag => return Err(invalid(format!("unsupported script expression {tag}"))),
+    };
+
+    let inner = match tag {
+        TAG_SH | TAG_WSH => script_expression(inner, fingerprint, depth + 1)?,
+
+        TAG_MULTI | TAG_SORTED_MULTI => {
+            let threshold =
+                inner.get(1).and_then(Cbor::as_u64).ok_or_else(|| invalid("missing threshold"))?;
+
+            let keys = inner
+                .get(2)
+                .and_then(Cbor::as_array)
+                .ok_or_else(|| invalid("missing multisig keys"))?
+                .iter()
+                .map(|key| key_expression(key, fingerprint))
+                .collect::<Result<Vec<_>>>()?;
+
+            format!("{threshold},{}", keys.join(","))
+        }
+
+        _ => key_expression(inner, fingerprint)?,
+    };
+
+    Ok(format!("{name}({inner})"))
+}
+
+fn key_expression(cbor: &Cbor, fingerprint: Option<u32>) -> Result<String> {
+    match cbor.as_tagged() {
+        Some((TAG_HDKEY, hdkey)) => hdkey_expression(hdkey, fingerprint),
+        Some((TAG_ECKEY, eckey)) => {
+            let key = eckey.get(3).and_then(Cbor::as_bytes).ok_or_else(|| invalid("ec key"))?;
+            if eckey.get(2).and_then(Cbor::as_bool) == Some(true) {
+                return Err(invalid("private keys are not supported"));
+            }
+
+            Ok(hex::encode(key))
+        }
+        _ => Err(invalid("expected a key")),
+    }
+}
+
+/// `[fingerprint/origin]xpub/children`, children default to `<0;1>/*`
+fn hdkey_expression(cbor: &Cbor, fingerprint: Option<u32>) -> Result<String> {
+    if cbor.get(2).and_then(Cbor::as_bool) == Some(true) {
+        return Err(invalid("private keys are not supported"));
+    }
+
+    let key_data = cbor.get(3).and_then(Cbor::as_bytes).ok_or_else(|| invalid("missing key"))?;
+    let chain_code: [u8; 32] = cbor
+        .get(4)
+        .and_then(Cbor::as_bytes)
+        .and_then(|chain_code| chain_code.try_into().ok())
+        .ok_or_else(|| invalid("missing chain code"))?;
+
+    let network = match cbor.get(5).map(untag_coin_info).and_then(|info| info.get(2)) {
+        Some(Cbor::Unsigned(1)) => NetworkKind::Test,
+        _ => NetworkKind::Main,
+    };
+
+    let origin = cbor.get(6).map(untag_keypath);
+    let origin_path = match origin.and_then(|origin| origin.get(1)) {
+        Some(components) => path_components(components)?,
+        None => vec![],
+    };
+
+    let origin_numbers = origin_path
+        .iter()
+        .map(|component| component.ok_or_else(|| invalid("wildcard in key origin")))
+        .collect::<Result<Vec<_>>>()?;
+
+    let depth = origin
+        .and_then(|origin| origin.get(3))
+        .and_then(Cbor::as_u64)
+        .unwrap_or(origin_numbers.len() as u64);
+
+    let parent_fingerprint = cbor.get(8).and_then(Cbor::as_u32).unwrap_or_default();
+
+    let xpub = Xpub {
+        network,
+        depth: u8::try_from(depth).map_err(|_| invalid("depth"))?,
+        parent_fingerprint: Fingerprint::from(parent_fingerprint.to_be_bytes()),
+        child_number: origin_numbers.last().copied().unwrap_or(ChildNumber::Normal { index: 0 }),
+        public_key: PublicKey::from_slice(key_data).map_err(|error| invalid(error.to_string()))?,
+        chain_code: ChainCode::from(chain_code),
+    };
+
+    // the source fingerprint, falling back to the account's master fingerprint
+    let source_fingerprint =
+        origin.and_then(|origin| origin.get(2)).and_then(Cbor::as_u32).or(fingerprint);
+
+    let origin = match source_fingerprint {
+        Some(fingerprint) => {
+            let path = origin_numbers.iter().map(|number| format!("/{}", child_string(number)));
+            format!("[{fingerprint:08x}{}]", path.collect::<String>())
+        }
+        None => String::new(),
+    };
+
+    let children = match cbor.get(7).map(untag_keypath).and_then(|children| children.get(1)) {
+        Some(components) => path_components(components)?,
+        None => vec![],
+    };
+
+    let children = match children.as_slice() {
+        // receive or change only, we want both
+        [] | [Some(ChildNumber::Normal { index: 0 | 1 }), None] => "/<0;1>/*".to_string(),
+        children => children
+            .iter()
+            .map(|child| match child {
+                Some(number) => format!("/{}", child_string(number)),
+                None => "/*".to_string(),
+            })
+            .collect(),
+    };
+
+    Ok(format!("{origin}{xpub}{children}"))
+}
+
+// MARK: encoding
+
+/// `crypto-output` for a descriptor, the inverse of [`output_descriptor`]
+pub fn output_cbor(descriptor: &ExtendedDescriptor) -> Result<Cbor> {
+    let script = match descriptor {
+        Descriptor::Pkh(pkh) => tagged(TAG_PKH, key_cbor(pkh.as_inner())?),
+        Descriptor::Wpkh(wpkh) => tagged(TAG_WPKH, key_cbor(wpkh.as_inner())?),
+
+        Descriptor::Sh(sh) => {
+            let inner = match sh.as_inner() {
+                ShInner::Wpkh(wpkh) => tagged(TAG_WPKH, key_cbor(wpkh.as_inner())?),
+                ShInner::Wsh(wsh) => wsh_cbor(wsh.as_inner())?,
+                ShInner::SortedMulti(multi) => sorted_multi_cbor(multi)?,
+                ShInner::Ms(_) => return Err(invalid("miniscript is not supported")),
+            };
+
+            tagged(TAG_SH, inner)
+        }
+
+        Descriptor::Wsh(wsh) => wsh_cbor(wsh.as_inner())?,
+
+        Descriptor::Tr(tr) => {
+            if tr.tap_tree().is_some() {
+                return Err(invalid("taproot script trees are not supported"));
+            }
+
+            tagged(TAG_TR, key_cbor(tr.internal_key())?)
+        }
+
+        Descriptor::Bare(_) => return Err(invalid("bare descriptors are not supported")),
+    };
+
+    Ok(script)
+}
+
+/// `crypto-account` with the single output, single sig only
+pub fn account_cbor(descriptor: &ExtendedDescriptor) -> Result<Cbor> {
+    let (fingerprint, _) =
+        descriptor.origin().map_err(|error| invalid(format!("account, {error}")))?;
+
+    let fingerprint = u32::from_be_bytes(fingerprint.to_bytes());
+    let output = tagged(TAG_OUTPUT, output_cbor(descriptor)?);
+
+    Ok(Cbor::Map(vec![
+        (Cbor::Unsigned(1), Cbor::Unsigned(fingerprint.into())),
+        (Cbor::Unsigned(2), Cbor::Array(vec![output])),
+    ]))
+}
+
+fn wsh_cbor(inner: &WshInner<DescriptorPublicKey>) -> Result<Cbor> {
+    match inner {
+        WshInner::SortedMulti(multi) => Ok(tagged(TAG_WSH, sorted_multi_cbor(multi)?)),
+        WshInner::Ms(_) => Err(invalid("miniscript is not supported")),
+    }
+}
+
+fn sorted_multi_cbor<Ctx>(multi: &SortedMultiVec<DescriptorPublicKey, Ctx>) -> Result<Cbor>
+where
+    Ctx: bdk_wallet::miniscript::ScriptContext,
+{
+    let keys = multi.pks().iter().map(key_cbor).collect::<Result<Vec<_>>>()?;
+    let multi = Cbor::Map(vec![
+        (Cbor::Unsigned(1), Cbor::Unsigned(multi.k() as u64)),
+        (Cbor::Unsigned(2), Cbor::Array(keys)),
+    ]);
+
+    Ok(tagged(TAG_SORTED_MULTI, multi))
+}
+
+fn key_cbor(key: &DescriptorPublicKey) -> Result<Cbor> {
+    let key = match key {
+        DescriptorPublicKey::Single(single) => {
+            let SinglePubKey::FullKey(key) = single.key else {
+                return Err(invalid("x-only keys are not supported"));
+            };
+
+            tagged(TAG_ECKEY, Cbor::Map(vec![(Cbor::Unsigned(3), Cbor::Bytes(key.to_bytes()))]))
+        }
+
+        DescriptorPublicKey::XPub(key) => {
+            let xpub = &key.xkey;
+            let mut entries = vec![
+                (Cbor::Unsigned(3), Cbor::Bytes(xpub.public_key.serialize().to_vec())),
+                (Cbor::Unsigned(4), Cbor::Bytes(xpub.chain_code.to_bytes().to_vec())),
+            ];
+
+            if xpub.network == NetworkKind::Test {
+                let coin_info = Cbor::Map(vec![(Cbor::Unsigned(2), Cbor::Unsigned(1))]);
+                entries.push((Cbor::Unsigned(5), tagged(TAG_COIN_INFO, coin_info)));
+            }
+
+            if let Some((fingerprint, path)) = &key.origin {
+                let fingerprint = u32::from_be_bytes(fingerprint.to_bytes());
+                let origin = vec![
+                    (Cbor::Unsigned(1), Cbor::Array(keypath_cbor(path, None))),
+                    (Cbor::Unsigned(2), Cbor::Unsigned(fingerprint.into())),
+                    (Cbor::Unsigned(3), Cbor::Unsigned(xpub.depth.into())),
+                ];
+
+                entries.push((Cbor::Unsigned(6), tagged(TAG_KEYPATH, Cbor::Map(origin))));
+            }
+
+            let children = keypath_cbor(&key.derivation_path, Some(key.wildcard));
+            if !children.is_empty() {
+                let children = Cbor::Map(vec![(Cbor::Unsigned(1), Cbor::Array(children))]);
+                entries.push((Cbor::Unsigned(7), tagged(TAG_KEYPATH, children)));
+            }
+
+            let parent_fingerprint = u32::from_be_bytes(xpub.parent_fingerprint.to_bytes());
+            entries.push((Cbor::Unsigned(8), Cbor::Unsigned(parent_fingerprint.into())));
+
+            tagged(TAG_HDKEY, Cbor::Map(entries))
+        }
+
+        DescriptorPublicKey::MultiXPub(_) => {
+            return Err(invalid("multipath keys are not supported, use the receive descriptor"));
+        }
+    };
+
+    Ok(key)
+}
+
+/// Flat `[index, hardened, ...]` components, a wildcard is an empty array
+fn keypath_cbor<'a>(
+    path: impl IntoIterator<Item = &'a ChildNumber>,
+    wildcard: Option<Wildcard>,
+) -> Vec<Cbor> {
+    let mut components = path
+        .into_iter()
+        .flat_map(|number| {
+            let (index, hardened) = match number {
+                ChildNumber::Normal { index } => (*index, false),
+                ChildNumber::Hardened { index } => (*index, true),
+            };
+
+            [Cbor::Unsigned(index.into()), Cbor::Bool(hardened)]
+        })
+        .collect::<Vec<_>>();
+
+    match wildcard {
+        Some(Wildcard::Unhardened) => components.extend([Cbor::Array(vec![]), Cbor::Bool(false)]),
+        Some(Wildcard::Hardened) => components.extend([Cbor::Array(vec![]), Cbor::Bool(true)]),
+        Some(Wildcard::None) | None => {}
+    }
+
+    components
+}
+
+fn tagged(tag: u64, item: Cbor) -> Cbor {
+    Cbor::Tag(tag, Box::new(item))
+}
+
+/// Keypath components, `None` is a wildcard
+fn path_components(components: &Cbor) -> Result<Vec<Option<ChildNumber>>> {
+    let components = components.as_array().ok_or_else(|| invalid("keypath components"))?;
+    if components.len() % 2 != 0 {
+        return Err(invalid("keypath components"));
+    }
+
+    components
+        .chunks(2)
+        .map(|pair| {
+            let hardened = pair[1].as_bool().ok_or_else(|| invalid("keypath hardened flag"))?;
+
+            let index = match &pair[0] {
+                Cbor::Unsigned(index) => *index,
+                Cbor::Array(range) if range.is_empty() => return Ok(None),
+                _ => return Err(invalid("keypath ranges are not supported")),
+            };
+
+            let index = u32::try_from(index).map_err(|_| invalid("keypath index"))?;
+            let number = match hardened {
+                true => ChildNumber::from_hardened_idx(index),
+                false => ChildNumber::from_normal_idx(index),
+            };
+
+            number.map(Some).map_err(|error| invalid(error.to_string()))
+        })
+        .collect()
+}
+
+fn child_string(number: &ChildNumber) -> String {
+    match number {
+        ChildNumber::Normal { index } => index.to_string(),
+        ChildNumber::Hardened { index } => format!("{index}h"),
+    }
+}
+
+fn untag(cbor: &Cbor, tag: u64) -> &Cbor {
+    match cbor.as_tagged() {
+        Some((found, inner)) if found == tag => inner,
+        _ => cbor,
+    }
+}
+
+fn untag_output(cbor: &Cbor) -> &Cbor {
+    untag(cbor, TAG_OUTPUT)
+}
+
+fn untag_keypath(cbor: &Cbor) -> &Cbor {
+    untag(cbor, TAG_KEYPATH)
+}
+
+fn untag_coin_info(cbor: &Cbor) -> &Cbor {
+    untag(cbor, TAG_COIN_INFO)
+}
+
+fn invalid(reason: impl Into<String>) -> Error {
+    Error::InvalidPayload(reason.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr as _;
+
+    use super::*;
+
+    // abandon abandon ... about
+    const XPUB: &str = "xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V";
+
+    fn keypath(components: &[(u32, bool)], fingerprint: Option<u32>) -> Cbor {
+        let components = components
+            .iter()
+            .flat_map(|(index, hardened)| [Cbor::Unsigned((*index).into()), Cbor::Bool(*hardened)])
+            .collect();
+
+        let mut entries = vec![(Cbor::Unsigned(1), Cbor::Array(components))];
+        if let Some(fingerprint) = fingerprint {
+            entries.push((Cbor::Unsigned(2), Cbor::Unsigned(fingerprint.into())));
+        }
+
+        Cbor::Tag(TAG_KEYPATH, Box::new(Cbor::Map(entries)))
+    }
+
+    fn hdkey(purpose: u32, fingerprint: Option<u32>) -> Cbor {
+        let xpub = Xpub::from_str(XPUB).unwrap();
+
+        Cbor::Map(vec![
+            (Cbor::Unsigned(3), Cbor::Bytes(xpub.public_key.serialize().to_vec())),
+            (Cbor::Unsigned(4), Cbor::Bytes(xpub.chain_code.to_bytes().to_vec())),
+            (Cbor::Unsigned(6), keypath(&[(purpose, true), (0, true), (0, true)], fingerprint)),
+            (
+                Cbor::Unsigned(8),
+                Cbor::Unsigned(u32::from_be_bytes(xpub.parent_fingerprint.to_bytes()).into()),
+            ),
+        ])
+    }
+
+    fn output(tag: u64, key: Cbor) -> Cbor {
+        Cbor::Tag(TAG_OUTPUT, Box::new(Cbor::Tag(tag, Box::new(key))))
+    }
+
+    fn tagged_hdkey(purpose: u32, fingerprint: Option<u32>) -> Cbor {
+        Cbor::Tag(TAG_HDKEY, Box::new(hdkey(purpose, fingerprint)))
+    }
+
+    #[test]
+    fn test_hdkey() {
+        let message = hdkey(84, Some(0x73c5_da0a)).encode();
+        let payload = UrPayload::try_new("crypto-hdkey", &message).unwrap();
+
+        assert_eq!(
+            payload,
+            UrPayload::Descriptor(format!("wpkh([73c5da0a/84h/0h/0h]{XPUB}/<0;1>/*)"))
+        );
+    }
+
+    #[test]
+    fn test_output() {
+        let message = output(TAG_WPKH, tagged_hdkey(84, Some(0x73c5_da0a))).encode();
+        let payload = UrPayload::try_new("crypto-output", &message).unwrap();
+        assert_eq!(
+            payload,
+            UrPayload::Descriptor(format!("wpkh([73c5da0a/84h/0h/0h]{XPUB}/<0;1>/*)"))
+        );
+
+        // 2 of 2 sorted multi, inside wsh
+        let multi = Cbor::Map(vec![
+            (Cbor::Unsigned(1), Cbor::Unsigned(2)),
+            (
+                Cbor::Unsigned(2),
+                Cbor::Array(vec![
+                    tagged_hdkey(48, Some(0x73c5_da0a)),
+                    tagged_hdkey(48, Some(0xf245_ae38)),
+                ]),
+            ),
+        ]);
+
+        let wsh = Cbor::Tag(TAG_WSH, Box::new(Cbor::Tag(TAG_SORTED_MULTI, Box::new(multi))));
+        let payload = UrPayload::try_new("crypto-output", &wsh.encode()).unwrap();
+        assert_eq!(
+            payload,
+            UrPayload::Descriptor(format!(
+                "wsh(sortedmulti(2,[73c5da0a/48h/0h/0h]{XPUB}/<0;1>/*,[f245ae38/48h/0h/0h]{XPUB}/<0;1>/*))"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_account_prefers_native_segwit() {
+        let account = Cbor::Map(vec![
+            (Cbor::Unsigned(1), Cbor::Unsigned(0x73c5_da0a)),
+            (
+                Cbor::Unsigned(2),
+                Cbor::Array(vec![
+                    output(TAG_PKH, tagged_hdkey(44, None)),
+                    output(TAG_TR, tagged_hdkey(86, None)),
+                    output(TAG_WPKH, tagged_hdkey(84, None)),
+                ]),
+            ),
+        ]);
+
+        let payload = UrPayload::try_new("crypto-account", &account.encode()).unwrap();
+        assert_eq!(
+            payload,
+            UrPayload::Descriptor(format!("wpkh([73c5da0a/84h/0h/0h]{XPUB}/<0;1>/*)"))
+        );
+    }
+
+    #[test]
+    fn test_encode_round_trip() {
+        let descriptors = [
+            format!("wpkh([73c5da0a/84h/0h/0h]{XPUB}/<0;1>/*)"),
+            format!("sh(wpkh([73c5da0a/49h/0h/0h]{XPUB}/<0;1>/*))"),
+            format!("pkh([73c5da0a/44h/0h/0h]{XPUB}/<0;1>/*)"),
+            format!(
+                "wsh(sortedmulti(2,[73c5da0a/48h/0h/0h/2h]{XPUB}/<0;1>/*,[f245ae38/48h/0h/0h/2h]{XPUB}/<0;1>/*))"
+            ),
+        ];
+
+        for expected in descriptors {
+            let external = ExtendedDescriptor::from_str(&expected.replace("<0;1>", "0")).unwrap();
+
+            let message = output_cbor(&external).unwrap().encode();
+            let payload = UrPayload::try_new("crypto-output", &message).unwrap();
+            assert_eq!(payload, UrPayload::Descriptor(expected.clone()));
+        }
+
+        // account is single sig only
+        let single = ExtendedDescriptor::from_str(&format!("wpkh([73c5da0a/84h/0h/0h]{XPUB}/0/*)"));
+        let message = account_cbor(&single.unwrap()).unwrap().encode();
+        assert_eq!(
+            UrPayload::try_new("crypto-account", &message).unwrap(),
+            UrPayload::Descriptor(format!("wpkh([73c5da0a/84h/0h/0h]{XPUB}/<0;1>/*)"))
+        );
+
+        let multi = ExtendedDescriptor::from_str(&format!(
+            "wsh(sortedmulti(1,[73c5da0a/48h/0h/0h/2h]{XPUB}/0/*,[f245ae38/48h/0h/0h/2h]{XPUB}/0/*))"
+        ));
+        assert!(account_cbor(&multi.unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_rejects_private_and_unknown() {
+        let mut private = hdkey(84, None);
+        let Cbor::Map(entries) = &mut private else { unreachable!() };
+        entries.push((Cbor::Unsigned(2), Cbor::Bool(true)));
+
+        assert!(matches!(
+            UrPayload::try_new("crypto-hdkey", &private.encode()),
+            Err(Error::InvalidPayload(_))
+        ));
+
+        assert_eq!(
+            UrPayload::try_new("crypto-seed", &Cbor::Null.encode()),
+            Err(Error::UnsupportedType("crypto-seed".into()))
+        );
+    }
+}
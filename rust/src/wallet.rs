@@ -1,4 +1,5 @@
 pub mod balance;
+pub mod export;
 pub mod ffi;
 pub mod fingerprint;
 pub mod metadata;
@@ -12,7 +13,10 @@ use crate::{
     keys::{Descriptor, Descriptors},
     mnemonic::MnemonicExt as _,
     multi_format::MultiFormatError,
+    multisig::{MultisigError, MultisigExport},
+    policy::MiniscriptExport,
     tap_card::tap_signer_reader::DeriveInfo,
+    ur::{UrPart, UrPayload},
     xpub::{self, XpubError},
 };
 use balance::Balance;
@@ -72,6 +76,9 @@ pub enum WalletError {
 
     #[error("failed to parse descriptor: {0}")]
     DescriptorKeyParseError(String),
+
+    #[error(transparent)]
+    MultisigError(#[from] MultisigError),
 }
 
 #[derive(Debug, uniffi::Object)]
@@ -104,6 +111,7 @@ pub enum WalletAddressType {
     NativeSegwit,
     WrappedSegwit,
     Legacy,
+    Taproot,
 }
 
 impl Wallet {
@@ -200,7 +208,12 @@ impl Wallet {
         // set and save the origin if not set
         // we should be able to remove this because we should always have the origin
         // unless its a xpub only wallet
-        if metadata.origin.is_none() && metadata.wallet_type != WalletType::XpubOnly {
+        if metadata.origin.is_none()
+            && !matches!(
+                metadata.wallet_type,
+                WalletType::XpubOnly | WalletType::Multisig | WalletType::Miniscript
+            )
+        {
             warn!("no origin found, setting using descriptor");
             let extended_descriptor = wallet.public_descriptor(KeychainKind::External);
             let descriptor = Descriptor::from(extended_descriptor.clone());
@@ -221,6 +234,14 @@ impl Wallet {
     /// Create a new watch-only wallet from the given xpub
     pub fn try_new_persisted_from_xpub(xpub: String) -> Result<Self, WalletError> {
         let xpub = xpub.trim();
+        if let Ok(multisig) = MultisigExport::try_from_str(xpub) {
+            return Self::try_new_persisted_from_multisig(multisig);
+        }
+
+        if let Ok(miniscript) = MiniscriptExport::try_from_str(xpub) {
+            return Self::try_new_persisted_from_miniscript(miniscript);
+        }
+
         let hardware_export = pubport::Format::try_new_from_str(xpub)
             .map_err(Into::into)
             .map_err(WalletError::ParseXpubError);
@@ -229,9 +250,12 @@ impl Wallet {
             return Self::try_new_persisted_from_pubport(hardware_export);
         }
 
-        let xpub = xpub.trim();
-        if xpub.starts_with("UR:") || xpub.starts_with("ur:") {
-            return Err(MultiFormatError::UrFormatNotSupported.into());
+        // single part UR wallet export, ex. `ur:crypto-account/...`
+        if UrPart::is_ur(xpub) {
+            return match crate::ur::decode(xpub).map_err(MultiFormatError::from)? {
+                UrPayload::Descriptor(descriptor) => Self::try_new_persisted_from_xpub(descriptor),
+                _ => Err(MultiFormatError::UrFormatNotSupported.into()),
+            };
         }
 
         // already returned if its a valid xpub
@@ -268,26 +292,39 @@ impl Wallet {
 
         let fingerprint = pubport_descriptors.fingerprint();
 
-        // make sure its not already imported
+        // update the fingerprint
         if let Some(fingerprint) = fingerprint.as_ref() {
-            // update the fingerprint
             let fingerprint: Fingerprint = (*fingerprint).into();
             metadata.master_fingerprint = Some(fingerprint.into());
-
-            check_for_duplicate_wallet(network, mode, fingerprint)?;
         }
 
-        let fingerprint = fingerprint.map(|s| s.to_string());
         let xpub =
             pubport_descriptors.xpub().map_err(Into::into).map_err(WalletError::ParseXpubError)?;
 
         let descriptors: Descriptors = pubport_descriptors.into();
+        if let Some(address_type) = descriptors.address_type() {
+            metadata.address_type = address_type;
+        }
+
+        // the account is part of the key origin, ex. `[fingerprint/84'/0'/1']`
+        metadata.account = descriptors.account().unwrap_or_default();
+
+        // make sure its not already imported
+        if let Some(fingerprint) = fingerprint {
+            check_for_duplicate_wallet(network, mode, fingerprint.into(), metadata.account)?;
+        }
+
+        let fingerprint = fingerprint.map(|s| s.to_string());
 
         metadata.name = match &fingerprint {
             Some(fingerprint) => format!("Imported {}", fingerprint.to_ascii_uppercase()),
             None => "Imported XPub".to_string(),
         };
 
+        if metadata.account > 0 {
+            metadata.name = format!("{} Account {}", metadata.name, metadata.account);
+        }
+
         metadata.wallet_type = match &fingerprint {
             Some(_) => WalletType::Cold,
             None => WalletType::XpubOnly,
@@ -323,6 +360,111 @@ impl Wallet {
         Ok(Self { id, metadata, network, bdk: wallet, db: Mutex::new(store.conn) })
     }
 
+    /// Import a watch-only multisig wallet from a coordinator export
+    pub fn try_new_persisted_from_multisig(export: MultisigExport) -> Result<Self, WalletError> {
+        let keychain = Keychain::global();
+        let database = Database::global();
+        let network = database.global_config.selected_network();
+        let mode = database.global_config.wallet_mode();
+
+        if bitcoin::NetworkKind::from(network) != export.network {
+            return Err(WalletError::UnsupportedWallet(
+                "multisig keys are for a different network".to_string(),
+            ));
+        }
+
+        // make sure its not already imported
+        let existing = database.wallets.get_all(network, mode).unwrap_or_default();
+        if let Some(wallet) =
+            existing.into_iter().find(|wallet| wallet.multisig.as_ref() == Some(&export.config))
+        {
+            return Err(WalletError::WalletAlreadyExists(wallet.id));
+        }
+
+        let id = WalletId::new();
+        let mut metadata = WalletMetadata::new_for_hardware(id.clone(), "", None);
+        metadata.wallet_type = WalletType::Multisig;
+        metadata.name =
+            export.name.clone().unwrap_or_else(|| format!("Imported {} Multisig", export.policy()));
+
+        let descriptors = export.descriptors();
+        metadata.multisig = Some(export.config);
+
+        let mut store =
+            BdkStore::try_new(&id, network).map_err(|e| WalletError::LoadError(e.to_string()))?;
+
+        let wallet = descriptors
+            .clone()
+            .into_create_params()
+            .network(network.into())
+            .create_wallet(&mut store.conn)
+            .map_err(|error| WalletError::BdkError(error.to_string()))?;
+
+        // no single xpub to save, only the public descriptors
+        keychain.save_public_descriptor(
+            &metadata.id,
+            descriptors.external.extended_descriptor,
+            descriptors.internal.extended_descriptor,
+        )?;
+
+        database.wallets.save_new_wallet_metadata(metadata.clone())?;
+
+        Ok(Self { id, metadata, network, bdk: wallet, db: Mutex::new(store.conn) })
+    }
+
+    /// Watch-only wallet for a miniscript policy descriptor
+    pub fn try_new_persisted_from_miniscript(
+        export: MiniscriptExport,
+    ) -> Result<Self, WalletError> {
+        let keychain = Keychain::global();
+        let database = Database::global();
+        let network = database.global_config.selected_network();
+        let mode = database.global_config.wallet_mode();
+
+        if export.network.is_some_and(|kind| kind != bitcoin::NetworkKind::from(network)) {
+            return Err(WalletError::UnsupportedWallet(
+                "descriptor keys are for a different network".to_string(),
+            ));
+        }
+
+        // make sure its not already imported
+        let descriptor = export.descriptor();
+        let existing = database.wallets.get_all(network, mode).unwrap_or_default();
+        if let Some(wallet) = existing
+            .into_iter()
+            .find(|wallet| wallet.policy_descriptor.as_ref() == Some(&descriptor))
+        {
+            return Err(WalletError::WalletAlreadyExists(wallet.id));
+        }
+
+        let id = WalletId::new();
+        let mut metadata = WalletMetadata::new_for_hardware(id.clone(), "", None);
+        metadata.wallet_type = WalletType::Miniscript;
+        metadata.name = export.name.clone().unwrap_or_else(|| export.default_name());
+        metadata.policy_descriptor = Some(descriptor);
+
+        let descriptors = export.descriptors();
+        let mut store =
+            BdkStore::try_new(&id, network).map_err(|e| WalletError::LoadError(e.to_string()))?;
+
+        let wallet = descriptors
+            .clone()
+            .into_create_params()
+            .network(network.into())
+            .create_wallet(&mut store.conn)
+            .map_err(|error| WalletError::BdkError(error.to_string()))?;
+
+        keychain.save_public_descriptor(
+            &metadata.id,
+            descriptors.external.extended_descriptor,
+            descriptors.internal.extended_descriptor,
+        )?;
+
+        database.wallets.save_new_wallet_metadata(metadata.clone())?;
+
+        Ok(Self { id, metadata, network, bdk: wallet, db: Mutex::new(store.conn) })
+    }
+
     pub fn try_new_persisted_from_tap_signer(
         tap_signer: Arc<cove_tap_card::TapSigner>,
         derive: DeriveInfo,
@@ -352,9 +494,10 @@ impl Wallet {
         metadata.origin = descriptors.origin().ok();
         metadata.master_fingerprint = Some(Arc::new(fingerprint));
         metadata.wallet_type = WalletType::Cold;
+        metadata.account = descriptors.account().unwrap_or_default();
 
         // make sure its not already imported
-        check_for_duplicate_wallet(network, mode, fingerprint)?;
+        check_for_duplicate_wallet(network, mode, fingerprint, metadata.account)?;
 
         let xpub =
             descriptors.external.xpub().expect("tap_signer descriptor always made with xpub");
@@ -477,7 +620,8 @@ impl Wallet {
         let mut store =
             BdkStore::try_new(&id, network).map_err(|e| WalletError::LoadError(e.to_string()))?;
 
-        let descriptors = mnemonic.into_descriptors(passphrase, network, address_type);
+        let descriptors =
+            mnemonic.into_descriptors(passphrase, network, address_type, metadata.account);
         let origin = descriptors.origin().ok();
 
         metadata.master_fingerprint = descriptors.fingerprint().map(|f| Arc::new(f.into()));
@@ -586,6 +730,24 @@ impl Wallet {
         Ok(address_info_with_derivation)
     }
 
+    /// Receive addresses that have been paid, most recent first, to prove ownership of
+    pub fn used_addresses(&self) -> Vec<AddressInfoWithDerivation> {
+        let keychain = KeychainKind::External;
+        let derivation_path = self.bdk.public_descriptor(keychain).derivation_path().ok();
+        let index = self.bdk.spk_index();
+
+        index
+            .revealed_keychain_spks(keychain)
+            .rev()
+            .filter(|(address_index, _)| index.is_used(keychain, *address_index))
+            .map(|(address_index, _)| {
+                let address_info =
+                    AddressInfo::from(self.bdk.peek_address(keychain, address_index));
+                AddressInfoWithDerivation::new(address_info, derivation_path.clone())
+            })
+            .collect()
+    }
+
     pub fn persist(&mut self) -> Result<(), WalletError> {
         self.bdk
             .persist(&mut self.db.lock())
@@ -595,10 +757,12 @@ impl Wallet {
     }
 }
 
-fn check_for_duplicate_wallet(
+/// A seed or hardware wallet can be added once per account
+pub(crate) fn check_for_duplicate_wallet(
     network: Network,
     mode: metadata::WalletMode,
     fingerprint: Fingerprint,
+    account: u32,
 ) -> Result<(), WalletError> {
     let all_fingerprints: Vec<(WalletId, Arc<Fingerprint>)> = Database::global()
         .wallets
@@ -606,6 +770,7 @@ fn check_for_duplicate_wallet(
         .map(|wallets| {
             wallets
                 .into_iter()
+                .filter(|wallet_metadata| wallet_metadata.account == account)
                 .filter_map(|wallet_metadata| {
                     let fingerprint = wallet_metadata.master_fingerprint?;
                     Some((wallet_metadata.id, fingerprint))
@@ -652,6 +817,7 @@ impl WalletAddressType {
             WalletAddressType::NativeSegwit => 0,
             WalletAddressType::WrappedSegwit => 1,
             WalletAddressType::Legacy => 2,
+            WalletAddressType::Taproot => 3,
         }
     }
 }
@@ -0,0 +1,454 @@
+//! Export a wallet's public descriptors so it can be watched or co-signed from another wallet
+
+use std::str::FromStr as _;
+
+use bdk_wallet::{
+    chain::ConfirmationBlockTime,
+    descriptor::ExtendedDescriptor,
+    miniscript::{ForEachKey as _, descriptor::DescriptorType},
+};
+use bitcoin::bip32::{ChildNumber, Fingerprint};
+use cove_bdk::descriptor_ext::DescriptorExt as _;
+use cove_types::Network;
+use serde_json::json;
+
+use crate::ur;
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, uniffi::Enum)]
+pub enum WalletExportFormat {
+    /// Checksummed multipath descriptor, ex. `wpkh([..]xpub/<0;1>/*)#checksum`
+    Descriptor,
+    /// JSON for Bitcoin Core's `importdescriptors`
+    BitcoinCore,
+    /// Wallet file Sparrow and Specter can both import
+    Specter,
+    /// Coldcard style generic JSON, single sig only
+    Coldcard,
+    /// `ur:crypto-output` of the receive descriptor, for registering with UR signers
+    CryptoOutput,
+    /// `ur:crypto-account`, single sig only
+    CryptoAccount,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Error, thiserror::Error)]
+pub enum WalletExportError {
+    #[error("unable to export descriptor: {0}")]
+    UnsupportedDescriptor(String),
+
+    #[error("{0:?} export is not supported for this wallet")]
+    UnsupportedFormat(WalletExportFormat),
+
+    #[error("unable to create json: {0}")]
+    Json(String),
+
+    #[error("unable to create QR code: {0}")]
+    QrCodeCreation(String),
+}
+
+type Error = WalletExportError;
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Everything an export needs from the wallet
+#[derive(Debug, Clone)]
+pub struct ExportSource {
+    pub name: String,
+    pub network: Network,
+    pub external: ExtendedDescriptor,
+    pub internal: ExtendedDescriptor,
+    /// Block of the wallet's first confirmed transaction, `None` if it has none yet
+    pub birthday: Option<ConfirmationBlockTime>,
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, uniffi::Object)]
+pub struct WalletExport {
+    format: WalletExportFormat,
+    file_name: String,
+    content: String,
+}
+
+impl WalletExport {
+    pub fn try_new(format: WalletExportFormat, source: &ExportSource) -> Result<Self> {
+        let content = match format {
+            WalletExportFormat::Descriptor => multipath_descriptor(source)?.to_string(),
+            WalletExportFormat::BitcoinCore => bitcoin_core(source)?,
+            WalletExportFormat::Specter => specter(source)?,
+            WalletExportFormat::Coldcard => coldcard(source)?,
+            WalletExportFormat::CryptoOutput => ur::descriptor_to_output_ur(&source.external)
+                .map_err(|error| Error::UnsupportedDescriptor(error.to_string()))?,
+            WalletExportFormat::CryptoAccount => ur::descriptor_to_account_ur(&source.external)
+                .map_err(|_| Error::UnsupportedFormat(format))?,
+        };
+
+        let suffix = match format {
+            WalletExportFormat::Descriptor => "descriptor.txt",
+            WalletExportFormat::BitcoinCore => "bitcoin-core.json",
+            WalletExportFormat::Specter => "specter.json",
+            WalletExportFormat::Coldcard => "coldcard.json",
+            WalletExportFormat::CryptoOutput => "crypto-output.txt",
+            WalletExportFormat::CryptoAccount => "crypto-account.txt",
+        };
+
+        let file_name = format!("{}-{suffix}", file_name_prefix(&source.name));
+        Ok(Self { format, file_name, content })
+    }
+
+    pub fn to_bbqr(&self) -> Result<Vec<String>> {
+        use bbqr::file_type::FileType;
+
+        let file_type = match self.format {
+            WalletExportFormat::Descriptor => FileType::UnicodeText,
+            _ => FileType::Json,
+        };
+
+        cove_types::qr::bbqr_split(self.content.as_bytes(), file_type)
+            .map_err(|e| Error::QrCodeCreation(e.to_string()))
+    }
+
+    /// Animated UR frames, only for the UR formats
+    pub fn to_ur(&self, max_fragment_len: usize) -> Result<Vec<String>> {
+        if !self.is_ur() {
+            return Err(Error::UnsupportedFormat(self.format));
+        }
+
+        ur::split(&self.content, max_fragment_len)
+            .map_err(|error| Error::QrCodeCreation(error.to_string()))
+    }
+
+    pub fn is_ur(&self) -> bool {
+        matches!(self.format, WalletExportFormat::CryptoOutput | WalletExportFormat::CryptoAccount)
+    }
+}
+
+/// Combine the receive and change descriptors into one `<0;1>` descriptor
+fn multipath_descriptor(source: &ExportSource) -> Result<ExtendedDescriptor> {
+    let external = without_checksum(&source.external);
+    let multipath = ExtendedDescriptor::from_str(&external.replace("/0/*", "/<0;1>/*"))
+        .map_err(|error| Error::UnsupportedDescriptor(error.to_string()))?;
+
+    let descriptors = multipath
+        .clone()
+        .into_single_descriptors()
+        .map_err(|error| Error::UnsupportedDescriptor(error.to_string()))?;
+
+    // older hot wallets keep their change in native segwit, those can't be combined
+    if descriptors != [source.external.clone(), source.internal.clone()] {
+        let error = "change descriptor is not the /1/* branch of the receive descriptor";
+        return Err(Error::UnsupportedDescriptor(error.to_string()));
+    }
+
+    Ok(multipath)
+}
+
+fn bitcoin_core(source: &ExportSource) -> Result<String> {
+    let timestamp = match source.birthday {
+        Some(birthday) => json!(birthday.confirmation_time),
+        None => json!("now"),
+    };
+
+    let descriptors =
+        [(&source.external, false), (&source.internal, true)].map(|(descriptor, internal)| {
+            json!({
+                "desc": descriptor.to_string(),
+                "timestamp": timestamp,
+                "active": true,
+                "internal": internal,
+            })
+        });
+
+    serde_json::to_string_pretty(&descriptors).map_err(|error| Error::Json(error.to_string()))
+}
+
+/// Specter splits the change branch off the receive descriptor itself
+fn specter(source: &ExportSource) -> Result<String> {
+    multipath_descriptor(source)?;
+
+    let mut fingerprints = Vec::new();
+    source.external.for_each_key(|key| {
+        fingerprints.push(key.master_fingerprint());
+        true
+    });
+
+    let devices = match fingerprints.as_slice() {
+        [_] => vec![json!({ "type": "other", "label": source.name })],
+        fingerprints => fingerprints
+            .iter()
+            .map(|fingerprint| json!({ "type": "other", "label": xfp(fingerprint) }))
+            .collect(),
+    };
+
+    let block_height = source.birthday.map(|birthday| birthday.block_id.height);
+
+    let wallet = json!({
+        "label": source.name,
+        "blockheight": block_height.unwrap_or_default(),
+        "descriptor": source.external.to_string(),
+        "devices": devices,
+    });
+
+    serde_json::to_string_pretty(&wallet).map_err(|error| Error::Json(error.to_string()))
+}
+
+fn coldcard(source: &ExportSource) -> Result<String> {
+    let unsupported = || Error::UnsupportedFormat(WalletExportFormat::Coldcard);
+
+    let key_path_only =
+        matches!(&source.external, ExtendedDescriptor::Tr(tr) if tr.tap_tree().is_none());
+
+    let (section, name) = match source.external.desc_type() {
+        DescriptorType::Pkh => ("bip44", "p2pkh"),
+        DescriptorType::ShWpkh => ("bip49", "p2sh-p2wpkh"),
+        DescriptorType::Wpkh => ("bip84", "p2wpkh"),
+        DescriptorType::Tr if key_path_only => ("bip86", "p2tr"),
+        _ => return Err(unsupported()),
+    };
+
+    let (fingerprint, path) = source.external.origin().map_err(|_| unsupported())?;
+    let xpub = source.external.xpub().ok_or_else(unsupported)?;
+
+    let account = match path.as_ref() {
+        [_, _, ChildNumber::Hardened { index }] => *index,
+        _ => return Err(unsupported()),
+    };
+
+    let first_address = source
+        .external
+        .at_derivation_index(0)
+        .map_err(|error| Error::UnsupportedDescriptor(error.to_string()))?
+        .address(source.network.into())
+        .map_err(|error| Error::UnsupportedDescriptor(error.to_string()))?;
+
+    let chain = match source.network {
+        Network::Bitcoin => "BTC",
+        Network::Regtest => "XRT",
+        _ => "XTN",
+    };
+
+    let wallet = json!({
+        "chain": chain,
+        "xfp": xfp(fingerprint),
+        "account": account,
+        section: {
+            "name": name,
+            "deriv": format!("m/{path}"),
+            "xpub": xpub.to_string(),
+            "desc": source.external.to_string(),
+            "first": first_address.to_string(),
+        },
+    });
+
+    serde_json::to_string_pretty(&wallet).map_err(|error| Error::Json(error.to_string()))
+}
+
+fn without_checksum(descriptor: &ExtendedDescriptor) -> String {
+    let string = descriptor.to_string();
+    string.split('#').next().unwrap_or_default().to_string()
+}
+
+fn xfp(fingerprint: &Fingerprint) -> String {
+    fingerprint.to_string().to_ascii_uppercase()
+}
+
+/// ex. "My Wallet" -> "my-wallet"
+fn file_name_prefix(name: &str) -> String {
+    let prefix = name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_ascii_lowercase();
+
+    if prefix.is_empty() { "wallet".to_string() } else { prefix }
+}
+
+// MARK: ffi
+
+#[uniffi::export]
+impl WalletExport {
+    #[uniffi::method(name = "format")]
+    fn ffi_format(&self) -> WalletExportFormat {
+        self.format
+    }
+
+    #[uniffi::method(name = "content")]
+    fn ffi_content(&self) -> String {
+        self.content.clone()
+    }
+
+    #[uniffi::method(name = "fileName")]
+    fn ffi_file_name(&self) -> String {
+        self.file_name.clone()
+    }
+
+    #[uniffi::method(name = "toBbqr")]
+    fn ffi_to_bbqr(&self) -> Result<Vec<String>> {
+        self.to_bbqr()
+    }
+
+    #[uniffi::method(name = "toUr")]
+    fn ffi_to_ur(&self, max_fragment_len: u32) -> Result<Vec<String>> {
+        self.to_ur(max_fragment_len as usize)
+    }
+
+    #[uniffi::method(name = "isUr")]
+    fn ffi_is_ur(&self) -> bool {
+        self.is_ur()
+    }
+}
+
+#[uniffi::export]
+fn wallet_export_format_to_string(format: WalletExportFormat) -> String {
+    match format {
+        WalletExportFormat::Descriptor => "Descriptor",
+        WalletExportFormat::BitcoinCore => "Bitcoin Core",
+        WalletExportFormat::Specter => "Sparrow / Specter",
+        WalletExportFormat::Coldcard => "Generic JSON",
+        WalletExportFormat::CryptoOutput => "UR Output",
+        WalletExportFormat::CryptoAccount => "UR Account",
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bdk_wallet::chain::BlockId;
+    use bitcoin::hashes::Hash as _;
+    use pretty_assertions::assert_eq;
+
+    // abandon * 11 + about, account 0
+    const WPKH: &str = "wpkh([73c5da0a/84'/0'/0']xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V/<0;1>/*)";
+    const MULTISIG: &str = "wsh(sortedmulti(2,[73c5da0a/48'/1'/0'/2']tpubDE2qaB2bR6mCJycd41cGvu4JFP2gXFTGyLb8nmhZcEqJY7JabfnPG6bXcuTekjpo6No3ZkjZYaPe7r7TBJJGR2mVRsC3hV2NHmEV2JwKFDs/<0;1>/*,[f245ae38/48'/1'/0'/2']tpubDE2qaB2bR6mCKJ2ztrMeDgejB6d7UZb6JKxKD5azHhvchQhYBmtuST8umbRZxm8kGhUmcLwaozs7NtXoU8udGoXoGpEJ2D1NpFcyozjELRe/<0;1>/*))";
+
+    fn source(multipath: &str, network: Network) -> ExportSource {
+        let descriptor = ExtendedDescriptor::from_str(multipath).unwrap();
+        let [external, internal] =
+            <[_; 2]>::try_from(descriptor.into_single_descriptors().unwrap()).unwrap();
+
+        ExportSource { name: "My Wallet".to_string(), network, external, internal, birthday: None }
+    }
+
+    fn birthday() -> ConfirmationBlockTime {
+        ConfirmationBlockTime {
+            block_id: BlockId { height: 800_000, hash: bitcoin::BlockHash::all_zeros() },
+            confirmation_time: 1_690_000_000,
+        }
+    }
+
+    #[test]
+    fn test_multipath_descriptor_round_trip() {
+        for (multipath, network) in [(WPKH, Network::Bitcoin), (MULTISIG, Network::Testnet)] {
+            let export =
+                WalletExport::try_new(WalletExportFormat::Descriptor, &source(multipath, network))
+                    .unwrap();
+
+            let (descriptor, checksum) = export.content.split_once('#').unwrap();
+            assert_eq!(descriptor, multipath);
+            assert_eq!(checksum.len(), 8);
+            assert_eq!(export.file_name, "my-wallet-descriptor.txt");
+        }
+
+        // change on a different script type can't be one descriptor
+        let mut mismatched = source(WPKH, Network::Bitcoin);
+        mismatched.external = ExtendedDescriptor::from_str(
+            &without_checksum(&mismatched.external).replace("wpkh(", "pkh("),
+        )
+        .unwrap();
+
+        let result = WalletExport::try_new(WalletExportFormat::Descriptor, &mismatched);
+        assert!(matches!(result, Err(Error::UnsupportedDescriptor(_))));
+    }
+
+    #[test]
+    fn test_bitcoin_core_export() {
+        let mut source = source(WPKH, Network::Bitcoin);
+        let export = WalletExport::try_new(WalletExportFormat::BitcoinCore, &source).unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&export.content).unwrap();
+        assert_eq!(json[0]["desc"], source.external.to_string());
+        assert_eq!(json[0]["internal"], false);
+        assert_eq!(json[1]["desc"], source.internal.to_string());
+        assert_eq!(json[1]["internal"], true);
+        assert_eq!(json[0]["timestamp"], "now");
+
+        source.birthday = Some(birthday());
+        let export = WalletExport::try_new(WalletExportFormat::BitcoinCore, &source).unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&export.content).unwrap();
+        assert_eq!(json[1]["timestamp"], 1_690_000_000);
+    }
+
+    #[test]
+    fn test_specter_export() {
+        let mut source = source(MULTISIG, Network::Testnet);
+        source.birthday = Some(birthday());
+
+        let export = WalletExport::try_new(WalletExportFormat::Specter, &source).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&export.content).unwrap();
+
+        assert_eq!(json["label"], "My Wallet");
+        assert_eq!(json["blockheight"], 800_000);
+        assert_eq!(json["descriptor"], source.external.to_string());
+        assert_eq!(json["devices"][0]["label"], "73C5DA0A");
+        assert_eq!(json["devices"][1]["label"], "F245AE38");
+    }
+
+    #[test]
+    fn test_coldcard_export() {
+        let export =
+            WalletExport::try_new(WalletExportFormat::Coldcard, &source(WPKH, Network::Bitcoin))
+                .unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&export.content).unwrap();
+        assert_eq!(json["chain"], "BTC");
+        assert_eq!(json["xfp"], "73C5DA0A");
+        assert_eq!(json["account"], 0);
+        assert_eq!(json["bip84"]["deriv"], "m/84'/0'/0'");
+        assert_eq!(json["bip84"]["first"], "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu");
+
+        let result = WalletExport::try_new(
+            WalletExportFormat::Coldcard,
+            &source(MULTISIG, Network::Testnet),
+        );
+
+        assert_eq!(result, Err(Error::UnsupportedFormat(WalletExportFormat::Coldcard)));
+    }
+
+    #[test]
+    fn test_export_to_bbqr() {
+        let export = WalletExport::try_new(
+            WalletExportFormat::BitcoinCore,
+            &source(MULTISIG, Network::Testnet),
+        )
+        .unwrap();
+
+        let parts = export.to_bbqr().unwrap();
+        assert!(!parts.is_empty());
+        assert!(parts.iter().all(|part| part.starts_with("B$") && &part[3..4] == "J"));
+    }
+
+    #[test]
+    fn test_export_to_ur() {
+        let single = source(WPKH, Network::Bitcoin);
+        let export = WalletExport::try_new(WalletExportFormat::CryptoAccount, &single).unwrap();
+        assert!(export.content.starts_with("ur:crypto-account/"));
+        assert_eq!(export.file_name, "my-wallet-crypto-account.txt");
+
+        let frames = export.to_ur(30).unwrap();
+        assert!(frames.len() > 1);
+        assert!(frames.iter().all(|frame| frame.starts_with("ur:crypto-account/")));
+
+        // multisig can only be an output
+        let multisig = source(MULTISIG, Network::Testnet);
+        let export = WalletExport::try_new(WalletExportFormat::CryptoOutput, &multisig).unwrap();
+        assert!(export.content.starts_with("ur:crypto-output/"));
+
+        let result = WalletExport::try_new(WalletExportFormat::CryptoAccount, &multisig);
+        assert_eq!(result, Err(Error::UnsupportedFormat(WalletExportFormat::CryptoAccount)));
+
+        let export = WalletExport::try_new(WalletExportFormat::Descriptor, &multisig).unwrap();
+        assert_eq!(
+            export.to_ur(200),
+            Err(Error::UnsupportedFormat(WalletExportFormat::Descriptor))
+        );
+    }
+}
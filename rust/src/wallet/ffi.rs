@@ -19,8 +19,17 @@ impl Wallet {
 
     #[uniffi::constructor]
     pub fn new_from_export(export: Arc<HardwareExport>) -> Result<Self, WalletError> {
-        let export = Arc::unwrap_or_clone(export);
-        Wallet::try_new_persisted_from_pubport(export.into_format())
+        match Arc::unwrap_or_clone(export) {
+            HardwareExport::SingleSig(format) => {
+                Wallet::try_new_persisted_from_pubport(Arc::unwrap_or_clone(format))
+            }
+            HardwareExport::Multisig(export) => {
+                Wallet::try_new_persisted_from_multisig(Arc::unwrap_or_clone(export))
+            }
+            HardwareExport::Miniscript(export) => {
+                Wallet::try_new_persisted_from_miniscript(Arc::unwrap_or_clone(export))
+            }
+        }
     }
 }
 
@@ -30,6 +39,7 @@ fn wallet_address_type_to_string(wallet_address_type: WalletAddressType) -> Stri
         WalletAddressType::NativeSegwit => "Native Segwit",
         WalletAddressType::WrappedSegwit => "Wrapped Segwit",
         WalletAddressType::Legacy => "Legacy",
+        WalletAddressType::Taproot => "Taproot",
     };
 
     str.to_string()
@@ -47,6 +57,14 @@ fn discovery_state_is_equal(lhs: DiscoveryState, rhs: DiscoveryState) -> bool {
 
 // PREVIEW
 
+#[uniffi::export]
+fn preview_new_taproot_found_address() -> FoundAddress {
+    FoundAddress {
+        type_: WalletAddressType::Taproot,
+        first_address: "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr".to_string(),
+    }
+}
+
 #[uniffi::export]
 fn preview_new_legacy_found_address() -> FoundAddress {
     FoundAddress {
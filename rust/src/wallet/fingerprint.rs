@@ -10,6 +10,7 @@ use serde::Serialize;
     Default,
     uniffi::Object,
     derive_more::From,
+    derive_more::Deref,
     PartialEq,
     Eq,
     PartialOrd,
@@ -9,7 +9,7 @@ use serde::{Deserialize, Serialize};
 
 use super::{AddressInfo, WalletAddressType, fingerprint::Fingerprint};
 use crate::transaction::Unit;
-use crate::{database::Database, network::Network};
+use crate::{database::Database, multisig::MultisigConfig, network::Network};
 use cove_tap_card::TapSigner;
 
 pub use cove_types::{BlockSizeLast, WalletId};
@@ -43,6 +43,10 @@ pub struct WalletMetadata {
     #[serde(default)]
     pub origin: Option<String>,
 
+    /// BIP32 account, `m/purpose'/coin'/account'`, lets one seed have several wallets
+    #[serde(default)]
+    pub account: u32,
+
     /// Metadata data specific to different hardware wallets
     #[serde(default)]
     pub hardware_metadata: Option<HardwareWalletMetadata>,
@@ -52,6 +56,22 @@ pub struct WalletMetadata {
     #[serde(default = "default_true")]
     pub show_labels: bool,
 
+    /// How UTXOs are picked when building a transaction
+    #[serde(default)]
+    pub coin_selection: CoinSelectionStrategy,
+
+    /// Threshold and cosigners, only set for multisig wallets
+    #[serde(default)]
+    pub multisig: Option<MultisigConfig>,
+
+    /// Receive descriptor, only set for miniscript policy wallets
+    #[serde(default)]
+    pub policy_descriptor: Option<String>,
+
+    /// Spending path picked by the user for policy wallets, `None` uses the first available one
+    #[serde(default)]
+    pub spending_path: Option<u32>,
+
     // internal only metadata, don't use in the UI
     // note: maybe better to use a separate table for this
     #[serde(default)]
@@ -132,6 +152,12 @@ pub enum WalletType {
 
     /// deprecated, use XpubOnly instead
     WatchOnly,
+
+    /// Watch-only multisig, cosigners are in `WalletMetadata::multisig`
+    Multisig,
+
+    /// Watch-only miniscript policy wallet, ex. a key with a timelocked recovery key
+    Miniscript,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Hash, Eq, PartialEq, uniffi::Enum)]
@@ -157,6 +183,22 @@ pub enum FiatOrBtc {
     Fiat,
 }
 
+#[derive(
+    Debug, Clone, Copy, Default, Serialize, Deserialize, Hash, Eq, PartialEq, uniffi::Enum,
+)]
+pub enum CoinSelectionStrategy {
+    /// Branch and bound, falling back to a random draw
+    #[default]
+    Default,
+    /// Avoid mixing UTXOs with different labels or addresses
+    Privacy,
+    OldestFirst,
+    /// Spend the largest UTXOs first, consolidates into fewer UTXOs
+    LargestFirst,
+    /// Prefer transactions without a change output
+    AvoidChange,
+}
+
 impl WalletMetadata {
     pub fn new(name: impl Into<String>, fingerprint: Option<impl Into<Arc<Fingerprint>>>) -> Self {
         let network = Database::global().global_config.selected_network();
@@ -168,6 +210,7 @@ impl WalletMetadata {
             color: WalletColor::random(),
             master_fingerprint: fingerprint.map(Into::into),
             origin: None,
+            account: 0,
             verified: false,
             network,
             fiat_or_btc: FiatOrBtc::Btc,
@@ -179,6 +222,10 @@ impl WalletMetadata {
             wallet_mode,
             hardware_metadata: None,
             show_labels: true,
+            coin_selection: CoinSelectionStrategy::default(),
+            multisig: None,
+            policy_descriptor: None,
+            spending_path: None,
             internal: InternalOnlyMetadata::default(),
             discovery_state: DiscoveryState::default(),
         }
@@ -211,6 +258,7 @@ impl WalletMetadata {
             name: "Test Wallet".to_string(),
             master_fingerprint: Some(Arc::new(Fingerprint::default())),
             origin: None,
+            account: 0,
             color: WalletColor::random(),
             verified: false,
             network: Network::Bitcoin,
@@ -223,6 +271,10 @@ impl WalletMetadata {
             wallet_type: WalletType::Hot,
             wallet_mode: WalletMode::Main,
             show_labels: true,
+            coin_selection: CoinSelectionStrategy::default(),
+            multisig: None,
+            policy_descriptor: None,
+            spending_path: None,
             internal: InternalOnlyMetadata::default(),
             discovery_state: DiscoveryState::default(),
         }
@@ -14,6 +14,12 @@ use tracing::{debug, error, info, warn};
 /// Default number of addresses to scan
 const DEFAULT_SCAN_LIMIT: u32 = 150;
 
+/// Most accounts checked for prior use, `0..ACCOUNT_SCAN_LIMIT`
+pub const ACCOUNT_SCAN_LIMIT: u32 = 10;
+
+/// Addresses checked on each account when looking for prior use
+const ACCOUNT_GAP_LIMIT: u32 = 20;
+
 use crate::{
     database::{
         Database,
@@ -22,7 +28,10 @@ use crate::{
     keychain::Keychain,
     manager::wallet_manager::{SingleOrMany, WalletManagerReconcileMessage},
     mnemonic::MnemonicExt,
-    node::{client::NodeClientOptions, client_builder::NodeClientBuilder},
+    node::{
+        client::{NodeClient, NodeClientOptions},
+        client_builder::NodeClientBuilder,
+    },
     task::spawn_actor,
     wallet::{
         WalletAddressType, WalletError,
@@ -33,7 +42,7 @@ use crate::{
 #[derive(
     Debug, Default, derive_more::From, derive_more::Into, derive_more::Deref, derive_more::DerefMut,
 )]
-pub struct Wallets([Option<(WalletAddressType, BdkWallet)>; 2]);
+pub struct Wallets([Option<(WalletAddressType, BdkWallet)>; 3]);
 
 #[derive(
     Debug,
@@ -44,7 +53,7 @@ pub struct Wallets([Option<(WalletAddressType, BdkWallet)>; 2]);
     derive_more::Deref,
     derive_more::DerefMut,
 )]
-pub struct Workers([Option<WorkerHandle>; 2]);
+pub struct Workers([Option<WorkerHandle>; 3]);
 
 #[derive(Debug, Clone)]
 pub struct WorkerHandle {
@@ -75,6 +84,9 @@ pub enum WalletScannerError {
 
     #[error("No mnemonic available for id {0}")]
     NoMnemonicAvailable(WalletId),
+
+    #[error("Unable to check for transactions: {0}")]
+    NodeError(String),
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, uniffi::Enum)]
@@ -143,7 +155,8 @@ impl WalletScanner {
                     .flatten()
                     .ok_or_else(|| WalletScannerError::NoMnemonicAvailable(id.clone()))?;
 
-                (Wallets::try_from_mnemonic(&mnemonic, network)?, ScanSource::Mnemonic)
+                let wallets = Wallets::try_from_mnemonic(&mnemonic, network, metadata.account)?;
+                (wallets, ScanSource::Mnemonic)
             }
             DiscoveryState::Single
             | DiscoveryState::NoneFound
@@ -503,6 +516,7 @@ fn index(type_: WalletAddressType) -> usize {
     match type_ {
         WalletAddressType::WrappedSegwit => 0,
         WalletAddressType::Legacy => 1,
+        WalletAddressType::Taproot => 2,
         WalletAddressType::NativeSegwit => panic!("Not scanning the default one NativeSegwit"),
     }
 }
@@ -529,19 +543,19 @@ impl Wallets {
         Ok(wallets)
     }
 
-    pub fn try_from_mnemonic(mnemonic: &Mnemonic, network: Network) -> Result<Self, WalletError> {
+    pub fn try_from_mnemonic(
+        mnemonic: &Mnemonic,
+        network: Network,
+        account: u32,
+    ) -> Result<Self, WalletError> {
         let mut wallets = Wallets::default();
 
-        for type_ in [WalletAddressType::WrappedSegwit, WalletAddressType::Legacy] {
-            let descriptor = mnemonic.clone().into_descriptors(None, network, type_);
-            let wallet = BdkWallet::create(
-                descriptor.external.into_tuple(),
-                descriptor.internal.into_tuple(),
-            )
-            .network(network)
-            .create_wallet_no_persist()
-            .map_err(|error| WalletError::BdkError(error.to_string()))?;
-
+        for type_ in [
+            WalletAddressType::WrappedSegwit,
+            WalletAddressType::Legacy,
+            WalletAddressType::Taproot,
+        ] {
+            let wallet = account_wallet(mnemonic, network, type_, account)?;
             wallets[index(type_)] = Some((type_, wallet));
         }
 
@@ -549,6 +563,59 @@ impl Wallets {
     }
 }
 
+fn account_wallet(
+    mnemonic: &Mnemonic,
+    network: Network,
+    address_type: WalletAddressType,
+    account: u32,
+) -> Result<BdkWallet, WalletError> {
+    let descriptor = mnemonic.clone().into_descriptors(None, network, address_type, account);
+
+    BdkWallet::create(descriptor.external.into_tuple(), descriptor.internal.into_tuple())
+        .network(network)
+        .create_wallet_no_persist()
+        .map_err(|error| WalletError::BdkError(error.to_string()))
+}
+
+/// Find the accounts of a seed that have been used before, for one address type
+///
+/// Like BIP44 account discovery, stops at the first account without any transactions
+pub async fn find_used_accounts(
+    mnemonic: &Mnemonic,
+    network: Network,
+    address_type: WalletAddressType,
+    client: &NodeClient,
+) -> Result<Vec<u32>, WalletScannerError> {
+    let mut used_accounts = Vec::new();
+
+    for account in 0..ACCOUNT_SCAN_LIMIT {
+        let wallet = account_wallet(mnemonic, network, address_type, account)?;
+
+        let mut used = false;
+        for index in 0..ACCOUNT_GAP_LIMIT {
+            let address = wallet.peek_address(KeychainKind::External, index).address;
+            let has_txn = client
+                .check_address_for_txn(address)
+                .await
+                .map_err(|error| WalletScannerError::NodeError(error.to_string()))?;
+
+            if has_txn {
+                used = true;
+                break;
+            }
+        }
+
+        debug!("account {account} for {address_type} used: {used}");
+        if !used {
+            break;
+        }
+
+        used_accounts.push(account);
+    }
+
+    Ok(used_accounts)
+}
+
 impl From<ScannerResponse> for WalletManagerReconcileMessage {
     fn from(response: ScannerResponse) -> Self {
         WalletManagerReconcileMessage::WalletScannerResponse(response)